@@ -121,6 +121,9 @@ if [[ -d /etc/polkit-1/localauthority/50-local.d ]]; then
 	$SUDO install -m 0644 "$ROOT_DIR/provisioning/polkit/90-sis-teacher.pkla" /etc/polkit-1/localauthority/50-local.d/90-sis-teacher.pkla
 fi
 
+# ロック画面の PAM 設定
+$SUDO install -m 0644 "$ROOT_DIR/provisioning/pam.d/sis-lock" /etc/pam.d/sis-lock
+
 # リモート転送(任意): rsyslog or journal-upload
 if [[ -n "${SIS_LOG_REMOTE:-}" ]]; then
 	log "- rsyslog をリモート($SIS_LOG_REMOTE)へ転送するよう設定 (簡易)"
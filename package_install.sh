@@ -34,6 +34,9 @@ sudo apt-get install -y \
     playerctl \
     gnome-screenshot \
     grim \
+    ffmpeg \
+    gstreamer1.0-tools \
+    gstreamer1.0-pipewire \
     slurp \
     xdg-utils \
     gvfs \
@@ -42,6 +45,7 @@ sudo apt-get install -y \
     x11-utils \
     xdotool \
     libgtk-3-dev \
+    libpam0g-dev \
     npm \
     rustup \
     libwebkit2gtk-4.1-dev
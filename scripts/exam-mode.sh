@@ -9,7 +9,7 @@ case "$MODE" in
     rfkill block bluetooth || true
     # Kill screen capture tools (best effort)
     pkill -f flameshot || true
-    # TODO: set xkb options to disable Alt+Tab etc (environment-dependent)
+    # Alt+Tab 等のウィンドウ切替と XKB のサーバーキー（VT 切替）は、このマーカーを見た SIS（input）が無効化する
     touch /tmp/sis-exam-mode
     ;;
   off)
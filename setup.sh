@@ -20,7 +20,7 @@ sudo apt install -y libraylib-dev
 
 # --- Step 3: Tauriくんのお家 ---
 echo "Step 3: Tauriが必要とする部品をインストールしています..."
-sudo apt install -y libwebkit2gtk-4.0-dev libssl-dev libgtk-3-dev librsvg2-dev
+sudo apt install -y libwebkit2gtk-4.0-dev libssl-dev libgtk-3-dev librsvg2-dev libpam0g-dev
 
 # --- Step 4: Rustのインストール (公式のrustupを使うよ！) ---
 if ! command -v rustc &> /dev/null
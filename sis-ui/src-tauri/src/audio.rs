@@ -0,0 +1,263 @@
+// オーディオミキサー(PipeWire / PulseAudio): 出力/入力デバイス・既定デバイス・音量/ミュート・アプリ別ストリーム
+// pipewire-pulse でも PulseAudio でも動くよう pactl（--format=json, pactl 16+）経由で操作する
+use std::io::{BufRead, BufReader};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::Emitter;
+
+use crate::error::SisError;
+use crate::{log_append, run_out, which};
+
+const MAX_VOLUME: u32 = 150;
+const PA_VOLUME_NORM: f64 = 65536.0;
+// ボリュームキー連打時などのイベントをまとめる
+const DEBOUNCE: Duration = Duration::from_millis(120);
+const RESTART_DELAY: Duration = Duration::from_secs(3);
+
+static LAST: Lazy<Mutex<Option<AudioState>>> = Lazy::new(|| Mutex::new(None));
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AudioDevice {
+    index: u32,
+    name: String,        // PulseAudio 上の名前（操作時の ID）
+    description: String, // 表示名（例: "内蔵オーディオ アナログステレオ"）
+    port: Option<String>,        // アクティブなポートの表示名（ヘッドホン / スピーカー など）
+    form_factor: Option<String>, // headset / headphone / speaker / internal など
+    bus: Option<String>,         // pci / usb / bluetooth
+    volume: u32,
+    muted: bool,
+    is_default: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AudioStream {
+    index: u32,
+    kind: String, // playback | record
+    app_name: String,
+    media_name: String,
+    icon: Option<String>,
+    pid: Option<u32>,
+    device: u32, // 出力先 sink / 入力元 source の index
+    volume: u32,
+    muted: bool,
+    corked: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AudioState {
+    sinks: Vec<AudioDevice>,
+    sources: Vec<AudioDevice>,
+    streams: Vec<AudioStream>,
+    default_sink: Option<String>,
+    default_source: Option<String>,
+}
+
+fn pactl_json(args: &[&str]) -> Result<Value, String> {
+    let mut a = vec!["--format=json"];
+    a.extend_from_slice(args);
+    let out = run_out("pactl", &a).ok_or("audio-unavailable")?;
+    serde_json::from_str(&out).map_err(|_| "pactl-json-unsupported".to_string())
+}
+
+fn pactl(args: &[&str]) -> Result<(), String> {
+    let out = Command::new("pactl").args(args).output().map_err(|_| "audio-unavailable".to_string())?;
+    if out.status.success() { return Ok(()); }
+    let err = String::from_utf8_lossy(&out.stderr).trim().to_string();
+    if err.contains("No such entity") { return Err("not-found".into()); }
+    Err(format!("pactl-failed: {}", err))
+}
+
+// 全チャンネルの平均を % に換算
+fn volume_percent(v: &Value) -> u32 {
+    let chans: Vec<f64> = v.as_object().map(|m| m.values().filter_map(|c| c.get("value").and_then(|x| x.as_f64())).collect()).unwrap_or_default();
+    if chans.is_empty() { return 0; }
+    let avg = chans.iter().sum::<f64>() / chans.len() as f64;
+    (avg / PA_VOLUME_NORM * 100.0).round() as u32
+}
+
+fn prop_str(obj: &Value, key: &str) -> Option<String> {
+    obj.get("properties").and_then(|p| p.get(key)).and_then(|v| v.as_str()).filter(|s| !s.is_empty()).map(|s| s.to_string())
+}
+
+fn parse_device(obj: &Value, default: Option<&str>) -> Option<AudioDevice> {
+    let name = obj.get("name")?.as_str()?.to_string();
+    let active = obj.get("active_port").and_then(|v| v.as_str()).unwrap_or("");
+    let port = obj.get("ports").and_then(|v| v.as_array()).and_then(|ports| {
+        ports.iter().find(|p| p.get("name").and_then(|n| n.as_str()) == Some(active))
+            .and_then(|p| p.get("description").and_then(|d| d.as_str()).map(|s| s.to_string()))
+    });
+    Some(AudioDevice {
+        index: obj.get("index").and_then(|v| v.as_u64()).unwrap_or(0) as u32,
+        description: obj.get("description").and_then(|v| v.as_str()).filter(|s| !s.is_empty()).map(|s| s.to_string())
+            .or_else(|| prop_str(obj, "device.description")).unwrap_or_else(|| name.clone()),
+        is_default: default == Some(name.as_str()),
+        name,
+        port,
+        form_factor: prop_str(obj, "device.form_factor"),
+        bus: prop_str(obj, "device.bus"),
+        volume: obj.get("volume").map(volume_percent).unwrap_or(0),
+        muted: obj.get("mute").and_then(|v| v.as_bool()).unwrap_or(false),
+    })
+}
+
+fn parse_stream(obj: &Value, kind: &str) -> Option<AudioStream> {
+    let device_key = if kind == "playback" { "sink" } else { "source" };
+    // pavucontrol 等のレベルメーター用ストリームは除外
+    if prop_str(obj, "media.name").as_deref() == Some("Peak detect") { return None; }
+    Some(AudioStream {
+        index: obj.get("index")?.as_u64()? as u32,
+        kind: kind.to_string(),
+        app_name: prop_str(obj, "application.name").or_else(|| prop_str(obj, "application.process.binary")).unwrap_or_else(|| "不明なアプリ".into()),
+        media_name: prop_str(obj, "media.name").unwrap_or_default(),
+        icon: prop_str(obj, "application.icon_name"),
+        pid: prop_str(obj, "application.process.id").and_then(|s| s.parse().ok()),
+        device: obj.get(device_key).and_then(|v| v.as_u64()).unwrap_or(0) as u32,
+        volume: obj.get("volume").map(volume_percent).unwrap_or(0),
+        muted: obj.get("mute").and_then(|v| v.as_bool()).unwrap_or(false),
+        corked: obj.get("corked").and_then(|v| v.as_bool()).unwrap_or(false),
+    })
+}
+
+pub fn read_state() -> Result<AudioState, String> {
+    let info = pactl_json(&["info"])?;
+    let default_sink = info.get("default_sink_name").and_then(|v| v.as_str()).map(|s| s.to_string());
+    let default_source = info.get("default_source_name").and_then(|v| v.as_str()).map(|s| s.to_string());
+    let list = |what: &str| pactl_json(&["list", what]).ok().and_then(|v| v.as_array().cloned()).unwrap_or_default();
+    let sinks = list("sinks").iter().filter_map(|o| parse_device(o, default_sink.as_deref())).collect();
+    // sink のモニターは入力デバイスとして見せない
+    let sources = list("sources").iter()
+        .filter(|o| o.get("monitor_of_sink").and_then(|v| v.as_str()).map(|s| s == "n/a").unwrap_or(true))
+        .filter(|o| prop_str(o, "device.class").as_deref() != Some("monitor"))
+        .filter_map(|o| parse_device(o, default_source.as_deref())).collect();
+    let mut streams: Vec<AudioStream> = list("sink-inputs").iter().filter_map(|o| parse_stream(o, "playback")).collect();
+    streams.extend(list("source-outputs").iter().filter_map(|o| parse_stream(o, "record")));
+    Ok(AudioState { sinks, sources, streams, default_sink, default_source })
+}
+
+/// 既定の出力デバイスの (音量, ミュート)（コントロールセンターのスライダー用）
+pub fn default_sink_volume() -> Option<(u32, bool)> {
+    // 入力やストリームまでは要らないので、既定の出力名と sink 一覧だけを読む
+    let info = pactl_json(&["info"]).ok()?;
+    let default_sink = info.get("default_sink_name").and_then(|v| v.as_str())?;
+    let sinks = pactl_json(&["list", "sinks"]).ok()?;
+    sinks.as_array()?.iter().filter_map(|o| parse_device(o, Some(default_sink))).find(|d| d.is_default).map(|d| (d.volume, d.muted))
+}
+
+fn device_kind(kind: &str) -> Result<&'static str, String> {
+    match kind { "sink" | "output" => Ok("sink"), "source" | "input" => Ok("source"), _ => Err("invalid-kind".into()) }
+}
+
+pub fn set_device_volume(kind: &str, name: &str, volume: u32) -> Result<(), String> {
+    let k = device_kind(kind)?;
+    let v = format!("{}%", volume.min(MAX_VOLUME));
+    pactl(&[&format!("set-{}-volume", k), name, &v])
+}
+
+pub fn start(app_handle: tauri::AppHandle) {
+    if !which("pactl") { log_append("WARN", "audio: pactl not found; watcher disabled"); return; }
+    if let Err(e) = read_state() { log_append("WARN", &format!("audio: watcher disabled ({})", e)); return; }
+    let (tx, rx) = mpsc::channel::<()>();
+    // pactl subscribe は pipewire-pulse の再起動で終了するので張り直す
+    std::thread::spawn(move || loop {
+        match Command::new("pactl").arg("subscribe").stdout(Stdio::piped()).stderr(Stdio::null()).spawn() {
+            Ok(mut child) => {
+                if let Some(out) = child.stdout.take() {
+                    for line in BufReader::new(out).lines().map_while(Result::ok) {
+                        // "Event 'change' on sink #57" など。client/module の変化は無視
+                        if line.contains(" on client ") || line.contains(" on module ") { continue; }
+                        if tx.send(()).is_err() { return; }
+                    }
+                }
+                let _ = child.wait();
+            }
+            Err(e) => log_append("WARN", &format!("audio: pactl subscribe failed: {}", e)),
+        }
+        let _ = tx.send(());
+        std::thread::sleep(RESTART_DELAY);
+    });
+    std::thread::spawn(move || {
+        while rx.recv().is_ok() {
+            while rx.recv_timeout(DEBOUNCE).is_ok() {}
+            let cur = match read_state() { Ok(s) => s, Err(_) => continue };
+            let mut last = LAST.lock().unwrap();
+            if last.as_ref() == Some(&cur) { continue; }
+            let def = |s: &AudioState| s.sinks.iter().find(|d| d.is_default).map(|d| (d.name.clone(), d.volume, d.muted));
+            let now = def(&cur);
+            if last.as_ref().map(def) != Some(now.clone()) {
+                if let Some((name, volume, muted)) = now {
+                    let _ = app_handle.emit("sis:volume", serde_json::json!({ "sink": name, "volume": volume, "muted": muted }));
+                }
+            }
+            let _ = app_handle.emit("sis:audio-changed", &cur);
+            *last = Some(cur);
+        }
+    });
+}
+
+#[tauri::command]
+pub fn audio_state() -> Result<AudioState, SisError> { Ok(read_state()?) }
+
+/// 既定の出力/入力デバイスを切り替え、再生中/録音中のストリームも移動する
+#[tauri::command]
+pub fn audio_set_default(kind: String, name: String) -> Result<String, SisError> {
+    let k = device_kind(&kind)?;
+    pactl(&[&format!("set-default-{}", k), &name])?;
+    // PulseAudio は既存ストリームを移動しないため明示的に移す（PipeWire では不要だが無害）
+    if let Ok(st) = read_state() {
+        let stream_kind = if k == "sink" { "playback" } else { "record" };
+        let cmd = if k == "sink" { "move-sink-input" } else { "move-source-output" };
+        for s in st.streams.iter().filter(|s| s.kind == stream_kind) {
+            let _ = pactl(&[cmd, &s.index.to_string(), &name]);
+        }
+    }
+    log_append("INFO", &format!("audio: default {} -> {}", k, name));
+    Ok("default-set".into())
+}
+
+#[tauri::command]
+pub fn audio_set_volume(kind: String, name: String, volume: u32) -> Result<String, SisError> {
+    set_device_volume(&kind, &name, volume)?;
+    Ok(format!("volume-set: {}", volume.min(MAX_VOLUME)))
+}
+
+/// mute を省略するとトグル
+#[tauri::command]
+pub fn audio_set_mute(kind: String, name: String, mute: Option<bool>) -> Result<String, SisError> {
+    let k = device_kind(&kind)?;
+    let m = match mute { Some(true) => "1", Some(false) => "0", None => "toggle" };
+    pactl(&[&format!("set-{}-mute", k), &name, m])?;
+    Ok("mute-set".into())
+}
+
+fn stream_cmd(kind: &str) -> Result<&'static str, String> {
+    match kind { "playback" => Ok("sink-input"), "record" => Ok("source-output"), _ => Err("invalid-kind".into()) }
+}
+
+#[tauri::command]
+pub fn audio_set_stream_volume(kind: String, index: u32, volume: u32) -> Result<String, SisError> {
+    let c = stream_cmd(&kind)?;
+    pactl(&[&format!("set-{}-volume", c), &index.to_string(), &format!("{}%", volume.min(MAX_VOLUME))])?;
+    Ok(format!("volume-set: {}", volume.min(MAX_VOLUME)))
+}
+
+#[tauri::command]
+pub fn audio_set_stream_mute(kind: String, index: u32, mute: Option<bool>) -> Result<String, SisError> {
+    let c = stream_cmd(&kind)?;
+    let m = match mute { Some(true) => "1", Some(false) => "0", None => "toggle" };
+    pactl(&[&format!("set-{}-mute", c), &index.to_string(), m])?;
+    Ok("mute-set".into())
+}
+
+/// アプリのストリームだけを別のデバイスへ移す
+#[tauri::command]
+pub fn audio_move_stream(kind: String, index: u32, device: String) -> Result<String, SisError> {
+    let c = stream_cmd(&kind)?;
+    pactl(&[&format!("move-{}", c), &index.to_string(), &device])?;
+    Ok("moved".into())
+}
@@ -0,0 +1,182 @@
+// バックライト: /sys/class/backlight（パネル）と /sys/class/leds/*::kbd_backlight（キーボード）
+// 書き込みは logind の Session.SetBrightness を使う（root 不要）。使えない場合は sysfs へ直接書く
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+use zbus::blocking::{Connection, Proxy};
+
+use crate::error::SisError;
+use crate::log_append;
+
+const BACKLIGHT_DIR: &str = "/sys/class/backlight";
+const LEDS_DIR: &str = "/sys/class/leds";
+const POLL: Duration = Duration::from_millis(500);
+// 知覚的な明るさカーブ（raw = max * (percent/100)^GAMMA）
+const GAMMA: f64 = 2.0;
+// 段数がこれ以下の LED（多くのキーボードバックライト）は線形で扱う
+const LINEAR_MAX_STEPS: u32 = 10;
+
+static LAST: Lazy<Mutex<Vec<BacklightDevice>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BacklightDevice {
+    subsystem: String, // backlight | leds
+    name: String,      // intel_backlight / amdgpu_bl0 / tpacpi::kbd_backlight など
+    kind: String,      // panel | keyboard
+    backlight_type: Option<String>, // firmware | platform | raw（パネルのみ）
+    brightness: u32,
+    max_brightness: u32,
+    percent: u32,
+}
+
+fn read_u32(p: &Path) -> Option<u32> {
+    fs::read_to_string(p).ok()?.trim().parse().ok()
+}
+
+fn is_linear(max: u32) -> bool { max <= LINEAR_MAX_STEPS }
+
+fn raw_to_percent(raw: u32, max: u32) -> u32 {
+    if max == 0 { return 0; }
+    let f = raw.min(max) as f64 / max as f64;
+    let p = if is_linear(max) { f } else { f.powf(1.0 / GAMMA) };
+    (p * 100.0).round() as u32
+}
+
+fn percent_to_raw(percent: u32, max: u32) -> u32 {
+    let f = percent.min(100) as f64 / 100.0;
+    let v = if is_linear(max) { f } else { f.powf(GAMMA) };
+    (v * max as f64).round() as u32
+}
+
+fn read_device(subsystem: &str, kind: &str, dir: &Path) -> Option<BacklightDevice> {
+    let max = read_u32(&dir.join("max_brightness"))?;
+    // actual_brightness はハードウェアキーによる変化も反映する（無ければ brightness）
+    let raw = read_u32(&dir.join("actual_brightness")).or_else(|| read_u32(&dir.join("brightness")))?;
+    Some(BacklightDevice {
+        subsystem: subsystem.to_string(),
+        name: dir.file_name()?.to_string_lossy().to_string(),
+        kind: kind.to_string(),
+        backlight_type: fs::read_to_string(dir.join("type")).ok().map(|s| s.trim().to_string()).filter(|_| kind == "panel"),
+        brightness: raw,
+        max_brightness: max,
+        percent: raw_to_percent(raw, max),
+    })
+}
+
+pub fn list_devices() -> Vec<BacklightDevice> {
+    let mut out = Vec::new();
+    if let Ok(rd) = fs::read_dir(BACKLIGHT_DIR) {
+        for e in rd.flatten() {
+            if let Some(d) = read_device("backlight", "panel", &e.path()) { out.push(d); }
+        }
+    }
+    if let Ok(rd) = fs::read_dir(LEDS_DIR) {
+        for e in rd.flatten() {
+            if !e.file_name().to_string_lossy().ends_with("::kbd_backlight") { continue; }
+            if let Some(d) = read_device("leds", "keyboard", &e.path()) { out.push(d); }
+        }
+    }
+    // パネルは firmware → platform → raw の順（systemd-backlight と同じ優先度）
+    let rank = |d: &BacklightDevice| match d.backlight_type.as_deref() { Some("firmware") => 0, Some("platform") => 1, Some("raw") => 2, _ => 3 };
+    out.sort_by(|a, b| a.kind.cmp(&b.kind).reverse().then(rank(a).cmp(&rank(b))).then(a.name.cmp(&b.name)));
+    out
+}
+
+fn login_session() -> Option<Connection> {
+    static CONN: Lazy<Option<Connection>> = Lazy::new(|| Connection::system().ok());
+    CONN.clone()
+}
+
+fn write_raw(dev: &BacklightDevice, raw: u32) -> Result<(), String> {
+    if let Some(conn) = login_session() {
+        let res = Proxy::new(&conn, "org.freedesktop.login1", "/org/freedesktop/login1/session/auto", "org.freedesktop.login1.Session")
+            .and_then(|p| p.call::<_, _, ()>("SetBrightness", &(dev.subsystem.as_str(), dev.name.as_str(), raw)));
+        match res {
+            Ok(()) => return Ok(()),
+            Err(e) => log_append("WARN", &format!("backlight: logind SetBrightness failed ({}); trying sysfs", e)),
+        }
+    }
+    // udev ルールで video グループに書き込み権限がある環境向け
+    let p = Path::new(if dev.subsystem == "leds" { LEDS_DIR } else { BACKLIGHT_DIR }).join(&dev.name).join("brightness");
+    fs::write(&p, raw.to_string()).map_err(|e| format!("backlight-write-failed: {}", e))
+}
+
+fn set_device_percent(dev: &BacklightDevice, percent: u32) -> Result<u32, String> {
+    let mut raw = percent_to_raw(percent, dev.max_brightness);
+    // パネルは真っ暗にならないよう最低 1 にする
+    if dev.kind == "panel" && raw == 0 && dev.max_brightness > 0 { raw = 1; }
+    write_raw(dev, raw)?;
+    Ok(raw_to_percent(raw, dev.max_brightness))
+}
+
+/// 主パネルの明るさ（%）。バックライトが無い環境（デスクトップ機など）では None
+pub fn panel_percent() -> Option<u32> {
+    list_devices().into_iter().find(|d| d.kind == "panel").map(|d| d.percent)
+}
+
+/// 主パネルの明るさを設定する。同じパネルに firmware/platform/raw の複数のインターフェースが出ていることがあり、
+/// 全部に書くと値がずれて競合するので、list_devices の並び（firmware → platform → raw）で最初の1つだけに書く
+pub fn set_panel(percent: u32) -> Result<u32, String> {
+    let panel = list_devices().into_iter().find(|d| d.kind == "panel").ok_or("no-backlight")?;
+    set_device_percent(&panel, percent)
+}
+
+pub fn start(app_handle: tauri::AppHandle) {
+    if list_devices().is_empty() { log_append("INFO", "backlight: no devices; watcher disabled"); return; }
+    std::thread::spawn(move || loop {
+        let cur = list_devices();
+        {
+            let mut last = LAST.lock().unwrap();
+            if !last.is_empty() {
+                for d in &cur {
+                    if last.iter().any(|p| p.name == d.name && p.brightness != d.brightness) {
+                        let _ = app_handle.emit("sis:brightness", d);
+                    }
+                }
+            }
+            *last = cur;
+        }
+        std::thread::sleep(POLL);
+    });
+}
+
+#[tauri::command]
+pub fn backlight_list() -> Result<Vec<BacklightDevice>, SisError> { Ok(list_devices()) }
+
+/// device 省略時は主パネル
+#[tauri::command]
+pub fn backlight_set(device: Option<String>, percent: u32) -> Result<u32, SisError> {
+    match device {
+        None => Ok(set_panel(percent)?),
+        Some(name) => {
+            let d = list_devices().into_iter().find(|d| d.name == name).ok_or("not-found")?;
+            Ok(set_device_percent(&d, percent)?)
+        }
+    }
+}
+
+/// キーボードバックライト。percent 省略時は次の段階へ循環（消灯 → … → 最大 → 消灯）
+#[tauri::command]
+pub fn keyboard_backlight_set(percent: Option<u32>) -> Result<u32, SisError> {
+    let kbds: Vec<BacklightDevice> = list_devices().into_iter().filter(|d| d.kind == "keyboard").collect();
+    let first = kbds.first().ok_or("no-keyboard-backlight")?;
+    let target = match percent {
+        Some(p) => p,
+        None => {
+            let steps = first.max_brightness.clamp(1, LINEAR_MAX_STEPS);
+            let level = (first.percent * steps + 50) / 100;
+            if level >= steps { 0 } else { (level + 1) * 100 / steps }
+        }
+    };
+    let mut res = Ok(target);
+    for d in &kbds {
+        let r = set_device_percent(d, target);
+        if d.name == first.name { res = r; }
+    }
+    Ok(res?)
+}
@@ -0,0 +1,426 @@
+// BlueZ(D-Bus): アダプタ電源・デバイス検出・ペアリング/信頼/接続/切断・バッテリー残量
+// パスキー/PIN の確認が要る機器（キーボードなど）のため、既定のエージェント（org.bluez.Agent1）も登録する
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+use zbus::blocking::{fdo::ObjectManagerProxy, Connection, Proxy};
+use zbus::zvariant::{ObjectPath, OwnedObjectPath, OwnedValue};
+
+use crate::error::SisError;
+use crate::log_append;
+
+const POLL: Duration = Duration::from_secs(2);
+const DEFAULT_DISCOVERY_SECS: u64 = 20;
+const BLUEZ_DEST: &str = "org.bluez";
+const IF_ADAPTER: &str = "org.bluez.Adapter1";
+const IF_DEVICE: &str = "org.bluez.Device1";
+const IF_BATTERY: &str = "org.bluez.Battery1";
+const AGENT_PATH: &str = "/org/sis/bluetooth/agent";
+// 画面表示と数字入力の両方ができる（パスキー表示・入力・確認のどれにも対応する）
+const AGENT_CAPABILITY: &str = "KeyboardDisplay";
+
+static LAST: Lazy<Mutex<Option<BluetoothState>>> = Lazy::new(|| Mutex::new(None));
+static APP: Lazy<Mutex<Option<tauri::AppHandle>>> = Lazy::new(|| Mutex::new(None));
+static AGENT_REGISTERED: AtomicBool = AtomicBool::new(false);
+static NEXT_REQUEST: AtomicU32 = AtomicU32::new(1);
+// UI の応答待ち（request id → 応答の受け口）
+static REQUESTS: Lazy<Mutex<HashMap<u32, ReplySlot>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BtAdapter {
+    path: String,
+    name: String,
+    address: String,
+    powered: bool,
+    discovering: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BtDevice {
+    path: String,
+    address: String,
+    name: String,
+    icon: String, // audio-headphones / input-keyboard / input-mouse など（BlueZ の Icon）
+    paired: bool,
+    trusted: bool,
+    connected: bool,
+    rssi: Option<i16>,
+    battery: Option<u8>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BluetoothState {
+    adapter: Option<BtAdapter>,
+    devices: Vec<BtDevice>,
+}
+
+type Props = HashMap<String, OwnedValue>;
+
+fn prop<T: TryFrom<OwnedValue>>(props: &Props, key: &str) -> Option<T> {
+    props.get(key)?.try_clone().ok().and_then(|v| T::try_from(v).ok())
+}
+
+// テスト時は SIS_BLUEZ_BUS=session でモックの org.bluez を使う
+fn bluez_bus() -> Result<Connection, String> {
+    static CONN: Lazy<Option<Connection>> = Lazy::new(|| {
+        if std::env::var("SIS_BLUEZ_BUS").map(|v| v == "session").unwrap_or(false) { Connection::session().ok() } else { Connection::system().ok() }
+    });
+    CONN.clone().ok_or_else(|| "dbus-unavailable".to_string())
+}
+
+fn bz_err(prefix: &str, e: zbus::Error) -> String {
+    let s = e.to_string();
+    if s.contains("ServiceUnknown") { return "bluez-unavailable".into(); }
+    if s.contains("AlreadyExists") { return "already-paired".into(); }
+    if s.contains("AuthenticationFailed") || s.contains("AuthenticationRejected") { return "authentication-failed".into(); }
+    if s.contains("NotReady") { return "adapter-off".into(); }
+    format!("{}: {}", prefix, s)
+}
+
+fn read_state(conn: &Connection) -> Result<BluetoothState, String> {
+    let om = ObjectManagerProxy::builder(conn)
+        .destination(BLUEZ_DEST).and_then(|b| b.path("/"))
+        .and_then(|b| b.build())
+        .map_err(|e| format!("bluez-unavailable: {}", e))?;
+    let objs = om.get_managed_objects().map_err(|e| bz_err("bluez-unavailable", e.into()))?;
+    let mut adapter: Option<BtAdapter> = None;
+    let mut devices = Vec::new();
+    for (path, ifs) in objs {
+        let ifs: HashMap<String, Props> = ifs.into_iter().map(|(k, v)| (k.to_string(), v)).collect();
+        if let Some(a) = ifs.get(IF_ADAPTER) {
+            // 複数アダプタ時は最初のもの（通常 hci0）を使う
+            if adapter.as_ref().map(|x| path.as_str() < x.path.as_str()).unwrap_or(true) {
+                adapter = Some(BtAdapter {
+                    path: path.to_string(),
+                    name: prop::<String>(a, "Alias").or_else(|| prop::<String>(a, "Name")).unwrap_or_default(),
+                    address: prop::<String>(a, "Address").unwrap_or_default(),
+                    powered: prop::<bool>(a, "Powered").unwrap_or(false),
+                    discovering: prop::<bool>(a, "Discovering").unwrap_or(false),
+                });
+            }
+        }
+        if let Some(d) = ifs.get(IF_DEVICE) {
+            let address = prop::<String>(d, "Address").unwrap_or_default();
+            devices.push(BtDevice {
+                path: path.to_string(),
+                name: prop::<String>(d, "Alias").or_else(|| prop::<String>(d, "Name")).unwrap_or_else(|| address.clone()),
+                address,
+                icon: prop::<String>(d, "Icon").unwrap_or_default(),
+                paired: prop::<bool>(d, "Paired").unwrap_or(false),
+                trusted: prop::<bool>(d, "Trusted").unwrap_or(false),
+                connected: prop::<bool>(d, "Connected").unwrap_or(false),
+                rssi: prop::<i16>(d, "RSSI"),
+                battery: ifs.get(IF_BATTERY).and_then(|b| prop::<u8>(b, "Percentage")),
+            });
+        }
+    }
+    // 接続中 → ペア済み → 電波の強い順
+    devices.sort_by(|a, b| b.connected.cmp(&a.connected).then(b.paired.cmp(&a.paired)).then(b.rssi.unwrap_or(i16::MIN).cmp(&a.rssi.unwrap_or(i16::MIN))));
+    Ok(BluetoothState { adapter, devices })
+}
+
+fn adapter_proxy(conn: &Connection) -> Result<Proxy<'static>, String> {
+    let a = read_state(conn)?.adapter.ok_or("no-adapter")?;
+    Proxy::new(conn, BLUEZ_DEST, a.path, IF_ADAPTER).map_err(|e| format!("bluez-unavailable: {}", e))
+}
+
+fn find_device(conn: &Connection, id: &str) -> Result<BtDevice, String> {
+    read_state(conn)?.devices.into_iter().find(|d| d.path == id || d.address.eq_ignore_ascii_case(id)).ok_or_else(|| "not-found".to_string())
+}
+
+fn device_proxy(conn: &Connection, dev: &BtDevice) -> Result<Proxy<'static>, String> {
+    Proxy::new(conn, BLUEZ_DEST, dev.path.clone(), IF_DEVICE).map_err(|e| format!("bluez-unavailable: {}", e))
+}
+
+/// アダプタの電源状態（BlueZ が無ければ None）
+pub fn adapter_powered() -> Option<bool> {
+    let conn = bluez_bus().ok()?;
+    read_state(&conn).ok()?.adapter.map(|a| a.powered)
+}
+
+pub fn set_powered(enable: bool) -> Result<(), String> {
+    let conn = bluez_bus()?;
+    let a = adapter_proxy(&conn)?;
+    a.set_property("Powered", enable).map_err(|e| bz_err("power-failed", e.into()))?;
+    Ok(())
+}
+
+// ---- ペアリング用エージェント ----
+
+// 応答は None が拒否、Some(値) が承諾（確認のみのときは空文字列）
+#[derive(Default)]
+struct Reply {
+    value: Option<Option<String>>,
+    waker: Option<Waker>,
+}
+
+type ReplySlot = Arc<Mutex<Reply>>;
+
+/// UI からの応答を待つ Future（zbus の実行スレッドを塞がないよう、スレッドではなく Waker で待つ）
+struct WaitReply(ReplySlot);
+
+impl Future for WaitReply {
+    type Output = Option<String>;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut r = self.0.lock().unwrap();
+        match r.value.take() {
+            Some(v) => Poll::Ready(v),
+            None => { r.waker = Some(cx.waker().clone()); Poll::Pending }
+        }
+    }
+}
+
+fn resolve(id: u32, value: Option<String>) -> bool {
+    let Some(slot) = REQUESTS.lock().unwrap().remove(&id) else { return false; };
+    let mut r = slot.lock().unwrap();
+    r.value = Some(value);
+    if let Some(w) = r.waker.take() { w.wake(); }
+    true
+}
+
+fn emit_passkey(payload: serde_json::Value) {
+    if let Some(app) = APP.lock().unwrap().as_ref() { let _ = app.emit("sis:bluetooth-passkey", payload); }
+}
+
+/// BlueZ を呼び返すと実行スレッド上でデッドロックするので、名前は直近の状態から引く
+fn agent_device(path: &ObjectPath<'_>) -> serde_json::Value {
+    let last = LAST.lock().unwrap();
+    let d = last.as_ref().and_then(|s| s.devices.iter().find(|d| d.path == path.as_str()));
+    serde_json::json!({
+        "device": path.as_str(),
+        "address": d.map(|d| d.address.clone()).unwrap_or_default(),
+        "name": d.map(|d| d.name.clone()).unwrap_or_default(),
+        "icon": d.map(|d| d.icon.clone()).unwrap_or_default(),
+    })
+}
+
+/// UI に確認/入力を求める。kind: confirm | request-passkey | request-pin | authorize
+fn ask(kind: &str, device: &ObjectPath<'_>, passkey: Option<u32>) -> WaitReply {
+    let id = NEXT_REQUEST.fetch_add(1, Ordering::Relaxed);
+    let slot = ReplySlot::default();
+    REQUESTS.lock().unwrap().insert(id, slot.clone());
+    let mut p = agent_device(device);
+    p["request"] = id.into();
+    p["kind"] = kind.into();
+    p["passkey"] = passkey.map(|k| format!("{:06}", k)).into();
+    emit_passkey(p);
+    WaitReply(slot)
+}
+
+fn show(kind: &str, device: &ObjectPath<'_>, code: String, entered: Option<u16>) {
+    let mut p = agent_device(device);
+    p["kind"] = kind.into();
+    p["passkey"] = code.into();
+    p["entered"] = entered.into();
+    emit_passkey(p);
+}
+
+#[derive(Debug, zbus::DBusError)]
+#[zbus(prefix = "org.bluez.Error")]
+enum AgentError {
+    #[zbus(error)]
+    ZBus(zbus::Error),
+    Rejected(String),
+    Canceled(String),
+}
+
+fn rejected() -> AgentError { AgentError::Rejected("rejected by user".into()) }
+
+struct Agent;
+
+#[zbus::interface(name = "org.bluez.Agent1")]
+impl Agent {
+    fn release(&self) {
+        AGENT_REGISTERED.store(false, Ordering::Relaxed);
+        log_append("INFO", "bluetooth: agent released");
+    }
+
+    async fn request_pin_code(&self, device: OwnedObjectPath) -> Result<String, AgentError> {
+        ask("request-pin", &device, None).await.filter(|p| !p.is_empty() && p.len() <= 16).ok_or_else(rejected)
+    }
+
+    fn display_pin_code(&self, device: OwnedObjectPath, pincode: String) {
+        show("display-pin", &device, pincode, None);
+    }
+
+    async fn request_passkey(&self, device: OwnedObjectPath) -> Result<u32, AgentError> {
+        ask("request-passkey", &device, None).await.and_then(|p| p.trim().parse::<u32>().ok()).filter(|k| *k <= 999_999).ok_or_else(rejected)
+    }
+
+    /// キーボードのペアリング: 表示した 6 桁を機器で打ってもらう（entered は入力済みの桁数）
+    fn display_passkey(&self, device: OwnedObjectPath, passkey: u32, entered: u16) {
+        show("display-passkey", &device, format!("{:06}", passkey), Some(entered));
+    }
+
+    async fn request_confirmation(&self, device: OwnedObjectPath, passkey: u32) -> Result<(), AgentError> {
+        ask("confirm", &device, Some(passkey)).await.map(|_| ()).ok_or_else(rejected)
+    }
+
+    async fn request_authorization(&self, device: OwnedObjectPath) -> Result<(), AgentError> {
+        ask("authorize", &device, None).await.map(|_| ()).ok_or_else(rejected)
+    }
+
+    /// ペア済み・信頼済みの機器のサービス接続だけ許可する
+    fn authorize_service(&self, device: OwnedObjectPath, _uuid: String) -> Result<(), AgentError> {
+        let last = LAST.lock().unwrap();
+        let known = last.as_ref().and_then(|s| s.devices.iter().find(|d| d.path == device.as_str())).is_some_and(|d| d.paired || d.trusted);
+        if known { Ok(()) } else { Err(rejected()) }
+    }
+
+    /// BlueZ 側のタイムアウトや機器側の中止。待っている要求はすべて取り消す
+    fn cancel(&self) {
+        let ids: Vec<u32> = REQUESTS.lock().unwrap().keys().copied().collect();
+        for id in ids { resolve(id, None); }
+        emit_passkey(serde_json::json!({ "kind": "cancel" }));
+    }
+}
+
+fn register_agent(conn: &Connection) -> Result<(), String> {
+    conn.object_server().at(AGENT_PATH, Agent).map_err(|e| format!("agent-failed: {}", e))?;
+    let path = ObjectPath::try_from(AGENT_PATH).map_err(|e| format!("invalid-path: {}", e))?;
+    let mgr = Proxy::new(conn, BLUEZ_DEST, "/org/bluez", "org.bluez.AgentManager1").map_err(|e| format!("bluez-unavailable: {}", e))?;
+    match mgr.call::<_, _, ()>("RegisterAgent", &(&path, AGENT_CAPABILITY)) {
+        Ok(()) => {}
+        Err(e) if e.to_string().contains("AlreadyExists") => {}
+        Err(e) => return Err(bz_err("agent-failed", e)),
+    }
+    mgr.call::<_, _, ()>("RequestDefaultAgent", &(&path,)).map_err(|e| bz_err("agent-failed", e))?;
+    log_append("INFO", "bluetooth: registered default agent");
+    Ok(())
+}
+
+pub fn start(app_handle: tauri::AppHandle) {
+    *APP.lock().unwrap() = Some(app_handle.clone());
+    let conn = match bluez_bus() { Ok(c) => c, Err(_) => { log_append("WARN", "bluetooth: D-Bus unavailable; watcher disabled"); return; } };
+    if let Err(e) = read_state(&conn) { log_append("WARN", &format!("bluetooth: watcher disabled ({})", e)); return; }
+    std::thread::spawn(move || loop {
+        let state = read_state(&conn);
+        // bluetoothd が再起動するとエージェントの登録は消えるので、つながらなかった後は登録し直す
+        if state.is_err() {
+            AGENT_REGISTERED.store(false, Ordering::Relaxed);
+        } else if !AGENT_REGISTERED.load(Ordering::Relaxed) {
+            // 失敗しても毎回はやり直さない（ログが溢れるため。次に bluetoothd が戻ったときに再試行する）
+            if let Err(e) = register_agent(&conn) { log_append("WARN", &format!("bluetooth: {}", e)); }
+            AGENT_REGISTERED.store(true, Ordering::Relaxed);
+        }
+        if let Ok(cur) = state {
+            let mut last = LAST.lock().unwrap();
+            if last.as_ref() != Some(&cur) {
+                if let Some(prev) = last.as_ref() {
+                    for d in &cur.devices {
+                        let before = prev.devices.iter().find(|p| p.path == d.path);
+                        let changed = match before { Some(p) => p.connected != d.connected || p.paired != d.paired, None => d.connected };
+                        if changed {
+                            log_append("INFO", &format!("bluetooth: {} connected={} paired={}", d.address, d.connected, d.paired));
+                            let _ = app_handle.emit("sis:bluetooth-device", d);
+                        }
+                    }
+                }
+                let _ = app_handle.emit("sis:bluetooth-changed", &cur);
+                *last = Some(cur);
+            }
+        }
+        std::thread::sleep(POLL);
+    });
+}
+
+// Pair/Connect は数十秒かかることがあるため別スレッドで実行し、結果はイベントで返す
+fn run_async(app_handle: tauri::AppHandle, id: String, action: &'static str, f: impl FnOnce(&Connection, &BtDevice) -> Result<(), String> + Send + 'static) -> Result<String, SisError> {
+    let conn = bluez_bus()?;
+    let dev = find_device(&conn, &id)?;
+    std::thread::spawn(move || {
+        let res = f(&conn, &dev);
+        if let Err(e) = &res { log_append("WARN", &format!("bluetooth: {} {} failed: {}", action, dev.address, e)); }
+        let _ = app_handle.emit("sis:bluetooth-action", serde_json::json!({
+            "address": dev.address,
+            "action": action,
+            "ok": res.is_ok(),
+            "error": res.err().map(SisError::from),
+        }));
+    });
+    Ok(format!("{}-started", action))
+}
+
+/// エージェントの要求への応答。request-passkey/request-pin では value に入力値を渡す
+#[tauri::command]
+pub fn bluetooth_agent_reply(request: u32, accept: bool, value: Option<String>) -> Result<String, SisError> {
+    let v = if accept { Some(value.unwrap_or_default()) } else { None };
+    if resolve(request, v) { Ok(if accept { "accepted".into() } else { "rejected".into() }) } else { Err("not-found".into()) }
+}
+
+#[tauri::command]
+pub fn bluetooth_state() -> Result<BluetoothState, SisError> { Ok(read_state(&bluez_bus()?)?) }
+
+/// 周辺デバイスの検出。enable=true で seconds 秒後に自動停止
+#[tauri::command]
+pub fn bluetooth_discover(enable: bool, seconds: Option<u64>) -> Result<String, SisError> {
+    let conn = bluez_bus()?;
+    let a = adapter_proxy(&conn)?;
+    if !enable {
+        let _: () = a.call("StopDiscovery", &()).map_err(|e| bz_err("discovery-failed", e))?;
+        return Ok("discovery-stopped".into());
+    }
+    let _: () = a.call("StartDiscovery", &()).map_err(|e| bz_err("discovery-failed", e))?;
+    let secs = seconds.unwrap_or(DEFAULT_DISCOVERY_SECS).clamp(5, 120);
+    std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_secs(secs));
+        let _ = a.call::<_, _, ()>("StopDiscovery", &());
+    });
+    Ok("discovery-started".into())
+}
+
+/// ペアリング → 信頼 → 接続 までを行う
+#[tauri::command]
+pub fn bluetooth_pair(app_handle: tauri::AppHandle, id: String) -> Result<String, SisError> {
+    run_async(app_handle, id, "pair", |conn, dev| {
+        let p = device_proxy(conn, dev)?;
+        if !dev.paired { let _: () = p.call("Pair", &()).map_err(|e| bz_err("pair-failed", e))?; }
+        // 次回から自動接続されるよう信頼済みにする
+        p.set_property("Trusted", true).map_err(|e| bz_err("trust-failed", e.into()))?;
+        let _: () = p.call("Connect", &()).map_err(|e| bz_err("connect-failed", e))?;
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn bluetooth_connect(app_handle: tauri::AppHandle, id: String) -> Result<String, SisError> {
+    run_async(app_handle, id, "connect", |conn, dev| {
+        let _: () = device_proxy(conn, dev)?.call("Connect", &()).map_err(|e| bz_err("connect-failed", e))?;
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn bluetooth_disconnect(id: String) -> Result<String, SisError> {
+    let conn = bluez_bus()?;
+    let dev = find_device(&conn, &id)?;
+    let _: () = device_proxy(&conn, &dev)?.call("Disconnect", &()).map_err(|e| bz_err("disconnect-failed", e))?;
+    Ok("disconnected".into())
+}
+
+#[tauri::command]
+pub fn bluetooth_set_trusted(id: String, trusted: bool) -> Result<String, SisError> {
+    let conn = bluez_bus()?;
+    let dev = find_device(&conn, &id)?;
+    device_proxy(&conn, &dev)?.set_property("Trusted", trusted).map_err(|e| bz_err("trust-failed", e.into()))?;
+    Ok(if trusted { "trusted".into() } else { "untrusted".into() })
+}
+
+/// ペアリング解除（アダプタからデバイスを削除）
+#[tauri::command]
+pub fn bluetooth_remove(id: String) -> Result<String, SisError> {
+    let conn = bluez_bus()?;
+    let dev = find_device(&conn, &id)?;
+    let path = OwnedObjectPath::from(ObjectPath::try_from(dev.path.clone()).map_err(|e| format!("invalid-path: {}", e))?);
+    let _: () = adapter_proxy(&conn)?.call("RemoveDevice", &(path,)).map_err(|e| bz_err("remove-failed", e))?;
+    log_append("INFO", &format!("bluetooth: removed {}", dev.address));
+    Ok("removed".into())
+}
@@ -0,0 +1,527 @@
+// クリップボード履歴: X11 は XFixes で CLIPBOARD/PRIMARY の所有者の変化を受けて xclip で読み取り、
+// Wayland は wl-paste --watch（data-control 対応のコンポジタ）で通知を受ける。
+// 履歴は ~/.local/share/sis-ui/clipboard に保存。/tmp/sis-exam-mode がある間は履歴を消して停止する
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use image::RgbaImage;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+
+use crate::error::SisError;
+use crate::{exam_mode, history_dir, log_append, which};
+
+const EXAM_POLL: Duration = Duration::from_secs(1);
+// ピン留め以外の保持件数
+const MAX_ENTRIES: usize = 200;
+const MAX_TEXT_BYTES: usize = 256 * 1024;
+const MAX_IMAGE_BYTES: usize = 16 * 1024 * 1024;
+// PRIMARY はドラッグ中に伸び縮みするので、直前の項目をこの時間内なら置き換える
+const PRIMARY_MERGE_MS: i64 = 5000;
+const READ_TIMEOUT: &str = "2";
+// パスワードマネージャ等が「履歴に残さないで」と示す MIME
+const SENSITIVE_TYPES: [&str; 2] = ["x-kde-passwordManagerHint", "application/x-nspasswordhint"];
+const TEXT_TYPES: [&str; 5] = ["text/plain;charset=utf-8", "UTF8_STRING", "text/plain", "STRING", "TEXT"];
+
+static APP: Lazy<Mutex<Option<tauri::AppHandle>>> = Lazy::new(|| Mutex::new(None));
+static HISTORY: Lazy<Mutex<Vec<ClipEntry>>> = Lazy::new(|| Mutex::new(load_history()));
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+static EXAM_MODE: AtomicBool = AtomicBool::new(false);
+// 選択ごとの直前のキー（X11 の TIMESTAMP か内容ハッシュ）
+static LAST_KEY: Lazy<Mutex<HashMap<String, String>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ClipEntry {
+    id: u64,
+    kind: String,      // text | html | image
+    selection: String, // clipboard | primary
+    text: String,      // プレーンテキスト（画像では空）
+    html: Option<String>,
+    image: Option<String>, // images/ 以下のファイル名
+    width: Option<u32>,
+    height: Option<u32>,
+    thumbnail: Option<String>,
+    bytes: usize,
+    hash: String,
+    pinned: bool,
+    created_ms: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ClipboardList {
+    enabled: bool,
+    exam_mode: bool,
+    backend: Option<String>, // x11 | wayland
+    entries: Vec<ClipEntry>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Backend { X11, Wayland }
+
+fn backend() -> Option<Backend> {
+    if crate::screenshot::is_wayland() {
+        if which("wl-paste") && which("wl-copy") { return Some(Backend::Wayland); }
+    } else if std::env::var("DISPLAY").is_ok() && which("xclip") {
+        return Some(Backend::X11);
+    }
+    None
+}
+
+fn now_ms() -> i64 { chrono::Utc::now().timestamp_millis() }
+
+fn clip_dir() -> Option<PathBuf> { history_dir().map(|d| d.join("clipboard")) }
+
+fn images_dir() -> Option<PathBuf> { clip_dir().map(|d| d.join("images")) }
+
+fn load_history() -> Vec<ClipEntry> {
+    let list: Vec<ClipEntry> = clip_dir()
+        .and_then(|d| fs::read_to_string(d.join("history.json")).ok())
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default();
+    if let Some(max) = list.iter().map(|e| e.id).max() { NEXT_ID.store(max + 1, Ordering::SeqCst); }
+    list
+}
+
+fn save_history(list: &[ClipEntry]) {
+    if let Some(dir) = clip_dir() {
+        let _ = fs::create_dir_all(&dir);
+        if let Ok(s) = serde_json::to_string(list) { let _ = fs::write(dir.join("history.json"), s); }
+    }
+}
+
+fn emit_changed() {
+    if let Some(app) = APP.lock().unwrap().as_ref() { let _ = app.emit("sis:clipboard-changed", !EXAM_MODE.load(Ordering::SeqCst)); }
+}
+
+fn hash_bytes(b: &[u8]) -> String {
+    let mut h = DefaultHasher::new();
+    b.hash(&mut h);
+    format!("{:016x}", h.finish())
+}
+
+// ---- 読み書き ----
+
+fn read_cmd(b: Backend, selection: &str, args: &[&str]) -> Option<Vec<u8>> {
+    let mut cmd = Command::new("timeout");
+    cmd.arg(READ_TIMEOUT);
+    match b {
+        Backend::X11 => { cmd.args(["xclip", "-o", "-selection", selection]); }
+        Backend::Wayland => {
+            cmd.args(["wl-paste", "--no-newline"]);
+            if selection == "primary" { cmd.arg("--primary"); }
+        }
+    }
+    let out = cmd.args(args).stderr(Stdio::null()).output().ok()?;
+    if out.status.success() { Some(out.stdout) } else { None }
+}
+
+fn targets(b: Backend, selection: &str) -> Vec<String> {
+    let raw = match b {
+        Backend::X11 => read_cmd(b, selection, &["-t", "TARGETS"]),
+        Backend::Wayland => read_cmd(b, selection, &["--list-types"]),
+    };
+    raw.map(|r| String::from_utf8_lossy(&r).lines().map(|l| l.trim().to_string()).filter(|l| !l.is_empty()).collect()).unwrap_or_default()
+}
+
+fn read_type(b: Backend, selection: &str, mime: &str) -> Option<Vec<u8>> {
+    match b {
+        Backend::X11 => read_cmd(b, selection, &["-t", mime]),
+        Backend::Wayland => read_cmd(b, selection, &["--type", mime]),
+    }
+}
+
+fn write_type(b: Backend, selection: &str, mime: &str, data: &[u8]) -> Result<(), String> {
+    let mut cmd = match b {
+        Backend::X11 => {
+            let mut c = Command::new("xclip");
+            c.args(["-selection", selection, "-t", mime, "-i"]);
+            c
+        }
+        Backend::Wayland => {
+            let mut c = Command::new("wl-copy");
+            if selection == "primary" { c.arg("--primary"); }
+            c.args(["--type", mime]);
+            c
+        }
+    };
+    let mut child = cmd.stdin(Stdio::piped()).stdout(Stdio::null()).stderr(Stdio::null()).spawn().map_err(|e| format!("clipboard-failed: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(data).map_err(|e| format!("clipboard-failed: {}", e))?;
+    }
+    let _ = child.wait();
+    Ok(())
+}
+
+// ---- 取り込み ----
+
+fn read_text(b: Backend, selection: &str, types: &[String]) -> Option<String> {
+    let mime = TEXT_TYPES.iter().find(|t| types.iter().any(|x| x == *t))?;
+    let data = read_type(b, selection, mime)?;
+    if data.is_empty() || data.len() > MAX_TEXT_BYTES { return None; }
+    let s = String::from_utf8_lossy(&data).to_string();
+    if s.trim().is_empty() { None } else { Some(s) }
+}
+
+fn store_image(png: &[u8]) -> Option<(String, RgbaImage)> {
+    let img = image::load_from_memory_with_format(png, image::ImageFormat::Png).ok()?.to_rgba8();
+    let dir = images_dir()?;
+    fs::create_dir_all(&dir).ok()?;
+    let name = format!("{}.png", hash_bytes(png));
+    let p = dir.join(&name);
+    if !p.exists() { fs::write(&p, png).ok()?; }
+    Some((name, img))
+}
+
+fn capture(b: Backend, selection: &str) {
+    if EXAM_MODE.load(Ordering::SeqCst) || exam_mode() { return; }
+    let types = targets(b, selection);
+    if types.is_empty() { return; }
+    if types.iter().any(|t| SENSITIVE_TYPES.contains(&t.as_str())) { return; }
+
+    // X11 は TIMESTAMP（所有者が選択を取った時刻）が変わらなければ中身は同じ
+    if b == Backend::X11 && types.iter().any(|t| t == "TIMESTAMP") {
+        if let Some(ts) = read_type(b, selection, "TIMESTAMP") {
+            let key = format!("{}:{}", String::from_utf8_lossy(&ts).trim(), types.join(","));
+            let mut last = LAST_KEY.lock().unwrap();
+            if last.get(selection) == Some(&key) { return; }
+            last.insert(selection.to_string(), key);
+        }
+    }
+
+    let mut entry = ClipEntry {
+        id: 0, kind: "text".into(), selection: selection.to_string(), text: String::new(), html: None, image: None,
+        width: None, height: None, thumbnail: None, bytes: 0, hash: String::new(), pinned: false, created_ms: now_ms(),
+    };
+    if selection == "clipboard" && types.iter().any(|t| t == "image/png") {
+        let Some(png) = read_type(b, selection, "image/png") else { return };
+        if png.is_empty() || png.len() > MAX_IMAGE_BYTES { return; }
+        let Some((name, img)) = store_image(&png) else { return };
+        entry.kind = "image".into();
+        entry.hash = name.trim_end_matches(".png").to_string();
+        entry.width = Some(img.width());
+        entry.height = Some(img.height());
+        entry.thumbnail = crate::screenshot::thumbnail_data_url(&img);
+        entry.image = Some(name);
+        entry.bytes = png.len();
+    } else {
+        let Some(text) = read_text(b, selection, &types) else { return };
+        if selection == "clipboard" && types.iter().any(|t| t == "text/html") {
+            entry.html = read_type(b, selection, "text/html").filter(|h| h.len() <= MAX_TEXT_BYTES).map(|h| String::from_utf8_lossy(&h).to_string());
+            if entry.html.is_some() { entry.kind = "html".into(); }
+        }
+        entry.bytes = text.len();
+        entry.hash = hash_bytes(text.as_bytes());
+        entry.text = text;
+    }
+
+    // TIMESTAMP が無い環境向けに内容でも判定する
+    {
+        let mut last = LAST_KEY.lock().unwrap();
+        let k = format!("{}#content", selection);
+        if last.get(&k) == Some(&entry.hash) { return; }
+        last.insert(k, entry.hash.clone());
+    }
+    add_entry(entry);
+}
+
+fn add_entry(mut entry: ClipEntry) {
+    {
+        let mut list = HISTORY.lock().unwrap();
+        // 読み取り中に試験モードになっていたら、消したあとの履歴に書き戻さない
+        if EXAM_MODE.load(Ordering::SeqCst) || exam_mode() { return; }
+        // 同じ内容は先頭へ移動（ピン留めは維持）
+        if let Some(pos) = list.iter().position(|e| e.hash == entry.hash && e.kind == entry.kind) {
+            let mut old = list.remove(pos);
+            old.created_ms = entry.created_ms;
+            list.insert(0, old);
+        } else {
+            if entry.selection == "primary" {
+                if let Some(first) = list.first() {
+                    let grew = first.selection == "primary" && !first.pinned && entry.created_ms - first.created_ms < PRIMARY_MERGE_MS
+                        && (entry.text.contains(&first.text) || first.text.contains(&entry.text));
+                    if grew { list.remove(0); }
+                }
+            }
+            entry.id = NEXT_ID.fetch_add(1, Ordering::SeqCst);
+            list.insert(0, entry);
+        }
+        prune(&mut list);
+        save_history(&list);
+    }
+    emit_changed();
+}
+
+/// 上限を超えた未ピン留めの項目と、参照されなくなった画像を消す
+fn prune(list: &mut Vec<ClipEntry>) {
+    let mut unpinned = 0;
+    list.retain(|e| {
+        if e.pinned { return true; }
+        unpinned += 1;
+        unpinned <= MAX_ENTRIES
+    });
+    if let Some(dir) = images_dir() {
+        if let Ok(rd) = fs::read_dir(&dir) {
+            for f in rd.flatten() {
+                let name = f.file_name().to_string_lossy().to_string();
+                if !list.iter().any(|e| e.image.as_deref() == Some(name.as_str())) { let _ = fs::remove_file(f.path()); }
+            }
+        }
+    }
+}
+
+/// 試験モード: 履歴（ピン留めも含む）と画像を消して停止する
+fn wipe() {
+    HISTORY.lock().unwrap().clear();
+    LAST_KEY.lock().unwrap().clear();
+    if let Some(dir) = clip_dir() { let _ = fs::remove_dir_all(dir); }
+}
+
+fn check_exam_mode() {
+    let on = exam_mode();
+    if on == EXAM_MODE.swap(on, Ordering::SeqCst) { return; }
+    if on {
+        wipe();
+        log_append("INFO", "clipboard: exam mode detected; history wiped and disabled");
+    } else {
+        log_append("INFO", "clipboard: exam mode ended; history resumed");
+    }
+    emit_changed();
+}
+
+mod x11 {
+    use x11rb::connection::Connection as _;
+    use x11rb::protocol::xfixes::{ConnectionExt as _, SelectionEventMask};
+    use x11rb::protocol::xproto::{self, Atom, AtomEnum, ConnectionExt as _, CreateWindowAux, EventMask, PropMode, SelectionRequestEvent, Window, WindowClass};
+    use x11rb::protocol::Event;
+    use x11rb::rust_connection::RustConnection;
+    use x11rb::wrapper::ConnectionExt as _;
+
+    fn err(e: impl std::fmt::Display) -> String { format!("x11-failed: {}", e) }
+
+    fn connect() -> Result<(RustConnection, Window), String> {
+        let (conn, screen) = x11rb::connect(None).map_err(|e| format!("x11-connect-failed: {}", e))?;
+        let root = conn.setup().roots.get(screen).ok_or("x11-no-screen")?.root;
+        Ok((conn, root))
+    }
+
+    fn atom(conn: &RustConnection, name: &str) -> Result<Atom, String> {
+        Ok(conn.intern_atom(false, name.as_bytes()).map_err(err)?.reply().map_err(err)?.atom)
+    }
+
+    /// CLIPBOARD/PRIMARY の所有者が変わるたびに on_change(選択名) を呼ぶ。接続が切れるまで戻らない
+    pub fn watch(on_change: impl Fn(&'static str)) -> Result<(), String> {
+        let (conn, root) = connect()?;
+        conn.xfixes_query_version(5, 0).map_err(err)?.reply().map_err(|e| format!("xfixes-unavailable: {}", e))?;
+        let clipboard = atom(&conn, "CLIPBOARD")?;
+        let mask = SelectionEventMask::SET_SELECTION_OWNER | SelectionEventMask::SELECTION_WINDOW_DESTROY | SelectionEventMask::SELECTION_CLIENT_CLOSE;
+        for sel in [clipboard, AtomEnum::PRIMARY.into()] { conn.xfixes_select_selection_input(root, sel, mask).map_err(err)?; }
+        conn.flush().map_err(err)?;
+        loop {
+            if let Event::XfixesSelectionNotify(ev) = conn.wait_for_event().map_err(err)? {
+                // 所有者がいなくなった通知では読むものが無い
+                if ev.owner != x11rb::NONE { on_change(if ev.selection == clipboard { "clipboard" } else { "primary" }); }
+            }
+        }
+    }
+
+    /// CLIPBOARD を自分で持ち、(MIME, 中身) の組を全部提供する。xclip は1種類しか出せないので、
+    /// HTML と同時にプレーンテキストも渡したいときに使う。他のアプリが所有権を取るまで別スレッドで応答する
+    pub fn offer(items: Vec<(&str, Vec<u8>)>) -> Result<(), String> {
+        let (conn, root) = connect()?;
+        let win = conn.generate_id().map_err(err)?;
+        conn.create_window(0, win, root, 0, 0, 1, 1, 0, WindowClass::INPUT_ONLY, 0, &CreateWindowAux::new()).map_err(err)?;
+        let clipboard = atom(&conn, "CLIPBOARD")?;
+        let targets = atom(&conn, "TARGETS")?;
+        let offered: Vec<(Atom, Vec<u8>)> = items.into_iter().map(|(t, d)| Ok((atom(&conn, t)?, d))).collect::<Result<_, String>>()?;
+        conn.set_selection_owner(win, clipboard, x11rb::CURRENT_TIME).map_err(err)?;
+        if conn.get_selection_owner(clipboard).map_err(err)?.reply().map_err(err)?.owner != win {
+            return Err("clipboard-failed: selection not acquired".into());
+        }
+        std::thread::spawn(move || {
+            while let Ok(ev) = conn.wait_for_event() {
+                match ev {
+                    Event::SelectionRequest(req) => reply(&conn, &req, targets, &offered),
+                    Event::SelectionClear(_) => break,
+                    _ => {}
+                }
+            }
+            let _ = conn.destroy_window(win);
+            let _ = conn.flush();
+        });
+        Ok(())
+    }
+
+    fn reply(conn: &RustConnection, req: &SelectionRequestEvent, targets: Atom, offered: &[(Atom, Vec<u8>)]) {
+        // 古いクライアントは property を None で送ってくる
+        let prop = if req.property == x11rb::NONE { req.target } else { req.property };
+        let ok = if req.target == targets {
+            let mut list: Vec<Atom> = offered.iter().map(|(a, _)| *a).collect();
+            list.push(targets);
+            conn.change_property32(PropMode::REPLACE, req.requestor, prop, AtomEnum::ATOM, &list).is_ok()
+        } else if let Some((_, data)) = offered.iter().find(|(a, _)| *a == req.target) {
+            conn.change_property8(PropMode::REPLACE, req.requestor, prop, req.target, data).is_ok()
+        } else {
+            false
+        };
+        let ev = xproto::SelectionNotifyEvent {
+            response_type: xproto::SELECTION_NOTIFY_EVENT,
+            sequence: 0,
+            time: req.time,
+            requestor: req.requestor,
+            selection: req.selection,
+            target: req.target,
+            property: if ok { prop } else { x11rb::NONE },
+        };
+        let _ = conn.send_event(false, req.requestor, EventMask::NO_EVENT, ev);
+        let _ = conn.flush();
+    }
+}
+
+/// HTML の項目は text/html だけだとプレーンテキストしか貼れないアプリで何も貼れないので、テキストも一緒に出す。
+/// wl-copy は1種類しか出せないため Wayland ではテキストとして戻す
+fn copy_html(b: Backend, entry: &ClipEntry) -> Result<(), String> {
+    let html = entry.html.as_deref().unwrap_or("");
+    if b == Backend::Wayland || html.is_empty() { return write_type(b, "clipboard", "text/plain;charset=utf-8", entry.text.as_bytes()); }
+    let mut items = vec![("text/html", html.as_bytes().to_vec())];
+    // STRING は Latin-1 なので UTF-8 の本文は出さない
+    items.extend(TEXT_TYPES.iter().filter(|t| **t != "STRING").map(|t| (*t, entry.text.as_bytes().to_vec())));
+    x11::offer(items)
+}
+
+fn watch_wayland(selection: &'static str) {
+    std::thread::spawn(move || loop {
+        let mut cmd = Command::new("wl-paste");
+        if selection == "primary" { cmd.arg("--primary"); }
+        // data-control が無いコンポジタでは即座に終了する
+        let child = cmd.args(["--watch", "echo", "changed"]).stdout(Stdio::piped()).stderr(Stdio::null()).spawn();
+        let Ok(mut child) = child else { return };
+        if let Some(out) = child.stdout.take() {
+            for _ in BufReader::new(out).lines().map_while(Result::ok) { capture(Backend::Wayland, selection); }
+        }
+        let _ = child.wait();
+        log_append("WARN", &format!("clipboard: wl-paste --watch ({}) exited", selection));
+        std::thread::sleep(Duration::from_secs(5));
+    });
+}
+
+pub fn start(app_handle: tauri::AppHandle) {
+    *APP.lock().unwrap() = Some(app_handle);
+    Lazy::force(&HISTORY);
+    check_exam_mode();
+    std::thread::spawn(|| loop {
+        check_exam_mode();
+        std::thread::sleep(EXAM_POLL);
+    });
+    match backend() {
+        Some(Backend::X11) => {
+            std::thread::spawn(|| loop {
+                // 起動時点の中身を取り込んでから変化を待つ
+                capture(Backend::X11, "clipboard");
+                capture(Backend::X11, "primary");
+                if let Err(e) = x11::watch(|selection| capture(Backend::X11, selection)) {
+                    log_append("WARN", &format!("clipboard: selection watch stopped: {}", e));
+                }
+                std::thread::sleep(Duration::from_secs(5));
+            });
+        }
+        Some(Backend::Wayland) => {
+            watch_wayland("clipboard");
+            watch_wayland("primary");
+        }
+        None => log_append("INFO", "clipboard: no xclip/wl-clipboard; history disabled"),
+    }
+}
+
+fn ensure_enabled() -> Result<(), String> {
+    if EXAM_MODE.load(Ordering::SeqCst) { Err("exam-mode".into()) } else { Ok(()) }
+}
+
+#[tauri::command]
+pub fn clipboard_list(query: Option<String>, kind: Option<String>, limit: Option<usize>) -> Result<ClipboardList, SisError> {
+    let exam = EXAM_MODE.load(Ordering::SeqCst);
+    let b = backend();
+    let q = query.unwrap_or_default().to_lowercase();
+    let entries = if exam { Vec::new() } else {
+        HISTORY.lock().unwrap().iter()
+            .filter(|e| kind.as_deref().map(|k| e.kind == k).unwrap_or(true))
+            .filter(|e| q.is_empty() || e.text.to_lowercase().contains(&q))
+            .take(limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect()
+    };
+    Ok(ClipboardList {
+        enabled: !exam && b.is_some(),
+        exam_mode: exam,
+        backend: b.map(|b| if b == Backend::X11 { "x11".to_string() } else { "wayland".to_string() }),
+        entries,
+    })
+}
+
+/// 履歴の項目をクリップボードへ戻す。plain=true なら HTML でもテキストとして貼る
+#[tauri::command]
+pub fn clipboard_copy(id: u64, plain: Option<bool>) -> Result<String, SisError> {
+    ensure_enabled()?;
+    let b = backend().ok_or("no-clipboard-tool")?;
+    let entry = HISTORY.lock().unwrap().iter().find(|e| e.id == id).cloned().ok_or("not-found")?;
+    match entry.kind.as_str() {
+        "image" => {
+            let p = images_dir().zip(entry.image.as_ref()).map(|(d, n)| d.join(n)).ok_or("not-found")?;
+            let png = fs::read(p).map_err(|e| format!("read-failed: {}", e))?;
+            write_type(b, "clipboard", "image/png", &png)?;
+        }
+        "html" if !plain.unwrap_or(false) => copy_html(b, &entry)?,
+        _ => write_type(b, "clipboard", "text/plain;charset=utf-8", entry.text.as_bytes())?,
+    }
+    Ok("copied".into())
+}
+
+#[tauri::command]
+pub fn clipboard_pin(id: u64, pinned: bool) -> Result<String, SisError> {
+    ensure_enabled()?;
+    {
+        let mut list = HISTORY.lock().unwrap();
+        let e = list.iter_mut().find(|e| e.id == id).ok_or("not-found")?;
+        e.pinned = pinned;
+        save_history(&list);
+    }
+    emit_changed();
+    Ok(if pinned { "pinned".into() } else { "unpinned".into() })
+}
+
+#[tauri::command]
+pub fn clipboard_delete(id: u64) -> Result<String, SisError> {
+    ensure_enabled()?;
+    {
+        let mut list = HISTORY.lock().unwrap();
+        let before = list.len();
+        list.retain(|e| e.id != id);
+        if list.len() == before { return Err("not-found".into()); }
+        prune(&mut list);
+        save_history(&list);
+    }
+    emit_changed();
+    Ok("deleted".into())
+}
+
+/// 既定ではピン留めを残す
+#[tauri::command]
+pub fn clipboard_clear(include_pinned: Option<bool>) -> Result<String, SisError> {
+    ensure_enabled()?;
+    {
+        let mut list = HISTORY.lock().unwrap();
+        if include_pinned.unwrap_or(false) { list.clear(); } else { list.retain(|e| e.pinned); }
+        prune(&mut list);
+        save_history(&list);
+    }
+    emit_changed();
+    Ok("cleared".into())
+}
@@ -0,0 +1,200 @@
+// ダウンロードの自動整理（任意）: ダウンロードフォルダを notify で監視し、書き込みが止まって quiet_secs 経った
+// ファイルを organizer のルールで移動する。.part/.crdownload などの一時ファイルや、ブラウザが最後のリネームを
+// する前の仮ファイルは対象外。移動したら「元に戻す」「フォルダを開く」付きの通知を出す
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+use notify::event::{AccessKind, AccessMode, ModifyKind, RenameMode};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+
+use crate::error::SisError;
+use crate::notifications::NotifAction;
+use crate::organizer::{self, OrganizePlan};
+use crate::{history_dir, log_append};
+
+const TICK: Duration = Duration::from_secs(1);
+const MIN_QUIET_SECS: u64 = 2;
+const MAX_QUIET_SECS: u64 = 600;
+
+static APP: Lazy<Mutex<Option<tauri::AppHandle>>> = Lazy::new(|| Mutex::new(None));
+static CONFIG: Lazy<Mutex<WatchConfig>> = Lazy::new(|| Mutex::new(read_config()));
+static WATCHER: Lazy<Mutex<Option<(RecommendedWatcher, PathBuf)>>> = Lazy::new(|| Mutex::new(None));
+// 書き込みが落ち着くのを待っているファイル
+static PENDING: Lazy<Mutex<HashMap<PathBuf, Pending>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+// 「元に戻す」でダウンロードフォルダへ戻したファイル（このセッションでは再び動かさない）
+static RESTORED: Lazy<Mutex<HashSet<PathBuf>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct WatchConfig {
+    enabled: bool,
+    quiet_secs: u64, // 最後の書き込みからこの秒数、大きさも更新日時も変わらなければ完了とみなす
+    notify: bool,
+}
+
+impl Default for WatchConfig {
+    fn default() -> Self { Self { enabled: false, quiet_secs: 5, notify: true } }
+}
+
+struct Pending {
+    seen: Instant,
+    size: u64,
+    modified: Option<SystemTime>,
+}
+
+fn config_path() -> Option<PathBuf> { history_dir().map(|d| d.join("downloads-watch.json")) }
+
+fn read_config() -> WatchConfig {
+    config_path().and_then(|p| fs::read_to_string(p).ok()).and_then(|s| serde_json::from_str(&s).ok()).unwrap_or_default()
+}
+
+fn write_config(c: &WatchConfig) -> Result<(), String> {
+    let p = config_path().ok_or("no-data-dir")?;
+    if let Some(dir) = p.parent() { let _ = fs::create_dir_all(dir); }
+    fs::write(&p, serde_json::to_string_pretty(c).map_err(|e| e.to_string())?).map_err(|e| format!("write-failed: {}", e))
+}
+
+fn emit<S: Serialize + Clone>(event: &str, payload: S) {
+    if let Some(app) = APP.lock().unwrap().as_ref() { let _ = app.emit(event, payload); }
+}
+
+fn touch(path: &Path) {
+    if RESTORED.lock().unwrap().contains(path) || organizer::download_in_progress(path) { return; }
+    let Ok(meta) = fs::metadata(path) else { return; };
+    if !meta.is_file() { return; }
+    PENDING.lock().unwrap().insert(path.to_path_buf(), Pending { seen: Instant::now(), size: meta.len(), modified: meta.modified().ok() });
+}
+
+fn forget(path: &Path) {
+    PENDING.lock().unwrap().remove(path);
+}
+
+fn handle_event(ev: Event) {
+    match ev.kind {
+        EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(RenameMode::From)) => ev.paths.iter().for_each(|p| forget(p)),
+        // ブラウザは "x.pdf.part" → "x.pdf" のように最後にリネームする
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => {
+            if let [from, to] = ev.paths.as_slice() { forget(from); touch(to); }
+        }
+        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Access(AccessKind::Close(AccessMode::Write)) => ev.paths.iter().for_each(|p| touch(p)),
+        _ => {}
+    }
+}
+
+/// 設定に合わせて監視を開始/停止する。ダウンロードフォルダの場所が変わっていれば張り直す
+fn apply(cfg: &WatchConfig) -> Result<(), String> {
+    let mut w = WATCHER.lock().unwrap();
+    if !cfg.enabled {
+        if w.take().is_some() { log_append("INFO", "downloads: watcher stopped"); }
+        PENDING.lock().unwrap().clear();
+        return Ok(());
+    }
+    let dir = organizer::user_dir("downloads").ok_or("cannot-detect-home")?;
+    if w.as_ref().is_some_and(|(_, d)| *d == dir) { return Ok(()); }
+    if !dir.is_dir() { return Err(format!("downloads-dir-missing: {}", dir.display())); }
+    let mut watcher = notify::recommended_watcher(|res: notify::Result<Event>| match res {
+        Ok(ev) => handle_event(ev),
+        Err(e) => log_append("WARN", &format!("downloads: watch error: {}", e)),
+    }).map_err(|e| format!("watch-failed: {}", e))?;
+    watcher.watch(&dir, RecursiveMode::NonRecursive).map_err(|e| format!("watch-failed: {}", e))?;
+    log_append("INFO", &format!("downloads: watching {}", dir.display()));
+    *w = Some((watcher, dir));
+    Ok(())
+}
+
+fn open_folder(dir: &Path) {
+    if let Err(e) = Command::new("xdg-open").arg(dir).spawn() {
+        log_append("WARN", &format!("downloads: xdg-open failed: {}", e));
+    }
+}
+
+fn undo(plan: &OrganizePlan) {
+    match organizer::undo_move(plan) {
+        Ok(back) => {
+            RESTORED.lock().unwrap().insert(back.clone());
+            forget(&back);
+            emit("sis:download-restored", serde_json::json!({ "path": back, "plan": plan }));
+        }
+        Err(e) => {
+            let err = SisError::from(e);
+            log_append("WARN", &format!("downloads: undo failed: {}", err));
+            crate::notifications::post("ファイル整理", "元に戻せませんでした", &err.message(), Some("dialog-warning"), "normal", Vec::new(), None);
+        }
+    }
+}
+
+fn notify_moved(plan: OrganizePlan) {
+    let dest = PathBuf::from(plan.destination().unwrap_or_default());
+    let name = dest.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let dir = dest.parent().map(Path::to_path_buf).unwrap_or_default();
+    let actions = vec![NotifAction::new("undo", "元に戻す"), NotifAction::new("open-folder", "フォルダを開く")];
+    let id = crate::notifications::post("ファイル整理", &format!("{} を整理しました", name), &format!("{} に移動しました", dir.display()), Some("folder-download"), "low", actions, None);
+    crate::notifications::on_action(id, move |key| match key {
+        "undo" => undo(&plan),
+        "open-folder" | "default" => open_folder(&dir),
+        _ => {}
+    });
+}
+
+fn organize(path: &Path) {
+    match organizer::organize_path(path, false) {
+        Ok(plan) if plan.done() => {
+            emit("sis:download-organized", &plan);
+            if CONFIG.lock().unwrap().notify { notify_moved(plan); }
+        }
+        // ルールに合わない・同名でスキップなどはそのまま置いておく
+        Ok(_) => {}
+        Err(e) => log_append("WARN", &format!("downloads: organize {} failed: {}", path.display(), e)),
+    }
+}
+
+/// 静かになったファイルを取り出す。待っている間に大きさや更新日時が変わっていたら（通知の来ない書き込み）待ち直す
+fn settled(quiet: Duration) -> Vec<PathBuf> {
+    let mut pending = PENDING.lock().unwrap();
+    let due: Vec<PathBuf> = pending.iter().filter(|(_, p)| p.seen.elapsed() >= quiet).map(|(k, _)| k.clone()).collect();
+    let mut out = Vec::new();
+    for path in due {
+        let Some(p) = pending.remove(&path) else { continue; };
+        let Ok(meta) = fs::metadata(&path) else { continue; };
+        if !meta.is_file() { continue; }
+        if meta.len() != p.size || meta.modified().ok() != p.modified {
+            pending.insert(path, Pending { seen: Instant::now(), size: meta.len(), modified: meta.modified().ok() });
+            continue;
+        }
+        // 仮ファイルは本体の .part がリネームされたときに改めて拾う
+        if !organizer::download_in_progress(&path) { out.push(path); }
+    }
+    out
+}
+
+pub fn start(app_handle: tauri::AppHandle) {
+    *APP.lock().unwrap() = Some(app_handle);
+    let cfg = CONFIG.lock().unwrap().clone();
+    if let Err(e) = apply(&cfg) { log_append("WARN", &format!("downloads: {}", e)); }
+    std::thread::spawn(|| loop {
+        std::thread::sleep(TICK);
+        let quiet = Duration::from_secs(CONFIG.lock().unwrap().quiet_secs);
+        for path in settled(quiet) { organize(&path); }
+    });
+}
+
+#[tauri::command]
+pub fn downloads_watch_get_config() -> Result<WatchConfig, SisError> { Ok(CONFIG.lock().unwrap().clone()) }
+
+#[tauri::command]
+pub fn downloads_watch_set_config(config: WatchConfig) -> Result<WatchConfig, SisError> {
+    if !(MIN_QUIET_SECS..=MAX_QUIET_SECS).contains(&config.quiet_secs) {
+        return Err(SisError::invalid("invalid-quiet-period", config.quiet_secs));
+    }
+    apply(&config)?;
+    write_config(&config)?;
+    *CONFIG.lock().unwrap() = config.clone();
+    Ok(config)
+}
@@ -0,0 +1,276 @@
+// エラー: Tauri コマンドが返す共通のエラー型。フロントエンドには {code, message, details} で渡す。
+// code は従来のケバブケースのコード（"not-authorized" など）をそのまま使い、message はロケールに合わせて
+// locales/errors.{ja,en}.json から引く（コードが載っていなければ種類ごとの既定文）。
+// 各モジュールの内部関数は "code: 詳細" の文字列を返してよく、コマンドの境界で From<String> により変換される。
+// 生の stderr などコードでない文字列は message にせず details に入れる
+use std::collections::HashMap;
+use std::fmt;
+
+use once_cell::sync::Lazy;
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+
+static CATALOG_JA: Lazy<HashMap<String, String>> = Lazy::new(|| parse_catalog(include_str!("../locales/errors.ja.json")));
+static CATALOG_EN: Lazy<HashMap<String, String>> = Lazy::new(|| parse_catalog(include_str!("../locales/errors.en.json")));
+static LOCALE: Lazy<Locale> = Lazy::new(detect_locale);
+
+// 見つからないのが対象ではなく外部コマンドの場合（"-not-found" で終わるが Unavailable として扱う）
+const TOOL_NOT_FOUND: [&str; 7] = [
+    "ffmpeg-not-found", "gsettings-not-found", "clamscan-not-found", "kdeconnect-cli-not-found",
+    "huggingface-cli-not-found", "lmstudio-not-found", "gstreamer-not-found",
+];
+const POLICY_CODES: [&str; 6] = ["exam-mode", "blocked-by-policy", "screentime-limit-reached", "command-not-allowed", "unsafe-command-rejected", "protected-process"];
+
+const INVALID_CODES: [&str; 6] = ["time-in-past", "command-too-long", "not-png", "not-an-image", "not-a-file", "outside-screenshots-dir"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Locale { Ja, En }
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SisError {
+    /// 対象（ファイル・デバイス・ウィンドウなど）が見つからない
+    NotFound { code: String, details: Option<String> },
+    /// 引数が正しくない
+    InvalidArgument { code: String, details: Option<String> },
+    /// polkit/PAM などで権限がない
+    NotAuthorized { code: String, details: Option<String> },
+    /// 試験モード・MDM・スクリーンタイムなどのポリシーで拒否された
+    Policy { code: String, details: Option<String> },
+    /// 必要なサービス・外部コマンド・デバイスがない
+    Unavailable { code: String, details: Option<String> },
+    /// この環境や対象では使えない操作
+    NotSupported { code: String, details: Option<String> },
+    /// 外部コマンドや D-Bus 呼び出しが失敗した
+    Failed { code: String, details: Option<String> },
+}
+
+fn parse_catalog(s: &str) -> HashMap<String, String> {
+    serde_json::from_str(s).unwrap_or_default()
+}
+
+/// SIS_LOCALE → LC_ALL → LC_MESSAGES → LANG の順に見る。未設定や C/POSIX は日本語（SIS の画面は日本語が基本）
+fn detect_locale() -> Locale {
+    let v = ["SIS_LOCALE", "LC_ALL", "LC_MESSAGES", "LANG"].iter()
+        .filter_map(|k| std::env::var(k).ok())
+        .find(|v| !v.is_empty())
+        .unwrap_or_default();
+    if v.is_empty() || v.starts_with("ja") || v == "C" || v.starts_with("C.") || v == "POSIX" { Locale::Ja } else { Locale::En }
+}
+
+pub fn locale() -> Locale { *LOCALE }
+
+fn is_code(s: &str) -> bool {
+    !s.is_empty()
+        && s.starts_with(|c: char| c.is_ascii_lowercase())
+        && s.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        && !s.ends_with('-')
+}
+
+impl SisError {
+    /// コードから種類を決める（内部関数が返す "code: 詳細" の文字列用。コマンド本体では not_found() などで種類を明示する）
+    pub fn from_code(code: &str, details: Option<String>) -> Self {
+        let code = code.to_string();
+        let c = code.as_str();
+        if c == "not-authorized" || c == "permission-denied" || c.starts_with("auth") || c.ends_with("-auth-failed") {
+            SisError::NotAuthorized { code, details }
+        } else if POLICY_CODES.contains(&c) || c.ends_with("-not-allowed") {
+            SisError::Policy { code, details }
+        } else if TOOL_NOT_FOUND.contains(&c) || c.ends_with("-unavailable") || c.starts_with("no-") || c.starts_with("dbus-") {
+            SisError::Unavailable { code, details }
+        } else if c == "not-found" || c.ends_with("-not-found") || c.ends_with("-missing") {
+            SisError::NotFound { code, details }
+        } else if c.starts_with("unsupported-") || c.ends_with("-unsupported") || c.ends_with("-not-supported") || c == "not-supported" || c == "not-seekable" {
+            SisError::NotSupported { code, details }
+        } else if c.starts_with("invalid-") || c.starts_with("empty-") || INVALID_CODES.contains(&c) {
+            SisError::InvalidArgument { code, details }
+        } else {
+            SisError::Failed { code, details }
+        }
+    }
+
+    pub fn not_found(code: &str, details: impl fmt::Display) -> Self {
+        SisError::NotFound { code: code.to_string(), details: Some(details.to_string()) }
+    }
+
+    pub fn invalid(code: &str, details: impl fmt::Display) -> Self {
+        SisError::InvalidArgument { code: code.to_string(), details: Some(details.to_string()) }
+    }
+
+    pub fn policy(code: &str, details: impl fmt::Display) -> Self {
+        SisError::Policy { code: code.to_string(), details: Some(details.to_string()) }
+    }
+
+    pub fn unavailable(code: &str, details: impl fmt::Display) -> Self {
+        SisError::Unavailable { code: code.to_string(), details: Some(details.to_string()) }
+    }
+
+    pub fn not_supported(code: &str, details: impl fmt::Display) -> Self {
+        SisError::NotSupported { code: code.to_string(), details: Some(details.to_string()) }
+    }
+
+    pub fn failed(code: &str, details: impl fmt::Display) -> Self {
+        SisError::Failed { code: code.to_string(), details: Some(details.to_string()) }
+    }
+
+    pub fn code(&self) -> &str {
+        match self {
+            SisError::NotFound { code, .. }
+            | SisError::InvalidArgument { code, .. }
+            | SisError::NotAuthorized { code, .. }
+            | SisError::Policy { code, .. }
+            | SisError::Unavailable { code, .. }
+            | SisError::NotSupported { code, .. }
+            | SisError::Failed { code, .. } => code,
+        }
+    }
+
+    pub fn details(&self) -> Option<&str> {
+        match self {
+            SisError::NotFound { details, .. }
+            | SisError::InvalidArgument { details, .. }
+            | SisError::NotAuthorized { details, .. }
+            | SisError::Policy { details, .. }
+            | SisError::Unavailable { details, .. }
+            | SisError::NotSupported { details, .. }
+            | SisError::Failed { details, .. } => details.as_deref(),
+        }
+    }
+
+    /// カタログの既定文のキー
+    fn kind_key(&self) -> &'static str {
+        match self {
+            SisError::NotFound { .. } => "@not-found",
+            SisError::InvalidArgument { .. } => "@invalid-argument",
+            SisError::NotAuthorized { .. } => "@not-authorized",
+            SisError::Policy { .. } => "@policy",
+            SisError::Unavailable { .. } => "@unavailable",
+            SisError::NotSupported { .. } => "@not-supported",
+            SisError::Failed { .. } => "@failed",
+        }
+    }
+
+    pub fn message_in(&self, locale: Locale) -> String {
+        let catalog = match locale { Locale::Ja => &*CATALOG_JA, Locale::En => &*CATALOG_EN };
+        catalog.get(self.code()).or_else(|| catalog.get(self.kind_key())).cloned().unwrap_or_else(|| self.code().to_string())
+    }
+
+    pub fn message(&self) -> String { self.message_in(locale()) }
+}
+
+impl fmt::Display for SisError {
+    /// ログ用（従来の "code: 詳細" 形式）
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.details() {
+            Some(d) => write!(f, "{}: {}", self.code(), d),
+            None => f.write_str(self.code()),
+        }
+    }
+}
+
+impl std::error::Error for SisError {}
+
+impl Serialize for SisError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("SisError", 3)?;
+        s.serialize_field("code", self.code())?;
+        s.serialize_field("message", &self.message())?;
+        s.serialize_field("details", &self.details())?;
+        s.end()
+    }
+}
+
+impl From<String> for SisError {
+    /// "code" / "code: 詳細" はそのまま、コードでない文字列（stderr など）は command-failed の詳細にする
+    fn from(s: String) -> Self {
+        let t = s.trim();
+        if is_code(t) { return SisError::from_code(t, None); }
+        if let Some((head, rest)) = t.split_once(':') {
+            // "nmcli: Error: ..." のようなコマンド名の前置きはコードとみなさない
+            if is_code(head) && (head.contains('-') || CATALOG_JA.contains_key(head)) {
+                let rest = rest.trim();
+                return SisError::from_code(head, if rest.is_empty() { None } else { Some(rest.to_string()) });
+            }
+        }
+        SisError::Failed { code: "command-failed".into(), details: if t.is_empty() { None } else { Some(t.to_string()) } }
+    }
+}
+
+impl From<&str> for SisError {
+    fn from(s: &str) -> Self { SisError::from(s.to_string()) }
+}
+
+impl From<std::io::Error> for SisError {
+    fn from(e: std::io::Error) -> Self {
+        match e.kind() {
+            std::io::ErrorKind::NotFound => SisError::NotFound { code: "file-not-found".into(), details: Some(e.to_string()) },
+            std::io::ErrorKind::PermissionDenied => SisError::NotAuthorized { code: "permission-denied".into(), details: Some(e.to_string()) },
+            _ => SisError::failed("io-error", e),
+        }
+    }
+}
+
+impl From<serde_json::Error> for SisError {
+    fn from(e: serde_json::Error) -> Self { SisError::failed("serialize-failed", e) }
+}
+
+impl From<zbus::Error> for SisError {
+    fn from(e: zbus::Error) -> Self {
+        let s = e.to_string();
+        if s.contains("NotAuthorized") || s.contains("AccessDenied") || s.contains("PermissionDenied") || s.contains("InteractiveAuthorizationRequired") {
+            return SisError::NotAuthorized { code: "not-authorized".into(), details: Some(s) };
+        }
+        if s.contains("ServiceUnknown") { return SisError::Unavailable { code: "dbus-service-unavailable".into(), details: Some(s) }; }
+        SisError::failed("dbus-failed", s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kind(e: &SisError) -> &'static str { e.kind_key() }
+
+    #[test]
+    fn from_code_classifies_by_prefix_and_suffix() {
+        let cases = [
+            ("not-authorized", "@not-authorized"),
+            ("pkexec-auth-failed", "@not-authorized"),
+            ("exam-mode", "@policy"),
+            ("command-not-allowed", "@policy"),
+            ("ffmpeg-not-found", "@unavailable"),
+            ("mpris-unavailable", "@unavailable"),
+            ("no-backend", "@unavailable"),
+            ("file-not-found", "@not-found"),
+            ("xkb-rules-missing", "@not-found"),
+            ("unsupported-mode", "@not-supported"),
+            ("not-seekable", "@not-supported"),
+            ("invalid-critical-percent", "@invalid-argument"),
+            ("time-in-past", "@invalid-argument"),
+            ("renice-failed", "@failed"),
+        ];
+        for (code, want) in cases {
+            let e = SisError::from_code(code, None);
+            assert_eq!((code, kind(&e)), (code, want));
+            assert_eq!(e.code(), code);
+        }
+    }
+
+    #[test]
+    fn from_string_splits_code_and_details() {
+        let e = SisError::from("invalid-xkb-name: us;rm".to_string());
+        assert_eq!((kind(&e), e.code(), e.details()), ("@invalid-argument", "invalid-xkb-name", Some("us;rm")));
+        // コマンド名の前置きや stderr はコードとみなさない
+        let e = SisError::from("nmcli: Error: no device".to_string());
+        assert_eq!((e.code(), e.details()), ("command-failed", Some("nmcli: Error: no device")));
+        assert_eq!(SisError::from("  ".to_string()).details(), None);
+    }
+
+    #[test]
+    fn explicit_constructors_match_the_heuristic() {
+        assert_eq!(SisError::not_found("file-not-found", "/x"), SisError::from("file-not-found: /x"));
+        assert_eq!(SisError::invalid("invalid-quiet-period", 0), SisError::from("invalid-quiet-period: 0"));
+        assert_eq!(SisError::unavailable("mpris-unavailable", "playerctl: x"), SisError::from("mpris-unavailable: playerctl: x"));
+        assert_eq!(SisError::policy("command-not-allowed", "rm"), SisError::from("command-not-allowed: rm"));
+        assert_eq!(SisError::not_supported("unsupported-mode", "x"), SisError::from("unsupported-mode: x"));
+    }
+}
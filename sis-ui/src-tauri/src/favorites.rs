@@ -0,0 +1,348 @@
+// Dock favorites keyed by desktop-file ID (exec fallback) with folder entries
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+
+use crate::error::SisError;
+use crate::{build_app_dirs, desktop_hidden_or_settings, desktop_id_of, history_dir, log_append, parse_desktop_fields, settings_path, AppInfo};
+
+const STORE_VERSION: u32 = 2;
+
+// read-modify-write をプロセス内で直列化する
+static STORE_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FavoriteApp {
+    id: String, // desktop ID か "exec:<cmdline>"
+    name: String,
+    exec: String,
+    icon_data_url: Option<String>,
+    desktop_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DockFolder {
+    id: String, // "folder:<n>"
+    name: String,
+    apps: Vec<FavoriteApp>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum DockEntry {
+    App(FavoriteApp),
+    Folder(DockFolder),
+}
+
+impl DockEntry {
+    fn id(&self) -> &str {
+        match self { DockEntry::App(a) => &a.id, DockEntry::Folder(f) => &f.id }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct FavoritesStore {
+    version: u32,
+    entries: Vec<DockEntry>,
+}
+
+fn favorites_path() -> Option<PathBuf> { history_dir().map(|d| d.join("favorites.json")) }
+
+fn normalize_exec(exec: &str) -> String {
+    let mut e = exec.to_string();
+    for code in ["%U", "%u", "%F", "%f", "%i", "%c", "%k"].iter() { e = e.replace(code, ""); }
+    e.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn favorite_id(desktop_id: Option<&str>, exec: &str) -> String {
+    match desktop_id { Some(d) if !d.is_empty() => d.to_string(), _ => format!("exec:{}", normalize_exec(exec)) }
+}
+
+/// exec -> desktop ID の索引（旧 favorites.json の移行や desktop_id 無しの追加で使う）
+fn exec_index() -> HashMap<String, String> {
+    let mut idx: HashMap<String, String> = HashMap::new();
+    for dir in build_app_dirs() {
+        let read = match fs::read_dir(&dir) { Ok(r) => r, Err(_) => continue };
+        for e in read.flatten() {
+            let p = e.path();
+            let id = match desktop_id_of(&p) { Some(id) => id, None => continue };
+            let content = match fs::read_to_string(&p) { Ok(s) => s, Err(_) => continue };
+            if desktop_hidden_or_settings(&content) { continue; }
+            let (_name, exec, _icon, _swm) = parse_desktop_fields(&content);
+            let key = normalize_exec(&exec);
+            if !key.is_empty() { idx.entry(key).or_insert(id); }
+        }
+    }
+    idx
+}
+
+fn to_favorite(app: AppInfo, idx: Option<&HashMap<String, String>>) -> FavoriteApp {
+    let desktop_id = app.desktop_id.clone()
+        .or_else(|| idx.and_then(|m| m.get(&normalize_exec(&app.exec)).cloned()));
+    FavoriteApp { id: favorite_id(desktop_id.as_deref(), &app.exec), name: app.name, exec: app.exec, icon_data_url: app.icon_data_url, desktop_id }
+}
+
+fn to_app_info(f: &FavoriteApp) -> AppInfo {
+    AppInfo { name: f.name.clone(), exec: f.exec.clone(), icon_data_url: f.icon_data_url.clone(), desktop_id: f.desktop_id.clone() }
+}
+
+/// 旧形式（AppInfo配列 + DeSettings.favorite_order）から一度だけ移行する
+fn migrate_legacy(apps: Vec<AppInfo>) -> FavoritesStore {
+    let order: Vec<String> = settings_path()
+        .and_then(|p| fs::read_to_string(p).ok())
+        .and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok())
+        .and_then(|v| v.get("favorite_order").cloned())
+        .and_then(|v| serde_json::from_value::<Vec<String>>(v).ok())
+        .unwrap_or_default();
+    let mut apps = apps;
+    if !order.is_empty() {
+        let pos = |n: &str| order.iter().position(|x| x == n).unwrap_or(usize::MAX);
+        apps.sort_by_key(|a| pos(&a.name));
+    }
+    let idx = exec_index();
+    let mut entries: Vec<DockEntry> = Vec::new();
+    for a in apps {
+        let f = to_favorite(a, Some(&idx));
+        if entries.iter().any(|e| e.id() == f.id) { continue; }
+        entries.push(DockEntry::App(f));
+    }
+    log_append("INFO", &format!("favorites: migrated {} legacy entries", entries.len()));
+    FavoritesStore { version: STORE_VERSION, entries }
+}
+
+fn read_store() -> Result<FavoritesStore, String> {
+    let path = favorites_path().ok_or_else(|| "cannot-detect-home".to_string())?;
+    if !path.exists() { return Ok(FavoritesStore { version: STORE_VERSION, entries: Vec::new() }); }
+    let content = fs::read_to_string(&path).map_err(|e| format!("read-failed: {}", e))?;
+    if let Ok(store) = serde_json::from_str::<FavoritesStore>(&content) { return Ok(store); }
+    let legacy: Vec<AppInfo> = serde_json::from_str(&content).map_err(|e| format!("parse-failed: {}", e))?;
+    // 旧ファイルは残しておく（ロールバック用）
+    let _ = fs::write(path.with_extension("json.v1.bak"), &content);
+    let store = migrate_legacy(legacy);
+    write_store(&store)?;
+    Ok(store)
+}
+
+fn write_store(store: &FavoritesStore) -> Result<(), String> {
+    let dir = history_dir().ok_or_else(|| "cannot-detect-home".to_string())?;
+    fs::create_dir_all(&dir).map_err(|e| format!("mkdir-failed: {}", e))?;
+    let path = dir.join("favorites.json");
+    let tmp = dir.join("favorites.json.tmp");
+    let content = serde_json::to_string_pretty(store).map_err(|e| format!("serialize-failed: {}", e))?;
+    fs::write(&tmp, content).map_err(|e| format!("write-failed: {}", e))?;
+    fs::rename(&tmp, &path).map_err(|e| format!("write-failed: {}", e))
+}
+
+/// ロックを取って読み込み→変更→保存→通知までを1操作として行う
+fn update_store<T>(app_handle: &tauri::AppHandle, f: impl FnOnce(&mut FavoritesStore) -> Result<T, String>) -> Result<T, SisError> {
+    let _guard = STORE_LOCK.lock().unwrap();
+    let mut store = read_store()?;
+    let out = f(&mut store)?;
+    write_store(&store)?;
+    let _ = app_handle.emit("sis:favorites-updated", &store.entries);
+    Ok(out)
+}
+
+fn contains_app(store: &FavoritesStore, id: &str) -> bool {
+    store.entries.iter().any(|e| match e {
+        DockEntry::App(a) => a.id == id,
+        DockEntry::Folder(f) => f.apps.iter().any(|a| a.id == id),
+    })
+}
+
+/// アプリをどこにあっても取り外す（フォルダ内も含む）
+fn take_app(store: &mut FavoritesStore, id: &str) -> Option<FavoriteApp> {
+    if let Some(i) = store.entries.iter().position(|e| matches!(e, DockEntry::App(a) if a.id == id)) {
+        if let DockEntry::App(a) = store.entries.remove(i) { return Some(a); }
+    }
+    for e in store.entries.iter_mut() {
+        if let DockEntry::Folder(f) = e {
+            if let Some(i) = f.apps.iter().position(|a| a.id == id) { return Some(f.apps.remove(i)); }
+        }
+    }
+    None
+}
+
+fn folder_mut<'a>(store: &'a mut FavoritesStore, folder_id: &str) -> Result<&'a mut DockFolder, String> {
+    store.entries.iter_mut()
+        .find_map(|e| match e { DockEntry::Folder(f) if f.id == folder_id => Some(f), _ => None })
+        .ok_or_else(|| format!("folder-not-found: {}", folder_id))
+}
+
+fn next_folder_id(store: &FavoritesStore) -> String {
+    let max = store.entries.iter()
+        .filter_map(|e| e.id().strip_prefix("folder:").and_then(|n| n.parse::<u64>().ok()))
+        .max().unwrap_or(0);
+    format!("folder:{}", max + 1)
+}
+
+fn flatten(store: &FavoritesStore) -> Vec<AppInfo> {
+    let mut out: Vec<AppInfo> = Vec::new();
+    for e in &store.entries {
+        match e {
+            DockEntry::App(a) => out.push(to_app_info(a)),
+            DockEntry::Folder(f) => out.extend(f.apps.iter().map(to_app_info)),
+        }
+    }
+    out
+}
+
+#[tauri::command]
+pub fn get_dock_entries() -> Result<Vec<DockEntry>, SisError> {
+    let _guard = STORE_LOCK.lock().unwrap();
+    Ok(read_store()?.entries)
+}
+
+#[tauri::command]
+pub fn dock_add_app(app_handle: tauri::AppHandle, app: AppInfo, folder_id: Option<String>, index: Option<usize>) -> Result<String, SisError> {
+    let idx = if app.desktop_id.is_none() { Some(exec_index()) } else { None };
+    let fav = to_favorite(app, idx.as_ref());
+    update_store(&app_handle, |store| {
+        if contains_app(store, &fav.id) { return Err("favorite-exists".to_string()); }
+        let id = fav.id.clone();
+        match folder_id {
+            Some(fid) => {
+                let folder = folder_mut(store, &fid)?;
+                let i = index.unwrap_or(folder.apps.len()).min(folder.apps.len());
+                folder.apps.insert(i, fav);
+            }
+            None => {
+                let i = index.unwrap_or(store.entries.len()).min(store.entries.len());
+                store.entries.insert(i, DockEntry::App(fav));
+            }
+        }
+        Ok(id)
+    })
+}
+
+/// アプリまたはフォルダを外す。フォルダの場合は中身をその位置に戻す
+#[tauri::command]
+pub fn dock_remove(app_handle: tauri::AppHandle, id: String) -> Result<String, SisError> {
+    update_store(&app_handle, |store| {
+        if let Some(i) = store.entries.iter().position(|e| matches!(e, DockEntry::Folder(f) if f.id == id)) {
+            if let DockEntry::Folder(f) = store.entries.remove(i) {
+                for (k, a) in f.apps.into_iter().enumerate() { store.entries.insert(i + k, DockEntry::App(a)); }
+            }
+            return Ok("folder-removed".to_string());
+        }
+        take_app(store, &id).map(|_| "favorite-removed".to_string()).ok_or_else(|| "not-found".to_string())
+    })
+}
+
+/// 1件を移動（トップレベル⇔フォルダ間も可）。folder_id 無しはトップレベル
+#[tauri::command]
+pub fn dock_move(app_handle: tauri::AppHandle, id: String, folder_id: Option<String>, index: usize) -> Result<String, SisError> {
+    update_store(&app_handle, |store| {
+        if let Some(i) = store.entries.iter().position(|e| matches!(e, DockEntry::Folder(f) if f.id == id)) {
+            if folder_id.is_some() { return Err("nested-folders-not-supported".to_string()); }
+            let entry = store.entries.remove(i);
+            let to = index.min(store.entries.len());
+            store.entries.insert(to, entry);
+            return Ok("moved".to_string());
+        }
+        // 移動先の存在確認を先に行い、失敗時に取り外したままにならないようにする
+        if let Some(fid) = folder_id.as_ref() { folder_mut(store, fid)?; }
+        let app = take_app(store, &id).ok_or_else(|| "not-found".to_string())?;
+        match folder_id {
+            Some(fid) => {
+                let folder = folder_mut(store, &fid)?;
+                let to = index.min(folder.apps.len());
+                folder.apps.insert(to, app);
+            }
+            None => {
+                let to = index.min(store.entries.len());
+                store.entries.insert(to, DockEntry::App(app));
+            }
+        }
+        Ok("moved".to_string())
+    })
+}
+
+/// 並び順を一括指定（未指定のものは末尾に元の順序で残す）
+#[tauri::command]
+pub fn dock_reorder(app_handle: tauri::AppHandle, ids: Vec<String>, folder_id: Option<String>) -> Result<String, SisError> {
+    update_store(&app_handle, |store| {
+        let pos = |n: &str| ids.iter().position(|x| x == n).unwrap_or(usize::MAX);
+        match folder_id {
+            Some(fid) => folder_mut(store, &fid)?.apps.sort_by_key(|a| pos(&a.id)),
+            None => store.entries.sort_by_key(|e| pos(e.id())),
+        }
+        Ok("favorites-reordered".to_string())
+    })
+}
+
+#[tauri::command]
+pub fn dock_create_folder(app_handle: tauri::AppHandle, name: String, app_ids: Vec<String>) -> Result<DockFolder, SisError> {
+    let name = name.trim().to_string();
+    if name.is_empty() { return Err("invalid-args".into()); }
+    update_store(&app_handle, |store| {
+        // フォルダは最初のアプリがあった位置に作る
+        let at = app_ids.first()
+            .and_then(|first| store.entries.iter().position(|e| e.id() == first))
+            .unwrap_or(store.entries.len());
+        let id = next_folder_id(store);
+        let mut apps: Vec<FavoriteApp> = Vec::new();
+        let mut removed_before = 0usize;
+        for aid in &app_ids {
+            let top_pos = store.entries.iter().position(|e| e.id() == aid);
+            if let Some(a) = take_app(store, aid) {
+                if top_pos.map(|p| p < at).unwrap_or(false) { removed_before += 1; }
+                apps.push(a);
+            }
+        }
+        let folder = DockFolder { id, name, apps };
+        let at = at.saturating_sub(removed_before).min(store.entries.len());
+        store.entries.insert(at, DockEntry::Folder(folder.clone()));
+        Ok(folder)
+    })
+}
+
+#[tauri::command]
+pub fn dock_rename_folder(app_handle: tauri::AppHandle, id: String, name: String) -> Result<String, SisError> {
+    let name = name.trim().to_string();
+    if name.is_empty() { return Err("invalid-args".into()); }
+    update_store(&app_handle, |store| {
+        folder_mut(store, &id)?.name = name;
+        Ok("folder-renamed".to_string())
+    })
+}
+
+// --- 旧API（AppInfo配列）互換 ---
+
+#[tauri::command]
+pub fn get_favorite_apps() -> Result<Vec<AppInfo>, SisError> {
+    let _guard = STORE_LOCK.lock().unwrap();
+    Ok(flatten(&read_store()?))
+}
+
+#[tauri::command]
+pub fn add_favorite_app(app_handle: tauri::AppHandle, app: AppInfo) -> Result<String, SisError> {
+    dock_add_app(app_handle, app, None, None).map(|_| "favorite-added".to_string())
+}
+
+/// app_id は desktop ID（または "exec:..."）。旧フロントエンド向けに exec 一致も受け付ける
+#[tauri::command]
+pub fn remove_favorite_app(app_handle: tauri::AppHandle, app_id: String) -> Result<String, SisError> {
+    let id = {
+        let _guard = STORE_LOCK.lock().unwrap();
+        let store = read_store()?;
+        if contains_app(&store, &app_id) { app_id.clone() } else {
+            let want = normalize_exec(&app_id);
+            flatten(&store).iter()
+                .find(|a| normalize_exec(&a.exec) == want)
+                .map(|a| favorite_id(a.desktop_id.as_deref(), &a.exec))
+                .unwrap_or_else(|| app_id.clone())
+        }
+    };
+    dock_remove(app_handle, id)
+}
+
+#[tauri::command]
+pub fn reorder_favorite_apps(app_handle: tauri::AppHandle, ids: Vec<String>) -> Result<String, SisError> {
+    dock_reorder(app_handle, ids, None)
+}
@@ -0,0 +1,303 @@
+// インプットメソッド: fcitx5（D-Bus の Controller1）か IBus（ibus コマンド）のエンジン一覧・切替と、
+// TopBar のインジケータ用の状態（日本語入力が有効か）を提供する。状態が変わると sis:ime-state で送る。
+// fcitx5 は D-Bus で 1 秒ごとに見る。IBus は現在のエンジンと有効なエンジンの一覧をキャッシュし、
+// IBus の GlobalEngineChanged シグナルと `gsettings monitor` の通知で更新する（毎回コマンドを起動しない）
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+use zbus::blocking::{Connection, Proxy};
+
+use crate::error::SisError;
+use crate::{log_append, run_out, which};
+
+const FCITX_DEST: &str = "org.fcitx.Fcitx5";
+const FCITX_PATH: &str = "/controller";
+const FCITX_IFACE: &str = "org.fcitx.Fcitx.Controller1";
+const POLL: Duration = Duration::from_secs(1);
+// IBus デーモンや gsettings monitor が終了したときに張り直すまでの間隔
+const RESTART_DELAY: Duration = Duration::from_secs(5);
+// 有効なエンジンの一覧が入っている設定（GNOME の入力ソース、なければ IBus の preload-engines）
+const GS_SOURCES: (&str, &str) = ("org.gnome.desktop.input-sources", "sources");
+const GS_PRELOAD: (&str, &str) = ("org.freedesktop.ibus.general", "preload-engines");
+// IBus の英字入力として使う既定のエンジン
+const IBUS_FALLBACK_ENGINE: &str = "xkb:jp::jpn";
+
+static APP: Lazy<Mutex<Option<tauri::AppHandle>>> = Lazy::new(|| Mutex::new(None));
+static LAST: Lazy<Mutex<Option<ImeState>>> = Lazy::new(|| Mutex::new(None));
+// ibus list-engine は遅いので名前→(表示名, 言語) を一度だけ読む
+static IBUS_ENGINES: Lazy<HashMap<String, (String, String)>> = Lazy::new(read_ibus_engines);
+// IBus の現在のエンジン（IBUS_WATCHED の間はシグナルで更新する。見張れていなければ毎回 ibus engine で読む）
+static IBUS_CURRENT: Lazy<Mutex<Option<String>>> = Lazy::new(|| Mutex::new(None));
+static IBUS_WATCHED: AtomicBool = AtomicBool::new(false);
+// 有効なエンジン名（gsettings monitor の通知で捨てて読み直す）
+static IBUS_ENABLED: Lazy<Mutex<Option<Vec<String>>>> = Lazy::new(|| Mutex::new(None));
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ImeEngine {
+    name: String,
+    label: String,
+    language: Option<String>,
+    japanese: bool, // かな漢字変換のエンジン（キーボード配列だけのものは false）
+    current: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ImeState {
+    framework: Option<String>, // fcitx5 | ibus
+    active: bool,              // 日本語入力中
+    engine: Option<String>,
+    indicator: String, // あ | A
+    engines: Vec<ImeEngine>,
+}
+
+fn session_bus() -> Result<Connection, String> {
+    static CONN: Lazy<Option<Connection>> = Lazy::new(|| Connection::session().ok());
+    CONN.clone().ok_or_else(|| "dbus-session-failed".to_string())
+}
+
+fn indicator(active: bool) -> String { if active { "あ".into() } else { "A".into() } }
+
+// ---- fcitx5 ----
+
+fn fcitx() -> Option<Proxy<'static>> {
+    let conn = session_bus().ok()?;
+    let p = Proxy::new(&conn, FCITX_DEST, FCITX_PATH, FCITX_IFACE).ok()?;
+    // 起動していなければ呼び出しに失敗するので、ここで確かめておく
+    p.call::<_, _, i32>("State", &()).ok()?;
+    Some(p)
+}
+
+/// 現在のグループ（Ctrl+Space で切り替わる並び）のエンジン
+fn fcitx_engines(p: &Proxy<'static>) -> Vec<ImeEngine> {
+    let current: String = p.call("CurrentInputMethod", &()).unwrap_or_default();
+    let available: Vec<(String, String, String, String, String, String, bool)> = p.call("AvailableInputMethods", &()).unwrap_or_default();
+    let info: HashMap<String, (String, String)> = available.into_iter().map(|(name, label, native, _icon, _short, lang, _)| {
+        (name, (if native.is_empty() { label } else { native }, lang))
+    }).collect();
+    let group: String = p.call("CurrentInputMethodGroup", &()).unwrap_or_default();
+    let (_layout, items): (String, Vec<(String, String)>) = p.call("InputMethodGroupInfo", &(group.as_str(),)).unwrap_or_default();
+    items.into_iter().map(|(name, _)| {
+        let (label, lang) = info.get(&name).cloned().unwrap_or_else(|| (name.clone(), String::new()));
+        ImeEngine {
+            japanese: lang.starts_with("ja") && !name.starts_with("keyboard-"),
+            current: name == current,
+            language: if lang.is_empty() { None } else { Some(lang) },
+            label,
+            name,
+        }
+    }).collect()
+}
+
+// ---- IBus ----
+
+/// `ibus list-engine` の "language: Japanese" 見出しと "  mozc-jp - Mozc" 行
+fn read_ibus_engines() -> HashMap<String, (String, String)> {
+    let mut out = HashMap::new();
+    if !which("ibus") { return out; }
+    let mut lang = String::new();
+    for l in run_out("ibus", &["list-engine"]).unwrap_or_default().lines() {
+        if let Some(x) = l.strip_prefix("language:") { lang = x.trim().to_string(); continue; }
+        if let Some((name, label)) = l.trim().split_once(" - ") { out.insert(name.trim().to_string(), (label.trim().to_string(), lang.clone())); }
+    }
+    out
+}
+
+fn read_ibus_current() -> Option<String> {
+    if !which("ibus") { return None; }
+    run_out("ibus", &["engine"]).map(|s| s.trim().to_string()).filter(|s| !s.is_empty())
+}
+
+fn ibus_current() -> Option<String> {
+    if IBUS_WATCHED.load(Ordering::SeqCst) { IBUS_CURRENT.lock().unwrap().clone() } else { read_ibus_current() }
+}
+
+fn ibus_enabled_names() -> Vec<String> {
+    IBUS_ENABLED.lock().unwrap().get_or_insert_with(read_ibus_enabled_names).clone()
+}
+
+/// 有効にしてあるエンジン（GNOME の入力ソース、なければ IBus の preload-engines）
+fn read_ibus_enabled_names() -> Vec<String> {
+    let re = regex::Regex::new(r"'([^']+)'").unwrap();
+    if let Some(src) = run_out("gsettings", &["get", GS_SOURCES.0, GS_SOURCES.1]) {
+        let names: Vec<String> = regex::Regex::new(r"\('(xkb|ibus)', '([^']+)'\)").unwrap().captures_iter(&src)
+            .map(|c| if &c[1] == "xkb" { format!("xkb:{}:", c[2].replacen('+', ":", 1)) } else { c[2].to_string() })
+            .collect();
+        if !names.is_empty() { return names; }
+    }
+    run_out("gsettings", &["get", GS_PRELOAD.0, GS_PRELOAD.1])
+        .map(|s| re.captures_iter(&s).map(|c| c[1].to_string()).collect())
+        .unwrap_or_default()
+}
+
+fn ibus_engines(current: &str) -> Vec<ImeEngine> {
+    let mut names = ibus_enabled_names();
+    if !names.iter().any(|n| current.starts_with(n.as_str())) { names.push(current.to_string()); }
+    names.into_iter().map(|short| {
+        // GNOME の xkb ソースは "xkb:jp::" のように言語が欠けているので前方一致で正式名を探す
+        let (name, (label, lang)) = IBUS_ENGINES.get_key_value(&short)
+            .or_else(|| IBUS_ENGINES.iter().find(|(k, _)| k.starts_with(&short)))
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .unwrap_or_else(|| (short.clone(), (short.clone(), String::new())));
+        ImeEngine {
+            japanese: !name.starts_with("xkb:") && (lang == "Japanese" || lang.starts_with("ja")),
+            current: name == current,
+            language: if lang.is_empty() { None } else { Some(lang) },
+            label,
+            name,
+        }
+    }).collect()
+}
+
+// ---- 共通 ----
+
+fn read_state() -> ImeState {
+    if let Some(p) = fcitx() {
+        let engines = fcitx_engines(&p);
+        let cur = engines.iter().find(|e| e.current).cloned();
+        let active = cur.as_ref().is_some_and(|e| e.japanese);
+        return ImeState { framework: Some("fcitx5".into()), active, engine: cur.map(|e| e.name), indicator: indicator(active), engines };
+    }
+    if let Some(cur) = ibus_current() {
+        let engines = ibus_engines(&cur);
+        let active = engines.iter().any(|e| e.current && e.japanese);
+        return ImeState { framework: Some("ibus".into()), active, engine: Some(cur), indicator: indicator(active), engines };
+    }
+    ImeState { framework: None, active: false, engine: None, indicator: indicator(false), engines: Vec::new() }
+}
+
+fn refresh() -> ImeState {
+    let st = read_state();
+    let mut last = LAST.lock().unwrap();
+    if last.as_ref() != Some(&st) {
+        *last = Some(st.clone());
+        if let Some(app) = APP.lock().unwrap().as_ref() { let _ = app.emit("sis:ime-state", &st); }
+    }
+    st
+}
+
+fn set_engine(name: &str) -> Result<(), String> {
+    if let Some(p) = fcitx() {
+        return p.call::<_, _, ()>("SetCurrentIM", &(name,)).map_err(|e| format!("fcitx-failed: {}", e));
+    }
+    if ibus_current().is_some() {
+        let ok = Command::new("ibus").args(["engine", name]).status().map(|s| s.success()).unwrap_or(false);
+        if !ok { return Err("ibus-failed".into()); }
+        // シグナルを待たずに反映する（直後の refresh で古い値を返さない）
+        *IBUS_CURRENT.lock().unwrap() = Some(name.to_string());
+        return Ok(());
+    }
+    Err("no-input-method".into())
+}
+
+/// IBus のバス（`ibus address`）で GlobalEngineChanged を受ける。デーモンが再起動したら張り直す
+fn watch_ibus() {
+    loop {
+        match watch_ibus_once() {
+            // デーモンが起動していないだけなら黙って待つ
+            Err(e) if e != "ibus-unavailable" => log_append("WARN", &format!("ime: {}", e)),
+            _ => {}
+        }
+        // 見張れていた間に止まったときだけ送り直す（起動していないあいだは何もしない）
+        if IBUS_WATCHED.swap(false, Ordering::SeqCst) { refresh(); }
+        std::thread::sleep(RESTART_DELAY);
+    }
+}
+
+fn watch_ibus_once() -> Result<(), String> {
+    let addr = run_out("ibus", &["address"]).map(|s| s.trim().to_string()).filter(|s| !s.is_empty() && s != "(null)").ok_or("ibus-unavailable")?;
+    let conn = zbus::blocking::connection::Builder::address(addr.as_str()).and_then(|b| b.build()).map_err(|e| format!("ibus-bus-failed: {}", e))?;
+    let proxy = Proxy::new(&conn, "org.freedesktop.IBus", "/org/freedesktop/IBus", "org.freedesktop.IBus").map_err(|e| format!("ibus-bus-failed: {}", e))?;
+    let signals = proxy.receive_signal("GlobalEngineChanged").map_err(|e| format!("ibus-bus-failed: {}", e))?;
+    // 購読してから今の値を読むので、その間の切替も取りこぼさない
+    *IBUS_CURRENT.lock().unwrap() = read_ibus_current();
+    IBUS_WATCHED.store(true, Ordering::SeqCst);
+    refresh();
+    for msg in signals {
+        let Ok(name) = msg.body().deserialize::<String>() else { continue };
+        *IBUS_CURRENT.lock().unwrap() = Some(name).filter(|n| !n.is_empty());
+        refresh();
+    }
+    Ok(())
+}
+
+/// 有効なエンジンの設定が変わったらキャッシュを捨てて送り直す
+fn watch_gsettings((schema, key): (&'static str, &'static str)) {
+    // スキーマが無い環境（GNOME でない・IBus の設定が無い）では見張らない
+    if run_out("gsettings", &["get", schema, key]).is_none() { return; }
+    loop {
+        match Command::new("gsettings").args(["monitor", schema, key]).stdout(Stdio::piped()).stderr(Stdio::null()).spawn() {
+            Ok(mut child) => {
+                if let Some(out) = child.stdout.take() {
+                    for _ in BufReader::new(out).lines().map_while(Result::ok) {
+                        IBUS_ENABLED.lock().unwrap().take();
+                        refresh();
+                    }
+                }
+                let _ = child.wait();
+            }
+            Err(e) => log_append("WARN", &format!("ime: gsettings monitor failed: {}", e)),
+        }
+        IBUS_ENABLED.lock().unwrap().take();
+        std::thread::sleep(RESTART_DELAY);
+    }
+}
+
+pub fn start(app_handle: tauri::AppHandle) {
+    *APP.lock().unwrap() = Some(app_handle);
+    if which("ibus") { std::thread::spawn(watch_ibus); }
+    if which("gsettings") {
+        std::thread::spawn(|| watch_gsettings(GS_SOURCES));
+        std::thread::spawn(|| watch_gsettings(GS_PRELOAD));
+    }
+    // fcitx5 には切替のシグナルが無いので D-Bus で見る（コマンドは起動しない）
+    std::thread::spawn(|| {
+        let mut was_fcitx = false;
+        loop {
+            // 終了したときも一度送り直して IBus 側の状態に切り替える
+            let is_fcitx = fcitx().is_some();
+            if is_fcitx || was_fcitx { refresh(); }
+            was_fcitx = is_fcitx;
+            std::thread::sleep(POLL);
+        }
+    });
+}
+
+#[tauri::command]
+pub fn ime_state() -> Result<ImeState, SisError> { Ok(refresh()) }
+
+#[tauri::command]
+pub fn ime_set_engine(name: String) -> Result<ImeState, SisError> {
+    if name.is_empty() || name.starts_with('-') { return Err("invalid-engine".into()); }
+    set_engine(&name)?;
+    Ok(refresh())
+}
+
+/// 日本語入力（かな漢字変換）と英字入力を切り替える
+#[tauri::command]
+pub fn ime_set_japanese(enabled: bool) -> Result<ImeState, SisError> {
+    let st = read_state();
+    let target = if enabled {
+        st.engines.iter().find(|e| e.japanese).map(|e| e.name.clone()).ok_or("no-japanese-engine")?
+    } else {
+        match st.engines.iter().find(|e| !e.japanese) {
+            Some(e) => e.name.clone(),
+            None if st.framework.as_deref() == Some("ibus") => IBUS_FALLBACK_ENGINE.to_string(),
+            None => return Err("no-keyboard-engine".into()),
+        }
+    };
+    set_engine(&target)?;
+    Ok(refresh())
+}
+
+#[tauri::command]
+pub fn ime_toggle() -> Result<ImeState, SisError> {
+    let active = read_state().active;
+    ime_set_japanese(!active)
+}
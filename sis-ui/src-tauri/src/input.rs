@@ -0,0 +1,464 @@
+// 入力デバイス設定: XKB（配列/オプション）、キーリピート、ポインタ速度、ナチュラルスクロール、タップでクリック。
+// X11 は setxkbmap / xset / xinput（libinput のデバイスプロパティ）、Wayland(GNOME) は gsettings に書く。
+// 設定は ~/.local/share/sis-ui/input.json に保存し、起動時と新しいデバイスの接続時に適用し直す。
+// /tmp/sis-exam-mode がある間は XKB のサーバーキー（VT 切替等）とウィンドウ切替（Alt+Tab）を無効にする
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+
+use crate::error::SisError;
+use crate::{exam_mode, history_dir, log_append, run_out, which};
+
+const POLL: Duration = Duration::from_secs(2);
+// 試験中に足す XKB オプション（Ctrl+Alt+Fn の VT 切替や Ctrl+Alt+Backspace を止める）
+const EXAM_XKB_OPTIONS: [&str; 1] = ["srvrkeys:none"];
+// 試験中に空にする GNOME のウィンドウ切替ショートカット（Alt+Tab など）
+const EXAM_WM_KEYS: [&str; 4] = ["switch-applications", "switch-applications-backward", "switch-windows", "switch-windows-backward"];
+const WM_KEYBINDINGS: &str = "org.gnome.desktop.wm.keybindings";
+
+static APP: Lazy<Mutex<Option<tauri::AppHandle>>> = Lazy::new(|| Mutex::new(None));
+static EXAM_MODE: AtomicBool = AtomicBool::new(false);
+// 設定を適用済みの xinput デバイス id（抜き差しで増えたものにだけ適用する）
+static APPLIED: Lazy<Mutex<HashSet<u32>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct XkbLayout {
+    layout: String,          // us | jp など
+    variant: Option<String>, // dvorak など
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct InputConfig {
+    layouts: Vec<XkbLayout>,
+    model: Option<String>,
+    options: Vec<String>, // ctrl:nocaps など
+    repeat_enabled: bool,
+    repeat_delay_ms: u32,
+    repeat_rate: u32, // 1 秒あたりの回数
+    mouse_speed: f64, // -1.0〜1.0（libinput Accel Speed）
+    mouse_natural_scroll: bool,
+    touchpad_speed: f64,
+    touchpad_natural_scroll: bool,
+    tap_to_click: bool,
+}
+
+impl Default for InputConfig {
+    fn default() -> Self {
+        InputConfig {
+            layouts: vec![XkbLayout { layout: "jp".into(), variant: None }],
+            model: None,
+            options: Vec::new(),
+            repeat_enabled: true,
+            repeat_delay_ms: 500,
+            repeat_rate: 25,
+            mouse_speed: 0.0,
+            mouse_natural_scroll: false,
+            touchpad_speed: 0.0,
+            touchpad_natural_scroll: true,
+            tap_to_click: true,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct InputDevice {
+    id: u32,
+    name: String,
+    kind: String, // keyboard | mouse | touchpad
+    accel_speed: Option<f64>,
+    natural_scroll: Option<bool>,
+    tapping: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct InputState {
+    backend: Option<String>, // x11 | gnome
+    config: InputConfig,
+    active_options: Vec<String>, // 実際に効いている XKB オプション（試験中の追加分を含む）
+    devices: Vec<InputDevice>,
+    exam_mode: bool,
+}
+
+fn config_path() -> Option<PathBuf> { history_dir().map(|d| d.join("input.json")) }
+// 試験モードで空にしたショートカットの元の値（クラッシュしても次回起動時に戻せるよう保存）
+fn exam_restore_path() -> Option<PathBuf> { history_dir().map(|d| d.join("input-exam-restore.json")) }
+
+fn read_config() -> InputConfig {
+    config_path().and_then(|p| fs::read_to_string(p).ok()).and_then(|s| serde_json::from_str(&s).ok()).unwrap_or_default()
+}
+
+fn write_config(c: &InputConfig) -> Result<(), String> {
+    let p = config_path().ok_or("no-data-dir")?;
+    if let Some(dir) = p.parent() { let _ = fs::create_dir_all(dir); }
+    fs::write(&p, serde_json::to_string_pretty(c).map_err(|e| e.to_string())?).map_err(|e| format!("write-failed: {}", e))
+}
+
+fn is_x11() -> bool { !crate::screenshot::is_wayland() && std::env::var("DISPLAY").is_ok() }
+
+fn backend() -> Option<&'static str> {
+    if is_x11() && which("setxkbmap") { Some("x11") } else if which("gsettings") { Some("gnome") } else { None }
+}
+
+fn gsettings_get(schema: &str, key: &str) -> Option<String> {
+    run_out("gsettings", &["get", schema, key]).map(|s| s.trim().to_string())
+}
+
+fn gsettings_set(schema: &str, key: &str, value: &str) -> bool {
+    Command::new("gsettings").args(["set", schema, key, value]).status().map(|s| s.success()).unwrap_or(false)
+}
+
+/// GVariant の文字列配列表記（['a', 'b']）
+fn gvariant_strv(items: &[String]) -> String {
+    format!("[{}]", items.iter().map(|s| format!("'{}'", s.replace('\'', "\\'"))).collect::<Vec<_>>().join(", "))
+}
+
+fn valid_xkb_token(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | ':' | '(' | ')' | '+'))
+}
+
+fn effective_options(cfg: &InputConfig) -> Vec<String> {
+    let mut opts = cfg.options.clone();
+    if EXAM_MODE.load(Ordering::SeqCst) {
+        for o in EXAM_XKB_OPTIONS { if !opts.iter().any(|x| x == o) { opts.push(o.to_string()); } }
+    }
+    opts
+}
+
+// ---- キーボード ----
+
+fn apply_keyboard(cfg: &InputConfig) -> Result<(), String> {
+    let opts = effective_options(cfg);
+    match backend() {
+        Some("x11") => {
+            let layouts = cfg.layouts.iter().map(|l| l.layout.as_str()).collect::<Vec<_>>().join(",");
+            let variants = cfg.layouts.iter().map(|l| l.variant.as_deref().unwrap_or("")).collect::<Vec<_>>().join(",");
+            // -option "" で既存のオプションを消してから付け直す
+            let mut args: Vec<String> = vec!["-layout".into(), layouts, "-variant".into(), variants, "-option".into(), String::new()];
+            if let Some(m) = &cfg.model { args.push("-model".into()); args.push(m.clone()); }
+            if !opts.is_empty() { args.push("-option".into()); args.push(opts.join(",")); }
+            let st = Command::new("setxkbmap").args(&args).status().map_err(|e| format!("setxkbmap-failed: {}", e))?;
+            if !st.success() { return Err("setxkbmap-failed".into()); }
+            if which("xset") {
+                if cfg.repeat_enabled {
+                    let _ = Command::new("xset").args(["r", "on", "r", "rate", &cfg.repeat_delay_ms.to_string(), &cfg.repeat_rate.to_string()]).status();
+                } else {
+                    let _ = Command::new("xset").args(["-r"]).status();
+                }
+            }
+            Ok(())
+        }
+        Some("gnome") => {
+            let sources: Vec<String> = cfg.layouts.iter()
+                .map(|l| format!("('xkb', '{}')", match &l.variant { Some(v) if !v.is_empty() => format!("{}+{}", l.layout, v), _ => l.layout.clone() }))
+                .collect();
+            // IBus のエンジン（('ibus', 'mozc-jp') など）は残す
+            let current = gsettings_get("org.gnome.desktop.input-sources", "sources").unwrap_or_default();
+            let re = regex::Regex::new(r"\('ibus', '([^']+)'\)").unwrap();
+            let keep: Vec<String> = re.captures_iter(&current).map(|c| format!("('ibus', '{}')", &c[1])).collect();
+            let all = sources.into_iter().chain(keep).collect::<Vec<_>>().join(", ");
+            gsettings_set("org.gnome.desktop.input-sources", "sources", &format!("[{}]", all));
+            gsettings_set("org.gnome.desktop.input-sources", "xkb-options", &gvariant_strv(&opts));
+            let kb = "org.gnome.desktop.peripherals.keyboard";
+            gsettings_set(kb, "repeat", if cfg.repeat_enabled { "true" } else { "false" });
+            gsettings_set(kb, "delay", &format!("uint32 {}", cfg.repeat_delay_ms));
+            gsettings_set(kb, "repeat-interval", &format!("uint32 {}", 1000 / cfg.repeat_rate.max(1)));
+            Ok(())
+        }
+        _ => Err("no-backend".into()),
+    }
+}
+
+/// 保存された設定が無いとき用: いまの配列やリピート設定には触れず、試験用のオプションだけを足す/外す
+fn apply_exam_options(on: bool) -> Result<(), String> {
+    let mut opts = active_options();
+    opts.retain(|o| !EXAM_XKB_OPTIONS.contains(&o.as_str()));
+    if on { opts.extend(EXAM_XKB_OPTIONS.iter().map(|o| o.to_string())); }
+    match backend() {
+        Some("x11") => {
+            // -layout を渡さなければ setxkbmap は現在の配列を引き継ぐ
+            let st = Command::new("setxkbmap").args(["-option", "", "-option", &opts.join(",")]).status().map_err(|e| format!("setxkbmap-failed: {}", e))?;
+            if st.success() { Ok(()) } else { Err("setxkbmap-failed".into()) }
+        }
+        Some("gnome") => {
+            gsettings_set("org.gnome.desktop.input-sources", "xkb-options", &gvariant_strv(&opts));
+            Ok(())
+        }
+        _ => Err("no-backend".into()),
+    }
+}
+
+/// いま効いている XKB オプション（X11 は setxkbmap -query、GNOME は gsettings）
+fn active_options() -> Vec<String> {
+    if backend() == Some("x11") {
+        run_out("setxkbmap", &["-query"]).unwrap_or_default().lines()
+            .find_map(|l| l.strip_prefix("options:").map(|o| o.trim().split(',').filter(|s| !s.is_empty()).map(|s| s.to_string()).collect()))
+            .unwrap_or_default()
+    } else {
+        let raw = gsettings_get("org.gnome.desktop.input-sources", "xkb-options").unwrap_or_default();
+        raw.trim_start_matches("@as ").trim_matches(['[', ']']).split(',').map(|s| s.trim().trim_matches('\'').to_string()).filter(|s| !s.is_empty()).collect()
+    }
+}
+
+/// 保存された設定が無いとき用: いまシステムで効いている配列・オプション・リピート・ポインタ設定から組み立てる
+fn live_config() -> InputConfig {
+    let mut c = InputConfig::default();
+    match backend() {
+        Some("x11") => {
+            let q = run_out("setxkbmap", &["-query"]).unwrap_or_default();
+            let field = |name: &str| q.lines().find_map(|l| l.strip_prefix(name).map(|v| v.trim().to_string()));
+            let layouts = field("layout:").unwrap_or_default();
+            let variants = field("variant:").unwrap_or_default();
+            let mut vs = variants.split(',');
+            let parsed: Vec<XkbLayout> = layouts.split(',').filter(|l| !l.is_empty())
+                .map(|l| XkbLayout { layout: l.to_string(), variant: vs.next().filter(|v| !v.is_empty()).map(|v| v.to_string()) })
+                .collect();
+            if !parsed.is_empty() { c.layouts = parsed; }
+            c.model = field("model:").filter(|m| !m.is_empty());
+            // xset q: "auto repeat:  on ..." / "auto repeat delay:  660    repeat rate:  25"
+            let xset = run_out("xset", &["q"]).unwrap_or_default();
+            for l in xset.lines().map(str::trim) {
+                if let Some(rest) = l.strip_prefix("auto repeat:") { c.repeat_enabled = rest.trim_start().starts_with("on"); }
+                if let Some(rest) = l.strip_prefix("auto repeat delay:") {
+                    let mut nums = rest.split_whitespace().filter_map(|w| w.parse::<u32>().ok());
+                    if let Some(d) = nums.next() { c.repeat_delay_ms = d.clamp(100, 2000); }
+                    if let Some(r) = nums.next() { c.repeat_rate = r.clamp(1, 100); }
+                }
+            }
+        }
+        Some("gnome") => {
+            let sources = gsettings_get("org.gnome.desktop.input-sources", "sources").unwrap_or_default();
+            let re = regex::Regex::new(r"\('xkb', '([^']+)'\)").unwrap();
+            let parsed: Vec<XkbLayout> = re.captures_iter(&sources).map(|cap| match cap[1].split_once('+') {
+                Some((l, v)) => XkbLayout { layout: l.to_string(), variant: Some(v.to_string()) },
+                None => XkbLayout { layout: cap[1].to_string(), variant: None },
+            }).collect();
+            if !parsed.is_empty() { c.layouts = parsed; }
+            let kb = "org.gnome.desktop.peripherals.keyboard";
+            if let Some(v) = gsettings_get(kb, "repeat") { c.repeat_enabled = v == "true"; }
+            let uint = |key: &str| gsettings_get(kb, key).and_then(|v| v.trim_start_matches("uint32 ").parse::<u32>().ok());
+            if let Some(d) = uint("delay") { c.repeat_delay_ms = d.clamp(100, 2000); }
+            if let Some(i) = uint("repeat-interval") { c.repeat_rate = (1000 / i.max(1)).clamp(1, 100); }
+        }
+        _ => {}
+    }
+    // 試験中に足しているオプションは保存しない
+    c.options = active_options().into_iter().filter(|o| !EXAM_XKB_OPTIONS.contains(&o.as_str())).collect();
+    let devices = list_devices();
+    if let Some(m) = devices.iter().find(|d| d.kind == "mouse") {
+        if let Some(v) = m.accel_speed { c.mouse_speed = v; }
+        if let Some(v) = m.natural_scroll { c.mouse_natural_scroll = v; }
+    }
+    if let Some(t) = devices.iter().find(|d| d.kind == "touchpad") {
+        if let Some(v) = t.accel_speed { c.touchpad_speed = v; }
+        if let Some(v) = t.natural_scroll { c.touchpad_natural_scroll = v; }
+        if let Some(v) = t.tapping { c.tap_to_click = v; }
+    }
+    c
+}
+
+// ---- ポインタ（xinput / libinput） ----
+
+fn xinput_props(id: u32) -> String { run_out("xinput", &["list-props", &id.to_string()]).unwrap_or_default() }
+
+/// "libinput Accel Speed (297): 0.000000" のような行の値
+fn prop_value<'a>(props: &'a str, name: &str) -> Option<&'a str> {
+    props.lines().find_map(|l| {
+        let l = l.trim();
+        let rest = l.strip_prefix(name)?;
+        if !rest.starts_with(" (") { return None; }
+        Some(rest.split_once(':')?.1.trim())
+    })
+}
+
+fn list_devices() -> Vec<InputDevice> {
+    if !is_x11() || !which("xinput") { return Vec::new(); }
+    run_out("xinput", &["list", "--short"]).unwrap_or_default().lines().filter_map(|l| {
+        let id: u32 = l.split("id=").nth(1)?.split_whitespace().next()?.parse().ok()?;
+        let name = l.split("id=").next()?.trim_start_matches(['⎡', '⎜', '⎣', '↳', ' ']).trim().to_string();
+        let slave_kbd = l.contains("slave  keyboard");
+        let slave_ptr = l.contains("slave  pointer");
+        if (!slave_kbd && !slave_ptr) || name.contains("XTEST") { return None; }
+        if slave_kbd {
+            // 電源ボタン等もキーボードとして出るので名前で大まかに除く
+            if name.contains("Button") || name.contains("Video Bus") { return None; }
+            return Some(InputDevice { id, name, kind: "keyboard".into(), accel_speed: None, natural_scroll: None, tapping: None });
+        }
+        let props = xinput_props(id);
+        let tapping = prop_value(&props, "libinput Tapping Enabled").map(|v| v.starts_with('1'));
+        Some(InputDevice {
+            id,
+            name,
+            kind: if tapping.is_some() { "touchpad" } else { "mouse" }.into(),
+            accel_speed: prop_value(&props, "libinput Accel Speed").and_then(|v| v.parse().ok()),
+            natural_scroll: prop_value(&props, "libinput Natural Scrolling Enabled").map(|v| v.starts_with('1')),
+            tapping,
+        })
+    }).collect()
+}
+
+fn xinput_set(id: u32, prop: &str, value: &str) {
+    let _ = Command::new("xinput").args(["set-prop", &id.to_string(), prop, value]).status();
+}
+
+fn apply_pointer_device(cfg: &InputConfig, d: &InputDevice) {
+    let touchpad = d.kind == "touchpad";
+    let (speed, natural) = if touchpad { (cfg.touchpad_speed, cfg.touchpad_natural_scroll) } else { (cfg.mouse_speed, cfg.mouse_natural_scroll) };
+    if d.accel_speed.is_some() { xinput_set(d.id, "libinput Accel Speed", &format!("{:.3}", speed.clamp(-1.0, 1.0))); }
+    if d.natural_scroll.is_some() { xinput_set(d.id, "libinput Natural Scrolling Enabled", if natural { "1" } else { "0" }); }
+    if touchpad && d.tapping.is_some() { xinput_set(d.id, "libinput Tapping Enabled", if cfg.tap_to_click { "1" } else { "0" }); }
+}
+
+/// 新しいキーボードが見つかったら true（X はそれを既定の配列で初期化するので付け直しが要る）
+fn apply_pointers(cfg: &InputConfig, only_new: bool) -> bool {
+    let mut new_keyboard = false;
+    match backend() {
+        Some("x11") => {
+            let devices = list_devices();
+            let mut applied = APPLIED.lock().unwrap();
+            if !only_new { applied.clear(); }
+            for d in &devices {
+                if !applied.insert(d.id) { continue; }
+                if d.kind == "keyboard" { new_keyboard |= only_new; } else { apply_pointer_device(cfg, d); }
+            }
+            // 抜かれたデバイスは忘れる（同じ id が別デバイスに再利用されるため）
+            applied.retain(|id| devices.iter().any(|d| d.id == *id));
+        }
+        Some("gnome") => {
+            let m = "org.gnome.desktop.peripherals.mouse";
+            let t = "org.gnome.desktop.peripherals.touchpad";
+            gsettings_set(m, "speed", &format!("{:.3}", cfg.mouse_speed.clamp(-1.0, 1.0)));
+            gsettings_set(m, "natural-scroll", if cfg.mouse_natural_scroll { "true" } else { "false" });
+            gsettings_set(t, "speed", &format!("{:.3}", cfg.touchpad_speed.clamp(-1.0, 1.0)));
+            gsettings_set(t, "natural-scroll", if cfg.touchpad_natural_scroll { "true" } else { "false" });
+            gsettings_set(t, "tap-to-click", if cfg.tap_to_click { "true" } else { "false" });
+        }
+        _ => {}
+    }
+    new_keyboard
+}
+
+// ---- 試験モード ----
+
+fn disable_window_switching() {
+    if !which("gsettings") || gsettings_get(WM_KEYBINDINGS, EXAM_WM_KEYS[0]).is_none() { return; }
+    let Some(p) = exam_restore_path() else { return };
+    // 既に保存済み（前回の試験中に落ちた）なら元の値を上書きしない
+    if !p.exists() {
+        let saved: Vec<(String, String)> = EXAM_WM_KEYS.iter().filter_map(|k| gsettings_get(WM_KEYBINDINGS, k).map(|v| (k.to_string(), v))).collect();
+        if let Ok(s) = serde_json::to_string(&saved) { let _ = fs::write(&p, s); }
+    }
+    for k in EXAM_WM_KEYS { gsettings_set(WM_KEYBINDINGS, k, "[]"); }
+}
+
+fn restore_window_switching() {
+    let Some(p) = exam_restore_path() else { return };
+    let Some(saved) = fs::read_to_string(&p).ok().and_then(|s| serde_json::from_str::<Vec<(String, String)>>(&s).ok()) else { return };
+    for (k, v) in saved { gsettings_set(WM_KEYBINDINGS, &k, &v); }
+    let _ = fs::remove_file(p);
+}
+
+fn check_exam_mode() {
+    let on = exam_mode();
+    if EXAM_MODE.swap(on, Ordering::SeqCst) == on { return; }
+    if on { disable_window_switching(); } else { restore_window_switching(); }
+    let res = if config_path().is_some_and(|p| p.exists()) { apply_keyboard(&read_config()) } else { apply_exam_options(on) };
+    if let Err(e) = res { log_append("WARN", &format!("input: {}", e)); }
+    log_append("INFO", &format!("input: exam mode {}", if on { "on; server keys and window switching disabled" } else { "off; restored" }));
+    emit_state();
+}
+
+fn state() -> InputState {
+    InputState {
+        backend: backend().map(|b| b.to_string()),
+        config: read_config(),
+        active_options: active_options(),
+        devices: list_devices(),
+        exam_mode: EXAM_MODE.load(Ordering::SeqCst),
+    }
+}
+
+fn emit_state() {
+    if let Some(app) = APP.lock().unwrap().as_ref() { let _ = app.emit("sis:input-state", &state()); }
+}
+
+pub fn start(app_handle: tauri::AppHandle) {
+    *APP.lock().unwrap() = Some(app_handle);
+    // 前回の試験モード中に終了していたらショートカットを戻す
+    if !exam_mode() { restore_window_switching(); }
+    std::thread::spawn(|| {
+        let cfg = read_config();
+        // 保存された設定がある場合だけ起動時に上書きする（初回はシステムの設定を尊重）
+        if config_path().is_some_and(|p| p.exists()) {
+            if let Err(e) = apply_keyboard(&cfg) { log_append("WARN", &format!("input: {}", e)); }
+            apply_pointers(&cfg, false);
+        }
+        loop {
+            check_exam_mode();
+            if backend() == Some("x11") && config_path().is_some_and(|p| p.exists()) {
+                let cfg = read_config();
+                if apply_pointers(&cfg, true) { let _ = apply_keyboard(&cfg); }
+            }
+            std::thread::sleep(POLL);
+        }
+    });
+}
+
+#[tauri::command]
+pub fn input_state() -> Result<InputState, SisError> { Ok(state()) }
+
+#[tauri::command]
+pub fn input_set_config(config: InputConfig) -> Result<InputState, SisError> {
+    if config.layouts.is_empty() { return Err("no-layout".into()); }
+    // setxkbmap/gsettings に渡すので XKB の名前に使う文字だけ許す
+    let tokens = config.layouts.iter().flat_map(|l| std::iter::once(&l.layout).chain(l.variant.iter().filter(|v| !v.is_empty())))
+        .chain(config.options.iter()).chain(config.model.iter());
+    if let Some(bad) = tokens.into_iter().find(|t| !valid_xkb_token(t)) { return Err(SisError::invalid("invalid-xkb-name", bad)); }
+    let mut c = config;
+    c.repeat_delay_ms = c.repeat_delay_ms.clamp(100, 2000);
+    c.repeat_rate = c.repeat_rate.clamp(1, 100);
+    c.mouse_speed = c.mouse_speed.clamp(-1.0, 1.0);
+    c.touchpad_speed = c.touchpad_speed.clamp(-1.0, 1.0);
+    write_config(&c)?;
+    apply_keyboard(&c)?;
+    apply_pointers(&c, false);
+    let st = state();
+    if let Some(app) = APP.lock().unwrap().as_ref() { let _ = app.emit("sis:input-state", &st); }
+    Ok(st)
+}
+
+/// XKB オプションだけを足す/外す（保存される。試験モードの一時オプションとは別）
+#[tauri::command]
+pub fn input_set_xkb_option(option: String, enabled: bool) -> Result<Vec<String>, SisError> {
+    if !valid_xkb_token(&option) { return Err("invalid-xkb-name".into()); }
+    // 初回（input.json が無い）は既定値（jp 配列）ではなく、いま効いている設定を土台にする
+    let mut c = if config_path().is_some_and(|p| p.exists()) { read_config() } else { live_config() };
+    c.options.retain(|o| o != &option);
+    if enabled { c.options.push(option); }
+    write_config(&c)?;
+    apply_keyboard(&c)?;
+    emit_state();
+    Ok(active_options())
+}
+
+/// 選べる配列（xkeyboard-config の base.lst から layout 節）
+#[tauri::command]
+pub fn input_list_layouts() -> Result<Vec<(String, String)>, SisError> {
+    let text = fs::read_to_string("/usr/share/X11/xkb/rules/base.lst").map_err(|_| "xkb-rules-missing")?;
+    let mut out = Vec::new();
+    let mut in_layouts = false;
+    for l in text.lines() {
+        if l.starts_with('!') { in_layouts = l.trim() == "! layout"; continue; }
+        if !in_layouts { continue; }
+        let l = l.trim();
+        if let Some((name, desc)) = l.split_once(char::is_whitespace) { out.push((name.to_string(), desc.trim().to_string())); }
+    }
+    Ok(out)
+}
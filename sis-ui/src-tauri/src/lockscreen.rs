@@ -0,0 +1,517 @@
+// ロック画面: 画面全体を覆う "lock" ウィンドウを最前面に出し、GTK でキーボード/ポインタを掴んで PAM で解除する。
+// 無操作（xprintidle か logind の IdleHint）、サスペンド直前（PrepareForSleep）、logind の Lock シグナル
+// （loginctl lock-sessions）で自動ロックする。試験モード/スクリーンタイムによるロックはその旨を表示する
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tauri::{Emitter, Manager};
+use zbus::zvariant::OwnedFd;
+
+use crate::error::SisError;
+use crate::{exam_mode, history_dir, log_append, run_out};
+
+const LOCK_WINDOW: &str = "lock";
+const POLICY_PATH: &str = "/var/lib/sis-mdm/policies.json";
+const IDLE_POLL: Duration = Duration::from_secs(5);
+// 他アプリがメニュー等で掴んでいると失敗するので少し待って取り直す
+const GRAB_RETRIES: u32 = 20;
+const GRAB_RETRY_WAIT: Duration = Duration::from_millis(100);
+// 続けて失敗したら一定時間入力を受け付けない
+const MAX_FAILED_ATTEMPTS: u32 = 5;
+const FAIL_COOLDOWN_MS: i64 = 30_000;
+// サスペンド前にロック画面が描画されるまで delay 抑止を持っておく時間
+const SLEEP_LOCK_WAIT: Duration = Duration::from_millis(500);
+
+static APP: Lazy<Mutex<Option<tauri::AppHandle>>> = Lazy::new(|| Mutex::new(None));
+static STATE: Lazy<Mutex<LockState>> = Lazy::new(|| Mutex::new(LockState::unlocked()));
+static GRABBED: AtomicBool = AtomicBool::new(false);
+static AUTH_BUSY: AtomicBool = AtomicBool::new(false);
+// サスペンド前にロックするための delay 抑止（fd を drop すると解放）
+static SLEEP_DELAY: Lazy<Mutex<Option<OwnedFd>>> = Lazy::new(|| Mutex::new(None));
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LockState {
+    locked: bool,
+    reason: Option<String>, // user | idle | suspend | logind | screentime | exam
+    message: Option<String>,
+    since_ms: Option<i64>,
+    user: String,
+    display_name: String,
+    failed_attempts: u32,
+    retry_after_ms: Option<i64>,
+    grabbed: bool,
+}
+
+impl LockState {
+    fn unlocked() -> Self {
+        LockState {
+            locked: false,
+            reason: None,
+            message: None,
+            since_ms: None,
+            user: current_user(),
+            display_name: display_name(),
+            failed_attempts: 0,
+            retry_after_ms: None,
+            grabbed: false,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LockConfig {
+    idle_minutes: u32, // 0 なら無操作ロックしない
+    lock_on_suspend: bool,
+}
+
+impl Default for LockConfig {
+    fn default() -> Self { LockConfig { idle_minutes: 10, lock_on_suspend: true } }
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct AuthResult {
+    ok: bool,
+    message: String,
+}
+
+fn now_ms() -> i64 { chrono::Utc::now().timestamp_millis() }
+
+fn current_user() -> String { std::env::var("USER").unwrap_or_default() }
+
+/// passwd の GECOS 先頭（なければユーザー名）
+fn display_name() -> String {
+    let user = current_user();
+    run_out("getent", &["passwd", &user])
+        .and_then(|l| l.trim().split(':').nth(4).map(|g| g.split(',').next().unwrap_or("").trim().to_string()))
+        .filter(|g| !g.is_empty())
+        .unwrap_or(user)
+}
+
+fn config_path() -> Option<PathBuf> { history_dir().map(|d| d.join("lock.json")) }
+
+fn read_config() -> LockConfig {
+    config_path().and_then(|p| fs::read_to_string(p).ok()).and_then(|s| serde_json::from_str(&s).ok()).unwrap_or_default()
+}
+
+fn emit_state() {
+    let st = STATE.lock().unwrap().clone();
+    if let Some(app) = APP.lock().unwrap().as_ref() { let _ = app.emit("sis:lock-state", &st); }
+}
+
+fn emit_auth(ok: bool, message: &str) {
+    if let Some(app) = APP.lock().unwrap().as_ref() {
+        let _ = app.emit_to(LOCK_WINDOW, "sis:lock-auth", AuthResult { ok, message: message.to_string() });
+    }
+}
+
+pub fn is_locked() -> bool { STATE.lock().unwrap().locked }
+
+// ---- ポリシー ----
+
+/// MDM の利用可能時間帯（screen_time.allow/start/end）の外なら (start, end) を返す
+fn outside_allowed_hours() -> Option<(String, String)> {
+    let v: serde_json::Value = serde_json::from_str(&fs::read_to_string(POLICY_PATH).ok()?).ok()?;
+    let st = v.get("screen_time")?;
+    if !st.get("allow").and_then(|a| a.as_bool()).unwrap_or(false) { return None; }
+    let start = st.get("start").and_then(|s| s.as_str()).unwrap_or("00:00").to_string();
+    let end = st.get("end").and_then(|s| s.as_str()).unwrap_or("23:59").to_string();
+    // enforce-screentime.sh と同じく HH:MM の文字列比較
+    let now = chrono::Local::now().format("%H:%M").to_string();
+    if now < start || now > end { Some((start, end)) } else { None }
+}
+
+/// logind 経由のロックが試験モード/スクリーンタイムによるものなら理由と表示文を返す
+fn policy_reason() -> Option<(String, String)> {
+    if exam_mode() {
+        return Some(("exam".into(), "試験モード中です。監督者の指示があるまでお待ちください。".into()));
+    }
+    outside_allowed_hours().map(|(start, end)| ("screentime".into(), format!("いまは使用できない時間帯です（利用可能: {}〜{}）", start, end)))
+}
+
+// ---- 入力の占有 ----
+
+/// GTK のメインスレッドでシートごと（キーボード+ポインタ）掴む。X11 のみ有効
+fn grab_once(app: &tauri::AppHandle, win: tauri::WebviewWindow, grab: bool) -> bool {
+    let (tx, rx) = std::sync::mpsc::channel();
+    let _ = app.run_on_main_thread(move || {
+        use gtk::prelude::*;
+        let ok = win.gtk_window().ok().and_then(|gw| gw.window()).and_then(|gdk_win| {
+            let seat = gdk_win.display().default_seat()?;
+            if grab {
+                Some(seat.grab(&gdk_win, gtk::gdk::SeatCapabilities::ALL, true, None, None, None) == gtk::gdk::GrabStatus::Success)
+            } else {
+                seat.ungrab();
+                Some(true)
+            }
+        });
+        let _ = tx.send(ok.unwrap_or(false));
+    });
+    rx.recv_timeout(Duration::from_secs(2)).unwrap_or(false)
+}
+
+fn grab_input(app: &tauri::AppHandle) -> bool {
+    let Some(win) = app.get_webview_window(LOCK_WINDOW) else { return false };
+    for _ in 0..GRAB_RETRIES {
+        if grab_once(app, win.clone(), true) { return true; }
+        std::thread::sleep(GRAB_RETRY_WAIT);
+    }
+    false
+}
+
+fn set_grabbed(v: bool) {
+    GRABBED.store(v, Ordering::SeqCst);
+    STATE.lock().unwrap().grabbed = v;
+    emit_state();
+}
+
+/// 画面全体（X11 はルートウィンドウ = 全出力）を覆って前面に出す
+fn show_window(app: &tauri::AppHandle) -> Result<(), String> {
+    let win = app.get_webview_window(LOCK_WINDOW).ok_or("lock-window-missing")?;
+    if crate::screenshot::is_wayland() {
+        // Wayland では通常のクライアントは入力を占有できない。全画面＋最前面で覆うだけにする
+        let _ = win.set_fullscreen(true);
+    } else if let Some((w, h)) = crate::recorder::x11_screen_size() {
+        let _ = win.set_position(tauri::PhysicalPosition::new(0, 0));
+        let _ = win.set_size(tauri::PhysicalSize::new(w, h));
+    }
+    let _ = win.show();
+    let _ = win.set_always_on_top(true);
+    let _ = win.set_focus();
+    Ok(())
+}
+
+// ---- ロック/解除 ----
+
+pub fn lock(reason: &str, message: Option<String>) -> Result<(), String> {
+    let app = APP.lock().unwrap().clone().ok_or("not-started")?;
+    {
+        let mut st = STATE.lock().unwrap();
+        if st.locked {
+            // すでにロック中でもポリシー由来の表示は上書きする
+            if matches!(reason, "exam" | "screentime") {
+                st.reason = Some(reason.to_string());
+                st.message = message;
+            }
+        } else {
+            *st = LockState { locked: true, reason: Some(reason.to_string()), message, since_ms: Some(now_ms()), ..LockState::unlocked() };
+        }
+    }
+    emit_state();
+    show_window(&app)?;
+    crate::session::set_locked_hint(true);
+    log_append("INFO", &format!("lockscreen: locked ({})", reason));
+    if !crate::screenshot::is_wayland() && !GRABBED.load(Ordering::SeqCst) {
+        std::thread::spawn(move || {
+            let ok = grab_input(&app);
+            if !ok { log_append("WARN", "lockscreen: failed to grab keyboard/pointer; will retry"); }
+            set_grabbed(ok);
+        });
+    }
+    Ok(())
+}
+
+fn unlock() {
+    let app = APP.lock().unwrap().clone();
+    if let Some(app) = app {
+        if let Some(win) = app.get_webview_window(LOCK_WINDOW) {
+            if GRABBED.load(Ordering::SeqCst) { grab_once(&app, win.clone(), false); }
+            let _ = win.set_fullscreen(false);
+            let _ = win.hide();
+        }
+    }
+    GRABBED.store(false, Ordering::SeqCst);
+    *STATE.lock().unwrap() = LockState::unlocked();
+    emit_state();
+    crate::session::set_locked_hint(false);
+    log_append("INFO", "lockscreen: unlocked");
+}
+
+// ---- PAM ----
+
+mod pam {
+    use std::ffi::CString;
+    use std::os::raw::{c_char, c_int, c_void};
+
+    const PAM_SUCCESS: c_int = 0;
+    const PAM_BUF_ERR: c_int = 5;
+    const PAM_AUTH_ERR: c_int = 7;
+    const PAM_CONV_ERR: c_int = 19;
+    const PAM_MAXTRIES: c_int = 11;
+    const PAM_USER_UNKNOWN: c_int = 10;
+    const PAM_PERM_DENIED: c_int = 6;
+    const PAM_NEW_AUTHTOK_REQD: c_int = 12;
+    const PAM_ACCT_EXPIRED: c_int = 13;
+    const PAM_PROMPT_ECHO_OFF: c_int = 1;
+    const PAM_PROMPT_ECHO_ON: c_int = 2;
+    const PAM_REFRESH_CRED: c_int = 0x0010;
+
+    #[repr(C)]
+    struct PamMessage {
+        msg_style: c_int,
+        msg: *const c_char,
+    }
+
+    #[repr(C)]
+    struct PamResponse {
+        resp: *mut c_char,
+        resp_retcode: c_int,
+    }
+
+    #[repr(C)]
+    struct PamConv {
+        conv: extern "C" fn(c_int, *mut *const PamMessage, *mut *mut PamResponse, *mut c_void) -> c_int,
+        appdata_ptr: *mut c_void,
+    }
+
+    #[repr(C)]
+    struct PamHandle {
+        _private: [u8; 0],
+    }
+
+    #[link(name = "pam")]
+    extern "C" {
+        fn pam_start(service: *const c_char, user: *const c_char, conv: *const PamConv, pamh: *mut *mut PamHandle) -> c_int;
+        fn pam_authenticate(pamh: *mut PamHandle, flags: c_int) -> c_int;
+        fn pam_acct_mgmt(pamh: *mut PamHandle, flags: c_int) -> c_int;
+        fn pam_setcred(pamh: *mut PamHandle, flags: c_int) -> c_int;
+        fn pam_end(pamh: *mut PamHandle, status: c_int) -> c_int;
+    }
+
+    /// パスワードを問うプロンプトにだけ答える（情報/エラーメッセージは空応答）
+    extern "C" fn converse(n: c_int, msgs: *mut *const PamMessage, resp: *mut *mut PamResponse, data: *mut c_void) -> c_int {
+        if n <= 0 || msgs.is_null() || resp.is_null() { return PAM_CONV_ERR; }
+        unsafe {
+            let out = libc::calloc(n as usize, std::mem::size_of::<PamResponse>()) as *mut PamResponse;
+            if out.is_null() { return PAM_BUF_ERR; }
+            let password = &*(data as *const CString);
+            for i in 0..n as usize {
+                let m = *msgs.add(i);
+                if m.is_null() { continue; }
+                if matches!((*m).msg_style, PAM_PROMPT_ECHO_OFF | PAM_PROMPT_ECHO_ON) {
+                    (*out.add(i)).resp = libc::strdup(password.as_ptr());
+                }
+            }
+            *resp = out;
+        }
+        PAM_SUCCESS
+    }
+
+    pub fn authenticate(service: &str, user: &str, password: &str) -> Result<(), String> {
+        let service = CString::new(service).map_err(|_| "invalid-service")?;
+        let user = CString::new(user).map_err(|_| "invalid-user")?;
+        let password = CString::new(password).map_err(|_| "invalid-password")?;
+        let conv = PamConv { conv: converse, appdata_ptr: &password as *const CString as *mut c_void };
+        let mut handle: *mut PamHandle = std::ptr::null_mut();
+        let rc = unsafe { pam_start(service.as_ptr(), user.as_ptr(), &conv, &mut handle) };
+        if rc != PAM_SUCCESS || handle.is_null() { return Err(format!("pam-start-failed: {}", rc)); }
+        let mut rc = unsafe { pam_authenticate(handle, 0) };
+        if rc == PAM_SUCCESS {
+            // アカウントの有効性（期限切れ・ログイン時間帯の制限など、pam.d の account 行）を確認する。
+            // パスワードの期限切れはロック画面では変更できないので解除は許す
+            rc = match unsafe { pam_acct_mgmt(handle, 0) } { PAM_NEW_AUTHTOK_REQD => PAM_SUCCESS, r => r };
+        }
+        if rc == PAM_SUCCESS {
+            // Kerberos チケット等の更新。失敗しても解除は妨げない
+            unsafe { pam_setcred(handle, PAM_REFRESH_CRED) };
+        }
+        unsafe { pam_end(handle, rc) };
+        let mut bytes = password.into_bytes();
+        bytes.iter_mut().for_each(|b| *b = 0);
+        match rc {
+            PAM_SUCCESS => Ok(()),
+            PAM_AUTH_ERR | PAM_MAXTRIES => Err("auth-failed".into()),
+            PAM_USER_UNKNOWN => Err("user-unknown".into()),
+            PAM_ACCT_EXPIRED => Err("account-expired".into()),
+            PAM_PERM_DENIED => Err("permission-denied".into()),
+            other => Err(format!("pam-error: {}", other)),
+        }
+    }
+}
+
+/// 専用設定（provisioning/pam.d/sis-lock）があればそれを、なければ login を使う
+fn pam_service() -> &'static str {
+    if std::path::Path::new("/etc/pam.d/sis-lock").exists() { "sis-lock" } else { "login" }
+}
+
+fn authenticate(password: String) {
+    let user = current_user();
+    let res = pam::authenticate(pam_service(), &user, &password);
+    AUTH_BUSY.store(false, Ordering::SeqCst);
+    match res {
+        Ok(()) => {
+            emit_auth(true, "");
+            unlock();
+        }
+        Err(e) => {
+            log_append("WARN", &format!("lockscreen: authentication failed for {}: {}", user, e));
+            let msg = {
+                let mut st = STATE.lock().unwrap();
+                st.failed_attempts += 1;
+                if st.failed_attempts >= MAX_FAILED_ATTEMPTS {
+                    st.failed_attempts = 0;
+                    st.retry_after_ms = Some(now_ms() + FAIL_COOLDOWN_MS);
+                    format!("{}回続けて失敗しました。{}秒後にもう一度お試しください", MAX_FAILED_ATTEMPTS, FAIL_COOLDOWN_MS / 1000)
+                } else if e == "auth-failed" {
+                    "パスワードが違います".to_string()
+                } else {
+                    format!("認証できませんでした（{}）", e)
+                }
+            };
+            emit_state();
+            emit_auth(false, &msg);
+        }
+    }
+}
+
+// ---- 自動ロック ----
+
+/// X サーバーの最後の入力からの経過（XScreenSaver 拡張。xprintidle と同じ値）
+fn x11_idle_ms() -> Option<u64> {
+    use x11rb::connection::Connection as _;
+    use x11rb::protocol::screensaver::ConnectionExt as _;
+    if crate::screenshot::is_wayland() || std::env::var("DISPLAY").is_err() { return None; }
+    let (conn, screen) = x11rb::connect(None).ok()?;
+    let root = conn.setup().roots.get(screen)?.root;
+    let info = conn.screensaver_query_info(root).ok()?.reply().ok()?;
+    Some(info.ms_since_user_input as u64)
+}
+
+/// 最後の入力からの経過。X11 は XScreenSaver 拡張、なければ logind の IdleHint
+fn idle_ms() -> Option<u64> {
+    if let Some(ms) = x11_idle_ms() { return Some(ms); }
+    let conn = crate::session::system_bus().ok()?;
+    let p = crate::session::session_proxy(&conn, crate::session::own_session_path()?).ok()?;
+    if !p.get_property::<bool>("IdleHint").ok()? { return Some(0); }
+    let since_us = p.get_property::<u64>("IdleSinceHint").ok()?;
+    Some(((now_ms() * 1000) as u64).saturating_sub(since_us) / 1000)
+}
+
+fn idle_loop() {
+    loop {
+        std::thread::sleep(IDLE_POLL);
+        if is_locked() {
+            // 他アプリのグラブで取れなかった場合は取り直す
+            if !crate::screenshot::is_wayland() && !GRABBED.load(Ordering::SeqCst) {
+                let app = APP.lock().unwrap().clone();
+                if let Some(app) = app {
+                    if grab_input(&app) { set_grabbed(true); }
+                }
+            }
+            continue;
+        }
+        let cfg = read_config();
+        if cfg.idle_minutes == 0 { continue; }
+        let Some(idle) = idle_ms() else { continue };
+        if idle >= cfg.idle_minutes as u64 * 60_000 && !crate::session::idle_inhibited() {
+            let _ = lock("idle", None);
+        }
+    }
+}
+
+/// logind の Lock/Unlock（loginctl lock-sessions / unlock-sessions）
+fn watch_session_signal(name: &'static str) {
+    let Some(path) = crate::session::own_session_path() else {
+        log_append("WARN", "lockscreen: no logind session for this process; Lock signals ignored");
+        return;
+    };
+    let Ok(conn) = crate::session::system_bus() else { return };
+    let Ok(proxy) = crate::session::session_proxy(&conn, path) else { return };
+    let Ok(signals) = proxy.receive_signal(name) else { return };
+    for _ in signals {
+        if name == "Unlock" {
+            if is_locked() { unlock(); }
+            continue;
+        }
+        let (reason, message) = match policy_reason() {
+            Some((r, m)) => (r, Some(m)),
+            None => ("logind".to_string(), None),
+        };
+        if let Err(e) = lock(&reason, message) { log_append("WARN", &format!("lockscreen: lock failed: {}", e)); }
+    }
+}
+
+fn take_sleep_delay() {
+    match crate::session::take_inhibitor("sleep", "サスペンド前に画面をロックする", "delay") {
+        Ok(fd) => *SLEEP_DELAY.lock().unwrap() = Some(fd),
+        Err(e) => log_append("WARN", &format!("lockscreen: {}", e)),
+    }
+}
+
+/// サスペンド直前にロックしてから delay 抑止を放す。復帰後に取り直す
+fn watch_sleep() {
+    if read_config().lock_on_suspend { take_sleep_delay(); }
+    let Ok(conn) = crate::session::system_bus() else { return };
+    let Ok(manager) = crate::session::manager(&conn) else { return };
+    let Ok(signals) = manager.receive_signal("PrepareForSleep") else { return };
+    for msg in signals {
+        let Ok(going_down) = msg.body().deserialize::<bool>() else { continue };
+        if going_down {
+            if read_config().lock_on_suspend && !is_locked() {
+                let _ = lock("suspend", None);
+                std::thread::sleep(SLEEP_LOCK_WAIT);
+            }
+            SLEEP_DELAY.lock().unwrap().take();
+        } else if read_config().lock_on_suspend {
+            take_sleep_delay();
+        }
+    }
+}
+
+pub fn start(app_handle: tauri::AppHandle) {
+    *APP.lock().unwrap() = Some(app_handle);
+    if !std::path::Path::new("/etc/pam.d/sis-lock").exists() {
+        log_append("INFO", "lockscreen: /etc/pam.d/sis-lock not found; using the login PAM service");
+    }
+    std::thread::spawn(idle_loop);
+    std::thread::spawn(|| watch_session_signal("Lock"));
+    std::thread::spawn(|| watch_session_signal("Unlock"));
+    std::thread::spawn(watch_sleep);
+}
+
+/// reason 省略時は利用者による手動ロック
+#[tauri::command]
+pub fn lock_screen(reason: Option<String>, message: Option<String>) -> Result<String, SisError> {
+    let reason = reason.unwrap_or_else(|| "user".into());
+    if !matches!(reason.as_str(), "user" | "idle" | "suspend" | "logind" | "screentime" | "exam") { return Err("invalid-reason".into()); }
+    lock(&reason, message)?;
+    Ok("locked".into())
+}
+
+#[tauri::command]
+pub fn lock_state() -> Result<LockState, SisError> { Ok(STATE.lock().unwrap().clone()) }
+
+/// 結果は sis:lock-auth（{ok,message}）で返す
+#[tauri::command]
+pub fn lock_authenticate(password: String) -> Result<String, SisError> {
+    {
+        let st = STATE.lock().unwrap();
+        if !st.locked { return Err("not-locked".into()); }
+        if st.retry_after_ms.is_some_and(|t| t > now_ms()) { return Err("cooldown".into()); }
+    }
+    if password.is_empty() { return Err("empty-password".into()); }
+    if AUTH_BUSY.swap(true, Ordering::SeqCst) { return Err("busy".into()); }
+    std::thread::spawn(move || authenticate(password));
+    Ok("checking".into())
+}
+
+#[tauri::command]
+pub fn lock_get_config() -> Result<LockConfig, SisError> { Ok(read_config()) }
+
+#[tauri::command]
+pub fn lock_set_config(config: LockConfig) -> Result<String, SisError> {
+    let p = config_path().ok_or("no-data-dir")?;
+    if let Some(dir) = p.parent() { let _ = fs::create_dir_all(dir); }
+    fs::write(&p, serde_json::to_string_pretty(&config).map_err(|e| e.to_string())?).map_err(|e| format!("write-failed: {}", e))?;
+    // サスペンド前ロックの切り替えは delay 抑止の有無に反映する
+    if config.lock_on_suspend {
+        if SLEEP_DELAY.lock().unwrap().is_none() { take_sleep_delay(); }
+    } else {
+        SLEEP_DELAY.lock().unwrap().take();
+    }
+    Ok("saved".into())
+}
@@ -3,7 +3,6 @@ use tauri::Manager;
 
 use sysinfo::System;
 use std::sync::{Arc, Mutex};
-use std::time::Instant;
 use std::path::{Path, PathBuf};
 use std::fs;
 use mime_guess;
@@ -22,8 +21,36 @@ use std::io::Read as _;
 use once_cell::sync::Lazy;
 use image::{DynamicImage, ImageBuffer, ImageFormat, Rgba};
 use regex;
+use error::SisError;
 // Use crate root re-exports for size/position types
 
+mod error;
+mod screentime;
+mod mimeapps;
+mod favorites;
+mod telemetry;
+mod power;
+mod taskmgr;
+mod storage;
+mod network;
+mod bluetooth;
+mod audio;
+mod backlight;
+mod media;
+mod notifications;
+mod tray;
+mod screenshot;
+mod recorder;
+mod clipboard;
+mod session;
+mod lockscreen;
+mod nightlight;
+mod input;
+mod ime;
+mod timedate;
+mod organizer;
+mod downloads;
+
 // Best-effort: on X11 sessions misconfigured to 800x600, bump primary output to the largest available mode
 fn adjust_x11_resolution_if_tiny() {
     // Only on X11 and if xrandr is available
@@ -108,12 +135,6 @@ fn adjust_x11_resolution_if_tiny() {
     }
 }
 
-struct NetworkStats {
-    last_received_bytes: u64,
-    last_transmitted_bytes: u64,
-    last_update_time: Instant,
-}
-
 #[derive(Debug, Serialize, Deserialize, Clone)]
 struct WindowInfo {
     id: String,
@@ -146,7 +167,7 @@ fn build_wmclass_cache() {
                             
                             let icon_path = resolve_icon_path(&icon_raw);
                             let icon_data_url = icon_path.as_ref().and_then(|p| to_data_url(p));
-                            let app_info = AppInfo { name: name.clone(), exec, icon_data_url };
+                            let app_info = AppInfo { name: name.clone(), exec, icon_data_url, desktop_id: desktop_id_of(&p) };
                             
                             // Cache by multiple keys
                             if !swm.is_empty() {
@@ -273,6 +294,24 @@ fn xprop_window_wm_class(window_id: &str) -> Option<String> {
     None
 }
 
+fn xprop_active_window_id() -> Option<String> {
+    if !which("xprop") { return None; }
+    let raw = run_out("xprop", &["-root", "_NET_ACTIVE_WINDOW"])?;
+    // Example: _NET_ACTIVE_WINDOW(WINDOW): window id # 0x3a00007
+    let id = raw.split_whitespace().last()?.trim().to_string();
+    if !id.starts_with("0x") || id == "0x0" { return None; }
+    Some(id)
+}
+
+fn xprop_window_title(window_id: &str) -> Option<String> {
+    if !which("xprop") { return None; }
+    let raw = run_out("xprop", &["-id", window_id, "_NET_WM_NAME"])?;
+    // Example: _NET_WM_NAME(UTF8_STRING) = "YouTube - Vivaldi"
+    let (_, rest) = raw.split_once('=')?;
+    let t = rest.trim().trim_matches('"').to_string();
+    if t.is_empty() { None } else { Some(t) }
+}
+
 fn read_proc_comm(pid: u32) -> Option<String> {
     let p = format!("/proc/{}/comm", pid);
     std::fs::read_to_string(p).ok().map(|s| s.trim().to_string())
@@ -473,147 +512,16 @@ fn guess_icon_candidates(wclass: &str, title: &str, exe: Option<&std::path::Path
     set.into_iter().collect()
 }
 
-fn read_net_totals() -> (u64, u64) {
-    // Sum rx/tx bytes from /proc/net/dev (Linux前提)
-    if let Ok(s) = std::fs::read_to_string("/proc/net/dev") {
-        let mut rx: u64 = 0;
-        let mut tx: u64 = 0;
-        for line in s.lines().skip(2) { // skip headers
-            if let Some(colon) = line.find(":") {
-                let rest = &line[colon + 1..];
-                let parts: Vec<&str> = rest.split_whitespace().collect();
-                if parts.len() >= 16 {
-                    // parts[0]=rx_bytes, parts[8]=tx_bytes
-                    if let Ok(v) = parts[0].parse::<u64>() { rx = rx.saturating_add(v); }
-                    if let Ok(v) = parts[8].parse::<u64>() { tx = tx.saturating_add(v); }
-                }
-            }
-        }
-        return (rx, tx);
-    }
-    (0, 0)
-}
-
-#[tauri::command]
-fn get_system_info(_system: tauri::State<System>, network_stats: tauri::State<Arc<Mutex<NetworkStats>>>) -> String {
-    // Create a fresh snapshot locally to avoid borrowing the managed System stored in state
-    let mut sys = sysinfo::System::new_all();
-    sys.refresh_all();
-
-    // sysinfo v0.36 uses global_cpu_usage()
-    let cpu_usage = sys.global_cpu_usage();
-    let mem_usage = if sys.total_memory() > 0 {
-        ((sys.used_memory() as f64 / sys.total_memory() as f64) * 100.0) as u64
-    } else { 0 };
-
-    // Network speed: compute delta since last call
-    let (rx, tx) = read_net_totals();
-    let mut dl_mbps: f64 = 0.0;
-    let mut ul_mbps: f64 = 0.0;
-    {
-        let mut ns = network_stats.lock().unwrap();
-        let now = Instant::now();
-        let dt = now.duration_since(ns.last_update_time).as_secs_f64();
-        if ns.last_update_time != Instant::now() && dt > 0.2 && ns.last_received_bytes > 0 {
-            let drx = rx.saturating_sub(ns.last_received_bytes) as f64; // bytes
-            let dtx = tx.saturating_sub(ns.last_transmitted_bytes) as f64; // bytes
-            dl_mbps = (drx / dt) / (1024.0 * 1024.0);
-            ul_mbps = (dtx / dt) / (1024.0 * 1024.0);
-        }
-        ns.last_received_bytes = rx;
-        ns.last_transmitted_bytes = tx;
-        ns.last_update_time = now;
-    }
-
-    format!("{{\"cpuUsage\":{:.1},\"memUsage\":{},\"downloadSpeed\":{:.2},\"uploadSpeed\":{:.2}}}", cpu_usage, mem_usage, dl_mbps, ul_mbps)
-}
-
-#[tauri::command]
-fn organize_file(file_path: String) -> Result<String, String> {
-    let path = Path::new(&file_path);
-    if !path.exists() {
-        return Err(format!("File does not exist: {}", file_path));
-    }
-    let path = Path::new(&file_path);
-    if !path.exists() {
-        return Err(format!("File does not exist: {}", file_path));
-    }
-
-    let mime_type = mime_guess::from_path(path).first_or_octet_stream();
-    // Resolve user directories under $HOME with sensible defaults
-    let home = dirs::home_dir().ok_or_else(|| "cannot-detect-home".to_string())?;
-    let mut target_dir: PathBuf = home.join("Downloads").join("Others"); // Default to Others
-
-    if mime_type.type_() == "image" {
-        target_dir = home.join("Pictures");
-    } else if mime_type.type_() == "video" {
-        target_dir = home.join("Videos");
-    } else if mime_type.type_() == "audio" {
-        target_dir = home.join("Music");
-    } else if mime_type.type_() == "text" || mime_type.subtype() == "pdf" {
-        target_dir = home.join("Documents");
-    } else if mime_type.type_() == "application" && (mime_type.subtype() == "zip" || mime_type.subtype() == "x-tar" || mime_type.subtype() == "x-rar-compressed") {
-        target_dir = home.join("Archives");
-    }
-
-    if !target_dir.exists() { fs::create_dir_all(&target_dir).map_err(|e| format!("Failed to create directory {:?}: {}", target_dir, e))?; }
-
-    let file_name = path.file_name().ok_or("Invalid file name")?;
-    let dest_path = target_dir.join(file_name);
-
-    fs::rename(path, &dest_path).map_err(|e| format!("Failed to move file from {} to {:?}: {}", file_path, dest_path, e))?;
-
-    Ok(format!("File {} organized to {:?}", file_path, dest_path))
-}
-
 #[tauri::command]
-fn organize_latest_download() -> Result<String, String> {
-    // Find the most recently modified regular file in ~/Downloads and organize it
-    let home = dirs::home_dir().ok_or_else(|| "cannot-detect-home".to_string())?;
-    let downloads = home.join("Downloads");
-    if !downloads.exists() { return Err("Downloads directory not found".into()); }
-
-    let mut latest_path: Option<PathBuf> = None;
-    let mut latest_mtime: Option<std::time::SystemTime> = None;
-
-    let entries = fs::read_dir(&downloads).map_err(|e| format!("Failed to read Downloads: {}", e))?;
-    for entry in entries {
-        let entry = entry.map_err(|e| format!("Failed to read entry: {}", e))?;
-        let path = entry.path();
-        if path.is_file() {
-            if let Ok(meta) = entry.metadata() {
-                if let Ok(modified) = meta.modified() {
-                    match latest_mtime {
-                        None => { latest_mtime = Some(modified); latest_path = Some(path); }
-                        Some(prev) => if modified > prev { latest_mtime = Some(modified); latest_path = Some(path); }
-                    }
-                }
-            }
-        }
-    }
-
-    let target = latest_path.ok_or_else(|| "no-files-in-downloads".to_string())?;
-    organize_file(target.to_string_lossy().to_string())
+fn get_system_info(system: tauri::State<telemetry::SharedSystem>) -> String {
+    // バックグラウンドサンプラーの最新値から旧形式のJSONを返す（毎回の全プロセス再スキャンを避ける）
+    telemetry::legacy_summary(&system).to_string()
 }
 
 #[tauri::command]
-fn set_volume(volume: u32) -> Result<String, String> {
-    let output = Command::new("pactl")
-        .arg("set-sink-volume")
-        .arg("@DEFAULT_SINK@")
-        .arg(format!("{}%", volume))
-        .output();
-
-    match output {
-        Ok(output) => {
-            if output.status.success() {
-                Ok(format!("Volume set to {}%", volume))
-            } else {
-                Err(format!("Failed to set volume: {}", String::from_utf8_lossy(&output.stderr)))
-            }
-        }
-    Err(e) => Err(format!("Failed to execute pactl: {}", e)),
-    }
+fn set_volume(volume: u32) -> Result<String, SisError> {
+    audio::set_device_volume("sink", "@DEFAULT_SINK@", volume)?;
+    Ok(format!("Volume set to {}%", volume))
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -621,6 +529,8 @@ struct AppInfo {
     name: String,
     exec: String,
     icon_data_url: Option<String>,
+    #[serde(default)]
+    desktop_id: Option<String>, // e.g. "org.gnome.Terminal.desktop"（.desktop 由来のときのみ）
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -798,7 +708,7 @@ fn to_data_url(path: &Path) -> Option<String> {
 }
 
 #[tauri::command]
-fn get_recent_apps() -> Result<Vec<AppInfo>, String> {
+fn get_recent_apps() -> Result<Vec<AppInfo>, SisError> {
     let mut apps = Vec::new();
     let mut app_dirs: Vec<PathBuf> = vec![
         PathBuf::from("/usr/share/applications"),
@@ -844,7 +754,7 @@ fn get_recent_apps() -> Result<Vec<AppInfo>, String> {
             // 緩める: validate_exec に失敗しても候補として掲載（起動は失敗する可能性あり）
             let icon_path = resolve_icon_path(&icon_raw);
             let icon_data_url = icon_path.as_ref().and_then(|p| to_data_url(p));
-            apps.push(AppInfo { name, exec, icon_data_url });
+            apps.push(AppInfo { name, exec, icon_data_url, desktop_id: desktop_id_of(&path) });
         }
     }
     // Merge AppImage candidates from common folders
@@ -859,8 +769,8 @@ fn get_recent_apps() -> Result<Vec<AppInfo>, String> {
             let exec_first = e.exec.split_whitespace().next().unwrap_or("").to_string();
             // include only if executable still exists or command is resolvable
             let p = Path::new(&exec_first);
-            if p.is_absolute() && p.exists() { Some(AppInfo { name: e.name, exec: e.exec, icon_data_url: e.icon_data_url }) }
-            else if which(&exec_first) { Some(AppInfo { name: e.name, exec: e.exec, icon_data_url: e.icon_data_url }) }
+            if p.is_absolute() && p.exists() { Some(AppInfo { name: e.name, exec: e.exec, icon_data_url: e.icon_data_url, desktop_id: None }) }
+            else if which(&exec_first) { Some(AppInfo { name: e.name, exec: e.exec, icon_data_url: e.icon_data_url, desktop_id: None }) }
             else { None }
         })
         .collect::<Vec<_>>();
@@ -925,7 +835,7 @@ fn scan_appimages(dirs: &[PathBuf]) -> Vec<AppInfo> {
                             // Try sibling icon (name.png/svg)
                             let icon = p.with_extension("png");
                             let icon_data_url = if icon.exists() { to_data_url(&icon) } else { None };
-                            out.push(AppInfo { name, exec, icon_data_url });
+                            out.push(AppInfo { name, exec, icon_data_url, desktop_id: None });
                         }
                     }
                 }
@@ -961,6 +871,11 @@ fn build_app_dirs() -> Vec<PathBuf> {
     app_dirs
 }
 
+fn desktop_id_of(path: &Path) -> Option<String> {
+    // applications/ 直下のみ走査しているため desktop ID = ファイル名
+    path.file_name().and_then(|s| s.to_str()).filter(|s| s.ends_with(".desktop")).map(|s| s.to_string())
+}
+
 fn parse_desktop_fields(content: &str) -> (String, String, String, String) {
     // returns (name, exec, icon_raw, startup_wmclass)
     let name = parse_localized_name(content);
@@ -1050,9 +965,9 @@ fn match_desktop_to_window(wclass: &str, title: &str) -> Option<AppInfo> {
 }
 
 #[tauri::command]
-fn file_to_data_url(path: String) -> Result<String, String> {
+fn file_to_data_url(path: String) -> Result<String, SisError> {
     let p = PathBuf::from(&path);
-    if !p.exists() { return Err("file-not-found".into()); }
+    if !p.exists() { return Err(SisError::not_found("file-not-found", &path)); }
     
     // Check if it's an image file
     let mime = mime_guess::from_path(&p).first_or_octet_stream();
@@ -1066,17 +981,17 @@ fn file_to_data_url(path: String) -> Result<String, String> {
             let b64 = base64::engine::general_purpose::STANDARD.encode(bytes);
             Ok(format!("data:{};base64,{}", mime_str, b64))
         }
-        Err(e) => Err(format!("read-error: {}", e))
+        Err(e) => Err(SisError::failed("read-error", e))
     }
 }
 
 #[tauri::command]
-fn resolve_window_app(wclass: String, title: String) -> Result<AppInfo, String> {
+fn resolve_window_app(wclass: String, title: String) -> Result<AppInfo, SisError> {
     match match_desktop_to_window(&wclass, &title) { Some(app) => Ok(app), None => Err("not-found".into()) }
 }
 
 #[tauri::command]
-fn resolve_window_icon(window_id: String, wclass: String, title: String) -> Result<AppInfo, String> {
+fn resolve_window_icon(window_id: String, wclass: String, title: String) -> Result<AppInfo, SisError> {
     log_append("INFO", &format!("resolve_window_icon: id={} wclass={} title={}", window_id, wclass, title));
     // 1) Try cache-based .desktop matching first
     if let Some(app) = match_desktop_to_window(&wclass, &title) {
@@ -1086,12 +1001,12 @@ fn resolve_window_icon(window_id: String, wclass: String, title: String) -> Resu
     if let Some(data_url) = net_wm_icon_png_data_url(&window_id) {
         let name = if !title.trim().is_empty() { title.clone() } else { wclass.clone() };
         log_append("INFO", "_NET_WM_ICON extracted");
-        return Ok(AppInfo { name, exec: "".into(), icon_data_url: Some(data_url) });
+        return Ok(AppInfo { name, exec: "".into(), icon_data_url: Some(data_url), desktop_id: None });
     } else { log_append("INFO", "_NET_WM_ICON not present or parse failed"); }
     // 2a) Quick WM_CLASS-based shortcuts for common apps
     if let Some(data_url) = icon_from_wmclass_shortcut(&wclass, &title) {
         let name = if !title.trim().is_empty() { title.clone() } else { wclass.clone() };
-        return Ok(AppInfo { name, exec: "".into(), icon_data_url: Some(data_url) });
+        return Ok(AppInfo { name, exec: "".into(), icon_data_url: Some(data_url), desktop_id: None });
     }
     // 3) Try PID -> process mapping -> icon
     if let Some(pid) = xprop_window_pid(&window_id) {
@@ -1099,7 +1014,7 @@ fn resolve_window_icon(window_id: String, wclass: String, title: String) -> Resu
             let name = if !title.trim().is_empty() { title.clone() } else { wclass.clone() };
             let exec = read_proc_exe(pid).map(|p| p.to_string_lossy().to_string()).unwrap_or_default();
             log_append("INFO", &format!("pid fallback icon resolved: pid={} exec={}", pid, exec));
-            return Ok(AppInfo { name, exec, icon_data_url: Some(data_url) });
+            return Ok(AppInfo { name, exec, icon_data_url: Some(data_url), desktop_id: None });
         }
         // 4) Heuristic guesses based on wmclass/title/exe/comm
         let exe = read_proc_exe(pid);
@@ -1112,7 +1027,7 @@ fn resolve_window_icon(window_id: String, wclass: String, title: String) -> Resu
                 if let Some(data) = to_data_url(&p) {
                     log_append("INFO", &format!("heuristic icon: pid={} name={} path={}", pid, icon_name, p.to_string_lossy()));
                     let exec = exe.as_ref().map(|p| p.to_string_lossy().to_string()).unwrap_or_default();
-                    return Ok(AppInfo { name: title.clone(), exec, icon_data_url: Some(data) });
+                    return Ok(AppInfo { name: title.clone(), exec, icon_data_url: Some(data), desktop_id: None });
                 }
             } else {
                 log_append("INFO", &format!("heuristic miss: pid={} name={}", pid, icon_name));
@@ -1128,7 +1043,7 @@ fn resolve_window_icon(window_id: String, wclass: String, title: String) -> Resu
             if let Some(data) = to_data_url(&p) {
                 log_append("INFO", &format!("heuristic icon (no pid): name={} path={}", icon_name, p.to_string_lossy()));
                 let name = if !title.trim().is_empty() { title.clone() } else { wclass.clone() };
-                return Ok(AppInfo { name, exec: "".into(), icon_data_url: Some(data) });
+                return Ok(AppInfo { name, exec: "".into(), icon_data_url: Some(data), desktop_id: None });
             }
         }
     }
@@ -1137,7 +1052,7 @@ fn resolve_window_icon(window_id: String, wclass: String, title: String) -> Resu
 }
 
 #[tauri::command]
-fn get_open_windows_with_icons() -> Result<Vec<WindowInfo>, String> {
+fn get_open_windows_with_icons() -> Result<Vec<WindowInfo>, SisError> {
     // Collect windows via wmctrl and resolve icons server-side so logs show resolution path
     log_append("INFO", "get_open_windows_with_icons: start");
     let text = match run_out("sh", &["-lc", "wmctrl -lx 2>/dev/null"]) {
@@ -1181,7 +1096,7 @@ fn get_open_windows_with_icons() -> Result<Vec<WindowInfo>, String> {
 }
 
 #[tauri::command]
-fn record_launch_guess(exec: String, name: String, icon_data_url: Option<String>) -> Result<String, String> {
+fn record_launch_guess(exec: String, name: String, icon_data_url: Option<String>) -> Result<String, SisError> {
     if exec.trim().is_empty() || name.trim().is_empty() { return Err("invalid-args".into()); }
     record_launch_from_exec(&exec, Some(&name), icon_data_url);
     Ok("recorded".into())
@@ -1191,7 +1106,7 @@ fn record_launch_guess(exec: String, name: String, icon_data_url: Option<String>
 struct FolderCounts { pictures: u64, documents: u64, videos: u64, downloads: u64, music: u64, others: u64 }
 
 #[tauri::command]
-fn get_folder_counts() -> Result<FolderCounts, String> {
+fn get_folder_counts() -> Result<FolderCounts, SisError> {
     let home = dirs::home_dir().ok_or_else(|| "cannot-detect-home".to_string())?;
     let mut counts = FolderCounts::default();
     let pairs = vec![
@@ -1223,124 +1138,71 @@ fn get_folder_counts() -> Result<FolderCounts, String> {
 }
 
 #[tauri::command]
-fn get_favorite_apps(_app_handle: tauri::AppHandle) -> Result<Vec<AppInfo>, String> {
-    // Use a simple, predictable path under the user's home directory to avoid AppHandle API differences
-    let home = dirs::home_dir().ok_or_else(|| "cannot-detect-home".to_string())?;
-    let dir = home.join(".local").join("share").join("sis-ui");
-    let path = dir.join("favorites.json");
-    if path.exists() {
-        let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read favorites.json: {}", e))?;
-        let apps: Vec<AppInfo> = serde_json::from_str(&content).map_err(|e| format!("Failed to parse favorites.json: {}", e))?;
-        Ok(apps)
-    } else {
-        Ok(Vec::new())
-    }
+fn take_screenshot() -> Result<String, SisError> {
+    // 全画面を撮って保存＋クリップボードへ（結果は sis:screenshot-taken）
+    Ok(screenshot::capture(screenshot::CaptureOptions { mode: "full".into(), window_id: None, delay_secs: None, copy: Some(true), save: Some(true) })?)
 }
 
 #[tauri::command]
-fn add_favorite_app(_app_handle: tauri::AppHandle, app: AppInfo) -> Result<String, String> {
-    let home = dirs::home_dir().ok_or_else(|| "cannot-detect-home".to_string())?;
-    let dir = home.join(".local").join("share").join("sis-ui");
-    if !dir.exists() { fs::create_dir_all(&dir).map_err(|e| format!("Failed to create dir: {}", e))?; }
-    let path = dir.join("favorites.json");
-    let mut apps = get_favorite_apps(_app_handle).unwrap_or_else(|_| Vec::new());
-
-    if !apps.iter().any(|a| a.name == app.name) {
-        apps.push(app);
-        let content = serde_json::to_string_pretty(&apps).map_err(|e| format!("Failed to serialize favorites: {}", e))?;
-        fs::write(&path, content).map_err(|e| format!("Failed to write favorites.json: {}", e))?;
-        Ok("App added to favorites".to_string())
-    } else {
-        Err("App already in favorites".to_string())
-    }
-}
-
-#[tauri::command]
-fn remove_favorite_app(_app_handle: tauri::AppHandle, app_name: String) -> Result<String, String> {
-    let home = dirs::home_dir().ok_or_else(|| "cannot-detect-home".to_string())?;
-    let dir = home.join(".local").join("share").join("sis-ui");
-    let path = dir.join("favorites.json");
-    let mut apps = get_favorite_apps(_app_handle).unwrap_or_else(|_| Vec::new());
-
-    let initial_len = apps.len();
-    apps.retain(|app| app.name != app_name);
-
-    if apps.len() < initial_len {
-        let content = serde_json::to_string_pretty(&apps).map_err(|e| format!("Failed to serialize favorites: {}", e))?;
-        fs::write(&path, content).map_err(|e| format!("Failed to write favorites.json: {}", e))?;
-        Ok("App removed from favorites".to_string())
-    } else {
-        Err("App not found in favorites".to_string())
-    }
-}
-
-#[tauri::command]
-fn take_screenshot() -> Result<String, String> {
-    let output = Command::new("gnome-screenshot")
-        .arg("--clipboard") // Copy to clipboard
-        .output();
-
-    match output {
-        Ok(output) => {
-            if output.status.success() {
-                Ok("Screenshot taken and copied to clipboard".to_string())
-            } else {
-                Err(format!("Failed to take screenshot: {}", String::from_utf8_lossy(&output.stderr)))
-            }
-        }
-    Err(e) => Err(format!("Failed to execute gnome-screenshot: {}", e)),
-    }
-}
-
-#[tauri::command]
-fn play_pause_music() -> Result<String, String> {
+fn play_pause_music() -> Result<String, SisError> {
+    // MPRIS を優先し、失敗時は playerctl
+    if media::control(None, "play-pause").is_ok() { return Ok("Music play/pause toggled".to_string()); }
     let output = Command::new("playerctl").arg("play-pause").output();
     match output {
         Ok(output) => {
             if output.status.success() {
                 Ok("Music play/pause toggled".to_string())
             } else {
-                Err(format!("Failed to toggle play/pause: {}", String::from_utf8_lossy(&output.stderr)))
+                Err(SisError::failed("mpris-failed", String::from_utf8_lossy(&output.stderr).trim()))
             }
         }
-    Err(e) => Err(format!("Failed to execute playerctl: {}", e)),
+    Err(e) => Err(SisError::unavailable("mpris-unavailable", format!("playerctl: {}", e))),
     }
 }
 
 #[tauri::command]
-fn next_track() -> Result<String, String> {
+fn next_track() -> Result<String, SisError> {
+    // MPRIS を優先し、失敗時は playerctl
+    if media::control(None, "next").is_ok() { return Ok("Next track".to_string()); }
     let output = Command::new("playerctl").arg("next").output();
     match output {
         Ok(output) => {
             if output.status.success() {
                 Ok("Next track".to_string())
             } else {
-                Err(format!("Failed to go to next track: {}", String::from_utf8_lossy(&output.stderr)))
+                Err(SisError::failed("mpris-failed", String::from_utf8_lossy(&output.stderr).trim()))
             }
         }
-    Err(e) => Err(format!("Failed to execute playerctl: {}", e)),
+    Err(e) => Err(SisError::unavailable("mpris-unavailable", format!("playerctl: {}", e))),
     }
 }
 
 #[tauri::command]
-fn previous_track() -> Result<String, String> {
+fn previous_track() -> Result<String, SisError> {
+    // MPRIS を優先し、失敗時は playerctl
+    if media::control(None, "previous").is_ok() { return Ok("Previous track".to_string()); }
     let output = Command::new("playerctl").arg("previous").output();
     match output {
         Ok(output) => {
             if output.status.success() {
                 Ok("Previous track".to_string())
             } else {
-                Err(format!("Failed to go to previous track: {}", String::from_utf8_lossy(&output.stderr)))
+                Err(SisError::failed("mpris-failed", String::from_utf8_lossy(&output.stderr).trim()))
             }
         }
-        Err(e) => Err(format!("Failed to execute playerctl: {}", e)),
+        Err(e) => Err(SisError::unavailable("mpris-unavailable", format!("playerctl: {}", e))),
     }
 }
 
 #[tauri::command]
-fn set_brightness(percent: u32) -> Result<String, String> {
+fn set_brightness(percent: u32) -> Result<String, SisError> {
     let clamped = if percent > 100 { 100 } else { percent };
-    // Try brightnessctl first
+    // sysfs + logind（知覚カーブ・複数パネル対応）
+    match backlight::set_panel(clamped) {
+        Ok(p) => return Ok(format!("Brightness set to {}%", p)),
+        Err(e) => log_append("WARN", &format!("backlight: {}; falling back to brightnessctl", e)),
+    }
+    // Try brightnessctl
     let out = Command::new("brightnessctl")
         .arg("set")
         .arg(format!("{}%", clamped))
@@ -1360,17 +1222,19 @@ fn set_brightness(percent: u32) -> Result<String, String> {
         .output();
     match out2 {
         Ok(o) => {
-            if o.status.success() { Ok(format!("Brightness set to {}% (xbacklight)", clamped)) } else { Err(String::from_utf8_lossy(&o.stderr).to_string()) }
+            if o.status.success() { Ok(format!("Brightness set to {}% (xbacklight)", clamped)) } else { Err(SisError::failed("backlight-write-failed", String::from_utf8_lossy(&o.stderr).trim())) }
         }
-        Err(e) => Err(format!("Failed to set brightness: {}", e)),
+        Err(e) => Err(SisError::failed("backlight-write-failed", e)),
     }
 }
 
 #[tauri::command]
-fn launch_app(exec: String) -> Result<String, String> {
+fn launch_app(exec: String) -> Result<String, SisError> {
     if exec.trim().is_empty() {
         return Err("empty-exec".into());
     }
+    // スクリーンタイム上限に達したアプリは起動しない
+    screentime::check_launch(&exec)?;
     // Strip desktop entry codes just in case
     let mut cmdline = exec.clone();
     for code in ["%U", "%u", "%F", "%f", "%i", "%c", "%k"].iter() {
@@ -1384,22 +1248,16 @@ fn launch_app(exec: String) -> Result<String, String> {
             record_launch_from_exec(&exec, name_hint, None);
             Ok("launched".into())
         },
-        Err(e) => Err(format!("failed-to-launch: {}", e)),
+        Err(e) => Err(SisError::failed("failed-to-launch", e)),
     }
 }
 
 fn main() {
-    let system = System::new_all();
-    let network_stats = Arc::new(Mutex::new(NetworkStats {
-        last_received_bytes: 0,
-        last_transmitted_bytes: 0,
-        last_update_time: Instant::now(),
-    }));
+    let system: telemetry::SharedSystem = Arc::new(Mutex::new(System::new()));
 
     let mut builder = tauri::Builder::default();
     builder
         .manage(system)
-        .manage(network_stats)
     .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         .plugin(tauri_plugin_single_instance::init(|app, argv, _cwd| {
             // When a second instance is launched with flags, route to running instance
@@ -1505,10 +1363,75 @@ fn main() {
                         .inner_size(SIDE as f64, H as f64)
                         .build();
                 }
+                // 通知トースト（右上・最前面）。表示する通知があるときだけフロント側で show する
+                let NOTIFY_W: u32 = 380;
+                if app.get_webview_window("notifications").is_none() {
+                    let _ = WebviewWindowBuilder::new(app, "notifications", url.clone())
+                        .title("SIS Notifications")
+                        .decorations(false)
+                        .resizable(false)
+                        .transparent(true)
+                        .always_on_top(true)
+                        .skip_taskbar(true)
+                        .focused(false)
+                        .visible(false)
+                        .inner_size(NOTIFY_W as f64, 120.0)
+                        .position(W.saturating_sub(NOTIFY_W + 12) as f64, (TOP + 8) as f64)
+                        .build();
+                }
+                // スクリーンショットの範囲選択オーバーレイ（全画面・最前面）。選択中だけ表示する
+                if app.get_webview_window("capture").is_none() {
+                    let _ = WebviewWindowBuilder::new(app, "capture", url.clone())
+                        .title("SIS Capture")
+                        .decorations(false)
+                        .resizable(false)
+                        .transparent(true)
+                        .always_on_top(true)
+                        .skip_taskbar(true)
+                        .visible(false)
+                        .position(0.0, 0.0)
+                        .inner_size(W as f64, H as f64)
+                        .build();
+                }
+                // ロック画面（全画面・最前面・不透明）。ロック中だけ表示する
+                if app.get_webview_window("lock").is_none() {
+                    let _ = WebviewWindowBuilder::new(app, "lock", url.clone())
+                        .title("SIS Lock")
+                        .decorations(false)
+                        .resizable(false)
+                        .always_on_top(true)
+                        .skip_taskbar(true)
+                        .visible(false)
+                        .position(0.0, 0.0)
+                        .inner_size(W as f64, H as f64)
+                        .build();
+                }
             }
 
             // Build WM_CLASS cache on startup
             build_wmclass_cache();
+            // Per-app screen time tracking (X11)
+            screentime::start(app.handle().clone());
+            telemetry::start(app.handle().clone());
+            power::start(app.handle().clone());
+            storage::start(app.handle().clone());
+            network::start(app.handle().clone());
+            bluetooth::start(app.handle().clone());
+            audio::start(app.handle().clone());
+            backlight::start(app.handle().clone());
+            media::start(app.handle().clone());
+            notifications::start(app.handle().clone());
+            tray::start(app.handle().clone());
+            screenshot::start(app.handle().clone());
+            recorder::start(app.handle().clone());
+            clipboard::start(app.handle().clone());
+            session::start(app.handle().clone());
+            lockscreen::start(app.handle().clone());
+            nightlight::start(app.handle().clone());
+            input::start(app.handle().clone());
+            ime::start(app.handle().clone());
+            timedate::start(app.handle().clone());
+            downloads::start(app.handle().clone());
             // Ensure windows are visible, titled, and configured
             for lbl in ["desktop", "topbar", "dock", "sidebar"] {
                 if let Some(w) = app.get_webview_window(lbl) {
@@ -1800,9 +1723,14 @@ fn main() {
         })
         .invoke_handler(tauri::generate_handler![
             get_system_info,
-            organize_file,
+            organizer::organize_file,
             set_volume,
-            organize_latest_download,
+            organizer::organize_latest_download,
+            organizer::organize_preview,
+            organizer::organizer_get_config,
+            organizer::organizer_set_config,
+            downloads::downloads_watch_get_config,
+            downloads::downloads_watch_set_config,
             set_brightness,
             get_recent_apps,
             get_launch_history,
@@ -1816,10 +1744,17 @@ fn main() {
             ubuntu_system_settings,
             ubuntu_software_center,
             try_start_lmstudio,
-            get_favorite_apps,
-            add_favorite_app,
-            remove_favorite_app,
-            reorder_favorite_apps,
+            favorites::get_favorite_apps,
+            favorites::add_favorite_app,
+            favorites::remove_favorite_app,
+            favorites::reorder_favorite_apps,
+            favorites::get_dock_entries,
+            favorites::dock_add_app,
+            favorites::dock_remove,
+            favorites::dock_move,
+            favorites::dock_reorder,
+            favorites::dock_create_folder,
+            favorites::dock_rename_folder,
             take_screenshot,
             play_pause_music,
             next_track,
@@ -1847,24 +1782,142 @@ fn main() {
             clamav_scan,
             kdeconnect_list
             ,open_settings_window
+            ,screentime::screentime_state
+            ,screentime::screentime_limits
+            ,mimeapps::mime_list_handlers
+            ,mimeapps::mime_get_default
+            ,mimeapps::mime_set_default
+            ,mimeapps::mime_add_association
+            ,mimeapps::open_with
+            ,telemetry::telemetry_snapshot
+            ,telemetry::telemetry_history
+            ,telemetry::telemetry_set_interval
+            ,power::battery_status
+            ,power::power_get_policy
+            ,power::power_set_policy
+            ,taskmgr::process_list
+            ,taskmgr::process_kill_request
+            ,taskmgr::process_kill_confirm
+            ,taskmgr::process_renice
+            ,taskmgr::process_stream_start
+            ,taskmgr::process_stream_stop
+            ,storage::storage_list_mounts
+            ,storage::storage_list_removable
+            ,storage::storage_policy
+            ,storage::storage_mount
+            ,storage::storage_unmount
+            ,storage::storage_busy_holders
+            ,storage::storage_eject
+            ,network::network_status
+            ,network::wifi_list_access_points
+            ,network::wifi_connect
+            ,network::network_saved_connections
+            ,network::network_forget
+            ,network::network_set_airplane_mode
+            ,bluetooth::bluetooth_state
+            ,bluetooth::bluetooth_agent_reply
+            ,bluetooth::bluetooth_discover
+            ,bluetooth::bluetooth_pair
+            ,bluetooth::bluetooth_connect
+            ,bluetooth::bluetooth_disconnect
+            ,bluetooth::bluetooth_set_trusted
+            ,bluetooth::bluetooth_remove
+            ,audio::audio_state
+            ,audio::audio_set_default
+            ,audio::audio_set_volume
+            ,audio::audio_set_mute
+            ,audio::audio_set_stream_volume
+            ,audio::audio_set_stream_mute
+            ,audio::audio_move_stream
+            ,backlight::backlight_list
+            ,backlight::backlight_set
+            ,backlight::keyboard_backlight_set
+            ,media::media_state
+            ,media::media_control
+            ,media::media_set_active
+            ,media::media_seek
+            ,media::media_set_volume
+            ,media::media_set_shuffle
+            ,media::media_set_loop
+            ,notifications::notify_post
+            ,notifications::notify_close
+            ,notifications::notify_invoke_action
+            ,notifications::notify_history
+            ,notifications::notify_mark_read
+            ,notifications::notify_clear_history
+            ,notifications::notify_get_dnd
+            ,notifications::notify_set_dnd
+            ,tray::tray_items
+            ,tray::tray_activate
+            ,tray::tray_secondary_activate
+            ,tray::tray_context_menu
+            ,tray::tray_scroll
+            ,tray::tray_menu
+            ,tray::tray_menu_event
+            ,screenshot::screenshot_capture
+            ,screenshot::screenshot_region_commit
+            ,screenshot::screenshot_region_cancel
+            ,screenshot::screenshot_copy
+            ,screenshot::screenshot_delete
+            ,recorder::recorder_status
+            ,recorder::recorder_start
+            ,recorder::recorder_stop
+            ,clipboard::clipboard_list
+            ,clipboard::clipboard_copy
+            ,clipboard::clipboard_pin
+            ,clipboard::clipboard_delete
+            ,clipboard::clipboard_clear
+            ,session::session_capabilities
+            ,session::session_state
+            ,session::session_action
+            ,session::session_cancel
+            ,session::session_list_inhibitors
+            ,session::session_inhibit
+            ,session::session_uninhibit
+            ,session::session_schedule_shutdown
+            ,session::session_cancel_scheduled_shutdown
+            ,lockscreen::lock_screen
+            ,lockscreen::lock_state
+            ,lockscreen::lock_authenticate
+            ,lockscreen::lock_get_config
+            ,lockscreen::lock_set_config
+            ,nightlight::nightlight_state
+            ,nightlight::nightlight_get_config
+            ,nightlight::nightlight_set_config
+            ,nightlight::nightlight_set
+            ,nightlight::nightlight_sun_times
+            ,input::input_state
+            ,input::input_set_config
+            ,input::input_set_xkb_option
+            ,input::input_list_layouts
+            ,ime::ime_state
+            ,ime::ime_set_engine
+            ,ime::ime_set_japanese
+            ,ime::ime_toggle
+            ,timedate::time_state
+            ,timedate::time_list_timezones
+            ,timedate::time_set_timezone
+            ,timedate::time_set_ntp
+            ,timedate::time_set_time
+            ,timedate::time_set_hour12
         ])
     .run(tauri::generate_context!())
     .expect("error while running tauri application");
 }
 
 #[tauri::command]
-fn open_settings_window(_app_handle: tauri::AppHandle) -> Result<String, String> {
+fn open_settings_window(_app_handle: tauri::AppHandle) -> Result<String, SisError> {
     // Deprecated: Settings window has been removed. Keep the command for backward compatibility.
     Err("settings-window-removed".into())
 }
 
 #[tauri::command]
-fn overlay_status() -> Result<bool, String> {
+fn overlay_status() -> Result<bool, SisError> {
     Ok(OVERLAY_RUNNING.load(Ordering::SeqCst))
 }
 
 #[tauri::command]
-fn overlay_start() -> Result<String, String> {
+fn overlay_start() -> Result<String, SisError> {
     if OVERLAY_RUNNING.swap(true, Ordering::SeqCst) {
         return Ok("overlay-already-running".into());
     }
@@ -1894,46 +1947,49 @@ fn overlay_start() -> Result<String, String> {
 }
 
 #[tauri::command]
-fn overlay_stop() -> Result<String, String> {
+fn overlay_stop() -> Result<String, SisError> {
     OVERLAY_RUNNING.store(false, Ordering::SeqCst);
     Ok("overlay-stopped".into())
 }
 
 #[tauri::command]
-fn network_set(enable: bool) -> Result<String, String> {
+fn network_set(enable: bool) -> Result<String, SisError> {
+    // NetworkManager(D-Bus) を優先し、使えない環境では nmcli にフォールバック
+    if network::set_networking(enable).is_ok() { return Ok(format!("networking {}", if enable { "on" } else { "off" })); }
     let cmd = if enable { "nmcli" } else { "nmcli" };
     let arg = if enable { vec!["networking", "on"] } else { vec!["networking", "off"] };
     let mut c = Command::new(cmd);
     for a in arg { c.arg(a); }
     match c.output() {
-        Ok(o) => if o.status.success() { Ok(format!("networking {}", if enable { "on" } else { "off" })) } else { Err(String::from_utf8_lossy(&o.stderr).to_string()) },
-        Err(e) => Err(format!("failed-to-run-nmcli: {}", e)),
+        Ok(o) => if o.status.success() { Ok(format!("networking {}", if enable { "on" } else { "off" })) } else { Err(SisError::failed("failed-to-run-nmcli", String::from_utf8_lossy(&o.stderr).trim())) },
+        Err(e) => Err(SisError::failed("failed-to-run-nmcli", e)),
     }
 }
 
 #[tauri::command]
-fn bluetooth_set(enable: bool) -> Result<String, String> {
-    // Try rfkill/block as a simple fallback
+fn bluetooth_set(enable: bool) -> Result<String, SisError> {
+    // rfkill でブロック解除してから BlueZ のアダプタ電源を切り替える（BlueZ が無ければ rfkill のみ）
     let action = if enable { "unblock" } else { "block" };
+    if enable { let _ = Command::new("rfkill").arg("unblock").arg("bluetooth").output(); }
+    if bluetooth::set_powered(enable).is_ok() {
+        if !enable { let _ = Command::new("rfkill").arg("block").arg("bluetooth").output(); }
+        return Ok(format!("bluetooth {}", action));
+    }
     match Command::new("rfkill").arg(action).arg("bluetooth").output() {
-        Ok(o) => if o.status.success() { Ok(format!("bluetooth {}", action)) } else { Err(String::from_utf8_lossy(&o.stderr).to_string()) },
-        Err(e) => Err(format!("failed-to-run-rfkill: {}", e)),
+        Ok(o) => if o.status.success() { Ok(format!("bluetooth {}", action)) } else { Err(SisError::failed("failed-to-run-rfkill", String::from_utf8_lossy(&o.stderr).trim())) },
+        Err(e) => Err(SisError::failed("failed-to-run-rfkill", e)),
     }
 }
 
 #[tauri::command]
-fn power_action(action: String) -> Result<String, String> {
-    // action: shutdown | reboot | logout
-    match action.as_str() {
-        "shutdown" => match Command::new("systemctl").arg("poweroff").spawn() { Ok(_) => Ok("shutting-down".into()), Err(e) => Err(format!("failed-to-shutdown: {}", e)) },
-        "reboot" => match Command::new("systemctl").arg("reboot").spawn() { Ok(_) => Ok("rebooting".into()), Err(e) => Err(format!("failed-to-reboot: {}", e)) },
-        "logout" => match Command::new("pkill").arg("-KILL").arg("-u").arg(std::env::var("USER").unwrap_or_else(|_| "".into())).spawn() { Ok(_) => Ok("logging-out".into()), Err(e) => Err(format!("failed-to-logout: {}", e)) },
-        other => Err(format!("unsupported-action: {}", other)),
-    }
+fn power_action(action: String) -> Result<String, SisError> {
+    // action: shutdown | reboot | logout | suspend | hibernate
+    // logind 経由。ログアウト等はアプリに閉じる要求を出してから進む（進捗は sis:session-state）
+    Ok(session::request(&action, false)?)
 }
 
 #[tauri::command]
-fn llm_query(prompt: String) -> Result<String, String> {
+fn llm_query(prompt: String) -> Result<String, SisError> {
     log_append("INFO", &format!("llm_query(local) prompt_len={}", prompt.len().min(2048)));
     let s = read_settings();
     if s.llm_mode.as_deref() != Some("local") {
@@ -1954,10 +2010,10 @@ fn llm_query(prompt: String) -> Result<String, String> {
             } else {
                 let err = String::from_utf8_lossy(&o.stderr).to_string();
                 log_append("ERROR", &format!("llm_query(local) failed: {}", err));
-                Err(format!("llm-error: {}", err))
+                Err(SisError::failed("llm-error", err))
             }
         }
-        Err(e) => { log_append("ERROR", &format!("llm runner spawn failed: {}", e)); Err(format!("failed-to-start-llm-runner: {}", e)) }
+        Err(e) => { log_append("ERROR", &format!("llm runner spawn failed: {}", e)); Err(SisError::failed("failed-to-start-llm-runner", e)) }
     }
 }
 
@@ -1968,7 +2024,7 @@ fn sanitize_model_id(mid: &str) -> String {
 }
 
 #[tauri::command]
-fn llm_download_hf(model_id: String) -> Result<String, String> {
+fn llm_download_hf(model_id: String) -> Result<String, SisError> {
     if !which("huggingface-cli") { return Err("huggingface-cli-not-found".into()); }
     let dir = models_dir().ok_or_else(|| "home-not-found".to_string())?;
     let _ = fs::create_dir_all(&dir);
@@ -1981,13 +2037,13 @@ fn llm_download_hf(model_id: String) -> Result<String, String> {
         .status();
     match status {
         Ok(s) if s.success() => Ok(target_str),
-        Ok(s) => Err(format!("download-failed: exit {}", s)),
-        Err(e) => Err(format!("failed-to-run-hf-cli: {}", e))
+        Ok(s) => Err(SisError::failed("download-failed", format!("exit {}", s))),
+        Err(e) => Err(SisError::failed("failed-to-run-hf-cli", e))
     }
 }
 
 #[tauri::command]
-fn list_local_models() -> Result<Vec<String>, String> {
+fn list_local_models() -> Result<Vec<String>, SisError> {
     let mut out = Vec::new();
     if let Some(d) = models_dir() {
         if d.exists() {
@@ -2011,6 +2067,11 @@ fn list_local_models() -> Result<Vec<String>, String> {
     Ok(out)
 }
 
+// 試験モードの目印。置かれている間はスクリーンショット・録画・クリップボード履歴・時刻変更などを止める
+const EXAM_MARKER: &str = "/tmp/sis-exam-mode";
+
+fn exam_mode() -> bool { std::path::Path::new(EXAM_MARKER).exists() }
+
 fn which(cmd: &str) -> bool {
     Command::new("sh")
         .arg("-c")
@@ -2031,7 +2092,7 @@ fn expand_user_path(input: &str) -> PathBuf {
 }
 
 #[tauri::command]
-fn run_safe_command(cmdline: String) -> Result<String, String> {
+fn run_safe_command(cmdline: String) -> Result<String, SisError> {
     if cmdline.len() > 8000 { return Err("command-too-long".into()); }
     let trimmed = cmdline.trim();
     if trimmed.is_empty() { return Err("empty-cmd".into()); }
@@ -2044,7 +2105,7 @@ fn run_safe_command(cmdline: String) -> Result<String, String> {
     "kdeconnect-cli","clamscan","echo","wmctrl","xprop","zenity","kdialog","base64",
         "sh","bash","sed","xdotool"
     ];
-    if !allow.contains(&base) { return Err(format!("command-not-allowed: {}", base)); }
+    if !allow.contains(&base) { return Err(SisError::policy("command-not-allowed", base)); }
     if trimmed.contains(" rm ") || trimmed.starts_with("rm ") || trimmed.contains(" sudo ") {
         return Err("unsafe-command-rejected".into());
     }
@@ -2068,33 +2129,33 @@ fn run_safe_command(cmdline: String) -> Result<String, String> {
             } else {
                 let with_exit = if combined.trim().is_empty() { format!("[exit {}]", code) } else { format!("{}\n[exit {}]", combined, code) };
                 log_append("ERROR", &format!("run_safe_command failed exit={} bytes={}", code, with_exit.len()));
-                Err(with_exit)
+                Err(SisError::failed("command-failed", with_exit))
             }
         }
-        Err(e) => { log_append("ERROR", &format!("run_safe_command spawn error: {}", e)); Err(format!("failed-to-run: {}", e)) }
+        Err(e) => { log_append("ERROR", &format!("run_safe_command spawn error: {}", e)); Err(SisError::failed("failed-to-run", e)) }
     }
 }
 
 #[tauri::command]
-fn clamav_scan(path: String) -> Result<String, String> {
+fn clamav_scan(path: String) -> Result<String, SisError> {
     if !which("clamscan") { return Err("clamscan-not-found".into()); }
     match Command::new("clamscan").arg("-i").arg("-r").arg(&path).output() {
         Ok(o) => Ok(String::from_utf8_lossy(&o.stdout).to_string()),
-        Err(e) => Err(format!("failed-to-run-clamscan: {}", e)),
+        Err(e) => Err(SisError::failed("failed-to-run-clamscan", e)),
     }
 }
 
 #[tauri::command]
-fn kdeconnect_list() -> Result<String, String> {
+fn kdeconnect_list() -> Result<String, SisError> {
     if !which("kdeconnect-cli") { return Err("kdeconnect-cli-not-found".into()); }
     match Command::new("kdeconnect-cli").arg("--list-devices").output() {
         Ok(o) => Ok(String::from_utf8_lossy(&o.stdout).to_string()),
-        Err(e) => Err(format!("failed-to-run-kdeconnect: {}", e)),
+        Err(e) => Err(SisError::failed("failed-to-run-kdeconnect", e)),
     }
 }
 
 #[tauri::command]
-fn run_with_sudo(cmdline: String, password: String) -> Result<String, String> {
+fn run_with_sudo(cmdline: String, password: String) -> Result<String, SisError> {
     if cmdline.len() > 8000 { return Err("command-too-long".into()); }
     // Very small wrapper to run a single command with sudo by providing the password via stdin.
     // Note: This is convenient but has security implications; prefer polkit or proper privilege separation in production.
@@ -2111,14 +2172,14 @@ fn run_with_sudo(cmdline: String, password: String) -> Result<String, String> {
         .spawn()
     {
         Ok(c) => c,
-        Err(e) => return Err(format!("failed-to-spawn-sudo: {}", e)),
+        Err(e) => return Err(SisError::failed("failed-to-spawn-sudo", e)),
     };
 
     if let Some(mut stdin) = child.stdin.take() {
         use std::io::Write;
         // write password + newline
         if let Err(e) = stdin.write_all(format!("{}\n", password).as_bytes()) {
-            return Err(format!("failed-to-write-password: {}", e));
+            return Err(SisError::failed("failed-to-write-password", e));
         }
     }
 
@@ -2131,14 +2192,15 @@ fn run_with_sudo(cmdline: String, password: String) -> Result<String, String> {
             } else {
                 let e = String::from_utf8_lossy(&out.stderr).to_string();
                 log_append("ERROR", &format!("run_with_sudo failed: {}", e));
-                Err(e)
+                Err(SisError::failed("sudo-failed", e.trim()))
             }
         }
-        Err(e) => { log_append("ERROR", &format!("wait sudo failed: {}", e)); Err(format!("failed-waiting-for-sudo: {}", e)) }
+        Err(e) => { log_append("ERROR", &format!("wait sudo failed: {}", e)); Err(SisError::failed("failed-waiting-for-sudo", e)) }
     }
 }
 
 fn read_network_enabled() -> bool {
+    if let Some(v) = network::networking_enabled() { return v; }
     match Command::new("nmcli").arg("networking").output() {
         Ok(o) => String::from_utf8_lossy(&o.stdout).to_lowercase().contains("enabled"),
         Err(_) => true,
@@ -2146,7 +2208,8 @@ fn read_network_enabled() -> bool {
 }
 
 fn read_bluetooth_enabled() -> bool {
-    // Prefer bluetoothctl show
+    if let Some(v) = bluetooth::adapter_powered() { return v; }
+    // Fallback: bluetoothctl show
     if let Ok(o) = Command::new("bluetoothctl").arg("show").output() {
         let s = String::from_utf8_lossy(&o.stdout).to_lowercase();
         if s.contains("powered: yes") { return true; }
@@ -2162,50 +2225,47 @@ fn read_bluetooth_enabled() -> bool {
     }
 }
 
-fn read_volume_percent() -> u32 {
-    if let Ok(o) = Command::new("pactl").arg("get-sink-volume").arg("@DEFAULT_SINK@").output() {
-        let s = String::from_utf8_lossy(&o.stdout);
-        // find last occurrence of % digits
-        if let Some(idx) = s.rfind('%') {
-            let start = s[..idx].rfind(|c: char| !c.is_ascii_digit()).map(|i| i+1).unwrap_or(0);
-            let num = &s[start..idx];
-            if let Ok(v) = num.trim().parse::<u32>() { return v.min(150); }
-        }
-    }
-    50
+/// 既定の出力の (音量, ミュート)。pactl を一度だけ呼ぶ
+fn read_volume() -> (u32, bool) {
+    audio::default_sink_volume().map(|(v, m)| (v.min(150), m)).unwrap_or((50, false))
 }
 
-fn read_brightness_percent() -> u32 {
+fn read_brightness_percent() -> Option<u32> {
+    if let Some(p) = backlight::panel_percent() { return Some(p); }
     // brightnessctl info shows (XX%) sometimes
     if let Ok(o) = Command::new("brightnessctl").arg("info").output() {
         let s = String::from_utf8_lossy(&o.stdout);
         if let Some(p1) = s.find('(') { if let Some(p2) = s[p1+1..].find('%') { 
-            let num = &s[p1+1..p1+1+p2]; if let Ok(v) = num.trim().parse::<u32>() { return v.min(100); }
+            let num = &s[p1+1..p1+1+p2]; if let Ok(v) = num.trim().parse::<u32>() { return Some(v.min(100)); }
         }}
     }
     // fallback xbacklight -get
     if let Ok(o) = Command::new("xbacklight").arg("-get").output() {
         let s = String::from_utf8_lossy(&o.stdout);
-        if let Ok(f) = s.trim().split_whitespace().next().unwrap_or("0").parse::<f32>() { return f.round() as u32; }
+        if let Ok(f) = s.trim().split_whitespace().next().unwrap_or("").parse::<f32>() { return Some(f.round() as u32); }
     }
-    80
+    None
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-struct ControlCenterState { volume: u32, brightness: u32, network: bool, bluetooth: bool }
+struct ControlCenterState { volume: u32, muted: bool, brightness: Option<u32>, network: bool, bluetooth: bool, battery: Option<power::BatteryStatus>, night_light: nightlight::NightLightState }
 
 #[tauri::command]
-fn control_center_state() -> Result<ControlCenterState, String> {
+fn control_center_state() -> Result<ControlCenterState, SisError> {
+    let (volume, muted) = read_volume();
     Ok(ControlCenterState{
-        volume: read_volume_percent(),
+        volume,
+        muted,
         brightness: read_brightness_percent(),
         network: read_network_enabled(),
         bluetooth: read_bluetooth_enabled(),
+        battery: power::read_battery(),
+        night_light: nightlight::state(),
     })
 }
 
 #[tauri::command]
-fn get_launch_history(limit: Option<u32>) -> Result<Vec<AppInfo>, String> {
+fn get_launch_history(limit: Option<u32>) -> Result<Vec<AppInfo>, SisError> {
     let mut out: Vec<AppInfo> = Vec::new();
     let mut n = 0u32;
     for e in read_launch_history() {
@@ -2213,7 +2273,7 @@ fn get_launch_history(limit: Option<u32>) -> Result<Vec<AppInfo>, String> {
         let p = Path::new(&exec_first);
         let ok = (p.is_absolute() && p.exists()) || which(&exec_first);
         if ok {
-            out.push(AppInfo { name: e.name, exec: e.exec, icon_data_url: e.icon_data_url });
+            out.push(AppInfo { name: e.name, exec: e.exec, icon_data_url: e.icon_data_url, desktop_id: None });
             n += 1;
             if let Some(lim) = limit { if n >= lim { break; } }
         }
@@ -2222,7 +2282,7 @@ fn get_launch_history(limit: Option<u32>) -> Result<Vec<AppInfo>, String> {
 }
 
 #[tauri::command]
-fn llm_query_remote(base_url: String, api_key: Option<String>, model: Option<String>, prompt: String) -> Result<String, String> {
+fn llm_query_remote(base_url: String, api_key: Option<String>, model: Option<String>, prompt: String) -> Result<String, SisError> {
     let safe_url = if base_url.contains("localhost") { base_url.clone() } else { "(redacted)".into() };
     log_append("INFO", &format!("llm_query_remote url={} prompt_len={}", safe_url, prompt.len().min(2048)));
     let url = base_url;
@@ -2236,7 +2296,7 @@ fn llm_query_remote(base_url: String, api_key: Option<String>, model: Option<Str
         .timeout(std::time::Duration::from_secs(30))
         .build() {
         Ok(c) => c,
-        Err(e) => return Err(format!("llm-client-error: {}", e)),
+        Err(e) => return Err(SisError::failed("llm-client-error", e)),
     };
     let mut req = client.post(&url).json(&body);
     if let Some(key) = api_key { if !key.is_empty() { req = req.bearer_auth(key); } }
@@ -2259,10 +2319,10 @@ fn llm_query_remote(base_url: String, api_key: Option<String>, model: Option<Str
                 let st = resp.status();
                 let txt = resp.text().unwrap_or_default();
                 log_append("ERROR", &format!("llm http error {}: {}", st, txt.chars().take(512).collect::<String>()));
-                Err(format!("llm-http-{}: {}", st, txt))
+                Err(SisError::failed("llm-http-error", format!("{} {}", st, txt)))
             }
         }
-        Err(e) => { log_append("ERROR", &format!("llm http send error: {}", e)); Err(format!("llm-http-error: {}", e)) }
+        Err(e) => { log_append("ERROR", &format!("llm http send error: {}", e)); Err(SisError::failed("llm-http-error", e)) }
     }
 }
 
@@ -2304,7 +2364,7 @@ fn desktop_dirs() -> Vec<PathBuf> {
 }
 
 #[tauri::command]
-fn list_desktop_items() -> Result<Vec<DesktopItem>, String> {
+fn list_desktop_items() -> Result<Vec<DesktopItem>, SisError> {
     let mut items: Vec<DesktopItem> = Vec::new();
     for d in desktop_dirs() {
         if d.exists() && d.is_dir() {
@@ -2323,9 +2383,9 @@ fn list_desktop_items() -> Result<Vec<DesktopItem>, String> {
 }
 
 #[tauri::command]
-fn set_wallpaper(path: String) -> Result<String, String> {
+fn set_wallpaper(path: String) -> Result<String, SisError> {
     let p = PathBuf::from(&path);
-    if !p.exists() { return Err("wallpaper-file-not-found".into()); }
+    if !p.exists() { return Err(SisError::not_found("wallpaper-file-not-found", &path)); }
     // Try GNOME / gsettings
     if which("gsettings") {
         let s = Command::new("gsettings")
@@ -2380,7 +2440,7 @@ fn documents_dir() -> Option<PathBuf> {
 }
 
 #[tauri::command]
-fn list_documents_items() -> Result<Vec<DesktopItem>, String> {
+fn list_documents_items() -> Result<Vec<DesktopItem>, SisError> {
     let mut items: Vec<DesktopItem> = Vec::new();
     if let Some(d) = documents_dir() {
         if d.exists() && d.is_dir() {
@@ -2415,8 +2475,8 @@ struct DeSettings {
     local_model_path: Option<String>,
     user_dirs: Option<serde_json::Value>, // { downloads, music, pictures, documents, videos, desktop }
     app_sort: Option<String>, // name|recent
-    favorite_order: Option<Vec<String>>, // favorite app names
     logging_enabled: Option<bool>,    // backend log toggle
+    telemetry_interval_ms: Option<u64>, // sis:telemetry の送出間隔
 }
 
 fn settings_dir() -> Option<PathBuf> { history_dir() }
@@ -2444,8 +2504,8 @@ fn read_settings() -> DeSettings {
         local_model_path: None,
         user_dirs: None,
         app_sort: Some("name".into()),
-        favorite_order: None,
         logging_enabled: Some(true),
+        telemetry_interval_ms: Some(telemetry::DEFAULT_INTERVAL_MS),
     }
 }
 
@@ -2457,10 +2517,10 @@ fn write_settings(s: &DeSettings) {
 }
 
 #[tauri::command]
-fn get_settings() -> Result<DeSettings, String> { Ok(read_settings()) }
+fn get_settings() -> Result<DeSettings, SisError> { Ok(read_settings()) }
 
 #[tauri::command]
-fn set_settings(app_handle: tauri::AppHandle, new_s: DeSettings) -> Result<String, String> {
+fn set_settings(app_handle: tauri::AppHandle, new_s: DeSettings) -> Result<String, SisError> {
     log_append("INFO", &format!("set_settings called with theme: {:?}, wallpaper: {:?}", new_s.theme, new_s.wallpaper));
     // Merge with existing settings to avoid dropping fields
     let mut cur = read_settings();
@@ -2477,8 +2537,8 @@ fn set_settings(app_handle: tauri::AppHandle, new_s: DeSettings) -> Result<Strin
     if let Some(v) = new_s.local_model_path { cur.local_model_path = Some(v); }
     if let Some(v) = new_s.user_dirs { cur.user_dirs = Some(v); }
     if let Some(v) = new_s.app_sort { cur.app_sort = Some(v); }
-    if let Some(v) = new_s.favorite_order { cur.favorite_order = Some(v); }
     if let Some(v) = new_s.logging_enabled { cur.logging_enabled = Some(v); }
+    if let Some(v) = new_s.telemetry_interval_ms.map(telemetry::clamp_interval) { telemetry::set_interval(v); cur.telemetry_interval_ms = Some(v); }
     // 改訂番号をインクリメント
     let next_rev = cur.rev.unwrap_or(0).saturating_add(1);
     cur.rev = Some(next_rev);
@@ -2499,7 +2559,7 @@ fn set_settings(app_handle: tauri::AppHandle, new_s: DeSettings) -> Result<Strin
 }
 
 #[tauri::command]
-fn ubuntu_set_theme(theme: String) -> Result<String, String> {
+fn ubuntu_set_theme(theme: String) -> Result<String, SisError> {
     // Best-effort GNOME color-scheme; also try legacy gtk-theme naming
     let t = theme.to_lowercase();
     if which("gsettings") {
@@ -2521,19 +2581,19 @@ fn ubuntu_set_theme(theme: String) -> Result<String, String> {
 }
 
 #[tauri::command]
-fn ubuntu_system_settings() -> Result<String, String> {
+fn ubuntu_system_settings() -> Result<String, SisError> {
     let _ = Command::new("sh").arg("-lc").arg("(gnome-control-center >/dev/null 2>&1 & disown) || true").status();
     Ok("spawned".into())
 }
 
 #[tauri::command]
-fn ubuntu_software_center() -> Result<String, String> {
+fn ubuntu_software_center() -> Result<String, SisError> {
     let _ = Command::new("sh").arg("-lc").arg("(gnome-software >/dev/null 2>&1 & disown) || true").status();
     Ok("spawned".into())
 }
 
 #[tauri::command]
-fn try_start_lmstudio() -> Result<String, String> {
+fn try_start_lmstudio() -> Result<String, SisError> {
     // Best-effort: try to spawn lmstudio if available
     let candidates = ["lmstudio", "lm-studio", "LM Studio"];
     for c in candidates.iter() {
@@ -2545,20 +2605,6 @@ fn try_start_lmstudio() -> Result<String, String> {
     Err("lmstudio-not-found".into())
 }
 
-#[tauri::command]
-fn reorder_favorite_apps(_app_handle: tauri::AppHandle, names: Vec<String>) -> Result<String, String> {
-    // Reorder favorites.json according to provided names sequence
-    let home = dirs::home_dir().ok_or_else(|| "cannot-detect-home".to_string())?;
-    let dir = home.join(".local").join("share").join("sis-ui");
-    let path = dir.join("favorites.json");
-    let mut apps = get_favorite_apps(_app_handle.clone())?.into_iter().collect::<Vec<_>>();
-    let pos = |n: &str| names.iter().position(|x| x==n).unwrap_or(usize::MAX);
-    apps.sort_by_key(|a| pos(&a.name));
-    let content = serde_json::to_string_pretty(&apps).map_err(|e| format!("Failed to serialize favorites: {}", e))?;
-    fs::write(&path, content).map_err(|e| format!("Failed to write favorites.json: {}", e))?;
-    Ok("favorites-reordered".into())
-}
-
 fn logs_dir() -> Option<PathBuf> { history_dir().map(|d| d.join("logs")) }
 fn backend_log_path() -> Option<PathBuf> { logs_dir().map(|d| d.join("backend.log")) }
 
@@ -2577,7 +2623,7 @@ fn log_append(level: &str, message: &str) {
 }
 
 #[tauri::command]
-fn get_backend_log(limit: Option<u32>) -> Result<String, String> {
+fn get_backend_log(limit: Option<u32>) -> Result<String, SisError> {
     let path = backend_log_path().ok_or_else(|| "log-path-missing".to_string())?;
     if !path.exists() { return Ok(String::new()); }
     let content = std::fs::read_to_string(&path).map_err(|e| format!("read-log-failed: {}", e))?;
@@ -2590,7 +2636,7 @@ fn get_backend_log(limit: Option<u32>) -> Result<String, String> {
 }
 
 #[tauri::command]
-fn clear_backend_log() -> Result<String, String> {
+fn clear_backend_log() -> Result<String, SisError> {
     if let Some(path) = backend_log_path() {
         let _ = std::fs::write(path, b"");
     }
@@ -0,0 +1,319 @@
+// MPRIS(D-Bus セッションバス): 再生中プレイヤーの一覧・メタデータ/アートワーク・再生操作・シーク・音量
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use base64::Engine;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+use zbus::blocking::fdo::{DBusProxy, PropertiesProxy};
+use zbus::blocking::{Connection, Proxy};
+use zbus::names::InterfaceName;
+use zbus::zvariant::{ObjectPath, OwnedValue};
+
+use crate::error::SisError;
+use crate::log_append;
+
+const POLL: Duration = Duration::from_secs(1);
+const MPRIS_PREFIX: &str = "org.mpris.MediaPlayer2.";
+const MPRIS_PATH: &str = "/org/mpris/MediaPlayer2";
+const IF_ROOT: &str = "org.mpris.MediaPlayer2";
+const IF_PLAYER: &str = "org.mpris.MediaPlayer2.Player";
+const ART_MAX_BYTES: usize = 5 * 1024 * 1024;
+const ART_CACHE_MAX: usize = 32;
+// 位置がこれ以上ずれたらシークとみなしてイベントを送る
+const SEEK_THRESHOLD_MS: i64 = 2000;
+
+// ユーザーが選んだプレイヤー（None なら再生中のものを自動選択）
+static ACTIVE: Lazy<Mutex<Option<String>>> = Lazy::new(|| Mutex::new(None));
+static ART_CACHE: Lazy<Mutex<HashMap<String, String>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+// 取得中、または取得に失敗した http(s) のアートワーク URL（毎秒のポーリングで取り直さない）
+static ART_FETCHING: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+static LAST: Lazy<Mutex<Option<(MediaState, Instant)>>> = Lazy::new(|| Mutex::new(None));
+static APP: Lazy<Mutex<Option<tauri::AppHandle>>> = Lazy::new(|| Mutex::new(None));
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct MediaPlayer {
+    id: String,       // D-Bus 名（org.mpris.MediaPlayer2.xxx）
+    identity: String, // 表示名（"Firefox" など）
+    desktop_entry: Option<String>,
+    status: String,   // playing | paused | stopped
+    title: String,
+    artist: Vec<String>,
+    album: String,
+    art_url: Option<String>, // data: URL に変換済み
+    track_id: Option<String>,
+    position_ms: i64,
+    duration_ms: Option<i64>,
+    volume: Option<u32>, // 0-100
+    shuffle: Option<bool>,
+    loop_status: Option<String>, // none | track | playlist
+    can_seek: bool,
+    can_go_next: bool,
+    can_go_previous: bool,
+    can_control: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct MediaState {
+    active: Option<String>,
+    players: Vec<MediaPlayer>,
+}
+
+fn session_bus() -> Result<Connection, String> {
+    static CONN: Lazy<Option<Connection>> = Lazy::new(|| Connection::session().ok());
+    CONN.clone().ok_or_else(|| "dbus-unavailable".to_string())
+}
+
+type Props = HashMap<String, OwnedValue>;
+
+fn prop<T: TryFrom<OwnedValue>>(props: &Props, key: &str) -> Option<T> {
+    props.get(key)?.try_clone().ok().and_then(|v| T::try_from(v).ok())
+}
+
+// mpris:length はプレイヤーによって x(i64) と t(u64) が混在する
+fn prop_i64(props: &Props, key: &str) -> Option<i64> {
+    prop::<i64>(props, key).or_else(|| prop::<u64>(props, key).map(|v| v as i64)).or_else(|| prop::<i32>(props, key).map(|v| v as i64))
+}
+
+fn get_all(conn: &Connection, dest: &str, iface: &str) -> Result<Props, String> {
+    let p = PropertiesProxy::builder(conn)
+        .destination(dest.to_string()).and_then(|b| b.path(MPRIS_PATH))
+        .and_then(|b| b.build())
+        .map_err(|e| format!("mpris-unavailable: {}", e))?;
+    let iface = InterfaceName::try_from(iface).map_err(|e| e.to_string())?;
+    p.get_all(Some(iface).into()).map_err(|e| format!("mpris-unavailable: {}", e))
+}
+
+fn sniff_mime(bytes: &[u8]) -> &'static str {
+    if bytes.starts_with(b"\x89PNG") { "image/png" }
+    else if bytes.starts_with(&[0xFF, 0xD8]) { "image/jpeg" }
+    else if bytes.starts_with(b"GIF8") { "image/gif" }
+    else if bytes.len() > 12 && &bytes[8..12] == b"WEBP" { "image/webp" }
+    else if bytes.starts_with(b"<svg") || bytes.starts_with(b"<?xml") { "image/svg+xml" }
+    else { "application/octet-stream" }
+}
+
+fn percent_decode(s: &str) -> String {
+    let b = s.as_bytes();
+    let mut out = Vec::with_capacity(b.len());
+    let mut i = 0;
+    while i < b.len() {
+        if b[i] == b'%' && i + 2 < b.len() {
+            if let Some(v) = std::str::from_utf8(&b[i + 1..i + 3]).ok().and_then(|h| u8::from_str_radix(h, 16).ok()) { out.push(v); i += 3; continue; }
+        }
+        out.push(b[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+fn cache_art(url: &str, bytes: &[u8]) -> Option<String> {
+    if bytes.is_empty() || bytes.len() > ART_MAX_BYTES { return None; }
+    let data = format!("data:{};base64,{}", sniff_mime(bytes), base64::engine::general_purpose::STANDARD.encode(bytes));
+    let mut cache = ART_CACHE.lock().unwrap();
+    if cache.len() >= ART_CACHE_MAX { cache.clear(); }
+    cache.insert(url.to_string(), data.clone());
+    Some(data)
+}
+
+fn download_art(url: &str) -> Option<Vec<u8>> {
+    let client = reqwest::blocking::Client::builder().timeout(Duration::from_secs(5)).build().ok()?;
+    let resp = client.get(url).send().ok()?;
+    if !resp.status().is_success() { return None; }
+    Some(resp.bytes().ok()?.to_vec())
+}
+
+/// http(s) のアートワークは別スレッドで取得し、届いたら状態を送り直す（ポーリングを止めない）
+fn fetch_art(url: &str) {
+    {
+        let mut fetching = ART_FETCHING.lock().unwrap();
+        if fetching.contains(url) { return; }
+        if fetching.len() >= ART_CACHE_MAX { fetching.clear(); }
+        fetching.insert(url.to_string());
+    }
+    let url = url.to_string();
+    std::thread::spawn(move || {
+        if download_art(&url).and_then(|b| cache_art(&url, &b)).is_none() { return; }
+        ART_FETCHING.lock().unwrap().remove(&url);
+        let Ok(cur) = session_bus().and_then(|c| read_state(&c)) else { return };
+        if let Some(app) = APP.lock().unwrap().as_ref() { let _ = app.emit("sis:media-changed", &cur); }
+        *LAST.lock().unwrap() = Some((cur, Instant::now()));
+    });
+}
+
+// アートワーク（file:// / http(s)://）を WebView から直接表示できる data: URL に変換する。
+// http(s) は取得が済むまで None を返す
+fn resolve_art(url: &str) -> Option<String> {
+    if url.starts_with("data:") { return Some(url.to_string()); }
+    if let Some(hit) = ART_CACHE.lock().unwrap().get(url) { return Some(hit.clone()); }
+    if let Some(path) = url.strip_prefix("file://") {
+        cache_art(url, &std::fs::read(percent_decode(path)).ok()?)
+    } else {
+        if url.starts_with("http://") || url.starts_with("https://") { fetch_art(url); }
+        None
+    }
+}
+
+fn read_player(conn: &Connection, id: &str) -> Result<MediaPlayer, String> {
+    let root = get_all(conn, id, IF_ROOT).unwrap_or_default();
+    let p = get_all(conn, id, IF_PLAYER)?;
+    let meta: Props = prop::<HashMap<String, OwnedValue>>(&p, "Metadata").unwrap_or_default();
+    let art = prop::<String>(&meta, "mpris:artUrl").filter(|s| !s.is_empty());
+    let track_id = prop::<ObjectPath>(&meta, "mpris:trackid").map(|p| p.to_string()).or_else(|| prop::<String>(&meta, "mpris:trackid"));
+    Ok(MediaPlayer {
+        id: id.to_string(),
+        identity: prop::<String>(&root, "Identity").unwrap_or_else(|| id.trim_start_matches(MPRIS_PREFIX).to_string()),
+        desktop_entry: prop::<String>(&root, "DesktopEntry"),
+        status: prop::<String>(&p, "PlaybackStatus").unwrap_or_else(|| "Stopped".into()).to_ascii_lowercase(),
+        title: prop::<String>(&meta, "xesam:title").unwrap_or_default(),
+        artist: prop::<Vec<String>>(&meta, "xesam:artist").or_else(|| prop::<String>(&meta, "xesam:artist").map(|s| vec![s])).unwrap_or_default(),
+        album: prop::<String>(&meta, "xesam:album").unwrap_or_default(),
+        art_url: art.as_deref().and_then(resolve_art),
+        track_id,
+        position_ms: prop_i64(&p, "Position").unwrap_or(0) / 1000,
+        duration_ms: prop_i64(&meta, "mpris:length").map(|us| us / 1000).filter(|v| *v > 0),
+        volume: prop::<f64>(&p, "Volume").map(|v| (v.clamp(0.0, 1.0) * 100.0).round() as u32),
+        shuffle: prop::<bool>(&p, "Shuffle"),
+        loop_status: prop::<String>(&p, "LoopStatus").map(|s| s.to_ascii_lowercase()),
+        can_seek: prop::<bool>(&p, "CanSeek").unwrap_or(false),
+        can_go_next: prop::<bool>(&p, "CanGoNext").unwrap_or(false),
+        can_go_previous: prop::<bool>(&p, "CanGoPrevious").unwrap_or(false),
+        can_control: prop::<bool>(&p, "CanControl").unwrap_or(false),
+    })
+}
+
+fn read_state(conn: &Connection) -> Result<MediaState, String> {
+    let dbus = DBusProxy::new(conn).map_err(|e| format!("dbus-unavailable: {}", e))?;
+    let names = dbus.list_names().map_err(|e| format!("dbus-unavailable: {}", e))?;
+    let mut players: Vec<MediaPlayer> = names.iter()
+        .map(|n| n.to_string())
+        .filter(|n| n.starts_with(MPRIS_PREFIX))
+        .filter_map(|n| read_player(conn, &n).ok())
+        .collect();
+    players.sort_by(|a, b| a.id.cmp(&b.id));
+    let chosen = ACTIVE.lock().unwrap().clone().filter(|id| players.iter().any(|p| &p.id == id));
+    let active = chosen
+        .or_else(|| players.iter().find(|p| p.status == "playing").map(|p| p.id.clone()))
+        .or_else(|| players.iter().find(|p| p.status == "paused").map(|p| p.id.clone()))
+        .or_else(|| players.first().map(|p| p.id.clone()));
+    Ok(MediaState { active, players })
+}
+
+fn target(conn: &Connection, player: Option<String>) -> Result<MediaPlayer, String> {
+    let st = read_state(conn)?;
+    let id = player.or(st.active).ok_or("no-player")?;
+    st.players.into_iter().find(|p| p.id == id).ok_or_else(|| "no-player".to_string())
+}
+
+fn player_proxy<'a>(conn: &Connection, id: &str) -> Result<Proxy<'a>, String> {
+    Proxy::new(conn, id.to_string(), MPRIS_PATH, IF_PLAYER).map_err(|e| format!("mpris-unavailable: {}", e))
+}
+
+/// 再生操作（play / pause / play-pause / next / previous / stop）
+pub fn control(player: Option<String>, action: &str) -> Result<(), String> {
+    let method = match action {
+        "play" => "Play", "pause" => "Pause", "play-pause" => "PlayPause",
+        "next" => "Next", "previous" => "Previous", "stop" => "Stop",
+        _ => return Err("invalid-action".into()),
+    };
+    let conn = session_bus()?;
+    let p = target(&conn, player)?;
+    let _: () = player_proxy(&conn, &p.id)?.call(method, &()).map_err(|e| format!("mpris-failed: {}", e))?;
+    Ok(())
+}
+
+// 位置を除いた比較用キー（再生中は毎秒変わるため）
+fn without_position(s: &MediaState) -> MediaState {
+    let mut c = s.clone();
+    for p in c.players.iter_mut() { p.position_ms = 0; }
+    c
+}
+
+pub fn start(app_handle: tauri::AppHandle) {
+    *APP.lock().unwrap() = Some(app_handle.clone());
+    let conn = match session_bus() { Ok(c) => c, Err(_) => { log_append("WARN", "media: D-Bus unavailable; watcher disabled"); return; } };
+    std::thread::spawn(move || loop {
+        if let Ok(cur) = read_state(&conn) {
+            let now = Instant::now();
+            let mut last = LAST.lock().unwrap();
+            let changed = match last.as_ref() {
+                None => true,
+                Some((prev, at)) => {
+                    let elapsed = now.duration_since(*at).as_millis() as i64;
+                    // 再生中の位置が経過時間から大きくずれていればシーク
+                    let seeked = cur.players.iter().any(|p| prev.players.iter().find(|q| q.id == p.id).map(|q| {
+                        let expected = if q.status == "playing" { q.position_ms + elapsed } else { q.position_ms };
+                        (p.position_ms - expected).abs() > SEEK_THRESHOLD_MS
+                    }).unwrap_or(false));
+                    seeked || without_position(prev) != without_position(&cur)
+                }
+            };
+            if changed {
+                let _ = app_handle.emit("sis:media-changed", &cur);
+            }
+            // シーク判定のため位置は毎回更新する
+            *last = Some((cur, now));
+        }
+        std::thread::sleep(POLL);
+    });
+}
+
+#[tauri::command]
+pub fn media_state() -> Result<MediaState, SisError> { Ok(read_state(&session_bus()?)?) }
+
+#[tauri::command]
+pub fn media_control(action: String, player: Option<String>) -> Result<String, SisError> {
+    control(player, &action)?;
+    Ok(action)
+}
+
+/// 操作対象のプレイヤーを固定する（None で自動選択に戻す）
+#[tauri::command]
+pub fn media_set_active(player: Option<String>) -> Result<String, SisError> {
+    *ACTIVE.lock().unwrap() = player.clone();
+    Ok(player.unwrap_or_else(|| "auto".into()))
+}
+
+/// 絶対位置へシーク（ミリ秒）
+#[tauri::command]
+pub fn media_seek(position_ms: i64, player: Option<String>) -> Result<String, SisError> {
+    let conn = session_bus()?;
+    let p = target(&conn, player)?;
+    if !p.can_seek { return Err("not-seekable".into()); }
+    let track = p.track_id.clone().ok_or("not-seekable")?;
+    let track = ObjectPath::try_from(track).map_err(|e| format!("invalid-track: {}", e))?;
+    let pos = position_ms.clamp(0, p.duration_ms.unwrap_or(i64::MAX / 1000)) * 1000;
+    let _: () = player_proxy(&conn, &p.id)?.call("SetPosition", &(track, pos)).map_err(|e| format!("mpris-failed: {}", e))?;
+    Ok("seeked".into())
+}
+
+#[tauri::command]
+pub fn media_set_volume(volume: u32, player: Option<String>) -> Result<String, SisError> {
+    let conn = session_bus()?;
+    let p = target(&conn, player)?;
+    let v = volume.min(100) as f64 / 100.0;
+    player_proxy(&conn, &p.id)?.set_property("Volume", v).map_err(|e| format!("mpris-failed: {}", e))?;
+    Ok(format!("volume-set: {}", volume.min(100)))
+}
+
+#[tauri::command]
+pub fn media_set_shuffle(shuffle: bool, player: Option<String>) -> Result<String, SisError> {
+    let conn = session_bus()?;
+    let p = target(&conn, player)?;
+    if p.shuffle.is_none() { return Err("not-supported".into()); }
+    player_proxy(&conn, &p.id)?.set_property("Shuffle", shuffle).map_err(|e| format!("mpris-failed: {}", e))?;
+    Ok("shuffle-set".into())
+}
+
+/// none / track / playlist
+#[tauri::command]
+pub fn media_set_loop(status: String, player: Option<String>) -> Result<String, SisError> {
+    let v = match status.as_str() { "none" => "None", "track" => "Track", "playlist" => "Playlist", _ => return Err("invalid-loop-status".into()) };
+    let conn = session_bus()?;
+    let p = target(&conn, player)?;
+    if p.loop_status.is_none() { return Err("not-supported".into()); }
+    player_proxy(&conn, &p.id)?.set_property("LoopStatus", v).map_err(|e| format!("mpris-failed: {}", e))?;
+    Ok("loop-set".into())
+}
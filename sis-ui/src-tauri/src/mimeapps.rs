@@ -0,0 +1,457 @@
+// Default applications / "Open with": freedesktop mime-apps spec (mimeapps.list + mimeinfo.cache)
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::SisError;
+use crate::{build_app_dirs, log_append, parse_desktop_fields, resolve_icon_path, run_out, to_data_url, which};
+
+const DEFAULT_SECTION: &str = "Default Applications";
+const ADDED_SECTION: &str = "Added Associations";
+const REMOVED_SECTION: &str = "Removed Associations";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MimeHandler {
+    desktop_id: String,
+    name: String,
+    exec: String,
+    icon_data_url: Option<String>,
+    is_default: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MimeHandlers {
+    mime: String,
+    default: Option<MimeHandler>,
+    handlers: Vec<MimeHandler>,
+}
+
+// INIの1セクション（キー順を保持して書き戻す）。
+// コメント行は "#..." をキーにして元の位置に残す（キーは '#' で始まらないので検索に引っかからない）。
+// 最初のセクションより前のコメントは名前が空のセクションに入れる
+#[derive(Debug, Clone, Default)]
+struct IniSection { name: String, entries: Vec<(String, String)> }
+
+fn is_comment(key: &str) -> bool { key.starts_with('#') }
+
+fn parse_ini(content: &str) -> Vec<IniSection> {
+    let mut out: Vec<IniSection> = Vec::new();
+    for line in content.lines() {
+        let l = line.trim();
+        if l.is_empty() { continue; }
+        if l.starts_with('[') && l.ends_with(']') {
+            out.push(IniSection { name: l[1..l.len() - 1].to_string(), entries: Vec::new() });
+            continue;
+        }
+        if is_comment(l) {
+            if out.is_empty() { out.push(IniSection::default()); }
+            if let Some(sec) = out.last_mut() { sec.entries.push((l.to_string(), String::new())); }
+            continue;
+        }
+        if let (Some(sec), Some((k, v))) = (out.last_mut(), l.split_once('=')) {
+            sec.entries.push((k.trim().to_string(), v.trim().to_string()));
+        }
+    }
+    out
+}
+
+fn render_ini(sections: &[IniSection]) -> String {
+    let mut s = String::new();
+    for sec in sections {
+        if !s.is_empty() { s.push('\n'); }
+        if !sec.name.is_empty() { s.push_str(&format!("[{}]\n", sec.name)); }
+        for (k, v) in &sec.entries {
+            if is_comment(k) { s.push_str(&format!("{}\n", k)); } else { s.push_str(&format!("{}={}\n", k, v)); }
+        }
+    }
+    s
+}
+
+fn ini_get<'a>(sections: &'a [IniSection], section: &str, key: &str) -> Option<&'a str> {
+    sections.iter().filter(|s| s.name == section)
+        .flat_map(|s| s.entries.iter())
+        .find(|(k, _)| k == key)
+        .map(|(_, v)| v.as_str())
+}
+
+fn split_ids(v: &str) -> Vec<String> {
+    v.split(';').map(|s| s.trim()).filter(|s| !s.is_empty()).map(|s| s.to_string()).collect()
+}
+
+fn config_home() -> Option<PathBuf> {
+    if let Ok(x) = std::env::var("XDG_CONFIG_HOME") { if !x.is_empty() { return Some(PathBuf::from(x)); } }
+    dirs::home_dir().map(|h| h.join(".config"))
+}
+
+fn user_mimeapps_path() -> Option<PathBuf> { config_home().map(|d| d.join("mimeapps.list")) }
+
+/// 仕様の優先順: $XDG_CONFIG_HOME, $XDG_CONFIG_DIRS, 各 applications ディレクトリ
+fn mimeapps_paths() -> Vec<PathBuf> {
+    let mut out: Vec<PathBuf> = Vec::new();
+    if let Some(p) = user_mimeapps_path() { out.push(p); }
+    let cfg_dirs = std::env::var("XDG_CONFIG_DIRS").unwrap_or_else(|_| "/etc/xdg".into());
+    for d in cfg_dirs.split(':') { if !d.is_empty() { out.push(PathBuf::from(d).join("mimeapps.list")); } }
+    for d in build_app_dirs() { out.push(d.join("mimeapps.list")); }
+    let mut seen: HashSet<PathBuf> = HashSet::new();
+    out.retain(|p| seen.insert(p.clone()));
+    out
+}
+
+fn app_dirs_unique() -> Vec<PathBuf> {
+    let mut seen: HashSet<PathBuf> = HashSet::new();
+    build_app_dirs().into_iter().filter(|p| p.is_dir() && seen.insert(p.clone())).collect()
+}
+
+/// desktop ID（サブディレクトリは '-' 区切り）から .desktop の実体を探す
+fn find_desktop_file(desktop_id: &str) -> Option<PathBuf> {
+    app_dirs_unique().into_iter().find_map(|dir| desktop_file_in(&dir, desktop_id))
+}
+
+/// どの '-' もサブディレクトリの区切りでありうる（kde-foo-bar.desktop → kde/foo/bar.desktop, kde/foo-bar.desktop, kde-foo/bar.desktop …）。
+/// 実在するディレクトリだけをたどるので、全部の組み合わせは試さない
+fn desktop_file_in(dir: &Path, id: &str) -> Option<PathBuf> {
+    let p = dir.join(id);
+    if p.is_file() { return Some(p); }
+    id.match_indices('-').find_map(|(i, _)| {
+        let sub = dir.join(&id[..i]);
+        if sub.is_dir() { desktop_file_in(&sub, &id[i + 1..]) } else { None }
+    })
+}
+
+fn handler_from_id(desktop_id: &str, is_default: bool) -> Option<MimeHandler> {
+    let path = find_desktop_file(desktop_id)?;
+    let content = fs::read_to_string(&path).ok()?;
+    if content.lines().any(|l| l.trim() == "Hidden=true") { return None; }
+    let (name, exec, icon_raw, _swm) = parse_desktop_fields(&content);
+    if exec.trim().is_empty() { return None; }
+    let icon_data_url = resolve_icon_path(&icon_raw).and_then(|p| to_data_url(&p));
+    Some(MimeHandler { desktop_id: desktop_id.to_string(), name, exec: exec.trim().to_string(), icon_data_url, is_default })
+}
+
+/// ファイルのMIMEタイプ。mime_guess で判定できなければ file(1) に頼る
+pub fn mime_for_path(path: &Path) -> String {
+    let guess = mime_guess::from_path(path).first_or_octet_stream().to_string();
+    if guess != "application/octet-stream" { return guess; }
+    if which("file") {
+        if let Some(s) = run_out("file", &["--mime-type", "-b", &path.to_string_lossy()]) {
+            let t = s.trim();
+            if t.contains('/') { return t.to_string(); }
+        }
+    }
+    guess
+}
+
+fn default_ids(mime: &str) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    for p in mimeapps_paths() {
+        if let Ok(c) = fs::read_to_string(&p) {
+            if let Some(v) = ini_get(&parse_ini(&c), DEFAULT_SECTION, mime) { out.extend(split_ids(v)); }
+        }
+    }
+    out
+}
+
+fn associated_ids(mime: &str) -> Vec<String> {
+    let mut added: Vec<String> = Vec::new();
+    let mut removed: HashSet<String> = HashSet::new();
+    for p in mimeapps_paths() {
+        if let Ok(c) = fs::read_to_string(&p) {
+            let ini = parse_ini(&c);
+            if let Some(v) = ini_get(&ini, ADDED_SECTION, mime) { added.extend(split_ids(v)); }
+            if let Some(v) = ini_get(&ini, REMOVED_SECTION, mime) { removed.extend(split_ids(v)); }
+        }
+    }
+    // mimeinfo.cache（update-desktop-database 生成）
+    for dir in app_dirs_unique() {
+        if let Ok(c) = fs::read_to_string(dir.join("mimeinfo.cache")) {
+            if let Some(v) = ini_get(&parse_ini(&c), "MIME Cache", mime) { added.extend(split_ids(v)); }
+        }
+    }
+    // キャッシュが古い/無い環境向けに MimeType= を直接走査
+    for dir in app_dirs_unique() {
+        let read = match fs::read_dir(&dir) { Ok(r) => r, Err(_) => continue };
+        for e in read.flatten() {
+            let p = e.path();
+            if p.extension().map_or(true, |x| x != "desktop") { continue; }
+            let content = match fs::read_to_string(&p) { Ok(s) => s, Err(_) => continue };
+            let types = content.lines().find(|l| l.starts_with("MimeType=")).and_then(|l| l.strip_prefix("MimeType=")).unwrap_or("");
+            if split_ids(types).iter().any(|t| t == mime) {
+                if let Some(id) = p.file_name().and_then(|s| s.to_str()) { added.push(id.to_string()); }
+            }
+        }
+    }
+    let mut seen: HashSet<String> = HashSet::new();
+    added.into_iter().filter(|id| !removed.contains(id) && seen.insert(id.clone())).collect()
+}
+
+fn resolve_default(mime: &str) -> Option<MimeHandler> {
+    // 最初にインストール済みで有効なものを採用
+    default_ids(mime).iter().find_map(|id| handler_from_id(id, true))
+}
+
+fn list_handlers(mime: &str) -> MimeHandlers {
+    let default = resolve_default(mime);
+    let default_id = default.as_ref().map(|d| d.desktop_id.clone());
+    let mut handlers: Vec<MimeHandler> = Vec::new();
+    if let Some(d) = default.clone() { handlers.push(d); }
+    for id in associated_ids(mime) {
+        if Some(&id) == default_id.as_ref() { continue; }
+        if let Some(h) = handler_from_id(&id, false) { handlers.push(h); }
+    }
+    MimeHandlers { mime: mime.to_string(), default, handlers }
+}
+
+fn validate_mime(mime: &str) -> Result<(), String> {
+    let ok = mime.split_once('/').map(|(a, b)| !a.is_empty() && !b.is_empty()).unwrap_or(false)
+        && !mime.contains(|c: char| c.is_whitespace() || c == '=' || c == ';' || c == '[' || c == ']');
+    if ok { Ok(()) } else { Err(format!("invalid-mime-type: {}", mime)) }
+}
+
+fn validate_desktop_id(desktop_id: &str) -> Result<(), String> {
+    if !desktop_id.ends_with(".desktop") || desktop_id.contains('/') || desktop_id.contains(';') {
+        return Err(format!("invalid-desktop-id: {}", desktop_id));
+    }
+    if find_desktop_file(desktop_id).is_none() { return Err(format!("desktop-entry-not-found: {}", desktop_id)); }
+    Ok(())
+}
+
+fn read_user_mimeapps() -> Result<(PathBuf, Vec<IniSection>), String> {
+    let path = user_mimeapps_path().ok_or_else(|| "cannot-detect-home".to_string())?;
+    let ini = match fs::read_to_string(&path) { Ok(c) => parse_ini(&c), Err(_) => Vec::new() };
+    Ok((path, ini))
+}
+
+fn write_user_mimeapps(path: &Path, ini: &[IniSection]) -> Result<(), String> {
+    if let Some(dir) = path.parent() { fs::create_dir_all(dir).map_err(|e| format!("mkdir-failed: {}", e))?; }
+    // 途中で落ちても壊れないよう一時ファイル経由で置き換える
+    let tmp = path.with_extension("list.tmp");
+    fs::write(&tmp, render_ini(ini)).map_err(|e| format!("write-failed: {}", e))?;
+    fs::rename(&tmp, path).map_err(|e| format!("write-failed: {}", e))
+}
+
+fn section_mut<'a>(ini: &'a mut Vec<IniSection>, name: &str) -> &'a mut IniSection {
+    if let Some(i) = ini.iter().position(|s| s.name == name) { return &mut ini[i]; }
+    ini.push(IniSection { name: name.to_string(), entries: Vec::new() });
+    ini.last_mut().unwrap()
+}
+
+/// セクション内の mime キーの値（;区切りリスト）を更新。空になったらキーごと消す
+fn update_list(ini: &mut Vec<IniSection>, section: &str, mime: &str, f: impl FnOnce(&mut Vec<String>)) {
+    let sec = section_mut(ini, section);
+    let mut ids = sec.entries.iter().find(|(k, _)| k == mime).map(|(_, v)| split_ids(v)).unwrap_or_default();
+    f(&mut ids);
+    sec.entries.retain(|(k, _)| k != mime);
+    if !ids.is_empty() { sec.entries.push((mime.to_string(), format!("{};", ids.join(";")))); }
+}
+
+fn add_association(ini: &mut Vec<IniSection>, mime: &str, desktop_id: &str) {
+    update_list(ini, ADDED_SECTION, mime, |ids| {
+        ids.retain(|x| x != desktop_id);
+        ids.insert(0, desktop_id.to_string());
+    });
+    update_list(ini, REMOVED_SECTION, mime, |ids| ids.retain(|x| x != desktop_id));
+    ini.retain(|s| !(s.name == REMOVED_SECTION && s.entries.is_empty()));
+}
+
+#[tauri::command]
+pub fn mime_list_handlers(mime: Option<String>, path: Option<String>) -> Result<MimeHandlers, SisError> {
+    let mime = match (mime, path) {
+        (Some(m), _) if !m.trim().is_empty() => m.trim().to_string(),
+        (_, Some(p)) => {
+            let pb = PathBuf::from(&p);
+            if !pb.exists() { return Err("file-not-found".into()); }
+            mime_for_path(&pb)
+        }
+        _ => return Err("invalid-args".into()),
+    };
+    validate_mime(&mime)?;
+    Ok(list_handlers(&mime))
+}
+
+#[tauri::command]
+pub fn mime_get_default(mime: String) -> Result<Option<MimeHandler>, SisError> {
+    validate_mime(&mime)?;
+    Ok(resolve_default(&mime))
+}
+
+#[tauri::command]
+pub fn mime_set_default(mime: String, desktop_id: String) -> Result<String, SisError> {
+    validate_mime(&mime)?;
+    validate_desktop_id(&desktop_id)?;
+    let (path, mut ini) = read_user_mimeapps()?;
+    let sec = section_mut(&mut ini, DEFAULT_SECTION);
+    sec.entries.retain(|(k, _)| k != &mime);
+    sec.entries.push((mime.clone(), format!("{};", desktop_id)));
+    // 既定にしたアプリは関連付けにも載せておく（Open with の候補に必ず出す）
+    add_association(&mut ini, &mime, &desktop_id);
+    write_user_mimeapps(&path, &ini)?;
+    log_append("INFO", &format!("mime_set_default: {} -> {}", mime, desktop_id));
+    Ok("default-set".into())
+}
+
+#[tauri::command]
+pub fn mime_add_association(mime: String, desktop_id: String) -> Result<String, SisError> {
+    validate_mime(&mime)?;
+    validate_desktop_id(&desktop_id)?;
+    let (path, mut ini) = read_user_mimeapps()?;
+    add_association(&mut ini, &mime, &desktop_id);
+    write_user_mimeapps(&path, &ini)?;
+    log_append("INFO", &format!("mime_add_association: {} += {}", mime, desktop_id));
+    Ok("association-added".into())
+}
+
+/// [Desktop Entry] セクションのキーの値（ロケール付きキーやアクションのセクションは見ない）
+fn desktop_entry_value(content: &str, key: &str) -> Option<String> {
+    let mut in_entry = false;
+    for line in content.lines() {
+        let l = line.trim();
+        if l.starts_with('[') { in_entry = l == "[Desktop Entry]"; continue; }
+        if !in_entry { continue; }
+        if let Some((k, v)) = l.split_once('=') {
+            if k.trim() == key { return Some(v.trim().to_string()); }
+        }
+    }
+    None
+}
+
+/// string 型の値のエスケープ（\s \n \t \r \\）を戻す
+fn unescape_value(v: &str) -> String {
+    let mut out = String::new();
+    let mut chars = v.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' { out.push(c); continue; }
+        match chars.next() {
+            Some('s') => out.push(' '),
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('r') => out.push('\r'),
+            Some(o) => out.push(o),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// Exec を引数に分ける。空白区切りで、"..." の中では \" \` \$ \\ をエスケープとして扱う（Desktop Entry 仕様）
+fn split_exec(exec: &str) -> Result<Vec<String>, String> {
+    let mut args: Vec<String> = Vec::new();
+    let mut cur = String::new();
+    let mut in_arg = false;
+    let mut chars = exec.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            ' ' | '\t' => {
+                if in_arg { args.push(std::mem::take(&mut cur)); in_arg = false; }
+            }
+            '"' => {
+                in_arg = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(e @ ('"' | '`' | '$' | '\\')) => cur.push(e),
+                            Some(o) => { cur.push('\\'); cur.push(o); }
+                            None => return Err("invalid-exec: unterminated escape".into()),
+                        },
+                        Some(o) => cur.push(o),
+                        None => return Err("invalid-exec: unterminated quote".into()),
+                    }
+                }
+            }
+            o => { in_arg = true; cur.push(o); }
+        }
+    }
+    if in_arg { args.push(cur); }
+    Ok(args)
+}
+
+/// file:// URI。パスの各バイトを、英数字と -._~/ 以外はパーセントエンコードする
+fn file_uri(path: &Path) -> String {
+    use std::os::unix::ffi::OsStrExt;
+    let mut out = String::from("file://");
+    for &b in path.as_os_str().as_bytes() {
+        if b.is_ascii_alphanumeric() || b"-._~/".contains(&b) { out.push(b as char); } else { out.push_str(&format!("%{:02X}", b)); }
+    }
+    out
+}
+
+/// Exec のフィールドコードを展開した argv。ファイル用のコードが無ければ末尾にファイルを付ける
+fn expand_exec(args: &[String], file: &Path, desktop: &Path, content: &str) -> Vec<String> {
+    let path = file.to_string_lossy().to_string();
+    let mut out: Vec<String> = Vec::new();
+    let mut has_file = false;
+    for arg in args {
+        match arg.as_str() {
+            "%f" | "%F" => { has_file = true; out.push(path.clone()); continue; }
+            "%u" | "%U" => { has_file = true; out.push(file_uri(file)); continue; }
+            "%i" => {
+                if let Some(icon) = desktop_entry_value(content, "Icon").filter(|i| !i.is_empty()) { out.push("--icon".into()); out.push(icon); }
+                continue;
+            }
+            _ => {}
+        }
+        // 引数の途中のコード（--file=%f など）と %% も展開する
+        let mut a = String::new();
+        let mut chars = arg.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' { a.push(c); continue; }
+            match chars.next() {
+                Some('%') => a.push('%'),
+                Some('f' | 'F') => { has_file = true; a.push_str(&path); }
+                Some('u' | 'U') => { has_file = true; a.push_str(&file_uri(file)); }
+                Some('c') => a.push_str(&desktop_entry_value(content, "Name").map(|n| unescape_value(&n)).unwrap_or_default()),
+                Some('k') => a.push_str(&desktop.to_string_lossy()),
+                // 廃止されたコード（%d %n %v など）は消す
+                _ => {}
+            }
+        }
+        out.push(a);
+    }
+    if !has_file { out.push(path); }
+    out
+}
+
+/// Exec のフィールドコードをファイルで展開し、シェルを通さずに起動
+#[tauri::command]
+pub fn open_with(path: String, desktop_id: String) -> Result<String, SisError> {
+    let file = PathBuf::from(&path);
+    if !file.exists() { return Err("file-not-found".into()); }
+    let desktop = find_desktop_file(&desktop_id).ok_or_else(|| format!("desktop-entry-not-found: {}", desktop_id))?;
+    let content = fs::read_to_string(&desktop).map_err(|e| format!("read-error: {}", e))?;
+    let raw = desktop_entry_value(&content, "Exec").map(|v| unescape_value(&v)).unwrap_or_default();
+    if raw.is_empty() { return Err("empty-exec".into()); }
+    crate::screentime::check_launch(&raw)?;
+    let argv = expand_exec(&split_exec(&raw)?, &file, &desktop, &content);
+    let (prog, args) = argv.split_first().ok_or("empty-exec")?;
+    log_append("INFO", &format!("open_with: {} via {}", path, desktop_id));
+    match Command::new(prog).args(args).spawn() {
+        Ok(_) => Ok("launched".into()),
+        Err(e) => Err(SisError::failed("failed-to-launch", e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_exec_handles_quotes_and_escapes() {
+        assert_eq!(split_exec("gimp-2.10 %U").unwrap(), ["gimp-2.10", "%U"]);
+        assert_eq!(split_exec("  a\tb  ").unwrap(), ["a", "b"]);
+        assert_eq!(split_exec(r#""/opt/My App/run" --flag "%f""#).unwrap(), ["/opt/My App/run", "--flag", "%f"]);
+        assert_eq!(split_exec(r#"sh -c "echo \"hi\" \$HOME \\ \`x\`""#).unwrap(), ["sh", "-c", r#"echo "hi" $HOME \ `x`"#]);
+        // 引用符の中の \ の後が特別な文字でなければそのまま残す
+        assert_eq!(split_exec(r#""a\nb""#).unwrap(), [r"a\nb"]);
+        assert_eq!(split_exec(r#""""#).unwrap(), [""]);
+        assert!(split_exec("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn split_exec_rejects_unterminated_quotes() {
+        assert_eq!(split_exec(r#"foo "bar"#).unwrap_err(), "invalid-exec: unterminated quote");
+        assert_eq!(split_exec(r#"foo "bar\"#).unwrap_err(), "invalid-exec: unterminated escape");
+    }
+}
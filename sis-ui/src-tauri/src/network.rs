@@ -0,0 +1,475 @@
+// NetworkManager(D-Bus): デバイス/Wi-Fi スキャン・接続（PSK / WPA2-Enterprise）・保存済み接続・機内モード・VPN/ホットスポット
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+use zbus::blocking::{Connection, Proxy};
+use zbus::zvariant::{ObjectPath, OwnedObjectPath, OwnedValue, Value};
+
+use crate::error::SisError;
+use crate::log_append;
+
+const POLL: Duration = Duration::from_secs(3);
+const NM_DEST: &str = "org.freedesktop.NetworkManager";
+const NM_PATH: &str = "/org/freedesktop/NetworkManager";
+const NM_SETTINGS_PATH: &str = "/org/freedesktop/NetworkManager/Settings";
+const IF_NM: &str = "org.freedesktop.NetworkManager";
+const IF_DEVICE: &str = "org.freedesktop.NetworkManager.Device";
+const IF_WIRELESS: &str = "org.freedesktop.NetworkManager.Device.Wireless";
+const IF_AP: &str = "org.freedesktop.NetworkManager.AccessPoint";
+const IF_ACTIVE: &str = "org.freedesktop.NetworkManager.Connection.Active";
+const IF_SETTINGS: &str = "org.freedesktop.NetworkManager.Settings";
+const IF_CONNECTION: &str = "org.freedesktop.NetworkManager.Settings.Connection";
+
+// NM_DEVICE_TYPE
+const DEVICE_TYPE_WIFI: u32 = 2;
+// NM_802_11_AP_SEC_*
+const AP_SEC_KEY_MGMT_PSK: u32 = 0x100;
+const AP_SEC_KEY_MGMT_802_1X: u32 = 0x200;
+const AP_SEC_KEY_MGMT_SAE: u32 = 0x400;
+
+static LAST: Lazy<Mutex<Option<NetworkStatus>>> = Lazy::new(|| Mutex::new(None));
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct NetDevice {
+    path: String,
+    interface: String,
+    kind: String,  // ethernet | wifi | bluetooth | modem | wireguard | other
+    state: String, // unavailable | disconnected | connecting | connected | failed | unmanaged | unknown
+    connection: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AccessPoint {
+    path: String,
+    ssid: String,
+    strength: u8,
+    security: String, // open | wep | wpa-psk | sae | enterprise
+    frequency: u32,
+    bssid: String,
+    active: bool,
+    saved: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ActiveConn {
+    id: String,
+    kind: String, // wifi | ethernet | vpn | wireguard | hotspot | other
+    state: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SavedConnection {
+    path: String,
+    id: String,
+    uuid: String,
+    kind: String,
+    ssid: Option<String>,
+    autoconnect: bool,
+    last_used: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct NetworkStatus {
+    networking_enabled: bool,
+    wireless_enabled: bool,
+    airplane_mode: bool,
+    connectivity: String, // unknown | none | portal | limited | full
+    primary: Option<String>,
+    devices: Vec<NetDevice>,
+    active: Vec<ActiveConn>,
+    vpn_active: bool,
+    hotspot_active: bool,
+}
+
+/// WPA2-Enterprise(802.1X) の資格情報
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EapCredentials {
+    identity: String,
+    password: String,
+    #[serde(default)]
+    method: Option<String>, // peap | ttls（既定 peap）
+    #[serde(default)]
+    phase2: Option<String>, // mschapv2 | pap（既定 mschapv2）
+    #[serde(default)]
+    anonymous_identity: Option<String>,
+    #[serde(default)]
+    ca_cert: Option<String>, // PEM/DER ファイルパス
+    #[serde(default)]
+    domain_suffix_match: Option<String>,
+}
+
+// テスト時は SIS_NM_BUS=session でモック(python-dbusmock 等)のセッションバスを使う
+fn nm_bus() -> Result<Connection, String> {
+    static CONN: Lazy<Option<Connection>> = Lazy::new(|| {
+        if std::env::var("SIS_NM_BUS").map(|v| v == "session").unwrap_or(false) { Connection::session().ok() } else { Connection::system().ok() }
+    });
+    CONN.clone().ok_or_else(|| "dbus-unavailable".to_string())
+}
+
+fn proxy<'a>(conn: &Connection, path: &'a str, iface: &'a str) -> Result<Proxy<'a>, String> {
+    Proxy::new(conn, NM_DEST, path, iface).map_err(|e| format!("networkmanager-unavailable: {}", e))
+}
+
+fn nm_err(prefix: &str, e: zbus::Error) -> String {
+    let s = e.to_string();
+    if s.contains("NotAuthorized") || s.contains("PermissionDenied") { return "not-authorized".into(); }
+    if s.contains("ServiceUnknown") { return "networkmanager-unavailable".into(); }
+    format!("{}: {}", prefix, s)
+}
+
+fn device_kind(t: u32) -> &'static str {
+    match t { 1 => "ethernet", DEVICE_TYPE_WIFI => "wifi", 5 => "bluetooth", 8 => "modem", 29 => "wireguard", _ => "other" }
+}
+
+fn device_state(s: u32) -> &'static str {
+    match s {
+        10 => "unmanaged",
+        20 => "unavailable",
+        30 => "disconnected",
+        40..=90 => "connecting",
+        100 => "connected",
+        110 => "disconnecting",
+        120 => "failed",
+        _ => "unknown",
+    }
+}
+
+fn connectivity_name(c: u32) -> &'static str {
+    match c { 1 => "none", 2 => "portal", 3 => "limited", 4 => "full", _ => "unknown" }
+}
+
+fn security_of(flags: u32, wpa: u32, rsn: u32) -> &'static str {
+    let km = wpa | rsn;
+    if km & AP_SEC_KEY_MGMT_802_1X != 0 { return "enterprise"; }
+    if km & AP_SEC_KEY_MGMT_SAE != 0 && km & AP_SEC_KEY_MGMT_PSK == 0 { return "sae"; }
+    if km & AP_SEC_KEY_MGMT_PSK != 0 { return "wpa-psk"; }
+    // NM_802_11_AP_FLAGS_PRIVACY のみ → WEP
+    if flags & 0x1 != 0 { return "wep"; }
+    "open"
+}
+
+fn ssid_string(bytes: &[u8]) -> String { String::from_utf8_lossy(bytes).to_string() }
+
+fn str_prop(p: &Proxy<'_>, name: &str) -> String { p.get_property::<String>(name).unwrap_or_default() }
+
+fn list_devices(conn: &Connection) -> Result<Vec<(OwnedObjectPath, NetDevice)>, String> {
+    let nm = proxy(conn, NM_PATH, IF_NM)?;
+    let paths: Vec<OwnedObjectPath> = nm.call("GetDevices", &()).map_err(|e| nm_err("get-devices-failed", e))?;
+    let mut out = Vec::new();
+    for p in paths {
+        let d = match proxy(conn, p.as_str(), IF_DEVICE) { Ok(d) => d, Err(_) => continue };
+        let t = d.get_property::<u32>("DeviceType").unwrap_or(0);
+        // ループバック(NM_DEVICE_TYPE_LOOPBACK)は除外
+        if t == 32 { continue; }
+        let connection = d.get_property::<OwnedObjectPath>("ActiveConnection").ok()
+            .filter(|a| a.as_str() != "/")
+            .and_then(|a| proxy(conn, a.as_str(), IF_ACTIVE).ok().map(|ap| str_prop(&ap, "Id")))
+            .filter(|s| !s.is_empty());
+        out.push((p.clone(), NetDevice {
+            path: p.to_string(),
+            interface: str_prop(&d, "Interface"),
+            kind: device_kind(t).into(),
+            state: device_state(d.get_property::<u32>("State").unwrap_or(0)).into(),
+            connection,
+        }));
+    }
+    Ok(out)
+}
+
+fn wifi_device(conn: &Connection) -> Result<OwnedObjectPath, String> {
+    list_devices(conn)?.into_iter()
+        .filter(|(_, d)| d.kind == "wifi" && d.state != "unmanaged")
+        .map(|(p, _)| p)
+        .next()
+        .ok_or_else(|| "no-wifi-device".to_string())
+}
+
+fn active_connections(conn: &Connection) -> Vec<ActiveConn> {
+    let nm = match proxy(conn, NM_PATH, IF_NM) { Ok(p) => p, Err(_) => return Vec::new() };
+    let paths = nm.get_property::<Vec<OwnedObjectPath>>("ActiveConnections").unwrap_or_default();
+    paths.iter().filter_map(|p| {
+        let a = proxy(conn, p.as_str(), IF_ACTIVE).ok()?;
+        let t = str_prop(&a, "Type");
+        let kind = match t.as_str() {
+            "802-11-wireless" => if is_hotspot(conn, &a) { "hotspot" } else { "wifi" },
+            "802-3-ethernet" => "ethernet",
+            "vpn" => "vpn",
+            "wireguard" => "wireguard",
+            _ => "other",
+        };
+        let state = match a.get_property::<u32>("State").unwrap_or(0) { 1 => "activating", 2 => "activated", 3 => "deactivating", _ => "unknown" };
+        Some(ActiveConn { id: str_prop(&a, "Id"), kind: kind.into(), state: state.into() })
+    }).collect()
+}
+
+fn connection_settings(conn: &Connection, path: &str) -> Option<HashMap<String, HashMap<String, OwnedValue>>> {
+    proxy(conn, path, IF_CONNECTION).ok()?.call("GetSettings", &()).ok()
+}
+
+// 802-11-wireless.mode == "ap" ならホットスポット
+fn is_hotspot(conn: &Connection, active: &Proxy<'_>) -> bool {
+    let c = match active.get_property::<OwnedObjectPath>("Connection") { Ok(c) => c, Err(_) => return false };
+    connection_settings(conn, c.as_str())
+        .and_then(|s| s.get("802-11-wireless").and_then(|w| w.get("mode")).and_then(|m| m.try_clone().ok()).and_then(|m| String::try_from(m).ok()))
+        .map(|m| m == "ap")
+        .unwrap_or(false)
+}
+
+fn bluetooth_soft_blocked() -> bool {
+    crate::run_out("rfkill", &["list", "bluetooth"]).map(|s| s.to_lowercase().contains("soft blocked: yes")).unwrap_or(false)
+}
+
+fn read_status(conn: &Connection) -> Result<NetworkStatus, String> {
+    let nm = proxy(conn, NM_PATH, IF_NM)?;
+    let networking_enabled = nm.get_property::<bool>("NetworkingEnabled").map_err(|e| nm_err("networkmanager-unavailable", e))?;
+    let wireless_enabled = nm.get_property::<bool>("WirelessEnabled").unwrap_or(false);
+    let primary = nm.get_property::<OwnedObjectPath>("PrimaryConnection").ok()
+        .filter(|p| p.as_str() != "/")
+        .and_then(|p| proxy(conn, p.as_str(), IF_ACTIVE).ok().map(|a| str_prop(&a, "Id")));
+    let active = active_connections(conn);
+    Ok(NetworkStatus {
+        networking_enabled,
+        wireless_enabled,
+        airplane_mode: !wireless_enabled && bluetooth_soft_blocked(),
+        connectivity: connectivity_name(nm.get_property::<u32>("Connectivity").unwrap_or(0)).into(),
+        primary,
+        devices: list_devices(conn)?.into_iter().map(|(_, d)| d).collect(),
+        vpn_active: active.iter().any(|a| (a.kind == "vpn" || a.kind == "wireguard") && a.state == "activated"),
+        hotspot_active: active.iter().any(|a| a.kind == "hotspot" && a.state == "activated"),
+        active,
+    })
+}
+
+/// NetworkingEnabled（取得できなければ None）
+pub fn networking_enabled() -> Option<bool> {
+    let conn = nm_bus().ok()?;
+    proxy(&conn, NM_PATH, IF_NM).ok()?.get_property::<bool>("NetworkingEnabled").ok()
+}
+
+pub fn set_networking(enable: bool) -> Result<(), String> {
+    let conn = nm_bus()?;
+    let nm = proxy(&conn, NM_PATH, IF_NM)?;
+    if nm.get_property::<bool>("NetworkingEnabled").ok() == Some(enable) { return Ok(()); }
+    let _: () = nm.call("Enable", &(enable,)).map_err(|e| nm_err("enable-failed", e))?;
+    Ok(())
+}
+
+fn list_saved(conn: &Connection) -> Result<Vec<SavedConnection>, String> {
+    let st = proxy(conn, NM_SETTINGS_PATH, IF_SETTINGS)?;
+    let paths: Vec<OwnedObjectPath> = st.call("ListConnections", &()).map_err(|e| nm_err("list-connections-failed", e))?;
+    let get = |m: &HashMap<String, HashMap<String, OwnedValue>>, sec: &str, key: &str| m.get(sec).and_then(|s| s.get(key)).and_then(|v| v.try_clone().ok());
+    let mut out = Vec::new();
+    for p in paths {
+        let s = match connection_settings(conn, p.as_str()) { Some(s) => s, None => continue };
+        let kind = get(&s, "connection", "type").and_then(|v| String::try_from(v).ok()).unwrap_or_default();
+        out.push(SavedConnection {
+            path: p.to_string(),
+            id: get(&s, "connection", "id").and_then(|v| String::try_from(v).ok()).unwrap_or_default(),
+            uuid: get(&s, "connection", "uuid").and_then(|v| String::try_from(v).ok()).unwrap_or_default(),
+            ssid: get(&s, "802-11-wireless", "ssid").and_then(|v| Vec::<u8>::try_from(v).ok()).map(|b| ssid_string(&b)),
+            autoconnect: get(&s, "connection", "autoconnect").and_then(|v| bool::try_from(v).ok()).unwrap_or(true),
+            last_used: get(&s, "connection", "timestamp").and_then(|v| u64::try_from(v).ok()).unwrap_or(0),
+            kind: match kind.as_str() { "802-11-wireless" => "wifi".into(), "802-3-ethernet" => "ethernet".into(), other => other.to_string() },
+        });
+    }
+    out.sort_by(|a, b| b.last_used.cmp(&a.last_used));
+    Ok(out)
+}
+
+pub fn start(app_handle: tauri::AppHandle) {
+    let conn = match nm_bus() { Ok(c) => c, Err(_) => { log_append("WARN", "network: D-Bus unavailable; watcher disabled"); return; } };
+    if let Err(e) = read_status(&conn) { log_append("WARN", &format!("network: watcher disabled ({})", e)); return; }
+    std::thread::spawn(move || loop {
+        if let Ok(cur) = read_status(&conn) {
+            let mut last = LAST.lock().unwrap();
+            if last.as_ref() != Some(&cur) {
+                if last.as_ref().map(|l| l.connectivity != cur.connectivity).unwrap_or(false) {
+                    log_append("INFO", &format!("network: connectivity -> {}", cur.connectivity));
+                    let _ = app_handle.emit("sis:connectivity-changed", serde_json::json!({ "connectivity": cur.connectivity, "primary": cur.primary }));
+                }
+                let _ = app_handle.emit("sis:network-changed", &cur);
+                *last = Some(cur);
+            }
+        }
+        std::thread::sleep(POLL);
+    });
+}
+
+#[tauri::command]
+pub fn network_status() -> Result<NetworkStatus, SisError> { Ok(read_status(&nm_bus()?)?) }
+
+/// アクセスポイント一覧。rescan=true でスキャンを要求（結果は数秒後に反映）
+#[tauri::command]
+pub fn wifi_list_access_points(rescan: Option<bool>) -> Result<Vec<AccessPoint>, SisError> {
+    let conn = nm_bus()?;
+    let dev = wifi_device(&conn)?;
+    let w = proxy(&conn, dev.as_str(), IF_WIRELESS)?;
+    if rescan.unwrap_or(false) {
+        let opts: HashMap<&str, Value> = HashMap::new();
+        // 直前のスキャンから間もない場合は NM が拒否するので無視
+        if let Err(e) = w.call::<_, _, ()>("RequestScan", &(opts,)) { log_append("INFO", &format!("network: scan request ignored: {}", e)); }
+    }
+    let active_ap = w.get_property::<OwnedObjectPath>("ActiveAccessPoint").ok().map(|p| p.to_string());
+    let saved: Vec<String> = list_saved(&conn).unwrap_or_default().into_iter().filter_map(|s| s.ssid).collect();
+    let paths: Vec<OwnedObjectPath> = w.call("GetAllAccessPoints", &()).map_err(|e| nm_err("scan-failed", e))?;
+    // 同じ SSID は最も強い BSS だけ残す
+    let mut by_ssid: HashMap<String, AccessPoint> = HashMap::new();
+    for p in paths {
+        let ap = match proxy(&conn, p.as_str(), IF_AP) { Ok(a) => a, Err(_) => continue };
+        let ssid = ssid_string(&ap.get_property::<Vec<u8>>("Ssid").unwrap_or_default());
+        if ssid.is_empty() { continue; }
+        let item = AccessPoint {
+            path: p.to_string(),
+            strength: ap.get_property::<u8>("Strength").unwrap_or(0),
+            security: security_of(ap.get_property::<u32>("Flags").unwrap_or(0), ap.get_property::<u32>("WpaFlags").unwrap_or(0), ap.get_property::<u32>("RsnFlags").unwrap_or(0)).into(),
+            frequency: ap.get_property::<u32>("Frequency").unwrap_or(0),
+            bssid: str_prop(&ap, "HwAddress"),
+            active: active_ap.as_deref() == Some(p.as_str()),
+            saved: saved.contains(&ssid),
+            ssid: ssid.clone(),
+        };
+        match by_ssid.get(&ssid) {
+            Some(prev) if prev.active || (prev.strength >= item.strength && !item.active) => {}
+            _ => { by_ssid.insert(ssid, item); }
+        }
+    }
+    let mut out: Vec<AccessPoint> = by_ssid.into_values().collect();
+    out.sort_by(|a, b| b.active.cmp(&a.active).then(b.strength.cmp(&a.strength)));
+    Ok(out)
+}
+
+// WPA3 専用（SAE のみ）の AP には "sae" で接続する。SAE のパスワードには WPA-PSK の長さ制限が無い
+fn psk_key_mgmt(conn: &Connection, dev: &OwnedObjectPath, ssid: &str, psk: &str) -> Result<&'static str, String> {
+    let key_mgmt = if ap_security(conn, dev, ssid) == Some("sae") { "sae" } else { "wpa-psk" };
+    if key_mgmt == "wpa-psk" && (psk.len() < 8 || psk.len() > 64) { return Err("invalid-psk".into()); }
+    Ok(key_mgmt)
+}
+
+/// スキャン結果からその SSID のセキュリティ種別を調べる（見つからなければ None）
+fn ap_security(conn: &Connection, dev: &OwnedObjectPath, ssid: &str) -> Option<&'static str> {
+    let w = proxy(conn, dev.as_str(), IF_WIRELESS).ok()?;
+    let paths: Vec<OwnedObjectPath> = w.call("GetAllAccessPoints", &()).ok()?;
+    paths.iter().filter_map(|p| proxy(conn, p.as_str(), IF_AP).ok()).find_map(|ap| {
+        if ssid_string(&ap.get_property::<Vec<u8>>("Ssid").unwrap_or_default()) != ssid { return None; }
+        Some(security_of(ap.get_property::<u32>("Flags").unwrap_or(0), ap.get_property::<u32>("WpaFlags").unwrap_or(0), ap.get_property::<u32>("RsnFlags").unwrap_or(0)))
+    })
+}
+
+fn current_user() -> String { std::env::var("USER").unwrap_or_default() }
+
+/// Wi-Fi へ接続。保存済みで資格情報の指定が無ければ既存の接続を再利用する
+#[tauri::command]
+pub fn wifi_connect(ssid: String, password: Option<String>, eap: Option<EapCredentials>, hidden: Option<bool>) -> Result<String, SisError> {
+    if ssid.trim().is_empty() { return Err("invalid-ssid".into()); }
+    let conn = nm_bus()?;
+    let dev = wifi_device(&conn)?;
+    let nm = proxy(&conn, NM_PATH, IF_NM)?;
+    let root = ObjectPath::try_from("/").unwrap();
+
+    if password.is_none() && eap.is_none() {
+        if let Some(saved) = list_saved(&conn)?.into_iter().find(|s| s.ssid.as_deref() == Some(ssid.as_str())) {
+            let saved_path = ObjectPath::try_from(saved.path.as_str()).map_err(|e| format!("invalid-path: {}", e))?;
+            let _: OwnedObjectPath = nm.call("ActivateConnection", &(saved_path, dev.as_ref(), root.clone())).map_err(|e| nm_err("connect-failed", e))?;
+            log_append("INFO", &format!("network: activating saved connection {}", saved.id));
+            return Ok("activating".into());
+        }
+    }
+
+    // 保存済みの SSID に新しいパスワードが来たら、プロファイルを増やさず既存の psk を書き換えて接続する
+    if let (Some(psk), None) = (password.as_deref().filter(|p| !p.is_empty()), eap.as_ref()) {
+        if let Some(saved) = list_saved(&conn)?.into_iter().find(|s| s.kind == "wifi" && s.ssid.as_deref() == Some(ssid.as_str())) {
+            let key_mgmt = psk_key_mgmt(&conn, &dev, &ssid, psk)?;
+            let mut settings = connection_settings(&conn, &saved.path).ok_or("connect-failed: settings unavailable")?;
+            let sec = settings.entry("802-11-wireless-security".into()).or_default();
+            let owned = |v: Value| OwnedValue::try_from(v).map_err(|e| format!("connect-failed: {}", e));
+            sec.insert("key-mgmt".into(), owned(Value::from(key_mgmt))?);
+            sec.insert("psk".into(), owned(Value::from(psk))?);
+            let _: () = proxy(&conn, saved.path.as_str(), IF_CONNECTION)?.call("Update", &(settings,)).map_err(|e| nm_err("connect-failed", e))?;
+            let saved_path = ObjectPath::try_from(saved.path.as_str()).map_err(|e| format!("invalid-path: {}", e))?;
+            let _: OwnedObjectPath = nm.call("ActivateConnection", &(saved_path, dev.as_ref(), root.clone())).map_err(|e| nm_err("connect-failed", e))?;
+            log_append("INFO", &format!("network: updated password of saved connection {}", saved.id));
+            return Ok("activating".into());
+        }
+    }
+
+    let mut connection: HashMap<&str, Value> = HashMap::new();
+    connection.insert("type", Value::from("802-11-wireless"));
+    connection.insert("id", Value::from(ssid.clone()));
+    // 共有端末のため、保存した資格情報はこのユーザーだけが使えるようにする
+    let user = current_user();
+    if !user.is_empty() { connection.insert("permissions", Value::from(vec![format!("user:{}", user)])); }
+
+    let mut wifi: HashMap<&str, Value> = HashMap::new();
+    wifi.insert("ssid", Value::from(ssid.as_bytes().to_vec()));
+    wifi.insert("mode", Value::from("infrastructure"));
+    if hidden.unwrap_or(false) { wifi.insert("hidden", Value::from(true)); }
+
+    let mut settings: HashMap<&str, HashMap<&str, Value>> = HashMap::new();
+    if let Some(e) = eap {
+        let mut sec: HashMap<&str, Value> = HashMap::new();
+        sec.insert("key-mgmt", Value::from("wpa-eap"));
+        let mut x: HashMap<&str, Value> = HashMap::new();
+        let method = e.method.unwrap_or_else(|| "peap".into()).to_lowercase();
+        if !matches!(method.as_str(), "peap" | "ttls") { return Err("unsupported-eap-method".into()); }
+        x.insert("eap", Value::from(vec![method]));
+        x.insert("identity", Value::from(e.identity));
+        x.insert("password", Value::from(e.password));
+        x.insert("phase2-auth", Value::from(e.phase2.unwrap_or_else(|| "mschapv2".into())));
+        if let Some(a) = e.anonymous_identity.filter(|s| !s.is_empty()) { x.insert("anonymous-identity", Value::from(a)); }
+        if let Some(ca) = e.ca_cert.filter(|s| !s.is_empty()) {
+            // NM は "file://<path>\0" のバイト列で証明書パスを受け取る
+            let mut b = format!("file://{}", ca).into_bytes();
+            b.push(0);
+            x.insert("ca-cert", Value::from(b));
+        }
+        if let Some(d) = e.domain_suffix_match.filter(|s| !s.is_empty()) { x.insert("domain-suffix-match", Value::from(d)); }
+        settings.insert("802-11-wireless-security", sec);
+        settings.insert("802-1x", x);
+    } else if let Some(psk) = password.filter(|p| !p.is_empty()) {
+        let key_mgmt = psk_key_mgmt(&conn, &dev, &ssid, &psk)?;
+        let mut sec: HashMap<&str, Value> = HashMap::new();
+        sec.insert("key-mgmt", Value::from(key_mgmt));
+        sec.insert("psk", Value::from(psk));
+        settings.insert("802-11-wireless-security", sec);
+    }
+    settings.insert("connection", connection);
+    settings.insert("802-11-wireless", wifi);
+
+    let _: (OwnedObjectPath, OwnedObjectPath) = nm.call("AddAndActivateConnection", &(settings, dev.as_ref(), root))
+        .map_err(|e| nm_err("connect-failed", e))?;
+    log_append("INFO", &format!("network: connecting to {}", ssid));
+    Ok("activating".into())
+}
+
+#[tauri::command]
+pub fn network_saved_connections() -> Result<Vec<SavedConnection>, SisError> { Ok(list_saved(&nm_bus()?)?) }
+
+/// 保存済み接続の削除（path / uuid / id / SSID いずれかで指定）
+#[tauri::command]
+pub fn network_forget(id: String) -> Result<String, SisError> {
+    let conn = nm_bus()?;
+    let targets: Vec<SavedConnection> = list_saved(&conn)?.into_iter().filter(|s| s.path == id || s.uuid == id || s.id == id || s.ssid.as_deref() == Some(id.as_str())).collect();
+    if targets.is_empty() { return Err("not-found".into()); }
+    for t in &targets {
+        let c = proxy(&conn, t.path.as_str(), IF_CONNECTION)?;
+        let _: () = c.call("Delete", &()).map_err(|e| nm_err("forget-failed", e))?;
+        log_append("INFO", &format!("network: forgot connection {}", t.id));
+    }
+    Ok("forgotten".into())
+}
+
+/// 機内モード: Wi-Fi/WWAN を NM で無効化し、Bluetooth は rfkill でブロック
+#[tauri::command]
+pub fn network_set_airplane_mode(enable: bool) -> Result<String, SisError> {
+    let conn = nm_bus()?;
+    let nm = proxy(&conn, NM_PATH, IF_NM)?;
+    nm.set_property("WirelessEnabled", !enable).map_err(|e| nm_err("airplane-failed", e.into()))?;
+    let _ = nm.set_property("WwanEnabled", !enable);
+    let _ = crate::bluetooth_set(!enable);
+    log_append("INFO", &format!("network: airplane mode {}", if enable { "on" } else { "off" }));
+    Ok(if enable { "airplane-on".into() } else { "airplane-off".into() })
+}
@@ -0,0 +1,438 @@
+// 夜間モード（ブルーライト軽減）: 色温度に応じた白色点で出力のガンマランプを書き換える。
+// X11 は RandR の CRTC ガンマ、Wayland は GNOME（gsd-color の Night Light）か wlroots 系（gammastep 経由の
+// wlr-gamma-control）に任せる。スケジュールは固定時刻か、位置（未設定ならタイムゾーンの代表地点）から
+// 計算した日の入り〜日の出。ネットワークは使わない
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{Datelike, Local, NaiveTime, Offset, Timelike};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+
+use crate::error::SisError;
+use crate::{history_dir, log_append, run_out, which};
+
+const TICK: Duration = Duration::from_secs(30);
+const NEUTRAL_K: u32 = 6500;
+const MIN_K: u32 = 1900;
+// 切り替え時刻の前後でこの時間をかけて徐々に変える（分）
+const FADE_MINUTES: f64 = 30.0;
+// これ未満の変化ではプロセス型のバックエンドを再起動しない
+const RESTART_STEP_K: u32 = 100;
+const ZONE_TAB: &str = "/usr/share/zoneinfo/zone.tab";
+const GNOME_SCHEMA: &str = "org.gnome.settings-daemon.plugins.color";
+
+static APP: Lazy<Mutex<Option<tauri::AppHandle>>> = Lazy::new(|| Mutex::new(None));
+static STATE: Lazy<Mutex<NightLightState>> = Lazy::new(|| Mutex::new(NightLightState::default()));
+// Wayland(wlroots) で色を保持している gammastep（終了するとガンマが戻る）
+static GAMMASTEP: Lazy<Mutex<Option<(Child, u32)>>> = Lazy::new(|| Mutex::new(None));
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct NightLightConfig {
+    enabled: bool,
+    temperature: u32, // 目標の色温度（K）
+    intensity: u32,   // 0-100。6500K から目標までどこまで寄せるか
+    schedule: String, // always | fixed | sunset
+    from: String,     // fixed の開始 HH:MM
+    to: String,       // fixed の終了 HH:MM
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+}
+
+impl Default for NightLightConfig {
+    fn default() -> Self {
+        NightLightConfig {
+            enabled: false,
+            temperature: 4000,
+            intensity: 100,
+            schedule: "sunset".into(),
+            from: "20:00".into(),
+            to: "06:00".into(),
+            latitude: None,
+            longitude: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct NightLightState {
+    enabled: bool,
+    active: bool,
+    schedule: String,
+    temperature: u32,
+    intensity: u32,
+    current_temperature: u32, // いま適用している色温度（フェード中は途中の値）
+    backend: Option<String>,  // randr | gnome | gammastep
+    sunset: Option<String>,   // sunset スケジュール時の今日の日の入り/日の出（HH:MM）
+    sunrise: Option<String>,
+    location_source: Option<String>, // config | timezone
+    error: Option<String>,
+}
+
+fn config_path() -> Option<PathBuf> { history_dir().map(|d| d.join("nightlight.json")) }
+
+fn read_config() -> NightLightConfig {
+    config_path().and_then(|p| fs::read_to_string(p).ok()).and_then(|s| serde_json::from_str(&s).ok()).unwrap_or_default()
+}
+
+fn write_config(c: &NightLightConfig) -> Result<(), String> {
+    let p = config_path().ok_or("no-data-dir")?;
+    if let Some(dir) = p.parent() { let _ = fs::create_dir_all(dir); }
+    fs::write(&p, serde_json::to_string_pretty(c).map_err(|e| e.to_string())?).map_err(|e| format!("write-failed: {}", e))
+}
+
+// ---- 色温度 ----
+
+/// 黒体放射の近似（Tanner Helland）を 6500K が (1,1,1) になるよう正規化した白色点
+fn whitepoint(kelvin: u32) -> (f64, f64, f64) {
+    fn raw(k: f64) -> (f64, f64, f64) {
+        let t = k / 100.0;
+        let r = if t <= 66.0 { 255.0 } else { 329.698_727_446 * (t - 60.0).powf(-0.133_204_759_2) };
+        let g = if t <= 66.0 { 99.470_802_586_1 * t.ln() - 161.119_568_166_1 } else { 288.122_169_528_3 * (t - 60.0).powf(-0.075_514_849_2) };
+        let b = if t >= 66.0 { 255.0 } else if t <= 19.0 { 0.0 } else { 138.517_731_223_1 * (t - 10.0).ln() - 305.044_792_730_7 };
+        (r.clamp(0.0, 255.0), g.clamp(0.0, 255.0), b.clamp(0.0, 255.0))
+    }
+    let (r, g, b) = raw(kelvin.clamp(MIN_K, NEUTRAL_K) as f64);
+    let (nr, ng, nb) = raw(NEUTRAL_K as f64);
+    ((r / nr).min(1.0), (g / ng).min(1.0), (b / nb).min(1.0))
+}
+
+/// 強さとスケジュールの係数（0-1）を反映した色温度
+fn effective_temperature(cfg: &NightLightConfig, factor: f64) -> u32 {
+    let target = cfg.temperature.clamp(MIN_K, NEUTRAL_K) as f64;
+    let strength = (cfg.intensity.min(100) as f64 / 100.0) * factor.clamp(0.0, 1.0);
+    (NEUTRAL_K as f64 - (NEUTRAL_K as f64 - target) * strength).round() as u32
+}
+
+// ---- スケジュール ----
+
+fn parse_hhmm(s: &str) -> Option<f64> {
+    let t = NaiveTime::parse_from_str(s.trim(), "%H:%M").ok()?;
+    Some((t.hour() * 60 + t.minute()) as f64)
+}
+
+fn fmt_minutes(m: f64) -> String {
+    let m = m.rem_euclid(1440.0).round() as u32 % 1440;
+    format!("{:02}:{:02}", m / 60, m % 60)
+}
+
+/// 一日の中の分 m が夜間（start → end、日をまたいでもよい）にどれだけ入っているか。境界の前後はフェード
+fn night_factor(m: f64, start: f64, end: f64) -> f64 {
+    let diff = |a: f64, b: f64| (a - b + 720.0).rem_euclid(1440.0) - 720.0;
+    let (ds, de) = (diff(m, start), diff(m, end));
+    if ds.abs() < FADE_MINUTES / 2.0 { return 0.5 + ds / FADE_MINUTES; }
+    if de.abs() < FADE_MINUTES / 2.0 { return 0.5 - de / FADE_MINUTES; }
+    let in_night = if start <= end { m >= start && m < end } else { m >= start || m < end };
+    if in_night { 1.0 } else { 0.0 }
+}
+
+enum SunTimes {
+    Normal { sunrise: f64, sunset: f64 }, // 現地時刻（一日の分）
+    PolarDay,
+    PolarNight,
+}
+
+/// NOAA の簡易式で日の出/日の入りを求める（大気差込みの太陽高度 -0.833°）
+fn sun_times(date: chrono::NaiveDate, lat: f64, lon: f64, utc_offset_min: f64) -> SunTimes {
+    let n = date.ordinal0() as f64;
+    let g = 2.0 * std::f64::consts::PI / 365.0 * n;
+    let eqtime = 229.18 * (0.000075 + 0.001868 * g.cos() - 0.032077 * g.sin() - 0.014615 * (2.0 * g).cos() - 0.040849 * (2.0 * g).sin());
+    let decl = 0.006918 - 0.399912 * g.cos() + 0.070257 * g.sin() - 0.006758 * (2.0 * g).cos() + 0.000907 * (2.0 * g).sin()
+        - 0.002697 * (3.0 * g).cos() + 0.00148 * (3.0 * g).sin();
+    let phi = lat.to_radians();
+    let cos_ha = 90.833_f64.to_radians().cos() / (phi.cos() * decl.cos()) - phi.tan() * decl.tan();
+    if cos_ha >= 1.0 { return SunTimes::PolarNight; }
+    if cos_ha <= -1.0 { return SunTimes::PolarDay; }
+    let ha = cos_ha.acos().to_degrees();
+    SunTimes::Normal {
+        sunrise: 720.0 - 4.0 * (lon + ha) - eqtime + utc_offset_min,
+        sunset: 720.0 - 4.0 * (lon - ha) - eqtime + utc_offset_min,
+    }
+}
+
+/// zone.tab の座標（±DDMM±DDDMM または ±DDMMSS±DDDMMSS）
+fn parse_iso6709(s: &str) -> Option<(f64, f64)> {
+    // 先頭は符号（空や壊れた行でスライスが panic しないよう先に確かめる）
+    if !s.starts_with(['+', '-']) { return None; }
+    let split = s[1..].find(['+', '-'])? + 1;
+    let part = |p: &str, deg_len: usize| -> Option<f64> {
+        let sign = if p.starts_with('-') { -1.0 } else { 1.0 };
+        let d = &p[1..];
+        let deg: f64 = d.get(..deg_len)?.parse().ok()?;
+        let min: f64 = d.get(deg_len..deg_len + 2)?.parse().ok()?;
+        let sec: f64 = d.get(deg_len + 2..deg_len + 4).and_then(|x| x.parse().ok()).unwrap_or(0.0);
+        Some(sign * (deg + min / 60.0 + sec / 3600.0))
+    };
+    Some((part(&s[..split], 2)?, part(&s[split..], 3)?))
+}
+
+/// 設定の位置、なければタイムゾーン代表地点の座標
+fn location(cfg: &NightLightConfig) -> Option<(f64, f64, &'static str)> {
+    if let (Some(lat), Some(lon)) = (cfg.latitude, cfg.longitude) { return Some((lat, lon, "config")); }
+    let tz = crate::timedate::timezone_name()?;
+    let tab = fs::read_to_string(ZONE_TAB).ok()?;
+    tab.lines().filter(|l| !l.starts_with('#')).find_map(|l| {
+        let cols: Vec<&str> = l.split('\t').collect();
+        if cols.get(2) == Some(&tz.as_str()) { parse_iso6709(cols.get(1)?).map(|(a, b)| (a, b, "timezone")) } else { None }
+    })
+}
+
+/// いまの係数（0 = 通常、1 = 目標色温度）と表示用の情報
+fn schedule_factor(cfg: &NightLightConfig, st: &mut NightLightState) -> f64 {
+    let now = Local::now();
+    let m = (now.hour() * 60 + now.minute()) as f64 + now.second() as f64 / 60.0;
+    match cfg.schedule.as_str() {
+        "fixed" => match (parse_hhmm(&cfg.from), parse_hhmm(&cfg.to)) {
+            (Some(a), Some(b)) if a != b => night_factor(m, a, b),
+            _ => 1.0,
+        },
+        "sunset" => {
+            let Some((lat, lon, source)) = location(cfg) else {
+                st.error = Some("no-location".into());
+                return 0.0;
+            };
+            st.location_source = Some(source.into());
+            let offset = now.offset().fix().local_minus_utc() as f64 / 60.0;
+            match sun_times(now.date_naive(), lat, lon, offset) {
+                SunTimes::Normal { sunrise, sunset } => {
+                    st.sunrise = Some(fmt_minutes(sunrise));
+                    st.sunset = Some(fmt_minutes(sunset));
+                    night_factor(m, sunset.rem_euclid(1440.0), sunrise.rem_euclid(1440.0))
+                }
+                SunTimes::PolarDay => 0.0,
+                SunTimes::PolarNight => 1.0,
+            }
+        }
+        _ => 1.0,
+    }
+}
+
+// ---- バックエンド ----
+
+fn is_gnome() -> bool {
+    std::env::var("XDG_CURRENT_DESKTOP").map(|d| d.to_uppercase().contains("GNOME")).unwrap_or(false)
+        && run_out("gsettings", &["list-keys", GNOME_SCHEMA]).is_some_and(|k| k.contains("night-light-temperature"))
+}
+
+fn backend() -> Option<&'static str> {
+    if !crate::screenshot::is_wayland() {
+        return if std::env::var("DISPLAY").is_ok() { Some("randr") } else { None };
+    }
+    if is_gnome() { Some("gnome") } else if which("gammastep") { Some("gammastep") } else { None }
+}
+
+/// 全 CRTC のガンマランプを白色点で線形に縮める（6500K で元の直線に戻る）
+fn apply_randr(kelvin: u32) -> Result<(), String> {
+    use x11rb::connection::Connection as _;
+    use x11rb::protocol::randr::ConnectionExt as _;
+    let (conn, screen) = x11rb::connect(None).map_err(|e| format!("x11-connect-failed: {}", e))?;
+    let root = conn.setup().roots.get(screen).ok_or("x11-no-screen")?.root;
+    let res = conn.randr_get_screen_resources_current(root).map_err(|e| e.to_string())?.reply().map_err(|e| format!("randr-unavailable: {}", e))?;
+    let (wr, wg, wb) = whitepoint(kelvin);
+    for crtc in res.crtcs {
+        let Ok(size) = conn.randr_get_crtc_gamma_size(crtc).map_err(|e| e.to_string()).and_then(|c| c.reply().map_err(|e| e.to_string())) else { continue };
+        let n = size.size as usize;
+        if n < 2 { continue; }
+        let ramp = |w: f64| -> Vec<u16> { (0..n).map(|i| (i as f64 / (n - 1) as f64 * w * 65535.0).round() as u16).collect() };
+        conn.randr_set_crtc_gamma(crtc, &ramp(wr), &ramp(wg), &ramp(wb)).map_err(|e| e.to_string())?;
+    }
+    conn.flush().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// GNOME の Night Light を常時有効にして色温度だけ渡す（スケジュールは SIS 側で回す）
+fn apply_gnome(kelvin: u32) -> Result<(), String> {
+    let on = kelvin < NEUTRAL_K;
+    let set = |k: &str, v: &str| Command::new("gsettings").args(["set", GNOME_SCHEMA, k, v]).status().map(|s| s.success()).unwrap_or(false);
+    if on {
+        set("night-light-schedule-automatic", "false");
+        set("night-light-schedule-from", "0.0");
+        set("night-light-schedule-to", "0.0");
+        set("night-light-temperature", &format!("uint32 {}", kelvin));
+    }
+    if set("night-light-enabled", if on { "true" } else { "false" }) { Ok(()) } else { Err("gsettings-failed".into()) }
+}
+
+fn apply_gammastep(kelvin: u32) -> Result<(), String> {
+    let mut cur = GAMMASTEP.lock().unwrap();
+    if let Some((child, k)) = cur.as_mut() {
+        let alive = matches!(child.try_wait(), Ok(None));
+        if alive && (*k).abs_diff(kelvin) < RESTART_STEP_K && kelvin != NEUTRAL_K { return Ok(()); }
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+    *cur = None;
+    if kelvin >= NEUTRAL_K { return Ok(()); }
+    // -O は一回限りの設定。Wayland では終了するとガンマが戻るので常駐させておく
+    let child = Command::new("gammastep")
+        .args(["-m", "wayland", "-P", "-O", &kelvin.to_string()])
+        .stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null())
+        .spawn().map_err(|e| format!("gammastep-failed: {}", e))?;
+    *cur = Some((child, kelvin));
+    Ok(())
+}
+
+fn apply_temperature(backend: &str, kelvin: u32) -> Result<(), String> {
+    match backend {
+        "randr" => apply_randr(kelvin),
+        "gnome" => apply_gnome(kelvin),
+        "gammastep" => apply_gammastep(kelvin),
+        _ => Err("no-backend".into()),
+    }
+}
+
+// ---- 状態 ----
+
+/// 設定とスケジュールから色温度を決めて適用し、変化があれば sis:nightlight-state を送る
+fn refresh() -> NightLightState {
+    let cfg = read_config();
+    let mut st = NightLightState {
+        enabled: cfg.enabled,
+        schedule: cfg.schedule.clone(),
+        temperature: cfg.temperature,
+        intensity: cfg.intensity,
+        backend: backend().map(|b| b.to_string()),
+        ..Default::default()
+    };
+    let factor = if cfg.enabled { schedule_factor(&cfg, &mut st) } else { 0.0 };
+    st.current_temperature = effective_temperature(&cfg, factor);
+    st.active = st.current_temperature < NEUTRAL_K;
+    let prev = STATE.lock().unwrap().clone();
+    match st.backend.clone() {
+        // RandR は他のクライアントに戻されることがあるので有効な間は毎回書き直す
+        Some(b) if (b == "randr" && st.active) || prev.current_temperature != st.current_temperature || prev.backend != st.backend => {
+            if let Err(e) = apply_temperature(&b, st.current_temperature) {
+                if prev.error.as_deref() != Some(e.as_str()) { log_append("WARN", &format!("nightlight: {}", e)); }
+                st.error = Some(e);
+            }
+        }
+        Some(_) => {}
+        None => st.error = Some("no-backend".into()),
+    }
+    if st != prev {
+        *STATE.lock().unwrap() = st.clone();
+        if let Some(app) = APP.lock().unwrap().as_ref() { let _ = app.emit("sis:nightlight-state", &st); }
+    }
+    st
+}
+
+pub fn state() -> NightLightState { STATE.lock().unwrap().clone() }
+
+pub fn start(app_handle: tauri::AppHandle) {
+    *APP.lock().unwrap() = Some(app_handle);
+    std::thread::spawn(|| loop {
+        refresh();
+        std::thread::sleep(TICK);
+    });
+}
+
+#[tauri::command]
+pub fn nightlight_state() -> Result<NightLightState, SisError> { Ok(refresh()) }
+
+#[tauri::command]
+pub fn nightlight_get_config() -> Result<NightLightConfig, SisError> { Ok(read_config()) }
+
+#[tauri::command]
+pub fn nightlight_set_config(config: NightLightConfig) -> Result<NightLightState, SisError> {
+    if !matches!(config.schedule.as_str(), "always" | "fixed" | "sunset") { return Err("invalid-schedule".into()); }
+    if config.schedule == "fixed" && (parse_hhmm(&config.from).is_none() || parse_hhmm(&config.to).is_none()) { return Err("invalid-time".into()); }
+    if config.latitude.is_some_and(|v| !(-90.0..=90.0).contains(&v)) || config.longitude.is_some_and(|v| !(-180.0..=180.0).contains(&v)) {
+        return Err("invalid-location".into());
+    }
+    let mut c = config;
+    c.temperature = c.temperature.clamp(MIN_K, NEUTRAL_K);
+    c.intensity = c.intensity.min(100);
+    write_config(&c)?;
+    Ok(refresh())
+}
+
+/// コントロールセンター用の簡易設定（指定した項目だけ変える）
+#[tauri::command]
+pub fn nightlight_set(enabled: Option<bool>, temperature: Option<u32>, intensity: Option<u32>) -> Result<NightLightState, SisError> {
+    let mut c = read_config();
+    if let Some(v) = enabled { c.enabled = v; }
+    if let Some(v) = temperature { c.temperature = v.clamp(MIN_K, NEUTRAL_K); }
+    if let Some(v) = intensity { c.intensity = v.min(100); }
+    write_config(&c)?;
+    Ok(refresh())
+}
+
+/// 日の入り/日の出の確認用（設定画面で位置を入れたときの表示）
+#[tauri::command]
+pub fn nightlight_sun_times(latitude: Option<f64>, longitude: Option<f64>) -> Result<(String, String), SisError> {
+    let cfg = NightLightConfig { latitude, longitude, ..read_config() };
+    let (lat, lon, _) = location(&cfg).ok_or("no-location")?;
+    let now = Local::now();
+    let offset = now.offset().fix().local_minus_utc() as f64 / 60.0;
+    match sun_times(now.date_naive(), lat, lon, offset) {
+        SunTimes::Normal { sunrise, sunset } => Ok((fmt_minutes(sunset), fmt_minutes(sunrise))),
+        SunTimes::PolarDay => Err("polar-day".into()),
+        SunTimes::PolarNight => Err("polar-night".into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64, eps: f64) -> bool { (a - b).abs() <= eps }
+
+    #[test]
+    fn whitepoint_is_neutral_at_6500k_and_warm_below() {
+        let (r, g, b) = whitepoint(NEUTRAL_K);
+        assert!(close(r, 1.0, 1e-9) && close(g, 1.0, 1e-9) && close(b, 1.0, 1e-9));
+        let (r, g, b) = whitepoint(3000);
+        assert!(close(r, 1.0, 1e-9));
+        assert!(b < g && g < 1.0);
+        // 範囲外は端に寄せる
+        assert_eq!(whitepoint(500), whitepoint(MIN_K));
+        assert_eq!(whitepoint(10_000), whitepoint(NEUTRAL_K));
+    }
+
+    #[test]
+    fn night_factor_handles_midnight_and_fades() {
+        let (start, end) = (22.0 * 60.0, 7.0 * 60.0);
+        assert_eq!(night_factor(0.0, start, end), 1.0);
+        assert_eq!(night_factor(12.0 * 60.0, start, end), 0.0);
+        assert!(close(night_factor(start, start, end), 0.5, 1e-9));
+        assert!(close(night_factor(end, start, end), 0.5, 1e-9));
+        let f = night_factor(start + FADE_MINUTES / 4.0, start, end);
+        assert!(f > 0.5 && f < 1.0);
+        // 日をまたがない夜間
+        assert_eq!(night_factor(3.0 * 60.0, 60.0, 5.0 * 60.0), 1.0);
+        assert_eq!(night_factor(23.0 * 60.0, 60.0, 5.0 * 60.0), 0.0);
+    }
+
+    #[test]
+    fn sun_times_tokyo_summer_solstice() {
+        let date = chrono::NaiveDate::from_ymd_opt(2024, 6, 21).unwrap();
+        let SunTimes::Normal { sunrise, sunset } = sun_times(date, 35.68, 139.69, 540.0) else { panic!("expected sunrise/sunset") };
+        // 東京の夏至はおよそ 4:25 / 19:00
+        assert!(close(sunrise, 4.0 * 60.0 + 25.0, 10.0), "sunrise {}", fmt_minutes(sunrise));
+        assert!(close(sunset, 19.0 * 60.0, 10.0), "sunset {}", fmt_minutes(sunset));
+    }
+
+    #[test]
+    fn sun_times_polar_day_and_night() {
+        let june = chrono::NaiveDate::from_ymd_opt(2024, 6, 21).unwrap();
+        let december = chrono::NaiveDate::from_ymd_opt(2024, 12, 21).unwrap();
+        assert!(matches!(sun_times(june, 78.2, 15.6, 120.0), SunTimes::PolarDay));
+        assert!(matches!(sun_times(december, 78.2, 15.6, 60.0), SunTimes::PolarNight));
+    }
+
+    #[test]
+    fn parse_iso6709_reads_minutes_and_seconds() {
+        let (lat, lon) = parse_iso6709("+3541+13942").unwrap();
+        assert!(close(lat, 35.0 + 41.0 / 60.0, 1e-9) && close(lon, 139.0 + 42.0 / 60.0, 1e-9));
+        let (lat, lon) = parse_iso6709("-334520+1511230").unwrap();
+        assert!(close(lat, -(33.0 + 45.0 / 60.0 + 20.0 / 3600.0), 1e-9) && close(lon, 151.0 + 12.0 / 60.0 + 30.0 / 3600.0, 1e-9));
+        assert_eq!(parse_iso6709(""), None);
+        assert_eq!(parse_iso6709("+"), None);
+        assert_eq!(parse_iso6709("3541+13942"), None);
+        assert_eq!(parse_iso6709("あ+3541"), None);
+    }
+}
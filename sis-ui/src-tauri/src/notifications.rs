@@ -0,0 +1,485 @@
+// 通知サーバー(org.freedesktop.Notifications): アプリからの通知を受け取り SIS のウィンドウで表示する
+// 履歴の永続化・おやすみモード（既定は授業時間帯）・SIS 内部からの投稿 API も提供する
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use base64::Engine;
+use chrono::{Datelike, Timelike};
+use image::{DynamicImage, ImageBuffer, ImageFormat, Rgba};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+use zbus::blocking::Connection;
+use zbus::names::BusName;
+use zbus::zvariant::OwnedValue;
+
+use crate::error::SisError;
+use crate::{history_dir, log_append, resolve_icon_path, to_data_url};
+
+const BUS_NAME: &str = "org.freedesktop.Notifications";
+const OBJ_PATH: &str = "/org/freedesktop/Notifications";
+const HISTORY_MAX: usize = 200;
+const DEFAULT_TIMEOUT_MS: u32 = 6000;
+const TICK: Duration = Duration::from_millis(250);
+// image-data ヒントで受け付ける一辺の上限（通知アイコンとしては十分）
+const MAX_IMAGE_SIDE: i32 = 1024;
+const SIS_CONF: &str = "/etc/sis/sis.conf";
+const DEFAULT_CLASS_START: &str = "0830";
+const DEFAULT_CLASS_END: &str = "1630";
+
+// NotificationClosed の reason
+const CLOSE_EXPIRED: u32 = 1;
+const CLOSE_DISMISSED: u32 = 2;
+const CLOSE_CALL: u32 = 3;
+
+static NEXT_ID: AtomicU32 = AtomicU32::new(1);
+static HISTORY: Lazy<Mutex<Vec<Notification>>> = Lazy::new(|| Mutex::new(load_history()));
+// 表示中の通知 id → 期限(ms, None は無期限)
+static ACTIVE: Lazy<Mutex<HashMap<u32, Option<i64>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+static APP: Lazy<Mutex<Option<tauri::AppHandle>>> = Lazy::new(|| Mutex::new(None));
+static CONN: Lazy<Mutex<Option<Connection>>> = Lazy::new(|| Mutex::new(None));
+// SIS 内部の通知のアクションをバックエンドで処理するもの（id → 処理）。一度実行したら捨てる
+type ActionHandler = Box<dyn FnOnce(&str) + Send>;
+static HANDLERS: Lazy<Mutex<HashMap<u32, ActionHandler>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct NotifAction {
+    key: String,
+    label: String,
+}
+
+impl NotifAction {
+    pub fn new(key: &str, label: &str) -> Self { Self { key: key.into(), label: label.into() } }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Notification {
+    id: u32,
+    app_name: String,
+    icon: Option<String>, // data: URL
+    summary: String,
+    body: String,
+    actions: Vec<NotifAction>,
+    urgency: String, // low | normal | critical
+    category: Option<String>,
+    desktop_entry: Option<String>,
+    timestamp: i64,          // unix ms
+    expire_ms: Option<u32>,  // None は自動で閉じない
+    resident: bool,          // アクション後も閉じない
+    transient: bool,         // 履歴に残さない
+    suppressed: bool,        // おやすみモードで表示を抑止した
+    read: bool,
+    source: String, // dbus | sis
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DndConfig {
+    mode: String, // off | on | schedule
+    start_hhmm: String,
+    end_hhmm: String,
+    weekdays_only: bool,
+    allow_critical: bool,
+}
+
+impl Default for DndConfig {
+    fn default() -> Self {
+        Self {
+            mode: "schedule".into(),
+            start_hhmm: class_hhmm("SIS_CLASS_START_HHMM").unwrap_or_else(|| DEFAULT_CLASS_START.into()),
+            end_hhmm: class_hhmm("SIS_CLASS_END_HHMM").unwrap_or_else(|| DEFAULT_CLASS_END.into()),
+            weekdays_only: true,
+            allow_critical: true,
+        }
+    }
+}
+
+fn now_ms() -> i64 { chrono::Utc::now().timestamp_millis() }
+
+// 環境変数 → /etc/sis/sis.conf（VAR="${VAR:-0830}" 形式）の順で授業時間を読む
+fn class_hhmm(var: &str) -> Option<String> {
+    let valid = |s: &str| s.len() == 4 && s.chars().all(|c| c.is_ascii_digit());
+    if let Ok(v) = std::env::var(var) { if valid(v.trim()) { return Some(v.trim().to_string()); } }
+    let conf = fs::read_to_string(SIS_CONF).ok()?;
+    let line = conf.lines().map(|l| l.trim()).find(|l| l.starts_with(&format!("{}=", var)))?;
+    let val = line.split_once('=')?.1.trim_matches('"');
+    let val = val.rsplit_once(":-").map(|(_, d)| d.trim_end_matches('}')).unwrap_or(val);
+    valid(val).then(|| val.to_string())
+}
+
+/// "HH:MM" または "HHMM" を 0 時からの分に変換する
+fn hhmm_minutes(s: &str) -> Option<u32> {
+    let (h, m) = match s.split_once(':') {
+        Some(p) => p,
+        None if s.len() == 4 && s.is_ascii() => s.split_at(2),
+        None => return None,
+    };
+    let two_digits = |v: &str| v.len() == 2 && v.bytes().all(|b| b.is_ascii_digit());
+    if !two_digits(h) || !two_digits(m) { return None; }
+    let (h, m): (u32, u32) = (h.parse().ok()?, m.parse().ok()?);
+    (h < 24 && m < 60).then_some(h * 60 + m)
+}
+
+fn dnd_path() -> Option<PathBuf> { history_dir().map(|d| d.join("dnd.json")) }
+fn history_file() -> Option<PathBuf> { history_dir().map(|d| d.join("notifications.json")) }
+
+fn read_dnd() -> DndConfig {
+    dnd_path().and_then(|p| fs::read_to_string(p).ok()).and_then(|s| serde_json::from_str(&s).ok()).unwrap_or_default()
+}
+
+pub fn dnd_active() -> bool {
+    let c = read_dnd();
+    match c.mode.as_str() {
+        "on" => true,
+        "schedule" => {
+            let now = chrono::Local::now();
+            if c.weekdays_only && now.weekday().number_from_monday() > 5 { return false; }
+            let (Some(s), Some(e)) = (hhmm_minutes(&c.start_hhmm), hhmm_minutes(&c.end_hhmm)) else { return false };
+            let m = now.hour() * 60 + now.minute();
+            // 日付をまたぐ設定（例: 2200-0700）にも対応
+            if s <= e { m >= s && m < e } else { m >= s || m < e }
+        }
+        _ => false,
+    }
+}
+
+fn load_history() -> Vec<Notification> {
+    let list: Vec<Notification> = history_file().and_then(|p| fs::read_to_string(p).ok()).and_then(|s| serde_json::from_str(&s).ok()).unwrap_or_default();
+    // 再起動後も id が重複しないように続きから振る
+    if let Some(max) = list.iter().map(|n| n.id).max() { NEXT_ID.store(max + 1, Ordering::SeqCst); }
+    list
+}
+
+fn save_history(list: &[Notification]) {
+    if let Some(p) = history_file() {
+        if let Some(dir) = p.parent() { let _ = fs::create_dir_all(dir); }
+        if let Ok(s) = serde_json::to_string(list) { let _ = fs::write(p, s); }
+    }
+}
+
+fn emit<S: Serialize + Clone>(event: &str, payload: S) {
+    if let Some(app) = APP.lock().unwrap().as_ref() { let _ = app.emit(event, payload); }
+}
+
+fn emit_dbus_signal(name: &str, body: &(u32, impl Serialize + zbus::zvariant::Type)) {
+    if let Some(conn) = CONN.lock().unwrap().as_ref() {
+        let _ = conn.emit_signal(None::<BusName>, OBJ_PATH, BUS_NAME, name, body);
+    }
+}
+
+// image-data ヒント (iiibiiay) を RGBA に展開する。値は送り手まかせなので、寸法と長さを先に全部確かめる
+fn image_data_rgba(w: i32, h: i32, rowstride: i32, has_alpha: bool, bits: i32, channels: i32, data: &[u8]) -> Option<Vec<u8>> {
+    if bits != 8 || w <= 0 || h <= 0 || w > MAX_IMAGE_SIDE || h > MAX_IMAGE_SIDE { return None; }
+    if channels < 3 || (has_alpha && channels < 4) { return None; }
+    let (w, h, rowstride, channels) = (w as usize, h as usize, usize::try_from(rowstride).ok()?, channels as usize);
+    if rowstride < w.checked_mul(channels)? { return None; }
+    // 最終行は詰め物が無いことがある（GdkPixbuf）ので、最後の画素までが収まっていればよい
+    let need = rowstride.checked_mul(h - 1)?.checked_add(w * channels)?;
+    if need > data.len() { return None; }
+    let mut rgba = Vec::with_capacity(w * h * 4);
+    for y in 0..h {
+        for x in 0..w {
+            let i = y * rowstride + x * channels;
+            let px = &data[i..i + channels];
+            rgba.extend_from_slice(&[px[0], px[1], px[2], if has_alpha { px[3] } else { 255 }]);
+        }
+    }
+    Some(rgba)
+}
+
+// image-data ヒント (iiibiiay) を PNG の data: URL に変換
+fn image_data_url(v: &OwnedValue) -> Option<String> {
+    let (w, h, rowstride, has_alpha, bits, channels, data) =
+        <(i32, i32, i32, bool, i32, i32, Vec<u8>)>::try_from(v.try_clone().ok()?).ok()?;
+    let rgba = image_data_rgba(w, h, rowstride, has_alpha, bits, channels, &data)?;
+    let buf: ImageBuffer<Rgba<u8>, Vec<u8>> = ImageBuffer::from_vec(w as u32, h as u32, rgba)?;
+    let mut cursor = std::io::Cursor::new(Vec::<u8>::new());
+    DynamicImage::ImageRgba8(buf).write_to(&mut cursor, ImageFormat::Png).ok()?;
+    Some(format!("data:image/png;base64,{}", base64::engine::general_purpose::STANDARD.encode(cursor.into_inner())))
+}
+
+fn icon_url(raw: &str) -> Option<String> {
+    let raw = raw.strip_prefix("file://").unwrap_or(raw);
+    if raw.trim().is_empty() { return None; }
+    resolve_icon_path(raw).and_then(|p| to_data_url(&p))
+}
+
+fn hint_str(hints: &HashMap<String, OwnedValue>, key: &str) -> Option<String> {
+    hints.get(key)?.try_clone().ok().and_then(|v| String::try_from(v).ok()).filter(|s| !s.is_empty())
+}
+
+fn hint_bool(hints: &HashMap<String, OwnedValue>, key: &str) -> bool {
+    hints.get(key).and_then(|v| v.try_clone().ok()).and_then(|v| bool::try_from(v).ok()).unwrap_or(false)
+}
+
+/// 表示・履歴・期限管理の共通処理。replaces_id が表示中なら置き換える
+fn submit(mut n: Notification, replaces_id: u32) -> u32 {
+    n.id = if replaces_id != 0 && ACTIVE.lock().unwrap().contains_key(&replaces_id) { replaces_id } else { NEXT_ID.fetch_add(1, Ordering::SeqCst) };
+    let dnd = dnd_active();
+    // ロック中はポップアップを出さない（内容が画面に出てしまうため）
+    n.suppressed = (dnd && !(n.urgency == "critical" && read_dnd().allow_critical)) || crate::lockscreen::is_locked();
+    if !n.suppressed {
+        let deadline = n.expire_ms.map(|ms| now_ms() + ms as i64);
+        ACTIVE.lock().unwrap().insert(n.id, deadline);
+    }
+    if !n.transient {
+        let mut h = HISTORY.lock().unwrap();
+        h.retain(|x| x.id != n.id);
+        h.insert(0, n.clone());
+        h.truncate(HISTORY_MAX);
+        save_history(&h);
+    }
+    emit("sis:notification", &n);
+    n.id
+}
+
+fn close(id: u32, reason: u32) -> bool {
+    if ACTIVE.lock().unwrap().remove(&id).is_none() { return false; }
+    emit("sis:notification-closed", serde_json::json!({ "id": id, "reason": reason }));
+    emit_dbus_signal("NotificationClosed", &(id, reason));
+    true
+}
+
+struct Server;
+
+#[zbus::interface(name = "org.freedesktop.Notifications")]
+impl Server {
+    fn get_capabilities(&self) -> Vec<String> {
+        ["actions", "body", "icon-static", "persistence"].iter().map(|s| s.to_string()).collect()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn notify(&self, app_name: String, replaces_id: u32, app_icon: String, summary: String, body: String,
+              actions: Vec<String>, hints: HashMap<String, OwnedValue>, expire_timeout: i32) -> u32 {
+        let urgency = hints.get("urgency").and_then(|v| v.try_clone().ok()).and_then(|v| u8::try_from(v).ok()).unwrap_or(1);
+        let urgency = match urgency { 0 => "low", 2 => "critical", _ => "normal" }.to_string();
+        let desktop_entry = hint_str(&hints, "desktop-entry");
+        let icon = ["image-data", "image_data", "icon_data"].iter().find_map(|k| hints.get(*k).and_then(image_data_url))
+            .or_else(|| ["image-path", "image_path"].iter().find_map(|k| hint_str(&hints, k)).and_then(|p| icon_url(&p)))
+            .or_else(|| icon_url(&app_icon))
+            .or_else(|| desktop_entry.as_deref().and_then(icon_url));
+        // -1 はサーバー既定、0 は無期限。critical は仕様上自動で閉じない
+        let expire_ms = match expire_timeout {
+            0 => None,
+            t if t > 0 => Some(t as u32),
+            _ if urgency == "critical" => None,
+            _ => Some(DEFAULT_TIMEOUT_MS),
+        };
+        let n = Notification {
+            id: 0,
+            app_name,
+            icon,
+            summary,
+            body,
+            actions: actions.chunks(2).filter(|c| c.len() == 2).map(|c| NotifAction { key: c[0].clone(), label: c[1].clone() }).collect(),
+            urgency,
+            category: hint_str(&hints, "category"),
+            desktop_entry,
+            timestamp: now_ms(),
+            expire_ms,
+            resident: hint_bool(&hints, "resident"),
+            transient: hint_bool(&hints, "transient"),
+            suppressed: false,
+            read: false,
+            source: "dbus".into(),
+        };
+        submit(n, replaces_id)
+    }
+
+    fn close_notification(&self, id: u32) {
+        close(id, CLOSE_CALL);
+    }
+
+    fn get_server_information(&self) -> (String, String, String, String) {
+        ("SIS".into(), "SIS".into(), env!("CARGO_PKG_VERSION").into(), "1.2".into())
+    }
+}
+
+pub fn start(app_handle: tauri::AppHandle) {
+    *APP.lock().unwrap() = Some(app_handle.clone());
+    Lazy::force(&HISTORY);
+    let built = zbus::blocking::connection::Builder::session()
+        .and_then(|b| b.name(BUS_NAME))
+        .and_then(|b| b.serve_at(OBJ_PATH, Server))
+        .and_then(|b| b.build());
+    match built {
+        Ok(c) => { *CONN.lock().unwrap() = Some(c); log_append("INFO", "notifications: serving org.freedesktop.Notifications"); }
+        // 別の通知デーモン（GNOME Shell 等）が名前を持っている場合は SIS 内部の通知のみ扱う
+        Err(e) => log_append("WARN", &format!("notifications: could not own {} ({}); internal notifications only", BUS_NAME, e)),
+    }
+    std::thread::spawn(move || {
+        let mut last_dnd = dnd_active();
+        let mut n = 0u32;
+        loop {
+            let now = now_ms();
+            let expired: Vec<u32> = ACTIVE.lock().unwrap().iter().filter(|(_, d)| d.map(|t| t <= now).unwrap_or(false)).map(|(id, _)| *id).collect();
+            for id in expired { close(id, CLOSE_EXPIRED); }
+            // おやすみモードの切り替わりは 15 秒ごとに確認
+            n = n.wrapping_add(1);
+            if n % 60 == 0 {
+                let cur = dnd_active();
+                if cur != last_dnd {
+                    log_append("INFO", &format!("notifications: do-not-disturb -> {}", cur));
+                    let _ = app_handle.emit("sis:dnd-changed", serde_json::json!({ "active": cur }));
+                    last_dnd = cur;
+                }
+            }
+            std::thread::sleep(TICK);
+        }
+    });
+}
+
+/// SIS 内部（電源・ストレージ等）から通知を出す
+pub fn post(app_name: &str, summary: &str, body: &str, icon: Option<&str>, urgency: &str, actions: Vec<NotifAction>, timeout_ms: Option<u32>) -> u32 {
+    let n = Notification {
+        id: 0,
+        app_name: app_name.to_string(),
+        icon: icon.and_then(|i| if i.starts_with("data:") { Some(i.to_string()) } else { icon_url(i) }),
+        summary: summary.to_string(),
+        body: body.to_string(),
+        actions,
+        urgency: match urgency { "low" | "critical" => urgency.to_string(), _ => "normal".into() },
+        category: None,
+        desktop_entry: None,
+        timestamp: now_ms(),
+        expire_ms: if urgency == "critical" { timeout_ms } else { Some(timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS)) },
+        resident: false,
+        transient: false,
+        suppressed: false,
+        read: false,
+        source: "sis".into(),
+    };
+    submit(n, 0)
+}
+
+/// post で出した通知のアクション（"default" を含む）をバックエンドで受け取る
+pub fn on_action(id: u32, handler: impl FnOnce(&str) + Send + 'static) {
+    let mut h = HANDLERS.lock().unwrap();
+    // 履歴から消えた通知の分は捨てる
+    if h.len() >= HISTORY_MAX {
+        let keep: Vec<u32> = HISTORY.lock().unwrap().iter().map(|n| n.id).collect();
+        h.retain(|k, _| keep.contains(k));
+    }
+    h.insert(id, Box::new(handler));
+}
+
+#[tauri::command]
+pub fn notify_post(summary: String, body: Option<String>, app_name: Option<String>, icon: Option<String>, urgency: Option<String>, actions: Option<Vec<NotifAction>>, timeout_ms: Option<u32>) -> Result<u32, SisError> {
+    if summary.trim().is_empty() { return Err("empty-summary".into()); }
+    Ok(post(app_name.as_deref().unwrap_or("SIS"), &summary, body.as_deref().unwrap_or(""), icon.as_deref(), urgency.as_deref().unwrap_or("normal"), actions.unwrap_or_default(), timeout_ms))
+}
+
+#[tauri::command]
+pub fn notify_close(id: u32) -> Result<String, SisError> {
+    if close(id, CLOSE_DISMISSED) { Ok("closed".into()) } else { Err("not-found".into()) }
+}
+
+/// アクションの実行（"default" は本文クリック）。アプリへ ActionInvoked を送り、resident でなければ閉じる
+#[tauri::command]
+pub fn notify_invoke_action(id: u32, action_key: String) -> Result<String, SisError> {
+    let n = HISTORY.lock().unwrap().iter().find(|n| n.id == id).cloned();
+    let resident = n.as_ref().map(|n| n.resident).unwrap_or(false);
+    if n.as_ref().map(|n| n.source == "sis").unwrap_or(false) {
+        let handler = HANDLERS.lock().unwrap().remove(&id);
+        if let Some(f) = handler { f(&action_key); }
+        emit("sis:notification-action", serde_json::json!({ "id": id, "action": action_key }));
+    } else {
+        emit_dbus_signal("ActionInvoked", &(id, action_key.as_str()));
+    }
+    if !resident { close(id, CLOSE_DISMISSED); }
+    let _ = notify_mark_read(Some(id));
+    Ok("invoked".into())
+}
+
+#[tauri::command]
+pub fn notify_history(limit: Option<usize>) -> Result<Vec<Notification>, SisError> {
+    let h = HISTORY.lock().unwrap();
+    Ok(h.iter().take(limit.unwrap_or(HISTORY_MAX)).cloned().collect())
+}
+
+/// id 省略ですべて既読
+#[tauri::command]
+pub fn notify_mark_read(id: Option<u32>) -> Result<String, SisError> {
+    let mut h = HISTORY.lock().unwrap();
+    for n in h.iter_mut().filter(|n| id.map(|i| n.id == i).unwrap_or(true)) { n.read = true; }
+    save_history(&h);
+    Ok("marked".into())
+}
+
+/// id 省略で履歴をすべて削除
+#[tauri::command]
+pub fn notify_clear_history(id: Option<u32>) -> Result<String, SisError> {
+    let mut h = HISTORY.lock().unwrap();
+    match id { Some(i) => h.retain(|n| n.id != i), None => h.clear() }
+    save_history(&h);
+    Ok("cleared".into())
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DndStatus {
+    config: DndConfig,
+    active: bool,
+}
+
+#[tauri::command]
+pub fn notify_get_dnd() -> Result<DndStatus, SisError> {
+    Ok(DndStatus { config: read_dnd(), active: dnd_active() })
+}
+
+#[tauri::command]
+pub fn notify_set_dnd(app_handle: tauri::AppHandle, config: DndConfig) -> Result<DndStatus, SisError> {
+    if !matches!(config.mode.as_str(), "off" | "on" | "schedule") { return Err("invalid-mode".into()); }
+    if hhmm_minutes(&config.start_hhmm).is_none() || hhmm_minutes(&config.end_hhmm).is_none() { return Err("invalid-time".into()); }
+    let p = dnd_path().ok_or("no-home")?;
+    if let Some(dir) = Path::new(&p).parent() { let _ = fs::create_dir_all(dir); }
+    let s = serde_json::to_string_pretty(&config).map_err(|e| e.to_string())?;
+    fs::write(&p, s).map_err(|e| format!("write-failed: {}", e))?;
+    let active = dnd_active();
+    let _ = app_handle.emit("sis:dnd-changed", serde_json::json!({ "active": active }));
+    Ok(DndStatus { config, active })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hhmm_minutes_accepts_both_forms() {
+        assert_eq!(hhmm_minutes("00:00"), Some(0));
+        assert_eq!(hhmm_minutes("22:30"), Some(22 * 60 + 30));
+        assert_eq!(hhmm_minutes("0730"), Some(7 * 60 + 30));
+        assert_eq!(hhmm_minutes("23:59"), Some(23 * 60 + 59));
+    }
+
+    #[test]
+    fn hhmm_minutes_rejects_malformed_input() {
+        for s in ["", "24:00", "12:60", "7:30", "07:3", "+1:30", "12345", "１２:００", "あいう", "ab:cd", "07:30:00"] {
+            assert_eq!(hhmm_minutes(s), None, "{:?}", s);
+        }
+    }
+
+    #[test]
+    fn image_data_rgba_converts_rgb_and_rgba() {
+        // RGB 2x2、rowstride 8（各行 2 バイトの詰め物、最終行は詰め物なし）
+        let rgb = [1, 2, 3, 4, 5, 6, 0, 0, 7, 8, 9, 10, 11, 12];
+        assert_eq!(image_data_rgba(2, 2, 8, false, 8, 3, &rgb).unwrap(),
+            vec![1, 2, 3, 255, 4, 5, 6, 255, 7, 8, 9, 255, 10, 11, 12, 255]);
+        let rgba = [1, 2, 3, 4, 5, 6, 7, 8];
+        assert_eq!(image_data_rgba(2, 1, 8, true, 8, 4, &rgba).unwrap(), rgba.to_vec());
+    }
+
+    #[test]
+    fn image_data_rgba_rejects_inconsistent_headers() {
+        let data = [0u8; 64];
+        assert_eq!(image_data_rgba(2, 2, 8, true, 8, 3, &data), None); // alpha なのに 3 チャンネル
+        assert_eq!(image_data_rgba(2, 2, 8, false, 16, 3, &data), None);
+        assert_eq!(image_data_rgba(2, 2, 5, false, 8, 3, &data), None); // rowstride が 1 行より短い
+        assert_eq!(image_data_rgba(2, 2, -8, false, 8, 3, &data), None);
+        assert_eq!(image_data_rgba(0, 2, 8, false, 8, 3, &data), None);
+        assert_eq!(image_data_rgba(MAX_IMAGE_SIDE + 1, 1, 4 * (MAX_IMAGE_SIDE + 1), true, 8, 4, &data), None);
+        assert_eq!(image_data_rgba(2, 2, 8, false, 8, 3, &data[..13]), None); // 最後の画素が足りない
+    }
+}
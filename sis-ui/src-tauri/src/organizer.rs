@@ -0,0 +1,584 @@
+// ファイル整理: organizer.json のルール（MIME・拡張子・ファイル名の正規表現・入手元 URL・サイズ・経過日数）で
+// 移動先を決める。移動先はテンプレート（{pictures}/{year} など）で、ユーザーディレクトリは設定の user_dirs →
+// XDG（~/ピクチャ など）→ 英語名の順に解決する。同名ファイルは連番で改名/スキップ/置換を選べ、
+// 別ファイルシステムへはコピーして元を消す。dry_run では実際には動かさず計画だけを返す
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::time::SystemTime;
+
+use chrono::{DateTime, Local};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::error::SisError;
+use crate::{expand_user_path, history_dir, log_append, read_settings};
+
+// Firefox/Chromium がダウンロード時に付ける入手元 URL の拡張属性
+const ORIGIN_XATTR: &str = "user.xdg.origin.url";
+const MAX_COUNTER: u32 = 999;
+const DIR_KEYS: [&str; 7] = ["home", "downloads", "documents", "pictures", "music", "videos", "desktop"];
+// ダウンロード途中のファイル（Firefox .part、Chromium .crdownload、Safari 系 .download など）
+const PARTIAL_EXTS: [&str; 6] = ["part", "crdownload", "tmp", "download", "partial", "opdownload"];
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Collision {
+    #[default]
+    Rename, // "name (2).ext" のように連番を付ける
+    Skip,
+    Replace,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct OrganizeRule {
+    name: String,
+    enabled: bool,
+    mime: Vec<String>,       // "image/*" や "application/pdf"（どれかに一致）
+    extensions: Vec<String>, // "pdf"（ドットなし・大文字小文字は区別しない）
+    name_regex: Option<String>,
+    source_url_regex: Option<String>,
+    min_size: Option<u64>, // バイト
+    max_size: Option<u64>,
+    min_age_days: Option<f64>, // 更新からの経過日数
+    max_age_days: Option<f64>,
+    target: String,
+    collision: Option<Collision>, // 省略時は全体の設定
+}
+
+impl Default for OrganizeRule {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            enabled: true,
+            mime: Vec::new(),
+            extensions: Vec::new(),
+            name_regex: None,
+            source_url_regex: None,
+            min_size: None,
+            max_size: None,
+            min_age_days: None,
+            max_age_days: None,
+            target: String::new(),
+            collision: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct OrganizerConfig {
+    rules: Vec<OrganizeRule>,
+    fallback_target: Option<String>, // どのルールにも合わないとき（None なら動かさない）
+    collision: Collision,
+}
+
+fn rule(name: &str, mime: &[&str], extensions: &[&str], target: &str) -> OrganizeRule {
+    OrganizeRule {
+        name: name.into(),
+        mime: mime.iter().map(|s| s.to_string()).collect(),
+        extensions: extensions.iter().map(|s| s.to_string()).collect(),
+        target: target.into(),
+        ..Default::default()
+    }
+}
+
+impl Default for OrganizerConfig {
+    /// 従来の organize_file と同じ振り分け（置き場所はユーザーディレクトリに従う）
+    fn default() -> Self {
+        Self {
+            rules: vec![
+                rule("画像", &["image/*"], &[], "{pictures}"),
+                rule("動画", &["video/*"], &[], "{videos}"),
+                rule("音楽", &["audio/*"], &[], "{music}"),
+                rule("文書", &["text/*", "application/pdf"], &[], "{documents}"),
+                rule("アーカイブ", &["application/zip", "application/x-tar", "application/x-rar-compressed"], &["7z", "gz", "xz"], "{home}/Archives"),
+            ],
+            fallback_target: Some("{downloads}/Others".into()),
+            collision: Collision::Rename,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct OrganizePlan {
+    source: String,
+    rule: Option<String>,
+    destination: Option<String>,
+    action: String,            // move | skip | none
+    collision: Option<String>, // renamed | replaced | skipped（同名があったとき）
+    reason: Option<String>,    // none/skip の理由（no-rule など）
+    cross_device: bool,
+    done: bool, // dry_run では常に false
+}
+
+impl OrganizePlan {
+    pub fn destination(&self) -> Option<&str> { self.destination.as_deref() }
+    pub fn done(&self) -> bool { self.done }
+}
+
+fn config_path() -> Option<PathBuf> { history_dir().map(|d| d.join("organizer.json")) }
+
+fn read_config() -> OrganizerConfig {
+    config_path().and_then(|p| fs::read_to_string(p).ok()).and_then(|s| serde_json::from_str(&s).ok()).unwrap_or_default()
+}
+
+fn write_config(c: &OrganizerConfig) -> Result<(), String> {
+    let p = config_path().ok_or("no-data-dir")?;
+    if let Some(dir) = p.parent() { let _ = fs::create_dir_all(dir); }
+    fs::write(&p, serde_json::to_string_pretty(c).map_err(|e| e.to_string())?).map_err(|e| format!("write-failed: {}", e))
+}
+
+/// 設定の user_dirs → XDG（dirs が user-dirs.dirs を読む）→ ホーム直下の英語名
+pub fn user_dir(key: &str) -> Option<PathBuf> {
+    let home = dirs::home_dir()?;
+    if key == "home" { return Some(home); }
+    if let Some(v) = read_settings().user_dirs.as_ref().and_then(|ud| ud.get(key)).and_then(|v| v.as_str()).filter(|v| !v.trim().is_empty()) {
+        return Some(expand_user_path(v));
+    }
+    let (xdg, fallback) = match key {
+        "downloads" => (dirs::download_dir(), "Downloads"),
+        "documents" => (dirs::document_dir(), "Documents"),
+        "pictures" => (dirs::picture_dir(), "Pictures"),
+        "music" => (dirs::audio_dir(), "Music"),
+        "videos" => (dirs::video_dir(), "Videos"),
+        "desktop" => (dirs::desktop_dir(), "Desktop"),
+        _ => return None,
+    };
+    // XDG 未設定だと dirs はホームそのものを返すことがあるので除く
+    Some(xdg.filter(|p| *p != home).unwrap_or_else(|| home.join(fallback)))
+}
+
+/// まだダウンロード中のファイルか。一時拡張子のほか、Firefox が先に作る空の仮ファイル
+/// （"report.pdf" と "report.pdf.part" が並ぶ）も途中とみなす
+pub fn download_in_progress(path: &Path) -> bool {
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    if name.starts_with('.') { return true; }
+    if path.extension().is_some_and(|e| PARTIAL_EXTS.iter().any(|p| e.eq_ignore_ascii_case(p))) { return true; }
+    PARTIAL_EXTS.iter().any(|ext| path.with_file_name(format!("{}.{}", name, ext)).exists())
+}
+
+fn origin_url(path: &Path) -> Option<String> {
+    use std::os::unix::ffi::OsStrExt;
+    let cpath = std::ffi::CString::new(path.as_os_str().as_bytes()).ok()?;
+    let cname = std::ffi::CString::new(ORIGIN_XATTR).ok()?;
+    let mut buf = vec![0u8; 4096];
+    // SAFETY: どちらも NUL 終端の C 文字列で、buf の長さをそのまま渡している
+    let n = unsafe { libc::getxattr(cpath.as_ptr(), cname.as_ptr(), buf.as_mut_ptr() as *mut libc::c_void, buf.len()) };
+    if n <= 0 { return None; }
+    buf.truncate(n as usize);
+    String::from_utf8(buf).ok().map(|s| s.trim_end_matches('\0').to_string())
+}
+
+/// "https://www.example.com:8080/a" → "example.com"（パスに使うので英数字と . - 以外は落とす）
+fn url_host(url: &str) -> Option<String> {
+    let rest = url.split_once("://")?.1;
+    let host = rest.split(['/', '?', '#']).next()?.rsplit('@').next()?.split(':').next()?;
+    let host = host.strip_prefix("www.").unwrap_or(host);
+    let clean: String = host.chars().filter(|c| c.is_ascii_alphanumeric() || *c == '.' || *c == '-').collect();
+    let clean = clean.trim_matches('.').to_string();
+    if clean.is_empty() { None } else { Some(clean) }
+}
+
+struct FileInfo {
+    path: PathBuf,
+    name: String,
+    ext: String,
+    mime: String,
+    size: u64,
+    age_days: f64,
+    modified: DateTime<Local>,
+    origin: Option<String>,
+}
+
+fn file_info(path: &Path) -> Result<FileInfo, String> {
+    let meta = fs::symlink_metadata(path).map_err(|e| format!("file-not-found: {}", e))?;
+    if !meta.is_file() { return Err(format!("not-a-file: {}", path.display())); }
+    let modified = meta.modified().unwrap_or_else(|_| SystemTime::now());
+    let age = SystemTime::now().duration_since(modified).map(|d| d.as_secs_f64()).unwrap_or(0.0);
+    Ok(FileInfo {
+        path: path.to_path_buf(),
+        name: path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+        ext: path.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default(),
+        mime: mime_guess::from_path(path).first_or_octet_stream().essence_str().to_string(),
+        size: meta.len(),
+        age_days: age / 86400.0,
+        modified: DateTime::<Local>::from(modified),
+        origin: origin_url(path),
+    })
+}
+
+fn mime_matches(pattern: &str, mime: &str) -> bool {
+    let p = pattern.trim().to_lowercase();
+    match p.strip_suffix("/*") {
+        Some(top) => mime.split('/').next() == Some(top),
+        None => p == mime,
+    }
+}
+
+fn rule_matches(r: &OrganizeRule, f: &FileInfo) -> Result<bool, String> {
+    if !r.mime.is_empty() && !r.mime.iter().any(|m| mime_matches(m, &f.mime)) { return Ok(false); }
+    if !r.extensions.is_empty() && !r.extensions.iter().any(|e| e.trim().trim_start_matches('.').eq_ignore_ascii_case(&f.ext)) { return Ok(false); }
+    if let Some(re) = r.name_regex.as_deref().filter(|s| !s.is_empty()) {
+        if !Regex::new(re).map_err(|e| format!("invalid-regex: {}", e))?.is_match(&f.name) { return Ok(false); }
+    }
+    if let Some(re) = r.source_url_regex.as_deref().filter(|s| !s.is_empty()) {
+        let re = Regex::new(re).map_err(|e| format!("invalid-regex: {}", e))?;
+        if !f.origin.as_deref().is_some_and(|u| re.is_match(u)) { return Ok(false); }
+    }
+    if r.min_size.is_some_and(|v| f.size < v) || r.max_size.is_some_and(|v| f.size > v) { return Ok(false); }
+    if r.min_age_days.is_some_and(|v| f.age_days < v) || r.max_age_days.is_some_and(|v| f.age_days > v) { return Ok(false); }
+    Ok(true)
+}
+
+/// {downloads} などのディレクトリ、{year}/{month}/{day}（更新日）、{ext}、{mime}（image など）、{host}（入手元）を展開する
+fn expand_target(template: &str, f: Option<&FileInfo>) -> Result<PathBuf, String> {
+    let re = Regex::new(r"\{([a-z_]+)\}").unwrap();
+    let mut err = None;
+    let out = re.replace_all(template.trim(), |c: &regex::Captures| {
+        let key = &c[1];
+        let v = if DIR_KEYS.contains(&key) {
+            user_dir(key).map(|p| p.to_string_lossy().to_string())
+        } else {
+            match (key, f) {
+                ("year", Some(f)) => Some(f.modified.format("%Y").to_string()),
+                ("month", Some(f)) => Some(f.modified.format("%m").to_string()),
+                ("day", Some(f)) => Some(f.modified.format("%d").to_string()),
+                ("ext", Some(f)) => Some(if f.ext.is_empty() { "noext".into() } else { f.ext.clone() }),
+                ("mime", Some(f)) => f.mime.split('/').next().map(|s| s.to_string()),
+                ("host", Some(f)) => Some(f.origin.as_deref().and_then(url_host).unwrap_or_else(|| "unknown".into())),
+                ("year" | "month" | "day" | "ext" | "mime" | "host", None) => Some("x".into()),
+                _ => None,
+            }
+        };
+        v.unwrap_or_else(|| { err = Some(format!("invalid-template: {{{}}}", key)); String::new() })
+    }).to_string();
+    if let Some(e) = err { return Err(e); }
+    let p = expand_user_path(&out);
+    if !p.is_absolute() || p.components().any(|c| matches!(c, Component::ParentDir)) {
+        return Err(format!("invalid-template: {}", template));
+    }
+    Ok(p)
+}
+
+/// "report.pdf" → "report (2).pdf"
+fn numbered(dest: &Path, n: u32) -> PathBuf {
+    let stem = dest.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let name = match dest.extension() {
+        Some(ext) => format!("{} ({}).{}", stem, n, ext.to_string_lossy()),
+        None => format!("{} ({})", stem, n),
+    };
+    dest.with_file_name(name)
+}
+
+fn same_device(a: &Path, b: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    match (fs::metadata(a), fs::metadata(b)) {
+        (Ok(x), Ok(y)) => x.dev() == y.dev(),
+        _ => true,
+    }
+}
+
+fn is_exdev(e: &io::Error) -> bool { e.raw_os_error() == Some(libc::EXDEV) }
+
+/// dest を新規作成してコピーし（既にあれば失敗）、更新日時と権限を引き継いでから元を消す
+fn copy_then_delete(src: &Path, dest: &Path) -> io::Result<()> {
+    let meta = fs::metadata(src)?;
+    let res = (|| {
+        let mut from = fs::File::open(src)?;
+        let mut to = fs::OpenOptions::new().write(true).create_new(true).open(dest)?;
+        io::copy(&mut from, &mut to)?;
+        to.set_permissions(meta.permissions())?;
+        if let Ok(m) = meta.modified() { let _ = to.set_modified(m); }
+        to.sync_all()
+    })();
+    if let Err(e) = res {
+        // 作りかけのコピーを残さない（create_new で失敗したときは dest は他人のもの）
+        if e.kind() != io::ErrorKind::AlreadyExists { let _ = fs::remove_file(dest); }
+        return Err(e);
+    }
+    fs::remove_file(src)
+}
+
+/// 上書きせずに移動する。同じファイルシステムではハードリンク→元の削除で、既存のファイルがあれば AlreadyExists になる
+fn move_no_clobber(src: &Path, dest: &Path) -> io::Result<()> {
+    match fs::hard_link(src, dest) {
+        Ok(()) => fs::remove_file(src),
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => Err(e),
+        // 別ファイルシステムやハードリンク非対応（FAT の USB メモリなど）
+        Err(_) => copy_then_delete(src, dest),
+    }
+}
+
+fn move_replace(src: &Path, dest: &Path) -> io::Result<()> {
+    match fs::rename(src, dest) {
+        Err(e) if is_exdev(&e) => {
+            // 置換でも途中で失敗したときに元の dest を壊さないよう、一時名にコピーしてから置き換える
+            let tmp = dest.with_file_name(format!(".{}.sis-part", dest.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default()));
+            let _ = fs::remove_file(&tmp);
+            let meta = fs::metadata(src)?;
+            fs::copy(src, &tmp)?;
+            if let Ok(m) = meta.modified() { let _ = fs::File::options().write(true).open(&tmp).and_then(|f| f.set_modified(m)); }
+            fs::rename(&tmp, dest).inspect_err(|_| { let _ = fs::remove_file(&tmp); })?;
+            fs::remove_file(src)
+        }
+        r => r,
+    }
+}
+
+fn plan_for(cfg: &OrganizerConfig, path: &Path) -> Result<(OrganizePlan, Collision), String> {
+    let f = file_info(path)?;
+    let mut plan = OrganizePlan {
+        source: path.to_string_lossy().to_string(),
+        rule: None,
+        destination: None,
+        action: "none".into(),
+        collision: None,
+        reason: None,
+        cross_device: false,
+        done: false,
+    };
+    let mut collision = cfg.collision;
+    let mut target = None;
+    for r in cfg.rules.iter().filter(|r| r.enabled && !r.target.trim().is_empty()) {
+        if rule_matches(r, &f)? {
+            plan.rule = Some(r.name.clone());
+            collision = r.collision.unwrap_or(cfg.collision);
+            target = Some(r.target.clone());
+            break;
+        }
+    }
+    let Some(template) = target.or_else(|| cfg.fallback_target.clone().filter(|t| !t.trim().is_empty())) else {
+        plan.reason = Some("no-rule".into());
+        return Ok((plan, collision));
+    };
+    let dir = expand_target(&template, Some(&f))?;
+    let mut dest = dir.join(&f.name);
+    if dir == f.path.parent().unwrap_or(Path::new("")) {
+        plan.reason = Some("already-in-place".into());
+        plan.destination = Some(dest.to_string_lossy().to_string());
+        return Ok((plan, collision));
+    }
+    plan.action = "move".into();
+    if fs::symlink_metadata(&dest).is_ok() {
+        match collision {
+            Collision::Skip => { plan.action = "skip".into(); plan.collision = Some("skipped".into()); plan.reason = Some("exists".into()); }
+            Collision::Replace => {
+                if dest.is_dir() { plan.action = "skip".into(); plan.reason = Some("target-is-directory".into()); } else { plan.collision = Some("replaced".into()); }
+            }
+            Collision::Rename => {
+                dest = (2..=MAX_COUNTER).map(|n| numbered(&dest, n)).find(|p| fs::symlink_metadata(p).is_err()).ok_or("too-many-duplicates")?;
+                plan.collision = Some("renamed".into());
+            }
+        }
+    }
+    // 移動先がまだ無いときは、作られるはずの親（既存の最も近い祖先）で判定する
+    let probe = dir.ancestors().find(|a| a.exists()).unwrap_or(&dir);
+    plan.cross_device = !same_device(path, probe);
+    plan.destination = Some(dest.to_string_lossy().to_string());
+    Ok((plan, collision))
+}
+
+fn skipped_plan(path: &Path, reason: String) -> OrganizePlan {
+    OrganizePlan {
+        source: path.to_string_lossy().to_string(),
+        rule: None,
+        destination: None,
+        action: "skip".into(),
+        collision: None,
+        reason: Some(reason),
+        cross_device: false,
+        done: false,
+    }
+}
+
+fn execute(mut plan: OrganizePlan, collision: Collision) -> Result<OrganizePlan, String> {
+    if plan.action != "move" { return Ok(plan); }
+    let src = PathBuf::from(&plan.source);
+    let mut dest = PathBuf::from(plan.destination.as_deref().unwrap_or_default());
+    if let Some(dir) = dest.parent() { fs::create_dir_all(dir).map_err(|e| format!("mkdir-failed: {}", e))?; }
+    let res = if collision == Collision::Replace {
+        move_replace(&src, &dest)
+    } else {
+        // 計画してから実行までの間に同名ができた場合も上書きしない
+        let mut r = move_no_clobber(&src, &dest);
+        let mut n = 2;
+        while collision == Collision::Rename && r.as_ref().is_err_and(|e| e.kind() == io::ErrorKind::AlreadyExists) && n <= MAX_COUNTER {
+            let base = src.file_name().map(|s| dest.with_file_name(s)).unwrap_or_else(|| dest.clone());
+            dest = numbered(&base, n);
+            plan.collision = Some("renamed".into());
+            r = move_no_clobber(&src, &dest);
+            n += 1;
+        }
+        r
+    };
+    match res {
+        Ok(()) => {
+            plan.destination = Some(dest.to_string_lossy().to_string());
+            plan.done = true;
+            log_append("INFO", &format!("organizer: {} -> {} ({})", plan.source, dest.display(), plan.rule.as_deref().unwrap_or("fallback")));
+            Ok(plan)
+        }
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+            plan.action = "skip".into();
+            plan.collision = Some("skipped".into());
+            plan.reason = Some("exists".into());
+            Ok(plan)
+        }
+        Err(e) => Err(format!("move-failed: {}: {}", plan.source, e)),
+    }
+}
+
+/// 1 ファイルを整理する（Downloads の監視からも使う）
+pub fn organize_path(path: &Path, dry_run: bool) -> Result<OrganizePlan, String> {
+    let cfg = read_config();
+    let (plan, collision) = plan_for(&cfg, path)?;
+    if dry_run { Ok(plan) } else { execute(plan, collision) }
+}
+
+/// 整理した移動を元に戻す。元の場所に同名ができていれば連番を付ける（置換で消えたファイルは戻らない）
+pub fn undo_move(plan: &OrganizePlan) -> Result<PathBuf, String> {
+    if !plan.done { return Err("not-moved".into()); }
+    let dest = PathBuf::from(plan.destination.as_deref().unwrap_or_default());
+    if fs::symlink_metadata(&dest).is_err() { return Err(format!("file-not-found: {}", dest.display())); }
+    let orig = PathBuf::from(&plan.source);
+    if let Some(dir) = orig.parent() { fs::create_dir_all(dir).map_err(|e| format!("mkdir-failed: {}", e))?; }
+    let mut back = orig.clone();
+    let mut n = 2;
+    loop {
+        match move_no_clobber(&dest, &back) {
+            Ok(()) => break,
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists && n <= MAX_COUNTER => { back = numbered(&orig, n); n += 1; }
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => return Err("too-many-duplicates".into()),
+            Err(e) => return Err(format!("move-failed: {}: {}", dest.display(), e)),
+        }
+    }
+    log_append("INFO", &format!("organizer: undo {} -> {}", dest.display(), back.display()));
+    Ok(back)
+}
+
+fn validate(cfg: &OrganizerConfig) -> Result<(), String> {
+    for r in &cfg.rules {
+        for re in [&r.name_regex, &r.source_url_regex].into_iter().flatten().filter(|s| !s.is_empty()) {
+            Regex::new(re).map_err(|e| format!("invalid-regex: {}: {}", r.name, e))?;
+        }
+        if r.target.trim().is_empty() { return Err(format!("invalid-template: {}", r.name)); }
+        expand_target(&r.target, None)?;
+    }
+    if let Some(t) = cfg.fallback_target.as_deref().filter(|t| !t.trim().is_empty()) { expand_target(t, None)?; }
+    Ok(())
+}
+
+fn downloads_files() -> Result<Vec<PathBuf>, String> {
+    let dir = user_dir("downloads").ok_or("cannot-detect-home")?;
+    let mut files: Vec<(SystemTime, PathBuf)> = fs::read_dir(&dir).map_err(|e| format!("read-failed: {}", e))?
+        .flatten()
+        .filter(|e| e.file_type().map(|t| t.is_file()).unwrap_or(false))
+        .filter(|e| !download_in_progress(&e.path()))
+        .map(|e| (e.metadata().and_then(|m| m.modified()).unwrap_or(SystemTime::UNIX_EPOCH), e.path()))
+        .collect();
+    files.sort_by_key(|f| std::cmp::Reverse(f.0));
+    Ok(files.into_iter().map(|(_, p)| p).collect())
+}
+
+#[tauri::command]
+pub fn organizer_get_config() -> Result<OrganizerConfig, SisError> { Ok(read_config()) }
+
+#[tauri::command]
+pub fn organizer_set_config(config: OrganizerConfig) -> Result<OrganizerConfig, SisError> {
+    validate(&config)?;
+    write_config(&config)?;
+    Ok(config)
+}
+
+/// 整理の予行（dry-run）。paths 省略時はダウンロードフォルダの全ファイル（新しい順）
+#[tauri::command]
+pub fn organize_preview(paths: Option<Vec<String>>) -> Result<Vec<OrganizePlan>, SisError> {
+    let cfg = read_config();
+    let files = match paths {
+        Some(p) => p.into_iter().map(PathBuf::from).collect(),
+        None => downloads_files()?,
+    };
+    // 同じ移動先に同名が重なる場合は、前の計画で埋まる名前を避けて連番を進める
+    let mut taken: Vec<String> = Vec::new();
+    let mut out = Vec::new();
+    for f in files {
+        // 読めない・規則が壊れているなどのファイルは理由付きで skip にし、残りの予行は続ける
+        let (mut plan, collision) = match plan_for(&cfg, &f) {
+            Ok(p) => p,
+            Err(e) => { out.push(skipped_plan(&f, e)); continue; }
+        };
+        if plan.action == "move" && collision != Collision::Replace {
+            if let Some(dest) = plan.destination.clone().filter(|d| taken.contains(d)) {
+                if collision == Collision::Skip {
+                    plan.action = "skip".into();
+                    plan.collision = Some("skipped".into());
+                    plan.reason = Some("exists".into());
+                } else {
+                    let base = PathBuf::from(&dest).with_file_name(f.file_name().unwrap_or_default());
+                    match (2..=MAX_COUNTER).map(|n| numbered(&base, n).to_string_lossy().to_string())
+                        .find(|d| !taken.contains(d) && fs::symlink_metadata(d).is_err()) {
+                        Some(free) => { plan.collision = Some("renamed".into()); plan.destination = Some(free); }
+                        None => plan = skipped_plan(&f, "too-many-duplicates".into()),
+                    }
+                }
+            }
+            if plan.action == "move" { taken.extend(plan.destination.clone()); }
+        }
+        out.push(plan);
+    }
+    Ok(out)
+}
+
+#[tauri::command]
+pub fn organize_file(file_path: String, dry_run: Option<bool>) -> Result<OrganizePlan, SisError> {
+    Ok(organize_path(Path::new(&file_path), dry_run.unwrap_or(false))?)
+}
+
+/// ダウンロードフォルダで最も新しいファイルを整理する（ダウンロード途中のものは除く）
+#[tauri::command]
+pub fn organize_latest_download(dry_run: Option<bool>) -> Result<OrganizePlan, SisError> {
+    let latest = downloads_files()?.into_iter().next().ok_or("no-files-in-downloads")?;
+    Ok(organize_path(&latest, dry_run.unwrap_or(false))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let nanos = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0);
+        let d = std::env::temp_dir().join(format!("sis-organizer-{}-{}-{}", name, std::process::id(), nanos));
+        fs::create_dir_all(&d).unwrap();
+        d
+    }
+
+    #[test]
+    fn numbered_keeps_the_extension() {
+        assert_eq!(numbered(Path::new("/d/report.pdf"), 2), PathBuf::from("/d/report (2).pdf"));
+        assert_eq!(numbered(Path::new("/d/archive.tar.gz"), 3), PathBuf::from("/d/archive.tar (3).gz"));
+        assert_eq!(numbered(Path::new("/d/README"), 2), PathBuf::from("/d/README (2)"));
+    }
+
+    #[test]
+    fn plan_for_skips_numbers_already_taken() {
+        let root = temp_dir("plan");
+        let (src, dest) = (root.join("src"), root.join("dest"));
+        fs::create_dir_all(&src).unwrap();
+        fs::create_dir_all(&dest).unwrap();
+        fs::write(src.join("report.txt"), "new").unwrap();
+        fs::write(dest.join("report.txt"), "old").unwrap();
+        fs::write(dest.join("report (2).txt"), "old").unwrap();
+        let mut cfg = OrganizerConfig { rules: vec![rule("text", &[], &["txt"], &dest.to_string_lossy())], fallback_target: None, collision: Collision::Rename };
+        let (plan, _) = plan_for(&cfg, &src.join("report.txt")).unwrap();
+        assert_eq!(plan.action, "move");
+        assert_eq!(plan.collision.as_deref(), Some("renamed"));
+        assert_eq!(plan.destination, Some(dest.join("report (3).txt").to_string_lossy().to_string()));
+
+        cfg.collision = Collision::Skip;
+        let (plan, _) = plan_for(&cfg, &src.join("report.txt")).unwrap();
+        assert_eq!((plan.action.as_str(), plan.reason.as_deref()), ("skip", Some("exists")));
+        let _ = fs::remove_dir_all(&root);
+    }
+}
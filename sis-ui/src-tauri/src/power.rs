@@ -0,0 +1,288 @@
+// Battery / power supply: UPower(D-Bus) を優先し、無ければ /sys/class/power_supply を直接読む
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+
+use crate::error::SisError;
+use crate::{history_dir, log_append};
+
+const POLL: Duration = Duration::from_secs(5);
+const SYSFS_ROOT: &str = "/sys/class/power_supply";
+const UPOWER_DEST: &str = "org.freedesktop.UPower";
+const UPOWER_DISPLAY_DEVICE: &str = "/org/freedesktop/UPower/devices/DisplayDevice";
+// 管理者既定値（ユーザー設定が無い場合に使用）
+const ADMIN_POLICY_PATH: &str = "/etc/sis/power.json";
+// dim アクションで下げる明るさ（%）
+const DIM_PERCENT: u32 = 30;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BatteryStatus {
+    present: bool,
+    percentage: f64,
+    state: String, // charging | discharging | full | not-charging | unknown
+    ac_online: bool,
+    time_to_empty_secs: Option<u64>,
+    time_to_full_secs: Option<u64>,
+    health_percent: Option<f64>, // full / design
+    energy_full_wh: Option<f64>,
+    energy_full_design_wh: Option<f64>,
+    source: String, // upower | sysfs
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PowerPolicy {
+    #[serde(default = "default_low")]
+    low_percent: f64,
+    #[serde(default = "default_critical")]
+    critical_percent: f64,
+    #[serde(default = "default_low_action")]
+    low_action: String, // none | warn | dim | suspend
+    #[serde(default = "default_critical_action")]
+    critical_action: String,
+}
+
+fn default_low() -> f64 { 20.0 }
+fn default_critical() -> f64 { 5.0 }
+fn default_low_action() -> String { "warn".into() }
+fn default_critical_action() -> String { "suspend".into() }
+
+impl Default for PowerPolicy {
+    fn default() -> Self {
+        PowerPolicy { low_percent: default_low(), critical_percent: default_critical(), low_action: default_low_action(), critical_action: default_critical_action() }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+enum Level { Normal, Low, Critical }
+
+struct Monitor {
+    last: Option<BatteryStatus>,
+    // 放電中に到達した最も深いレベル（充電/回復でリセット）
+    fired: Level,
+    dimmed_from: Option<u32>,
+}
+
+static MONITOR: Lazy<Mutex<Monitor>> = Lazy::new(|| Mutex::new(Monitor { last: None, fired: Level::Normal, dimmed_from: None }));
+
+fn user_policy_path() -> Option<PathBuf> { history_dir().map(|d| d.join("power.json")) }
+
+pub fn read_policy() -> PowerPolicy {
+    let user = user_policy_path().filter(|p| p.exists());
+    for p in user.into_iter().chain(std::iter::once(PathBuf::from(ADMIN_POLICY_PATH))) {
+        if let Ok(s) = fs::read_to_string(&p) {
+            if let Ok(v) = serde_json::from_str::<PowerPolicy>(&s) { return v; }
+        }
+    }
+    PowerPolicy::default()
+}
+
+fn read_trim(p: &Path) -> Option<String> { fs::read_to_string(p).ok().map(|s| s.trim().to_string()) }
+fn read_u64(p: &Path) -> Option<u64> { read_trim(p).and_then(|s| s.parse::<u64>().ok()) }
+
+fn normalize_state(s: &str) -> String {
+    match s.to_lowercase().as_str() {
+        "charging" => "charging".into(),
+        "discharging" => "discharging".into(),
+        "full" => "full".into(),
+        "not charging" => "not-charging".into(),
+        _ => "unknown".into(),
+    }
+}
+
+// sysfs のバッテリーを集計（複数バッテリー機は容量で合算）
+fn read_sysfs() -> Option<BatteryStatus> {
+    let rd = fs::read_dir(SYSFS_ROOT).ok()?;
+    let mut ac_online = false;
+    let mut found = false;
+    let (mut now_wh, mut full_wh, mut design_wh, mut power_w) = (0.0f64, 0.0f64, 0.0f64, 0.0f64);
+    let mut pct_fallback: Option<f64> = None;
+    let mut state = "unknown".to_string();
+    for e in rd.flatten() {
+        let dir = e.path();
+        let kind = read_trim(&dir.join("type")).unwrap_or_default();
+        match kind.as_str() {
+            "Mains" | "USB" => { if read_u64(&dir.join("online")) == Some(1) { ac_online = true; } }
+            "Battery" => {
+                // 周辺機器(マウス等)の電池は scope=Device なので除外
+                if read_trim(&dir.join("scope")).as_deref() == Some("Device") { continue; }
+                if read_u64(&dir.join("present")) == Some(0) { continue; }
+                found = true;
+                let st = normalize_state(&read_trim(&dir.join("status")).unwrap_or_default());
+                if st != "unknown" && (state == "unknown" || st == "charging" || st == "discharging") { state = st; }
+                if pct_fallback.is_none() { pct_fallback = read_u64(&dir.join("capacity")).map(|v| v as f64); }
+                // energy_* は µWh/µW、charge_* は µAh/µA（電圧で Wh に換算）
+                let volt = read_u64(&dir.join("voltage_min_design")).or_else(|| read_u64(&dir.join("voltage_now"))).map(|v| v as f64 / 1e6);
+                let wh = |name: &str, charge: &str| -> Option<f64> {
+                    read_u64(&dir.join(name)).map(|v| v as f64 / 1e6)
+                        .or_else(|| read_u64(&dir.join(charge)).and_then(|v| volt.map(|vv| v as f64 / 1e6 * vv)))
+                };
+                now_wh += wh("energy_now", "charge_now").unwrap_or(0.0);
+                full_wh += wh("energy_full", "charge_full").unwrap_or(0.0);
+                design_wh += wh("energy_full_design", "charge_full_design").unwrap_or(0.0);
+                power_w += wh("power_now", "current_now").unwrap_or(0.0);
+            }
+            _ => {}
+        }
+    }
+    if !found {
+        return Some(BatteryStatus { present: false, percentage: 0.0, state: "unknown".into(), ac_online, time_to_empty_secs: None, time_to_full_secs: None, health_percent: None, energy_full_wh: None, energy_full_design_wh: None, source: "sysfs".into() });
+    }
+    let percentage = if full_wh > 0.0 { (now_wh / full_wh * 100.0).clamp(0.0, 100.0) } else { pct_fallback.unwrap_or(0.0) };
+    let rate = if power_w > 0.01 { Some(power_w) } else { None };
+    let time_to_empty_secs = if state == "discharging" { rate.map(|r| (now_wh / r * 3600.0) as u64) } else { None };
+    let time_to_full_secs = if state == "charging" { rate.map(|r| ((full_wh - now_wh).max(0.0) / r * 3600.0) as u64) } else { None };
+    Some(BatteryStatus {
+        present: true,
+        percentage,
+        state,
+        ac_online,
+        time_to_empty_secs,
+        time_to_full_secs,
+        health_percent: if design_wh > 0.0 && full_wh > 0.0 { Some((full_wh / design_wh * 100.0).min(100.0)) } else { None },
+        energy_full_wh: if full_wh > 0.0 { Some(full_wh) } else { None },
+        energy_full_design_wh: if design_wh > 0.0 { Some(design_wh) } else { None },
+        source: "sysfs".into(),
+    })
+}
+
+fn read_upower(conn: &zbus::blocking::Connection) -> Option<BatteryStatus> {
+    let dev = zbus::blocking::Proxy::new(conn, UPOWER_DEST, UPOWER_DISPLAY_DEVICE, "org.freedesktop.UPower.Device").ok()?;
+    let present = dev.get_property::<bool>("IsPresent").ok()?;
+    let percentage = dev.get_property::<f64>("Percentage").unwrap_or(0.0);
+    // UPower Device.State: 1=charging 2=discharging 3=empty 4=fully-charged 5=pending-charge 6=pending-discharge
+    let state = match dev.get_property::<u32>("State").unwrap_or(0) {
+        1 => "charging",
+        2 | 3 | 6 => "discharging",
+        4 => "full",
+        5 => "not-charging",
+        _ => "unknown",
+    }.to_string();
+    let secs = |name: &str| dev.get_property::<i64>(name).ok().filter(|v| *v > 0).map(|v| v as u64);
+    let on_battery = zbus::blocking::Proxy::new(conn, UPOWER_DEST, "/org/freedesktop/UPower", "org.freedesktop.UPower").ok()
+        .and_then(|p| p.get_property::<bool>("OnBattery").ok())
+        .unwrap_or(state == "discharging");
+    let energy_full = dev.get_property::<f64>("EnergyFull").ok().filter(|v| *v > 0.0);
+    let energy_design = dev.get_property::<f64>("EnergyFullDesign").ok().filter(|v| *v > 0.0);
+    let mut st = BatteryStatus {
+        present,
+        percentage,
+        time_to_empty_secs: secs("TimeToEmpty"),
+        time_to_full_secs: secs("TimeToFull"),
+        health_percent: match (energy_full, energy_design) { (Some(f), Some(d)) => Some((f / d * 100.0).min(100.0)), _ => None },
+        energy_full_wh: energy_full,
+        energy_full_design_wh: energy_design,
+        state,
+        ac_online: !on_battery,
+        source: "upower".into(),
+    };
+    // DisplayDevice は集約値のため設計容量を持たないことがある → sysfs で補完
+    if st.health_percent.is_none() {
+        if let Some(s) = read_sysfs() {
+            st.health_percent = s.health_percent;
+            st.energy_full_wh = st.energy_full_wh.or(s.energy_full_wh);
+            st.energy_full_design_wh = st.energy_full_design_wh.or(s.energy_full_design_wh);
+        }
+    }
+    Some(st)
+}
+
+fn system_bus() -> Option<zbus::blocking::Connection> {
+    static CONN: Lazy<Option<zbus::blocking::Connection>> = Lazy::new(|| zbus::blocking::Connection::system().ok());
+    CONN.clone()
+}
+
+/// 現在のバッテリー状態。バッテリー非搭載機では None
+pub fn read_battery() -> Option<BatteryStatus> {
+    let st = system_bus().and_then(|c| read_upower(&c)).or_else(read_sysfs)?;
+    if st.present { Some(st) } else { None }
+}
+
+fn run_action(app_handle: &tauri::AppHandle, action: &str, level: &str, st: &BatteryStatus, m: &mut Monitor) {
+    log_append("WARN", &format!("power: battery {} ({:.0}%) action={}", level, st.percentage, action));
+    // フロントへのイベントはいずれのアクションでも送る（デスクトップ通知は "none" 以外のみ）
+    let _ = app_handle.emit("sis:power-warning", serde_json::json!({
+        "level": level,
+        "action": action,
+        "percentage": st.percentage,
+        "time_to_empty_secs": st.time_to_empty_secs,
+    }));
+    if action == "none" { return; }
+    let summary = if level == "critical" { "バッテリー残量がごくわずかです" } else { "バッテリー残量が少なくなっています" };
+    crate::notifications::post("電源", summary, &format!("残り {:.0}%。充電器を接続してください。", st.percentage), Some("battery-caution"), if level == "critical" { "critical" } else { "normal" }, Vec::new(), None);
+    match action {
+        "dim" => {
+            if m.dimmed_from.is_none() { m.dimmed_from = crate::read_brightness_percent(); }
+            let _ = crate::set_brightness(DIM_PERCENT);
+        }
+        "suspend" => {
+            if let Err(e) = crate::session::suspend() { log_append("ERROR", &format!("power: suspend failed: {}", e)); }
+        }
+        _ => {}
+    }
+}
+
+fn evaluate(app_handle: &tauri::AppHandle, st: &BatteryStatus, m: &mut Monitor) {
+    let policy = read_policy();
+    if st.state != "discharging" {
+        // 充電開始で dim を元に戻す
+        if let Some(b) = m.dimmed_from.take() { let _ = crate::set_brightness(b); }
+        m.fired = Level::Normal;
+        return;
+    }
+    let level = if st.percentage <= policy.critical_percent { Level::Critical }
+        else if st.percentage <= policy.low_percent { Level::Low }
+        else { Level::Normal };
+    if level == Level::Normal { m.fired = Level::Normal; return; }
+    if level <= m.fired { return; }
+    m.fired = level;
+    match level {
+        Level::Critical => run_action(app_handle, &policy.critical_action, "critical", st, m),
+        Level::Low => run_action(app_handle, &policy.low_action, "low", st, m),
+        Level::Normal => {}
+    }
+}
+
+pub fn start(app_handle: tauri::AppHandle) {
+    if read_battery().is_none() {
+        log_append("INFO", "power: no battery found; monitor disabled");
+        return;
+    }
+    std::thread::spawn(move || loop {
+        if let Some(st) = read_battery() {
+            let mut m = MONITOR.lock().unwrap();
+            // 残り時間の細かな揺れでは通知しない
+            let changed = match &m.last {
+                Some(prev) => prev.state != st.state || prev.ac_online != st.ac_online || prev.percentage.round() != st.percentage.round(),
+                None => true,
+            };
+            if changed { let _ = app_handle.emit("sis:power", &st); }
+            evaluate(&app_handle, &st, &mut m);
+            m.last = Some(st);
+        }
+        std::thread::sleep(POLL);
+    });
+}
+
+#[tauri::command]
+pub fn battery_status() -> Result<Option<BatteryStatus>, SisError> { Ok(read_battery()) }
+
+#[tauri::command]
+pub fn power_get_policy() -> Result<PowerPolicy, SisError> { Ok(read_policy()) }
+
+#[tauri::command]
+pub fn power_set_policy(policy: PowerPolicy) -> Result<String, SisError> {
+    let valid = |a: &str| matches!(a, "none" | "warn" | "dim" | "suspend");
+    if !valid(&policy.low_action) || !valid(&policy.critical_action) { return Err("invalid-action".into()); }
+    if !(0.0..=100.0).contains(&policy.critical_percent) { return Err(SisError::invalid("invalid-critical-percent", policy.critical_percent)); }
+    if !(0.0..=100.0).contains(&policy.low_percent) || policy.critical_percent > policy.low_percent { return Err("invalid-threshold".into()); }
+    let p = user_policy_path().ok_or("cannot-detect-home")?;
+    if let Some(dir) = p.parent() { let _ = fs::create_dir_all(dir); }
+    let txt = serde_json::to_string_pretty(&policy).map_err(|e| format!("serialize-failed: {}", e))?;
+    fs::write(&p, txt).map_err(|e| format!("write-failed: {}", e))?;
+    Ok("power-policy-saved".into())
+}
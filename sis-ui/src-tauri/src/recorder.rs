@@ -0,0 +1,358 @@
+// 画面録画: ffmpeg で録る。X11 は x11grab、Wayland は ScreenCast ポータルの PipeWire ストリームを
+// gst-launch（pipewiresrc → y4m）経由で ffmpeg に流す。状態は sis:recording-state で通知する。/tmp/sis-exam-mode がある間は録画を始めない
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+use zbus::blocking::{Connection, Proxy};
+use zbus::zvariant::{DynamicType, ObjectPath, OwnedObjectPath, OwnedValue, Value};
+
+use crate::error::SisError;
+use crate::screenshot::{self, RegionTarget};
+use crate::{exam_mode, history_dir, log_append, run_out, which};
+
+const PORTAL: &str = "org.freedesktop.portal.Desktop";
+const PORTAL_PATH: &str = "/org/freedesktop/portal/desktop";
+const DEFAULT_FPS: u32 = 30;
+const POLL: Duration = Duration::from_millis(500);
+// 起動直後に落ちたら失敗とみなす
+const STARTUP_GRACE: Duration = Duration::from_millis(800);
+// SIGINT 後、mp4 の書き出しを待つ上限
+const STOP_TIMEOUT: Duration = Duration::from_secs(8);
+
+static APP: Lazy<Mutex<Option<tauri::AppHandle>>> = Lazy::new(|| Mutex::new(None));
+static STATUS: Lazy<Mutex<RecorderStatus>> = Lazy::new(|| Mutex::new(RecorderStatus::idle()));
+static SESSION: Lazy<Mutex<Option<Session>>> = Lazy::new(|| Mutex::new(None));
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RecordOptions {
+    pub mode: String, // full | window | region
+    pub window_id: Option<String>,
+    pub mic: Option<bool>,
+    pub framerate: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RecorderStatus {
+    state: String, // idle | selecting | starting | recording | stopping
+    mode: Option<String>,
+    path: Option<String>,
+    started_at_ms: Option<i64>,
+    mic: bool,
+    backend: Option<String>, // x11 | portal
+    error: Option<String>,   // 直前の録画が失敗した理由
+}
+
+impl RecorderStatus {
+    fn idle() -> Self {
+        RecorderStatus { state: "idle".into(), mode: None, path: None, started_at_ms: None, mic: false, backend: None, error: None }
+    }
+}
+
+struct Session {
+    ffmpeg: Child,
+    source: Option<Child>, // Wayland の gst-launch
+    portal: Option<(Connection, OwnedObjectPath)>,
+    stop_requested: Option<Instant>,
+}
+
+fn emit_status(st: &RecorderStatus) {
+    if let Some(app) = APP.lock().unwrap().as_ref() { let _ = app.emit("sis:recording-state", st); }
+}
+
+fn update(f: impl FnOnce(&mut RecorderStatus)) {
+    let st = {
+        let mut s = STATUS.lock().unwrap();
+        f(&mut s);
+        s.clone()
+    };
+    emit_status(&st);
+}
+
+/// 状態が from のときだけ to へ進める。確認と更新を同じロックの中で行うので、連打しても ffmpeg は1つしか起動しない
+fn transition(from: &str, to: &str, mode: &str) -> Result<(), String> {
+    let st = {
+        let mut s = STATUS.lock().unwrap();
+        if s.state != from { return Err(format!("busy: {}", s.state)); }
+        *s = RecorderStatus::idle();
+        s.state = to.into();
+        s.mode = Some(mode.into());
+        s.clone()
+    };
+    emit_status(&st);
+    Ok(())
+}
+
+fn fail(err: &str) {
+    log_append("WARN", &format!("recorder: {}", err));
+    let err = err.to_string();
+    update(|s| { *s = RecorderStatus::idle(); s.error = Some(err); });
+}
+
+pub fn videos_dir() -> Option<PathBuf> {
+    dirs::video_dir().or_else(|| dirs::home_dir().map(|h| h.join("Videos")))
+}
+
+fn even(v: u32) -> u32 { (v & !1).max(2) }
+
+pub fn x11_screen_size() -> Option<(u32, u32)> {
+    let text = run_out("xdpyinfo", &[])?;
+    let dims = text.lines().find_map(|l| l.trim().strip_prefix("dimensions:"))?.split_whitespace().next()?.to_string();
+    let (w, h) = dims.split_once('x')?;
+    Some((w.parse().ok()?, h.parse().ok()?))
+}
+
+// ---- ScreenCast ポータル ----
+
+/// Request を発行し、Response シグナルの結果を返す（handle_token は options に入れておく）
+fn portal_request<B>(conn: &Connection, method: &str, token: &str, body: &B) -> Result<HashMap<String, OwnedValue>, String>
+where
+    B: serde::Serialize + DynamicType,
+{
+    let sender = conn.unique_name().ok_or("dbus-no-unique-name")?.as_str().trim_start_matches(':').replace('.', "_");
+    let req_path = format!("{}/request/{}/{}", PORTAL_PATH, sender, token);
+    let request = Proxy::new(conn, PORTAL, req_path.as_str(), "org.freedesktop.portal.Request").map_err(|e| e.to_string())?;
+    let mut responses = request.receive_signal("Response").map_err(|e| e.to_string())?;
+    let portal = Proxy::new(conn, PORTAL, PORTAL_PATH, "org.freedesktop.portal.ScreenCast").map_err(|e| e.to_string())?;
+    portal.call::<_, _, OwnedObjectPath>(method, body).map_err(|e| format!("portal-{}-failed: {}", method, e))?;
+    let msg = responses.next().ok_or("portal-no-response")?;
+    let (code, results): (u32, HashMap<String, OwnedValue>) = msg.body().deserialize().map_err(|e| e.to_string())?;
+    match code {
+        0 => Ok(results),
+        1 => Err("cancelled".into()),
+        c => Err(format!("portal-response: {}", c)),
+    }
+}
+
+/// CreateSession → SelectSources → Start で PipeWire のノード ID を得る。セッションは録画終了まで保持する
+fn portal_screencast(window: bool) -> Result<(Connection, OwnedObjectPath, u32), String> {
+    let conn = Connection::session().map_err(|e| format!("dbus-session-failed: {}", e))?;
+    let stamp = chrono::Local::now().timestamp_millis();
+
+    let token = format!("sis_rec_{}_create", stamp);
+    let session_token = format!("sis_rec_{}", stamp);
+    let mut opts: HashMap<&str, Value> = HashMap::new();
+    opts.insert("handle_token", Value::from(token.as_str()));
+    opts.insert("session_handle_token", Value::from(session_token.as_str()));
+    let res = portal_request(&conn, "CreateSession", &token, &(opts,))?;
+    let handle = res.get("session_handle").and_then(|v| v.try_clone().ok()).and_then(|v| String::try_from(v).ok()).ok_or("portal-no-session")?;
+    let session = OwnedObjectPath::from(ObjectPath::try_from(handle).map_err(|e| e.to_string())?);
+
+    let token = format!("sis_rec_{}_select", stamp);
+    let mut opts: HashMap<&str, Value> = HashMap::new();
+    opts.insert("handle_token", Value::from(token.as_str()));
+    opts.insert("types", Value::from(if window { 2u32 } else { 1u32 })); // 1=MONITOR 2=WINDOW
+    opts.insert("multiple", Value::from(false));
+    opts.insert("cursor_mode", Value::from(2u32)); // 2=EMBEDDED
+    portal_request(&conn, "SelectSources", &token, &(session.as_ref(), opts))?;
+
+    let token = format!("sis_rec_{}_start", stamp);
+    let mut opts: HashMap<&str, Value> = HashMap::new();
+    opts.insert("handle_token", Value::from(token.as_str()));
+    let res = portal_request(&conn, "Start", &token, &(session.as_ref(), "", opts))?;
+    // streams: a(ua{sv}) の先頭のノード ID
+    let node = match res.get("streams").map(|v| &**v) {
+        Some(Value::Array(a)) => a.iter().find_map(|v| match v {
+            Value::Structure(s) => match s.fields().first() { Some(Value::U32(n)) => Some(*n), _ => None },
+            _ => None,
+        }),
+        _ => None,
+    }
+    .ok_or("portal-no-stream")?;
+    Ok((conn, session, node))
+}
+
+fn close_portal(portal: Option<(Connection, OwnedObjectPath)>) {
+    if let Some((conn, session)) = portal {
+        if let Ok(p) = Proxy::new(&conn, PORTAL, session.as_str(), "org.freedesktop.portal.Session") {
+            let _ = p.call::<_, _, ()>("Close", &());
+        }
+    }
+}
+
+// ---- 起動/監視 ----
+
+fn ffmpeg_log() -> Stdio {
+    history_dir()
+        .and_then(|d| { let _ = fs::create_dir_all(&d); fs::File::create(d.join("recorder.log")).ok() })
+        .map(Stdio::from)
+        .unwrap_or_else(Stdio::null)
+}
+
+fn launch(opts: &RecordOptions, region: Option<(i32, i32, u32, u32)>) -> Result<(), String> {
+    if !which("ffmpeg") { return Err("ffmpeg-not-found".into()); }
+    let fps = opts.framerate.unwrap_or(DEFAULT_FPS).clamp(5, 60);
+    let mic = opts.mic.unwrap_or(false);
+    let dir = videos_dir().ok_or("no-videos-dir")?;
+    fs::create_dir_all(&dir).map_err(|e| format!("mkdir-failed: {}", e))?;
+    let path = dir.join(format!("Recording_{}.mp4", chrono::Local::now().format("%Y-%m-%d_%H-%M-%S")));
+
+    let mut args: Vec<String> = vec!["-hide_banner".into(), "-loglevel".into(), "warning".into(), "-y".into()];
+    if screenshot::is_wayland() {
+        if !which("gst-launch-1.0") { return Err("gstreamer-not-found".into()); }
+        let (conn, session, node) = portal_screencast(opts.mode == "window")?;
+        let caps = format!("video/x-raw,format=I420,framerate={}/1", fps);
+        let node_arg = format!("path={}", node);
+        let spawned = Command::new("gst-launch-1.0")
+            .args(["-q", "pipewiresrc", &node_arg, "do-timestamp=true", "keepalive-time=1000", "!", "videoconvert", "!", "videorate", "!", &caps, "!", "y4menc", "!", "fdsink", "fd=1"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn();
+        let mut child = match spawned {
+            Ok(c) => c,
+            Err(e) => { close_portal(Some((conn, session))); return Err(format!("gst-launch-failed: {}", e)); }
+        };
+        args.extend(["-f", "yuv4mpegpipe", "-thread_queue_size", "512", "-i", "pipe:0"].map(String::from));
+        // 範囲はストリーム上で切り抜く
+        let filter = region.map(|(x, y, w, h)| format!("crop={}:{}:{}:{}", even(w), even(h), x.max(0), y.max(0)));
+        let stdin = child.stdout.take().map(Stdio::from).unwrap_or_else(Stdio::null);
+        return spawn_ffmpeg(args, filter, mic, path, stdin, Some(child), Some((conn, session)), opts, "portal");
+    }
+
+    let display = std::env::var("DISPLAY").map_err(|_| "no-display".to_string())?;
+    let (x, y, w, h) = match (opts.mode.as_str(), region) {
+        ("region", Some(r)) => r,
+        ("window", _) => {
+            let id = opts.window_id.clone().or_else(|| {
+                run_out("xprop", &["-root", "_NET_ACTIVE_WINDOW"]).and_then(|s| s.split_whitespace().last().map(|s| s.trim_end_matches(',').to_string()))
+            }).ok_or("no-active-window")?;
+            screenshot::x11_window_geometry(&id).ok_or("window-not-found")?
+        }
+        _ => { let (w, h) = x11_screen_size().ok_or("no-screen-size")?; (0, 0, w, h) }
+    };
+    args.extend(["-f", "x11grab", "-thread_queue_size", "512", "-framerate"].map(String::from));
+    args.push(fps.to_string());
+    args.extend(["-draw_mouse", "1", "-video_size"].map(String::from));
+    args.push(format!("{}x{}", even(w), even(h)));
+    args.push("-i".into());
+    args.push(format!("{}+{},{}", display, x.max(0), y.max(0)));
+    spawn_ffmpeg(args, None, mic, path, Stdio::null(), None, None, opts, "x11")
+}
+
+#[allow(clippy::too_many_arguments)]
+fn spawn_ffmpeg(mut args: Vec<String>, filter: Option<String>, mic: bool, path: PathBuf, stdin: Stdio, mut source: Option<Child>, portal: Option<(Connection, OwnedObjectPath)>, opts: &RecordOptions, backend: &str) -> Result<(), String> {
+    if mic { args.extend(["-f", "pulse", "-thread_queue_size", "512", "-i", "default"].map(String::from)); }
+    if let Some(f) = filter { args.push("-vf".into()); args.push(f); }
+    args.extend(["-c:v", "libx264", "-preset", "veryfast", "-crf", "23", "-pix_fmt", "yuv420p"].map(String::from));
+    if mic { args.extend(["-c:a", "aac", "-b:a", "128k"].map(String::from)); }
+    args.extend(["-movflags", "+faststart"].map(String::from));
+    args.push(path.to_string_lossy().to_string());
+
+    let cleanup = |source: &mut Option<Child>, portal: Option<(Connection, OwnedObjectPath)>| {
+        if let Some(mut s) = source.take() { let _ = s.kill(); let _ = s.wait(); }
+        close_portal(portal);
+    };
+    let mut ffmpeg = match Command::new("ffmpeg").args(&args).stdin(stdin).stdout(Stdio::null()).stderr(ffmpeg_log()).spawn() {
+        Ok(c) => c,
+        Err(e) => { cleanup(&mut source, portal); return Err(format!("ffmpeg-failed: {}", e)); }
+    };
+    std::thread::sleep(STARTUP_GRACE);
+    if let Ok(Some(st)) = ffmpeg.try_wait() {
+        cleanup(&mut source, portal);
+        let _ = fs::remove_file(&path);
+        return Err(format!("ffmpeg-exited: {}", st.code().unwrap_or(-1)));
+    }
+    log_append("INFO", &format!("recorder: started {} ({}) mic={} -> {}", opts.mode, backend, mic, path.display()));
+    *SESSION.lock().unwrap() = Some(Session { ffmpeg, source, portal, stop_requested: None });
+    let (mode, backend, p) = (opts.mode.clone(), backend.to_string(), path.to_string_lossy().to_string());
+    update(|s| {
+        *s = RecorderStatus { state: "recording".into(), mode: Some(mode), path: Some(p), started_at_ms: Some(chrono::Local::now().timestamp_millis()), mic, backend: Some(backend), error: None };
+    });
+    Ok(())
+}
+
+/// ffmpeg の終了を待ち、片付けて idle に戻す
+fn monitor() {
+    loop {
+        std::thread::sleep(POLL);
+        let mut guard = SESSION.lock().unwrap();
+        let Some(sess) = guard.as_mut() else { return };
+        if let Some(t) = sess.stop_requested {
+            if t.elapsed() > STOP_TIMEOUT { let _ = sess.ffmpeg.kill(); }
+        }
+        let exited = match sess.ffmpeg.try_wait() { Ok(Some(st)) => Some(st.success()), Ok(None) => None, Err(_) => Some(false) };
+        let Some(success) = exited else { continue };
+        let mut sess = guard.take().unwrap();
+        drop(guard);
+        let requested = sess.stop_requested.is_some();
+        if let Some(mut s) = sess.source.take() { let _ = s.kill(); let _ = s.wait(); }
+        close_portal(sess.portal.take());
+        let path = STATUS.lock().unwrap().path.clone();
+        if requested || success {
+            log_append("INFO", &format!("recorder: saved {:?}", path));
+            update(|s| *s = RecorderStatus::idle());
+            if let Some(p) = path {
+                crate::notifications::post("SIS", "録画を保存しました", &p, Some("media-record"), "normal", Vec::new(), None);
+            }
+        } else {
+            fail("ffmpeg-exited-unexpectedly");
+        }
+        return;
+    }
+}
+
+fn run(opts: RecordOptions, region: Option<(i32, i32, u32, u32)>) {
+    update(|s| { s.state = "starting".into(); s.mode = Some(opts.mode.clone()); s.error = None; });
+    match launch(&opts, region) {
+        Ok(()) => monitor(),
+        Err(e) => fail(&e),
+    }
+}
+
+/// 範囲選択オーバーレイで確定した領域で録画を始める（screenshot から呼ばれる）
+pub fn start_region(opts: RecordOptions, region: (i32, i32, u32, u32)) -> Result<(), String> {
+    if exam_mode() { cancel_selection(); return Err("exam-mode".into()); }
+    transition("selecting", "starting", &opts.mode).map_err(|_| "not-selecting".to_string())?;
+    std::thread::spawn(move || run(opts, Some(region)));
+    Ok(())
+}
+
+pub fn cancel_selection() {
+    if STATUS.lock().unwrap().state == "selecting" { update(|s| *s = RecorderStatus::idle()); }
+}
+
+pub fn start(app_handle: tauri::AppHandle) {
+    *APP.lock().unwrap() = Some(app_handle);
+}
+
+#[tauri::command]
+pub fn recorder_status() -> Result<RecorderStatus, SisError> { Ok(STATUS.lock().unwrap().clone()) }
+
+#[tauri::command]
+pub fn recorder_start(mode: String, window_id: Option<String>, mic: Option<bool>, framerate: Option<u32>) -> Result<String, SisError> {
+    if exam_mode() { return Err("exam-mode".into()); }
+    if !matches!(mode.as_str(), "full" | "window" | "region") { return Err(SisError::not_supported("unsupported-mode", mode)); }
+    if !which("ffmpeg") { return Err("ffmpeg-not-found".into()); }
+    transition("idle", if mode == "region" { "selecting" } else { "starting" }, &mode)?;
+    let opts = RecordOptions { mode, window_id, mic, framerate };
+    if opts.mode == "region" {
+        std::thread::spawn(move || {
+            if let Err(e) = screenshot::select_region(RegionTarget::Record(opts)) { fail(&e); }
+        });
+        return Ok("selecting".into());
+    }
+    std::thread::spawn(move || run(opts, None));
+    Ok("starting".into())
+}
+
+/// ffmpeg に SIGINT を送り、mp4 を書き終えるのを待つ（監視スレッドが idle に戻す）
+#[tauri::command]
+pub fn recorder_stop() -> Result<String, SisError> {
+    let mut guard = SESSION.lock().unwrap();
+    let sess = guard.as_mut().ok_or("not-recording")?;
+    if sess.stop_requested.is_some() { return Ok("stopping".into()); }
+    sess.stop_requested = Some(Instant::now());
+    // SAFETY: 自分が起動した ffmpeg の pid にシグナルを送るだけ
+    if unsafe { libc::kill(sess.ffmpeg.id() as libc::pid_t, libc::SIGINT) } != 0 {
+        let _ = sess.ffmpeg.kill();
+    }
+    drop(guard);
+    update(|s| s.state = "stopping".into());
+    Ok("stopping".into())
+}
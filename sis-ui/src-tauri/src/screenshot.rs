@@ -0,0 +1,457 @@
+// スクリーンショット: X11 では x11rb の GetImage でルートウィンドウを読み（ウィンドウは切り抜く）、Wayland では xdg-desktop-portal を使う
+// 範囲選択は全画面を撮ってから "capture" ウィンドウ（最前面オーバーレイ）で矩形を選ばせて切り抜く。/tmp/sis-exam-mode がある間は撮影しない
+use std::collections::HashMap;
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use base64::Engine;
+use image::{DynamicImage, ImageFormat, RgbaImage};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tauri::{Emitter, Manager};
+use zbus::blocking::{Connection, Proxy};
+use zbus::zvariant::{OwnedObjectPath, OwnedValue, Value};
+
+use crate::error::SisError;
+use crate::{exam_mode, log_append, run_out, which};
+
+const PORTAL: &str = "org.freedesktop.portal.Desktop";
+const PORTAL_PATH: &str = "/org/freedesktop/portal/desktop";
+const MAX_DELAY_SECS: u32 = 60;
+const THUMB_WIDTH: u32 = 360;
+// ウィンドウを前面に出してから描画が落ち着くまで待つ
+const RAISE_SETTLE: Duration = Duration::from_millis(250);
+
+static APP: Lazy<Mutex<Option<tauri::AppHandle>>> = Lazy::new(|| Mutex::new(None));
+// 範囲選択中の全画面画像と、確定後に何をするか
+static PENDING: Lazy<Mutex<Option<(RgbaImage, RegionTarget)>>> = Lazy::new(|| Mutex::new(None));
+static BUSY: Lazy<Mutex<bool>> = Lazy::new(|| Mutex::new(false));
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CaptureOptions {
+    pub mode: String, // full | window | region
+    pub window_id: Option<String>,
+    pub delay_secs: Option<u32>,
+    pub copy: Option<bool>, // 既定 true
+    pub save: Option<bool>, // 既定 true
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Screenshot {
+    mode: String,
+    path: Option<String>,
+    width: u32,
+    height: u32,
+    copied: bool,
+    thumbnail: Option<String>,
+    backend: String, // x11 | portal
+}
+
+/// 範囲選択の用途（撮影 or 録画）
+pub enum RegionTarget {
+    Screenshot(CaptureOptions),
+    Record(crate::recorder::RecordOptions),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+struct RegionRequest {
+    image: String, // 選択用の全画面画像（data URL）
+    width: u32,
+    height: u32,
+}
+
+pub fn is_wayland() -> bool {
+    std::env::var("WAYLAND_DISPLAY").is_ok() || std::env::var("XDG_SESSION_TYPE").map(|s| s == "wayland").unwrap_or(false)
+}
+
+pub fn screenshots_dir() -> Option<PathBuf> {
+    let pics = dirs::picture_dir().or_else(|| dirs::home_dir().map(|h| h.join("Pictures")))?;
+    Some(pics.join("Screenshots"))
+}
+
+// ---- X11（x11rb の GetImage でルートウィンドウを読む） ----
+
+fn mask_channel(px: u32, mask: u32) -> u8 {
+    if mask == 0 { return 0; }
+    let shift = mask.trailing_zeros();
+    let max = mask >> shift;
+    (((px & mask) >> shift) * 255 / max.max(1)) as u8
+}
+
+/// ZPixmap の画素の並び
+struct PixelLayout {
+    bits_per_pixel: u32,
+    stride: usize, // 1 行のバイト数（scanline_pad で揃えた長さ）
+    lsb_first: bool,
+    masks: (u32, u32, u32),
+}
+
+/// ZPixmap（TrueColor 24/32bpp）を RGBA に変換する
+fn zpixmap_to_rgba(data: &[u8], width: u32, height: u32, layout: &PixelLayout) -> Result<RgbaImage, String> {
+    if layout.bits_per_pixel != 24 && layout.bits_per_pixel != 32 { return Err(format!("unsupported-depth: {}", layout.bits_per_pixel)); }
+    let step = (layout.bits_per_pixel / 8) as usize;
+    if layout.stride < width as usize * step || data.len() < layout.stride * height as usize { return Err("x11-image-truncated".into()); }
+    let (rmask, gmask, bmask) = layout.masks;
+    let mut img = RgbaImage::new(width, height);
+    for y in 0..height as usize {
+        let row = &data[y * layout.stride..];
+        for x in 0..width as usize {
+            let p = &row[x * step..x * step + step];
+            let px = match (step, layout.lsb_first) {
+                (4, true) => u32::from_le_bytes([p[0], p[1], p[2], p[3]]),
+                (4, false) => u32::from_be_bytes([p[0], p[1], p[2], p[3]]),
+                (_, true) => u32::from_le_bytes([p[0], p[1], p[2], 0]),
+                (_, false) => u32::from_be_bytes([0, p[0], p[1], p[2]]),
+            };
+            img.put_pixel(x as u32, y as u32, image::Rgba([mask_channel(px, rmask), mask_channel(px, gmask), mask_channel(px, bmask), 255]));
+        }
+    }
+    Ok(img)
+}
+
+fn x11_capture_root() -> Result<RgbaImage, String> {
+    use x11rb::connection::Connection as _;
+    use x11rb::protocol::xproto::{self, ConnectionExt as _};
+
+    fn err(e: impl std::fmt::Display) -> String { format!("x11-failed: {}", e) }
+    let (conn, screen_num) = x11rb::connect(None).map_err(|e| format!("x11-connect-failed: {}", e))?;
+    let setup = conn.setup();
+    let screen = setup.roots.get(screen_num).ok_or("x11-no-screen")?;
+    let (width, height) = (screen.width_in_pixels, screen.height_in_pixels);
+    let reply = conn.get_image(xproto::ImageFormat::Z_PIXMAP, screen.root, 0, 0, width, height, !0)
+        .map_err(err)?.reply().map_err(err)?;
+    let format = setup.pixmap_formats.iter().find(|f| f.depth == reply.depth).ok_or("x11-no-pixmap-format")?;
+    let visual = screen.allowed_depths.iter().flat_map(|d| d.visuals.iter()).find(|v| v.visual_id == reply.visual).ok_or("x11-no-visual")?;
+    let pad = format.scanline_pad.max(8) as usize;
+    let layout = PixelLayout {
+        bits_per_pixel: format.bits_per_pixel as u32,
+        stride: (width as usize * format.bits_per_pixel as usize).div_ceil(pad) * pad / 8,
+        lsb_first: setup.image_byte_order == xproto::ImageOrder::LSB_FIRST,
+        masks: (visual.red_mask, visual.green_mask, visual.blue_mask),
+    };
+    zpixmap_to_rgba(&reply.data, width as u32, height as u32, &layout)
+}
+
+/// xwininfo から枠を含む絶対座標を得る
+pub fn x11_window_geometry(id: &str) -> Option<(i32, i32, u32, u32)> {
+    let text = run_out("xwininfo", &["-id", id])?;
+    let field = |k: &str| -> Option<i64> {
+        text.lines().find_map(|l| l.trim().strip_prefix(k).and_then(|v| v.trim().parse().ok()))
+    };
+    let (x, y) = (field("Absolute upper-left X:")?, field("Absolute upper-left Y:")?);
+    let (w, h) = (field("Width:")?, field("Height:")?);
+    let border = field("Border width:").unwrap_or(0);
+    // 装飾（WM フレーム）分を _NET_FRAME_EXTENTS で広げる
+    let (mut l, mut r, mut t, mut b) = (0i64, 0i64, 0i64, 0i64);
+    if let Some(raw) = run_out("xprop", &["-id", id, "_NET_FRAME_EXTENTS"]) {
+        let nums: Vec<i64> = raw.split('=').nth(1).unwrap_or("").split(',').filter_map(|s| s.trim().parse().ok()).collect();
+        if nums.len() == 4 { (l, r, t, b) = (nums[0], nums[1], nums[2], nums[3]); }
+    }
+    Some(((x - border - l) as i32, (y - border - t) as i32, (w + 2 * border + l + r).max(1) as u32, (h + 2 * border + t + b).max(1) as u32))
+}
+
+fn crop(img: &RgbaImage, x: i32, y: i32, w: u32, h: u32) -> Result<RgbaImage, String> {
+    let x0 = x.max(0) as u32;
+    let y0 = y.max(0) as u32;
+    let x1 = ((x as i64 + w as i64).max(0) as u32).min(img.width());
+    let y1 = ((y as i64 + h as i64).max(0) as u32).min(img.height());
+    if x1 <= x0 || y1 <= y0 { return Err("empty-region".into()); }
+    Ok(image::imageops::crop_imm(img, x0, y0, x1 - x0, y1 - y0).to_image())
+}
+
+// ---- Wayland（xdg-desktop-portal） ----
+
+fn uri_to_path(uri: &str) -> PathBuf {
+    let raw = uri.strip_prefix("file://").unwrap_or(uri).as_bytes();
+    let mut out = Vec::with_capacity(raw.len());
+    let mut i = 0;
+    while i < raw.len() {
+        if raw[i] == b'%' && i + 2 < raw.len() {
+            if let Ok(b) = u8::from_str_radix(std::str::from_utf8(&raw[i + 1..i + 3]).unwrap_or(""), 16) {
+                out.push(b);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(raw[i]);
+        i += 1;
+    }
+    PathBuf::from(String::from_utf8_lossy(&out).to_string())
+}
+
+/// Screenshot ポータルを呼び、Request の Response シグナルで結果の URI を受け取る
+fn portal_capture(interactive: bool) -> Result<RgbaImage, String> {
+    let conn = Connection::session().map_err(|e| format!("dbus-session-failed: {}", e))?;
+    let token = format!("sis_shot_{}", chrono::Local::now().timestamp_millis());
+    let sender = conn.unique_name().ok_or("dbus-no-unique-name")?.as_str().trim_start_matches(':').replace('.', "_");
+    let req_path = format!("{}/request/{}/{}", PORTAL_PATH, sender, token);
+    let request = Proxy::new(&conn, PORTAL, req_path.as_str(), "org.freedesktop.portal.Request").map_err(|e| e.to_string())?;
+    // 競合を避けるため、呼び出し前に Response を購読しておく
+    let mut responses = request.receive_signal("Response").map_err(|e| e.to_string())?;
+    let portal = Proxy::new(&conn, PORTAL, PORTAL_PATH, "org.freedesktop.portal.Screenshot").map_err(|e| e.to_string())?;
+    let mut opts: HashMap<&str, Value> = HashMap::new();
+    opts.insert("handle_token", Value::from(token.as_str()));
+    opts.insert("interactive", Value::from(interactive));
+    portal.call::<_, _, OwnedObjectPath>("Screenshot", &("", opts)).map_err(|e| format!("portal-failed: {}", e))?;
+    let msg = responses.next().ok_or("portal-no-response")?;
+    let (code, results): (u32, HashMap<String, OwnedValue>) = msg.body().deserialize().map_err(|e| e.to_string())?;
+    if code != 0 { return Err(if code == 1 { "cancelled".into() } else { format!("portal-response: {}", code) }); }
+    let uri = results.get("uri").and_then(|v| v.try_clone().ok()).and_then(|v| String::try_from(v).ok()).ok_or("portal-no-uri")?;
+    let path = uri_to_path(&uri);
+    let img = image::open(&path).map(|i| i.to_rgba8()).map_err(|e| format!("decode-failed: {}", e))?;
+    // ポータルは ~/Pictures 等に一時保存するので、こちらで保存し直す前提で消しておく
+    let _ = fs::remove_file(&path);
+    Ok(img)
+}
+
+// ---- 共通 ----
+
+fn capture_full() -> Result<(RgbaImage, &'static str), String> {
+    if is_wayland() { portal_capture(false).map(|i| (i, "portal")) } else { x11_capture_root().map(|i| (i, "x11")) }
+}
+
+fn capture_window(id: Option<&str>) -> Result<(RgbaImage, &'static str), String> {
+    if is_wayland() {
+        // Wayland では他ウィンドウの座標が取れないので、ポータルの対話モードでウィンドウを選ばせる
+        return portal_capture(true).map(|i| (i, "portal"));
+    }
+    let id = match id {
+        Some(i) => i.to_string(),
+        None => run_out("xprop", &["-root", "_NET_ACTIVE_WINDOW"])
+            .and_then(|s| s.split_whitespace().last().map(|s| s.trim_end_matches(',').to_string()))
+            .ok_or("no-active-window")?,
+    };
+    if which("wmctrl") {
+        let _ = Command::new("wmctrl").args(["-i", "-a", &id]).status();
+        std::thread::sleep(RAISE_SETTLE);
+    }
+    let (x, y, w, h) = x11_window_geometry(&id).ok_or("window-not-found")?;
+    let root = x11_capture_root()?;
+    Ok((crop(&root, x, y, w, h)?, "x11"))
+}
+
+fn encode_png(img: &RgbaImage) -> Result<Vec<u8>, String> {
+    let mut buf = Cursor::new(Vec::new());
+    DynamicImage::ImageRgba8(img.clone()).write_to(&mut buf, ImageFormat::Png).map_err(|e| format!("encode-failed: {}", e))?;
+    Ok(buf.into_inner())
+}
+
+pub fn thumbnail_data_url(img: &RgbaImage) -> Option<String> {
+    let dynimg = DynamicImage::ImageRgba8(img.clone());
+    let t = if img.width() > THUMB_WIDTH { dynimg.thumbnail(THUMB_WIDTH, THUMB_WIDTH * img.height() / img.width().max(1)) } else { dynimg };
+    let png = encode_png(&t.to_rgba8()).ok()?;
+    Some(format!("data:image/png;base64,{}", base64::engine::general_purpose::STANDARD.encode(png)))
+}
+
+/// PNG をクリップボードへ。xclip / wl-copy はバックグラウンドで所有し続ける
+pub fn copy_png(png: &[u8]) -> Result<(), String> {
+    let mut cmd = if is_wayland() && which("wl-copy") {
+        let mut c = Command::new("wl-copy");
+        c.args(["--type", "image/png"]);
+        c
+    } else if which("xclip") {
+        let mut c = Command::new("xclip");
+        c.args(["-selection", "clipboard", "-t", "image/png", "-i"]);
+        c
+    } else {
+        return Err("no-clipboard-tool".into());
+    };
+    let mut child = cmd.stdin(Stdio::piped()).stdout(Stdio::null()).stderr(Stdio::null()).spawn().map_err(|e| format!("clipboard-failed: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        use std::io::Write;
+        stdin.write_all(png).map_err(|e| format!("clipboard-failed: {}", e))?;
+    }
+    let _ = child.wait();
+    Ok(())
+}
+
+fn finish(img: RgbaImage, opts: &CaptureOptions, backend: &str) -> Result<Screenshot, String> {
+    let png = encode_png(&img)?;
+    let mut path = None;
+    if opts.save.unwrap_or(true) {
+        let dir = screenshots_dir().ok_or("no-pictures-dir")?;
+        fs::create_dir_all(&dir).map_err(|e| format!("mkdir-failed: {}", e))?;
+        let stamp = chrono::Local::now().format("%Y-%m-%d_%H-%M-%S");
+        let mut p = dir.join(format!("Screenshot_{}.png", stamp));
+        let mut n = 2;
+        while p.exists() { p = dir.join(format!("Screenshot_{}_{}.png", stamp, n)); n += 1; }
+        fs::write(&p, &png).map_err(|e| format!("write-failed: {}", e))?;
+        path = Some(p.to_string_lossy().to_string());
+    }
+    let copied = opts.copy.unwrap_or(true) && match copy_png(&png) {
+        Ok(()) => true,
+        Err(e) => { log_append("WARN", &format!("screenshot: copy failed: {}", e)); false }
+    };
+    let shot = Screenshot { mode: opts.mode.clone(), path, width: img.width(), height: img.height(), copied, thumbnail: thumbnail_data_url(&img), backend: backend.to_string() };
+    log_append("INFO", &format!("screenshot: {} {}x{} saved={:?} copied={}", shot.mode, shot.width, shot.height, shot.path, copied));
+    if let Some(app) = APP.lock().unwrap().as_ref() { let _ = app.emit("sis:screenshot-taken", &shot); }
+    Ok(shot)
+}
+
+fn emit_failed(err: &str) {
+    log_append("WARN", &format!("screenshot: {}", err));
+    if let Some(app) = APP.lock().unwrap().as_ref() { let _ = app.emit("sis:screenshot-failed", err); }
+}
+
+fn show_capture_window(app: &tauri::AppHandle, show: bool) {
+    if let Some(w) = app.get_webview_window("capture") {
+        if show { let _ = w.show(); let _ = w.set_focus(); } else { let _ = w.hide(); }
+    }
+}
+
+fn run_capture(opts: CaptureOptions) -> Result<Option<Screenshot>, String> {
+    match opts.mode.as_str() {
+        "full" => { let (img, b) = capture_full()?; finish(img, &opts, b).map(Some) }
+        "window" => { let (img, b) = capture_window(opts.window_id.as_deref())?; finish(img, &opts, b).map(Some) }
+        "region" => { select_region(RegionTarget::Screenshot(opts))?; Ok(None) }
+        other => Err(format!("unsupported-mode: {}", other)),
+    }
+}
+
+/// 全画面を撮ってオーバーレイで範囲を選ばせる。確定は screenshot_region_commit
+pub fn select_region(target: RegionTarget) -> Result<(), String> {
+    let (img, _) = capture_full()?;
+    let req = RegionRequest { image: format!("data:image/png;base64,{}", base64::engine::general_purpose::STANDARD.encode(encode_png(&img)?)), width: img.width(), height: img.height() };
+    *PENDING.lock().unwrap() = Some((img, target));
+    let app = APP.lock().unwrap().clone().ok_or("not-started")?;
+    let _ = app.emit_to("capture", "sis:screenshot-select", &req);
+    show_capture_window(&app, true);
+    Ok(())
+}
+
+/// 撮影（遅延があれば別スレッドで待つ）。結果は sis:screenshot-taken / sis:screenshot-failed で届く
+pub fn capture(opts: CaptureOptions) -> Result<String, String> {
+    if exam_mode() { return Err("exam-mode".into()); }
+    {
+        let mut busy = BUSY.lock().unwrap();
+        if *busy { return Err("busy".into()); }
+        *busy = true;
+    }
+    let delay = opts.delay_secs.unwrap_or(0).min(MAX_DELAY_SECS);
+    std::thread::spawn(move || {
+        if delay > 0 { std::thread::sleep(Duration::from_secs(delay as u64)); }
+        // 待っている間に試験モードになったら撮らない
+        let res = if exam_mode() { Err("exam-mode".to_string()) } else { run_capture(opts) };
+        if let Err(e) = res { emit_failed(&e); }
+        *BUSY.lock().unwrap() = false;
+    });
+    Ok(if delay > 0 { format!("scheduled: {}s", delay) } else { "capturing".into() })
+}
+
+pub fn start(app_handle: tauri::AppHandle) {
+    *APP.lock().unwrap() = Some(app_handle);
+}
+
+#[tauri::command]
+pub fn screenshot_capture(mode: String, window_id: Option<String>, delay_secs: Option<u32>, copy: Option<bool>, save: Option<bool>) -> Result<String, SisError> {
+    Ok(capture(CaptureOptions { mode, window_id, delay_secs, copy, save })?)
+}
+
+/// 範囲選択の確定（座標は撮影画像のピクセル単位）
+#[tauri::command]
+pub fn screenshot_region_commit(x: i32, y: i32, width: u32, height: u32) -> Result<Option<Screenshot>, SisError> {
+    let pending = PENDING.lock().unwrap().take();
+    if let Some(app) = APP.lock().unwrap().as_ref() { show_capture_window(app, false); }
+    let (img, target) = pending.ok_or("no-pending-region")?;
+    if exam_mode() {
+        if matches!(target, RegionTarget::Record(_)) { crate::recorder::cancel_selection(); }
+        return Err("exam-mode".into());
+    }
+    match target {
+        RegionTarget::Screenshot(opts) => {
+            let region = crop(&img, x, y, width, height)?;
+            let backend = if is_wayland() { "portal" } else { "x11" };
+            Ok(finish(region, &opts, backend).map(Some)?)
+        }
+        RegionTarget::Record(opts) => {
+            // 画面外にはみ出した分を丸めてから録画を始める
+            let x0 = x.clamp(0, img.width() as i32);
+            let y0 = y.clamp(0, img.height() as i32);
+            let w = width.min(img.width() - x0 as u32);
+            let h = height.min(img.height() - y0 as u32);
+            crate::recorder::start_region(opts, (x0, y0, w, h))?;
+            Ok(None)
+        }
+    }
+}
+
+#[tauri::command]
+pub fn screenshot_region_cancel() -> Result<String, SisError> {
+    if let Some((_, RegionTarget::Record(_))) = PENDING.lock().unwrap().take() { crate::recorder::cancel_selection(); }
+    if let Some(app) = APP.lock().unwrap().as_ref() { show_capture_window(app, false); }
+    Ok("cancelled".into())
+}
+
+#[tauri::command]
+pub fn screenshot_copy(path: String) -> Result<String, SisError> {
+    let p = Path::new(&path);
+    if p.extension().and_then(|e| e.to_str()) != Some("png") { return Err("not-png".into()); }
+    let png = fs::read(p).map_err(|e| format!("read-failed: {}", e))?;
+    copy_png(&png)?;
+    Ok("copied".into())
+}
+
+/// 撮影直後のポップアップからの削除（Screenshots フォルダ内のみ）
+#[tauri::command]
+pub fn screenshot_delete(path: String) -> Result<String, SisError> {
+    let dir = screenshots_dir().ok_or("no-pictures-dir")?;
+    let p = PathBuf::from(&path);
+    if p.parent() != Some(dir.as_path()) { return Err("outside-screenshots-dir".into()); }
+    fs::remove_file(&p).map_err(|e| format!("delete-failed: {}", e))?;
+    Ok("deleted".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RGB_MASKS: (u32, u32, u32) = (0x00ff_0000, 0x0000_ff00, 0x0000_00ff);
+
+    #[test]
+    fn zpixmap_32bpp_lsb_first_with_row_padding() {
+        // 2x2、1 行 12 バイト（末尾 4 バイトは詰め物）
+        let mut data = Vec::new();
+        for row in [[0x00ff_0000u32, 0x0000_ff00], [0x0000_00ff, 0x0012_3456]] {
+            for px in row { data.extend_from_slice(&px.to_le_bytes()); }
+            data.extend_from_slice(&[0xaa; 4]);
+        }
+        let layout = PixelLayout { bits_per_pixel: 32, stride: 12, lsb_first: true, masks: RGB_MASKS };
+        let img = zpixmap_to_rgba(&data, 2, 2, &layout).unwrap();
+        assert_eq!(img.get_pixel(0, 0).0, [255, 0, 0, 255]);
+        assert_eq!(img.get_pixel(1, 0).0, [0, 255, 0, 255]);
+        assert_eq!(img.get_pixel(0, 1).0, [0, 0, 255, 255]);
+        assert_eq!(img.get_pixel(1, 1).0, [0x12, 0x34, 0x56, 255]);
+    }
+
+    #[test]
+    fn zpixmap_24bpp_msb_first() {
+        let data = [0x12, 0x34, 0x56, 0xff, 0x00, 0x00];
+        let layout = PixelLayout { bits_per_pixel: 24, stride: 6, lsb_first: false, masks: RGB_MASKS };
+        let img = zpixmap_to_rgba(&data, 2, 1, &layout).unwrap();
+        assert_eq!(img.get_pixel(0, 0).0, [0x12, 0x34, 0x56, 255]);
+        assert_eq!(img.get_pixel(1, 0).0, [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn zpixmap_scales_16bit_style_masks() {
+        // RGB565 の各チャンネルを 8 bit に広げる（16bpp 自体は未対応なので 32bpp に入れて確かめる）
+        let px: u32 = 0xf800;
+        let layout = PixelLayout { bits_per_pixel: 32, stride: 4, lsb_first: true, masks: (0xf800, 0x07e0, 0x001f) };
+        let img = zpixmap_to_rgba(&px.to_le_bytes(), 1, 1, &layout).unwrap();
+        assert_eq!(img.get_pixel(0, 0).0, [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn zpixmap_rejects_bad_input() {
+        let layout = PixelLayout { bits_per_pixel: 16, stride: 4, lsb_first: true, masks: RGB_MASKS };
+        assert_eq!(zpixmap_to_rgba(&[0; 8], 2, 1, &layout).unwrap_err(), "unsupported-depth: 16");
+        let layout = PixelLayout { bits_per_pixel: 32, stride: 8, lsb_first: true, masks: RGB_MASKS };
+        assert_eq!(zpixmap_to_rgba(&[0; 12], 2, 2, &layout).unwrap_err(), "x11-image-truncated");
+        let layout = PixelLayout { bits_per_pixel: 32, stride: 4, lsb_first: true, masks: RGB_MASKS };
+        assert_eq!(zpixmap_to_rgba(&[0; 16], 2, 2, &layout).unwrap_err(), "x11-image-truncated");
+    }
+}
@@ -0,0 +1,368 @@
+// Per-app screen time: アクティブウィンドウの使用時間をアプリ/カテゴリ単位で集計し、日次上限を適用する
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+
+use crate::error::SisError;
+use crate::{build_app_dirs, desktop_hidden_or_settings, history_dir, log_append, match_desktop_to_window, parse_desktop_fields, run_out, which};
+
+const TICK: Duration = Duration::from_secs(5);
+const SAVE_EVERY_TICKS: u32 = 6;
+const IDLE_CUTOFF_MS: u64 = 5 * 60 * 1000;
+// 残り時間の警告しきい値（秒）: 10分前と1分前
+const WARN_AT: [u64; 2] = [600, 60];
+// SIS 自身のウィンドウの WM_CLASS（インスタンス名）。開発ビルドなど名前が違う場合は _NET_WM_PID で判定する
+const SIS_WM_CLASSES: [&str; 2] = ["sis-ui", "lorinta.sis-ui"];
+
+const POLICY_PATH: &str = "/var/lib/sis-mdm/policies.json";
+// 保護者/教員が管理するローカルプロファイル（root所有を想定）
+const PROFILE_PATH: &str = "/etc/sis/screentime.json";
+
+// desktop entry の Main Categories（最初に見つかったものを採用）
+const MAIN_CATEGORIES: [&str; 13] = [
+    "AudioVideo", "Audio", "Video", "Development", "Education", "Game", "Graphics",
+    "Network", "Office", "Science", "Settings", "System", "Utility",
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ScreenTimeLimits {
+    #[serde(default)]
+    app_limits: HashMap<String, u32>,      // key(lowercase) -> minutes/day
+    #[serde(default)]
+    category_limits: HashMap<String, u32>, // desktop category -> minutes/day
+    #[serde(default)]
+    over_limit_action: Option<String>,     // "minimize" | "close"
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+struct UsageDay {
+    date: String,
+    apps: HashMap<String, u64>,       // key -> seconds
+    categories: HashMap<String, u64>, // category -> seconds
+    names: HashMap<String, String>,   // key -> display name
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScreenTimeEntry {
+    key: String,
+    name: String,
+    kind: String, // "app" | "category"
+    used_secs: u64,
+    limit_secs: Option<u64>,
+    remaining_secs: Option<u64>,
+    blocked: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScreenTimeState {
+    date: String,
+    active: Option<String>,
+    active_name: Option<String>,
+    active_remaining_secs: Option<u64>, // 前面のアプリの残り時間（上限がなければ None）。TopBar に表示する
+    entries: Vec<ScreenTimeEntry>,
+}
+
+struct Tracker {
+    usage: UsageDay,
+    active: Option<String>,
+    active_name: Option<String>,
+    active_remaining: Option<u64>,
+    warned: HashSet<(String, u64)>, // (キー, しきい値)。0 は上限到達の通知
+}
+
+static TRACKER: Lazy<Mutex<Tracker>> = Lazy::new(|| Mutex::new(Tracker { usage: read_usage(), active: None, active_name: None, active_remaining: None, warned: HashSet::new() }));
+
+// exec basename / WM_CLASS -> category
+static CATEGORY_CACHE: Lazy<Mutex<HashMap<String, String>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn today() -> String { chrono::Local::now().format("%Y-%m-%d").to_string() }
+
+fn usage_path() -> Option<PathBuf> { history_dir().map(|d| d.join("screentime_usage.json")) }
+
+fn read_usage() -> UsageDay {
+    if let Some(p) = usage_path() {
+        if let Ok(s) = fs::read_to_string(&p) {
+            if let Ok(u) = serde_json::from_str::<UsageDay>(&s) {
+                if u.date == today() { return u; }
+            }
+        }
+    }
+    UsageDay { date: today(), ..Default::default() }
+}
+
+fn write_usage(u: &UsageDay) {
+    if let Some(dir) = history_dir() { let _ = fs::create_dir_all(&dir); }
+    if let Some(p) = usage_path() {
+        if let Ok(s) = serde_json::to_string_pretty(u) { let _ = fs::write(p, s); }
+    }
+}
+
+fn lower_keys(m: HashMap<String, u32>) -> HashMap<String, u32> {
+    m.into_iter().map(|(k, v)| (k.to_lowercase(), v)).collect()
+}
+
+/// MDMポリシー(screen_time.*)と保護者/教員プロファイルを合成。両方にある場合は厳しい方を採用
+pub fn read_limits() -> ScreenTimeLimits {
+    let mut out = ScreenTimeLimits::default();
+    let mut sources: Vec<ScreenTimeLimits> = Vec::new();
+    if let Ok(s) = fs::read_to_string(POLICY_PATH) {
+        if let Ok(v) = serde_json::from_str::<serde_json::Value>(&s) {
+            if let Some(st) = v.get("screen_time") {
+                if let Ok(l) = serde_json::from_value::<ScreenTimeLimits>(st.clone()) { sources.push(l); }
+            }
+        }
+    }
+    if let Ok(s) = fs::read_to_string(PROFILE_PATH) {
+        if let Ok(l) = serde_json::from_str::<ScreenTimeLimits>(&s) { sources.push(l); }
+    }
+    for src in sources {
+        for (k, v) in lower_keys(src.app_limits) {
+            let e = out.app_limits.entry(k).or_insert(v);
+            *e = (*e).min(v);
+        }
+        for (k, v) in src.category_limits {
+            let e = out.category_limits.entry(k).or_insert(v);
+            *e = (*e).min(v);
+        }
+        if out.over_limit_action.is_none() { out.over_limit_action = src.over_limit_action; }
+    }
+    out
+}
+
+fn build_category_cache() {
+    let mut cache = CATEGORY_CACHE.lock().unwrap();
+    cache.clear();
+    for dir in build_app_dirs() {
+        let read = match fs::read_dir(&dir) { Ok(r) => r, Err(_) => continue };
+        for entry in read.flatten() {
+            let p = entry.path();
+            if p.extension().map_or(true, |e| e != "desktop") { continue; }
+            let content = match fs::read_to_string(&p) { Ok(s) => s, Err(_) => continue };
+            if desktop_hidden_or_settings(&content) { continue; }
+            let cats = content.lines().find(|l| l.starts_with("Categories=")).and_then(|l| l.strip_prefix("Categories=")).unwrap_or("");
+            let main = match cats.split(';').find(|c| MAIN_CATEGORIES.contains(c)) { Some(c) => c.to_string(), None => continue };
+            let (name, exec, _icon, swm) = parse_desktop_fields(&content);
+            if !swm.is_empty() { cache.insert(swm.to_lowercase(), main.clone()); }
+            if let Some(k) = exec_key(&exec) { cache.insert(k, main.clone()); }
+            if !name.is_empty() { cache.insert(name.to_lowercase(), main); }
+        }
+    }
+}
+
+/// exec 行から集計キー（実行ファイル名の小文字）を取り出す
+pub fn exec_key(exec: &str) -> Option<String> {
+    let first = exec.split_whitespace().find(|t| !t.contains('=') && *t != "env")?;
+    let bn = std::path::Path::new(first.trim_matches('"')).file_name()?.to_str()?.to_lowercase();
+    if bn.is_empty() { None } else { Some(bn) }
+}
+
+fn category_for(keys: &[&str]) -> Option<String> {
+    let cache = CATEGORY_CACHE.lock().unwrap();
+    keys.iter().find_map(|k| cache.get(&k.to_lowercase()).cloned())
+}
+
+fn idle_ms() -> Option<u64> {
+    if !which("xprintidle") { return None; }
+    run_out("xprintidle", &[]).and_then(|s| s.trim().parse::<u64>().ok())
+}
+
+fn is_sis_window(id: &str, wclass: &str) -> bool {
+    let instance = wclass.split('.').next().unwrap_or("");
+    SIS_WM_CLASSES.iter().any(|c| instance.eq_ignore_ascii_case(c) || wclass.eq_ignore_ascii_case(c))
+        || crate::xprop_window_pid(id) == Some(std::process::id())
+}
+
+struct ActiveApp { window_id: String, key: String, name: String, category: Option<String>, title: String }
+
+fn detect_active() -> Option<ActiveApp> {
+    let id = crate::xprop_active_window_id()?;
+    let wclass = crate::xprop_window_wm_class(&id).unwrap_or_default();
+    let title = crate::xprop_window_title(&id).unwrap_or_default();
+    // SIS自身のウィンドウ（デスクトップ/ドック等）は集計しない
+    if is_sis_window(&id, &wclass) { return None; }
+    let (key, name) = match match_desktop_to_window(&wclass, &title) {
+        Some(app) => (exec_key(&app.exec).unwrap_or_else(|| app.name.to_lowercase()), app.name),
+        None => {
+            let k = wclass.split('.').next().unwrap_or("").to_lowercase();
+            if k.is_empty() { return None; }
+            (k, wclass.split('.').last().unwrap_or(&wclass).to_string())
+        }
+    };
+    let category = category_for(&[key.as_str(), wclass.as_str(), name.as_str()]);
+    Some(ActiveApp { window_id: id, key, name, category, title })
+}
+
+fn limit_secs(minutes: Option<&u32>) -> Option<u64> { minutes.map(|m| (*m as u64) * 60) }
+
+/// 指定キー（+カテゴリ）の残り秒数。上限なしなら None
+fn remaining_for(usage: &UsageDay, limits: &ScreenTimeLimits, key: &str, category: Option<&str>) -> Option<u64> {
+    let mut rem: Option<u64> = None;
+    if let Some(l) = limit_secs(limits.app_limits.get(key)) {
+        let used = usage.apps.get(key).copied().unwrap_or(0);
+        rem = Some(l.saturating_sub(used));
+    }
+    if let Some(cat) = category {
+        if let Some(l) = limit_secs(limits.category_limits.get(cat)) {
+            let used = usage.categories.get(cat).copied().unwrap_or(0);
+            let r = l.saturating_sub(used);
+            rem = Some(rem.map_or(r, |x| x.min(r)));
+        }
+    }
+    rem
+}
+
+/// ウィンドウタイトルに上限キーが含まれる場合（例: ブラウザ内の "YouTube"）も集計対象にする
+fn title_keys(limits: &ScreenTimeLimits, title: &str) -> Vec<String> {
+    let tl = title.to_lowercase();
+    limits.app_limits.keys().filter(|k| !k.is_empty() && tl.contains(k.as_str())).cloned().collect()
+}
+
+fn build_state(t: &Tracker, limits: &ScreenTimeLimits) -> ScreenTimeState {
+    let mut entries: Vec<ScreenTimeEntry> = Vec::new();
+    let mut keys: Vec<&String> = t.usage.apps.keys().chain(limits.app_limits.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    for k in keys {
+        let used = t.usage.apps.get(k).copied().unwrap_or(0);
+        let limit = limit_secs(limits.app_limits.get(k));
+        let remaining = limit.map(|l| l.saturating_sub(used));
+        let name = t.usage.names.get(k).cloned().unwrap_or_else(|| k.clone());
+        entries.push(ScreenTimeEntry { key: k.clone(), name, kind: "app".into(), used_secs: used, limit_secs: limit, remaining_secs: remaining, blocked: remaining == Some(0) });
+    }
+    let mut cats: Vec<&String> = t.usage.categories.keys().chain(limits.category_limits.keys()).collect();
+    cats.sort();
+    cats.dedup();
+    for c in cats {
+        let used = t.usage.categories.get(c).copied().unwrap_or(0);
+        let limit = limit_secs(limits.category_limits.get(c));
+        let remaining = limit.map(|l| l.saturating_sub(used));
+        entries.push(ScreenTimeEntry { key: c.clone(), name: c.clone(), kind: "category".into(), used_secs: used, limit_secs: limit, remaining_secs: remaining, blocked: remaining == Some(0) });
+    }
+    ScreenTimeState {
+        date: t.usage.date.clone(),
+        active: t.active.clone(),
+        active_name: t.active_name.clone(),
+        active_remaining_secs: t.active_remaining,
+        entries,
+    }
+}
+
+fn enforce_over_limit(app: &ActiveApp, action: &str) {
+    // 既定は最小化。"close" の場合は WM に閉じる要求を送る
+    let cmd = if action == "close" {
+        format!("wmctrl -ic {} || true", app.window_id)
+    } else if which("xdotool") {
+        format!("xdotool windowminimize {} || true", app.window_id)
+    } else {
+        format!("wmctrl -ir {} -b add,hidden || true", app.window_id)
+    };
+    log_append("INFO", &format!("screentime: over limit key={} action={}", app.key, action));
+    let _ = std::process::Command::new("sh").arg("-lc").arg(&cmd).status();
+}
+
+fn format_remaining(secs: u64) -> String {
+    if secs >= 60 { format!("{} 分", secs.div_ceil(60)) } else { format!("{} 秒", secs) }
+}
+
+fn tick(app_handle: &tauri::AppHandle, elapsed: u64, save: bool) {
+    let limits = read_limits();
+    let active = detect_active();
+    let idle = idle_ms().map(|ms| ms >= IDLE_CUTOFF_MS).unwrap_or(false);
+    let mut t = TRACKER.lock().unwrap();
+    if t.usage.date != today() {
+        write_usage(&t.usage);
+        t.usage = UsageDay { date: today(), ..Default::default() };
+        t.warned.clear();
+    }
+    t.active = active.as_ref().map(|a| a.key.clone());
+    t.active_name = active.as_ref().map(|a| a.name.clone());
+    t.active_remaining = None;
+    if let Some(a) = active.as_ref() {
+        if !idle {
+            *t.usage.apps.entry(a.key.clone()).or_insert(0) += elapsed;
+            t.usage.names.insert(a.key.clone(), a.name.clone());
+            if let Some(c) = &a.category { *t.usage.categories.entry(c.clone()).or_insert(0) += elapsed; }
+            for k in title_keys(&limits, &a.title) {
+                if k != a.key { *t.usage.apps.entry(k).or_insert(0) += elapsed; }
+            }
+        }
+        let mut checks: Vec<String> = vec![a.key.clone()];
+        checks.extend(title_keys(&limits, &a.title).into_iter().filter(|k| *k != a.key));
+        for k in checks {
+            let (cat, name) = if k == a.key { (a.category.as_deref(), a.name.clone()) } else { (None, k.clone()) };
+            let rem = match remaining_for(&t.usage, &limits, &k, cat) { Some(r) => r, None => continue };
+            t.active_remaining = Some(t.active_remaining.map_or(rem, |x| x.min(rem)));
+            if rem == 0 {
+                let _ = app_handle.emit("sis:screentime-blocked", serde_json::json!({ "key": k, "name": name }));
+                if t.warned.insert((k.clone(), 0)) {
+                    crate::notifications::post("スクリーンタイム", &format!("{} の今日の利用時間が終わりました", name), "また明日使えます。", Some("appointment-missed"), "normal", Vec::new(), None);
+                }
+                enforce_over_limit(a, limits.over_limit_action.as_deref().unwrap_or("minimize"));
+                continue;
+            }
+            // 一度に複数のしきい値を越えた（起動直後など）ときは最も近いものだけ知らせる
+            let crossed: Vec<u64> = WARN_AT.iter().copied().filter(|w| rem <= *w && t.warned.insert((k.clone(), *w))).collect();
+            if !crossed.is_empty() {
+                let _ = app_handle.emit("sis:screentime-warning", serde_json::json!({ "key": k, "name": name, "remaining_secs": rem }));
+                crate::notifications::post("スクリーンタイム", &format!("{} はあと {} です", name, format_remaining(rem)), "時間になるとアプリは使えなくなります。作業を保存してください。", Some("appointment-soon"), if rem <= 60 { "critical" } else { "normal" }, Vec::new(), None);
+            }
+        }
+    }
+    if save { write_usage(&t.usage); }
+    let state = build_state(&t, &limits);
+    drop(t);
+    let _ = app_handle.emit("sis:screentime", &state);
+}
+
+/// バックグラウンドで集計を開始（X11のみ）
+pub fn start(app_handle: tauri::AppHandle) {
+    let is_x11 = std::env::var("WAYLAND_DISPLAY").is_err() && std::env::var("DISPLAY").is_ok();
+    if !is_x11 || !which("xprop") {
+        log_append("WARN", "screentime: tracker disabled (requires X11 + xprop)");
+        return;
+    }
+    std::thread::spawn(move || {
+        build_category_cache();
+        let mut last = Instant::now();
+        let mut n: u32 = 0;
+        loop {
+            std::thread::sleep(TICK);
+            // サスペンド復帰などの長い空白は数えない
+            let elapsed = last.elapsed().min(TICK * 2).as_secs();
+            last = Instant::now();
+            n = n.wrapping_add(1);
+            tick(&app_handle, elapsed, n % SAVE_EVERY_TICKS == 0);
+        }
+    });
+}
+
+/// launch_app 前のチェック。上限到達済みなら起動を拒否
+pub fn check_launch(exec: &str) -> Result<(), String> {
+    let key = match exec_key(exec) { Some(k) => k, None => return Ok(()) };
+    let limits = read_limits();
+    if limits.app_limits.is_empty() && limits.category_limits.is_empty() { return Ok(()); }
+    let category = category_for(&[key.as_str()]);
+    let t = TRACKER.lock().unwrap();
+    if t.usage.date != today() { return Ok(()); }
+    if remaining_for(&t.usage, &limits, &key, category.as_deref()) == Some(0) {
+        log_append("INFO", &format!("screentime: launch blocked key={}", key));
+        return Err(format!("screentime-limit-reached: {}", key));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn screentime_state() -> Result<ScreenTimeState, SisError> {
+    let limits = read_limits();
+    let t = TRACKER.lock().unwrap();
+    Ok(build_state(&t, &limits))
+}
+
+#[tauri::command]
+pub fn screentime_limits() -> Result<ScreenTimeLimits, SisError> { Ok(read_limits()) }
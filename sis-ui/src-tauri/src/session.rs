@@ -0,0 +1,471 @@
+// セッション/電源: logind（org.freedesktop.login1）でサスペンド・休止・再起動・電源断・ログアウトを行う。
+// ログアウト/再起動/電源断の前に、X11 のウィンドウ（_NET_CLIENT_LIST）へ閉じる要求（WM_DELETE_WINDOW）を送り、
+// 残ったアプリは利用者の確認後に SIGTERM → SIGKILL で終了させる
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+use zbus::blocking::{Connection, Proxy};
+use zbus::zvariant::OwnedFd;
+
+use crate::error::SisError;
+use crate::log_append;
+
+const LOGIN1: &str = "org.freedesktop.login1";
+const MANAGER_PATH: &str = "/org/freedesktop/login1";
+const MANAGER_IFACE: &str = "org.freedesktop.login1.Manager";
+const SESSION_PATH: &str = "/org/freedesktop/login1/session/auto";
+// 閉じる要求後にアプリが自分で終了するのを待つ時間
+const CLOSE_GRACE: Duration = Duration::from_secs(10);
+// SIGTERM 後、SIGKILL までの猶予
+const TERM_GRACE: Duration = Duration::from_secs(3);
+const CLOSE_POLL: Duration = Duration::from_millis(500);
+const SCHEDULE_POLL: Duration = Duration::from_secs(15);
+// 予約シャットダウンの事前通知（分前）
+const SCHEDULE_WARN_MINUTES: [i64; 2] = [5, 1];
+
+static APP: Lazy<Mutex<Option<tauri::AppHandle>>> = Lazy::new(|| Mutex::new(None));
+static STATE: Lazy<Mutex<SessionState>> = Lazy::new(|| Mutex::new(SessionState::idle()));
+static CANCEL: AtomicBool = AtomicBool::new(false);
+// SIS が保持している抑止ロック（fd を閉じると解放される）
+static HELD: Lazy<Mutex<HashMap<u32, (Inhibitor, OwnedFd)>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+static NEXT_INHIBIT_ID: AtomicU32 = AtomicU32::new(1);
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PowerCapabilities {
+    suspend: String, // yes | no | challenge | na
+    hibernate: String,
+    hybrid_sleep: String,
+    suspend_then_hibernate: String,
+    reboot: String,
+    power_off: String,
+    scheduled: Option<ScheduledShutdown>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ScheduledShutdown {
+    kind: String, // poweroff | reboot | halt
+    at_ms: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Inhibitor {
+    id: Option<u32>, // SIS が保持しているものだけ
+    what: String,    // shutdown:sleep:idle:handle-power-key など
+    who: String,
+    why: String,
+    mode: String, // block | delay
+    uid: u32,
+    pid: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BlockingApp {
+    window_id: String,
+    pid: Option<u32>,
+    title: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SessionState {
+    phase: String, // idle | closing | blocked | terminating | executing
+    action: Option<String>,
+    apps: Vec<BlockingApp>,
+    inhibitors: Vec<Inhibitor>,
+}
+
+impl SessionState {
+    fn idle() -> Self { SessionState { phase: "idle".into(), action: None, apps: Vec::new(), inhibitors: Vec::new() } }
+}
+
+pub fn system_bus() -> Result<Connection, String> {
+    static CONN: Lazy<Option<Connection>> = Lazy::new(|| Connection::system().ok());
+    CONN.clone().ok_or_else(|| "logind-unavailable".to_string())
+}
+
+pub fn manager(conn: &Connection) -> Result<Proxy<'static>, String> {
+    Proxy::new(conn, LOGIN1, MANAGER_PATH, MANAGER_IFACE).map_err(|e| format!("logind-unavailable: {}", e))
+}
+
+fn emit_state(st: &SessionState) {
+    if let Some(app) = APP.lock().unwrap().as_ref() { let _ = app.emit("sis:session-state", st); }
+}
+
+fn set_state(st: SessionState) {
+    *STATE.lock().unwrap() = st.clone();
+    emit_state(&st);
+}
+
+/// idle/blocked のときだけ closing にする。確認と更新を同じロックの中で行い、連打で二重に終了処理を走らせない
+fn begin_closing(action: &str) -> Result<(), String> {
+    let st = {
+        let mut st = STATE.lock().unwrap();
+        if st.phase != "idle" && st.phase != "blocked" { return Err(format!("busy: {}", st.phase)); }
+        *st = SessionState { phase: "closing".into(), action: Some(action.to_string()), apps: Vec::new(), inhibitors: Vec::new() };
+        st.clone()
+    };
+    emit_state(&st);
+    Ok(())
+}
+
+fn can(method: &str) -> String {
+    system_bus().and_then(|c| manager(&c)).ok()
+        .and_then(|m| m.call::<_, _, String>(method, &()).ok())
+        .unwrap_or_else(|| "na".into())
+}
+
+fn scheduled() -> Option<ScheduledShutdown> {
+    let conn = system_bus().ok()?;
+    let (kind, usec) = manager(&conn).ok()?.get_property::<(String, u64)>("ScheduledShutdown").ok()?;
+    if kind.is_empty() || usec == 0 { return None; }
+    Some(ScheduledShutdown { kind, at_ms: (usec / 1000) as i64 })
+}
+
+pub fn capabilities() -> PowerCapabilities {
+    PowerCapabilities {
+        suspend: can("CanSuspend"),
+        hibernate: can("CanHibernate"),
+        hybrid_sleep: can("CanHybridSleep"),
+        suspend_then_hibernate: can("CanSuspendThenHibernate"),
+        reboot: can("CanReboot"),
+        power_off: can("CanPowerOff"),
+        scheduled: scheduled(),
+    }
+}
+
+pub fn list_inhibitors() -> Vec<Inhibitor> {
+    let held: Vec<(u32, Inhibitor)> = HELD.lock().unwrap().iter().map(|(id, (i, _))| (*id, i.clone())).collect();
+    let Ok(conn) = system_bus() else { return Vec::new() };
+    let Ok(list) = manager(&conn).and_then(|m| m.call::<_, _, Vec<(String, String, String, String, u32, u32)>>("ListInhibitors", &()).map_err(|e| e.to_string())) else { return Vec::new() };
+    let me = std::process::id();
+    list.into_iter()
+        .map(|(what, who, why, mode, uid, pid)| {
+            let id = if pid == me { held.iter().find(|(_, h)| h.what == what && h.why == why).map(|(id, _)| *id) } else { None };
+            Inhibitor { id, what, who, why, mode, uid, pid }
+        })
+        .collect()
+}
+
+/// 何かが idle を block で抑止しているか（動画再生・プレゼン中など）
+pub fn idle_inhibited() -> bool {
+    list_inhibitors().iter().any(|i| i.mode == "block" && i.what.split(':').any(|w| w == "idle"))
+}
+
+/// 自分が属するセッションの実パス（シグナルは auto ではなくこちらから届く）
+pub fn own_session_path() -> Option<String> {
+    let conn = system_bus().ok()?;
+    let p: zbus::zvariant::OwnedObjectPath = manager(&conn).ok()?.call("GetSessionByPID", &(std::process::id(),)).ok()?;
+    Some(p.as_str().to_string())
+}
+
+pub fn session_proxy(conn: &Connection, path: String) -> Result<Proxy<'static>, String> {
+    Proxy::new(conn, LOGIN1, path, "org.freedesktop.login1.Session").map_err(|e| format!("logind-unavailable: {}", e))
+}
+
+/// ロック状態を logind に伝える（loginctl show-session の LockedHint）
+pub fn set_locked_hint(locked: bool) {
+    let Ok(conn) = system_bus() else { return };
+    if let Ok(p) = session_proxy(&conn, SESSION_PATH.to_string()) {
+        let _ = p.call::<_, _, ()>("SetLockedHint", &(locked,));
+    }
+}
+
+/// 管理者権限が要る操作は polkit の対話認証に任せる（interactive = true）
+fn call_manager(method: &str) -> Result<(), String> {
+    let conn = system_bus()?;
+    manager(&conn)?.call::<_, _, ()>(method, &(true,)).map_err(|e| format!("{}-failed: {}", method.to_lowercase(), e))
+}
+
+pub fn suspend() -> Result<(), String> {
+    if matches!(can("CanSuspend").as_str(), "no" | "na") { return Err("suspend-not-allowed".into()); }
+    call_manager("Suspend")
+}
+
+// ---- アプリを閉じる ----
+
+mod x11 {
+    use x11rb::connection::Connection as _;
+    use x11rb::protocol::xproto::{Atom, AtomEnum, ClientMessageEvent, ConnectionExt as _, EventMask, Window};
+    use x11rb::rust_connection::RustConnection;
+
+    pub struct Display {
+        conn: RustConnection,
+        root: Window,
+    }
+
+    /// (id, _NET_WM_DESKTOP, _NET_WM_PID, タイトル)
+    pub type Client = (Window, Option<u32>, Option<u32>, String);
+
+    fn err(e: impl std::fmt::Display) -> String { format!("x11-failed: {}", e) }
+
+    impl Display {
+        pub fn open() -> Result<Self, String> {
+            let (conn, screen) = x11rb::connect(None).map_err(|e| format!("x11-connect-failed: {}", e))?;
+            let root = conn.setup().roots.get(screen).ok_or("x11-no-screen")?.root;
+            Ok(Self { conn, root })
+        }
+
+        fn atom(&self, name: &str) -> Result<Atom, String> {
+            Ok(self.conn.intern_atom(false, name.as_bytes()).map_err(err)?.reply().map_err(err)?.atom)
+        }
+
+        fn prop32(&self, w: Window, prop: Atom, ty: impl Into<Atom>) -> Vec<u32> {
+            self.conn.get_property(false, w, prop, ty, 0, u32::MAX).ok()
+                .and_then(|c| c.reply().ok())
+                .and_then(|r| r.value32().map(|v| v.collect()))
+                .unwrap_or_default()
+        }
+
+        fn text(&self, w: Window, prop: Atom, ty: impl Into<Atom>) -> Option<String> {
+            let r = self.conn.get_property(false, w, prop, ty, 0, 1024).ok()?.reply().ok()?;
+            if r.value.is_empty() { None } else { Some(String::from_utf8_lossy(&r.value).to_string()) }
+        }
+
+        /// _NET_CLIENT_LIST の各ウィンドウ
+        pub fn clients(&self) -> Result<Vec<Client>, String> {
+            let list = self.atom("_NET_CLIENT_LIST")?;
+            let desktop = self.atom("_NET_WM_DESKTOP")?;
+            let pid = self.atom("_NET_WM_PID")?;
+            let net_name = self.atom("_NET_WM_NAME")?;
+            let utf8 = self.atom("UTF8_STRING")?;
+            Ok(self.prop32(self.root, list, AtomEnum::WINDOW).into_iter().map(|w| {
+                let title = self.text(w, net_name, utf8).or_else(|| self.text(w, AtomEnum::WM_NAME.into(), AtomEnum::STRING)).unwrap_or_default();
+                (w, self.prop32(w, desktop, AtomEnum::CARDINAL).first().copied(), self.prop32(w, pid, AtomEnum::CARDINAL).first().copied(), title)
+            }).collect())
+        }
+
+        /// WM_DELETE_WINDOW に対応しているウィンドウにだけ送る（非対応のものは WM に閉じさせると強制終了になる）
+        pub fn request_close(&self, w: Window) -> Result<bool, String> {
+            let protocols = self.atom("WM_PROTOCOLS")?;
+            let delete = self.atom("WM_DELETE_WINDOW")?;
+            if !self.prop32(w, protocols, AtomEnum::ATOM).contains(&delete) { return Ok(false); }
+            let ev = ClientMessageEvent::new(32, w, protocols, [delete, x11rb::CURRENT_TIME, 0, 0, 0]);
+            self.conn.send_event(false, w, EventMask::NO_EVENT, ev).map_err(err)?;
+            self.conn.flush().map_err(err)?;
+            Ok(true)
+        }
+    }
+}
+
+/// 自分（SIS）と全デスクトップ固定（0xFFFFFFFF: デスクトップ/パネル類）を除いた X11 のトップレベルウィンドウ。
+/// 列挙できないときはエラーにする（黙って空にするとアプリを閉じずにログアウトしてしまう）
+fn app_windows() -> Result<Vec<BlockingApp>, String> {
+    if crate::screenshot::is_wayland() { return Ok(Vec::new()); }
+    let me = std::process::id();
+    Ok(x11::Display::open()?.clients()?.into_iter()
+        .filter(|(_, desktop, pid, _)| *desktop != Some(u32::MAX) && *pid != Some(me))
+        .map(|(w, _, pid, title)| BlockingApp { window_id: format!("0x{:08x}", w), pid: pid.filter(|p| *p > 0), title })
+        .collect())
+}
+
+/// WM_DELETE_WINDOW を送り、全ウィンドウが消えるか猶予切れまで待つ
+fn close_windows_gracefully(wins: &[BlockingApp]) -> Vec<BlockingApp> {
+    if let Ok(d) = x11::Display::open() {
+        for w in wins {
+            let Ok(id) = u32::from_str_radix(w.window_id.trim_start_matches("0x"), 16) else { continue };
+            match d.request_close(id) {
+                Ok(true) => {}
+                Ok(false) => log_append("INFO", &format!("session: {} does not support WM_DELETE_WINDOW", w.window_id)),
+                Err(e) => log_append("WARN", &format!("session: close {} failed: {}", w.window_id, e)),
+            }
+        }
+    }
+    let deadline = Instant::now() + CLOSE_GRACE;
+    loop {
+        let left = app_windows().unwrap_or_else(|_| wins.to_vec());
+        if left.is_empty() || Instant::now() >= deadline || CANCEL.load(Ordering::SeqCst) { return left; }
+        std::thread::sleep(CLOSE_POLL);
+    }
+}
+
+fn signal(pid: u32, sig: libc::c_int) {
+    // SAFETY: pid とシグナル番号を渡すだけ（存在しない pid なら ESRCH が返る）
+    unsafe { libc::kill(pid as libc::pid_t, sig); }
+}
+
+/// 残ったアプリを SIGTERM → SIGKILL で終わらせる
+fn terminate_apps(apps: &[BlockingApp]) {
+    let mut pids: Vec<u32> = apps.iter().filter_map(|a| a.pid).collect();
+    pids.sort_unstable();
+    pids.dedup();
+    for p in &pids { signal(*p, libc::SIGTERM); }
+    let deadline = Instant::now() + TERM_GRACE;
+    while Instant::now() < deadline && pids.iter().any(|p| std::path::Path::new(&format!("/proc/{}", p)).exists()) {
+        std::thread::sleep(CLOSE_POLL);
+    }
+    for p in pids.iter().filter(|p| std::path::Path::new(&format!("/proc/{}", p)).exists()) {
+        log_append("WARN", &format!("session: pid {} ignored SIGTERM; killing", p));
+        signal(*p, libc::SIGKILL);
+    }
+}
+
+fn execute(action: &str) -> Result<(), String> {
+    match action {
+        "logout" => {
+            let conn = system_bus()?;
+            Proxy::new(&conn, LOGIN1, SESSION_PATH, "org.freedesktop.login1.Session")
+                .and_then(|p| p.call::<_, _, ()>("Terminate", &()))
+                .map_err(|e| format!("logout-failed: {}", e))
+        }
+        "reboot" => call_manager("Reboot"),
+        "poweroff" => call_manager("PowerOff"),
+        other => Err(format!("unsupported-action: {}", other)),
+    }
+}
+
+/// ログアウト/再起動/電源断の一連の流れ（別スレッドで実行し、進捗は sis:session-state）
+fn end_session(action: String, force: bool) {
+    CANCEL.store(false, Ordering::SeqCst);
+    let wins = match app_windows() {
+        Ok(w) => w,
+        Err(e) => {
+            log_append("WARN", &format!("session: cannot list windows before {}: {}", action, e));
+            // 強制でなければ確認を求める（開いているアプリが分からないまま終了しない）
+            if !force {
+                set_state(SessionState { phase: "blocked".into(), action: Some(action), apps: Vec::new(), inhibitors: Vec::new() });
+                return;
+            }
+            Vec::new()
+        }
+    };
+    set_state(SessionState { phase: "closing".into(), action: Some(action.clone()), apps: wins.clone(), inhibitors: Vec::new() });
+    let left = close_windows_gracefully(&wins);
+    if CANCEL.load(Ordering::SeqCst) { set_state(SessionState::idle()); return; }
+    // shutdown を block している抑止（SIS 自身以外）
+    let blockers: Vec<Inhibitor> = if action == "logout" { Vec::new() } else {
+        list_inhibitors().into_iter().filter(|i| i.mode == "block" && i.what.split(':').any(|w| w == "shutdown") && i.id.is_none()).collect()
+    };
+    if !force && (!left.is_empty() || !blockers.is_empty()) {
+        log_append("INFO", &format!("session: {} blocked by {} app(s), {} inhibitor(s)", action, left.len(), blockers.len()));
+        set_state(SessionState { phase: "blocked".into(), action: Some(action), apps: left, inhibitors: blockers });
+        return;
+    }
+    if !left.is_empty() {
+        set_state(SessionState { phase: "terminating".into(), action: Some(action.clone()), apps: left.clone(), inhibitors: Vec::new() });
+        terminate_apps(&left);
+    }
+    set_state(SessionState { phase: "executing".into(), action: Some(action.clone()), apps: Vec::new(), inhibitors: Vec::new() });
+    log_append("INFO", &format!("session: executing {}", action));
+    if let Err(e) = execute(&action) {
+        log_append("ERROR", &format!("session: {}", e));
+        set_state(SessionState::idle());
+    }
+}
+
+/// power_action 互換の入口（shutdown/reboot/logout/suspend/hibernate）
+pub fn request(action: &str, force: bool) -> Result<String, String> {
+    let action = match action { "shutdown" | "poweroff" => "poweroff", a => a };
+    match action {
+        "suspend" => { suspend()?; Ok("suspending".into()) }
+        "hibernate" => {
+            if matches!(can("CanHibernate").as_str(), "no" | "na") { return Err("hibernate-not-allowed".into()); }
+            call_manager("Hibernate")?;
+            Ok("hibernating".into())
+        }
+        "logout" | "reboot" | "poweroff" => {
+            if action == "reboot" && matches!(can("CanReboot").as_str(), "no" | "na") { return Err("reboot-not-allowed".into()); }
+            if action == "poweroff" && matches!(can("CanPowerOff").as_str(), "no" | "na") { return Err("poweroff-not-allowed".into()); }
+            begin_closing(action)?;
+            let a = action.to_string();
+            std::thread::spawn(move || end_session(a, force));
+            Ok("closing-apps".into())
+        }
+        other => Err(format!("unsupported-action: {}", other)),
+    }
+}
+
+pub fn start(app_handle: tauri::AppHandle) {
+    *APP.lock().unwrap() = Some(app_handle.clone());
+    // 予約シャットダウンの変化を通知し、直前に警告を出す
+    std::thread::spawn(move || {
+        let mut last: Option<ScheduledShutdown> = None;
+        let mut warned: Vec<i64> = Vec::new();
+        loop {
+            let cur = scheduled();
+            if cur != last {
+                let _ = app_handle.emit("sis:shutdown-scheduled", &cur);
+                warned.clear();
+                last = cur.clone();
+            }
+            if let Some(s) = &cur {
+                let mins = (s.at_ms - chrono::Utc::now().timestamp_millis()) / 60_000;
+                for m in SCHEDULE_WARN_MINUTES {
+                    if mins < m && !warned.contains(&m) {
+                        warned.push(m);
+                        let what = if s.kind == "reboot" { "再起動" } else { "シャットダウン" };
+                        crate::notifications::post("電源", &format!("まもなく{}します", what), "作業中のファイルを保存してください。", Some("system-shutdown"), "critical", Vec::new(), None);
+                    }
+                }
+            }
+            std::thread::sleep(SCHEDULE_POLL);
+        }
+    });
+}
+
+#[tauri::command]
+pub fn session_capabilities() -> Result<PowerCapabilities, SisError> { Ok(capabilities()) }
+
+#[tauri::command]
+pub fn session_state() -> Result<SessionState, SisError> { Ok(STATE.lock().unwrap().clone()) }
+
+/// force=true なら閉じなかったアプリも終了させて続行する
+#[tauri::command]
+pub fn session_action(action: String, force: Option<bool>) -> Result<String, SisError> {
+    Ok(request(&action, force.unwrap_or(false))?)
+}
+
+#[tauri::command]
+pub fn session_cancel() -> Result<String, SisError> {
+    CANCEL.store(true, Ordering::SeqCst);
+    let phase = STATE.lock().unwrap().phase.clone();
+    if phase == "blocked" { set_state(SessionState::idle()); }
+    Ok("cancelled".into())
+}
+
+#[tauri::command]
+pub fn session_list_inhibitors() -> Result<Vec<Inhibitor>, SisError> { Ok(list_inhibitors()) }
+
+/// logind の抑止ロックを取る。返した fd を drop すると解放される
+pub fn take_inhibitor(what: &str, why: &str, mode: &str) -> Result<OwnedFd, String> {
+    let conn = system_bus()?;
+    manager(&conn)?.call("Inhibit", &(what, "SIS", why, mode)).map_err(|e| format!("inhibit-failed: {}", e))
+}
+
+/// what: "sleep" | "shutdown" | "idle" などを ':' で連結、mode: block | delay
+#[tauri::command]
+pub fn session_inhibit(what: String, why: String, mode: Option<String>) -> Result<u32, SisError> {
+    let mode = mode.unwrap_or_else(|| "block".into());
+    if !matches!(mode.as_str(), "block" | "delay") { return Err("invalid-mode".into()); }
+    let fd = take_inhibitor(&what, &why, &mode)?;
+    let id = NEXT_INHIBIT_ID.fetch_add(1, Ordering::SeqCst);
+    let info = Inhibitor { id: Some(id), what, who: "SIS".into(), why, mode, uid: 0, pid: std::process::id() };
+    HELD.lock().unwrap().insert(id, (info, fd));
+    Ok(id)
+}
+
+#[tauri::command]
+pub fn session_uninhibit(id: u32) -> Result<String, SisError> {
+    HELD.lock().unwrap().remove(&id).map(|_| "released".to_string()).ok_or_else(|| "not-found".into())
+}
+
+/// kind: poweroff | reboot、at_ms: 実行時刻（UNIX ミリ秒）
+#[tauri::command]
+pub fn session_schedule_shutdown(kind: String, at_ms: i64) -> Result<String, SisError> {
+    if !matches!(kind.as_str(), "poweroff" | "reboot") { return Err("invalid-kind".into()); }
+    if at_ms <= chrono::Utc::now().timestamp_millis() { return Err("time-in-past".into()); }
+    let conn = system_bus()?;
+    manager(&conn)?.call::<_, _, ()>("ScheduleShutdown", &(kind.as_str(), (at_ms as u64) * 1000)).map_err(|e| format!("schedule-failed: {}", e))?;
+    log_append("INFO", &format!("session: scheduled {} at {}", kind, at_ms));
+    Ok("scheduled".into())
+}
+
+#[tauri::command]
+pub fn session_cancel_scheduled_shutdown() -> Result<String, SisError> {
+    let conn = system_bus()?;
+    let ok: bool = manager(&conn)?.call("CancelScheduledShutdown", &()).map_err(|e| format!("cancel-failed: {}", e))?;
+    if ok { Ok("cancelled".into()) } else { Err("not-scheduled".into()) }
+}
@@ -0,0 +1,329 @@
+// Storage / removable media: UDisks2(D-Bus) で USB/SD を検出し、マウント・アンマウント・安全な取り外しを行う
+use std::collections::HashMap;
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use sysinfo::Disks;
+use tauri::Emitter;
+use zbus::blocking::{fdo::ObjectManagerProxy, Connection, Proxy};
+use zbus::zvariant::{OwnedObjectPath, OwnedValue, Value};
+
+use crate::error::SisError;
+use crate::log_append;
+
+const POLL: Duration = Duration::from_secs(2);
+const UDISKS_DEST: &str = "org.freedesktop.UDisks2";
+const UDISKS_PATH: &str = "/org/freedesktop/UDisks2";
+const IF_BLOCK: &str = "org.freedesktop.UDisks2.Block";
+const IF_FS: &str = "org.freedesktop.UDisks2.Filesystem";
+const IF_DRIVE: &str = "org.freedesktop.UDisks2.Drive";
+const IF_LOOP: &str = "org.freedesktop.UDisks2.Loop";
+const POLICY_PATH: &str = "/var/lib/sis-mdm/policies.json";
+// install.sh が書き出す外部媒体の既定マウントオプション
+const MEDIA_RULES_PATH: &str = "/etc/udev/rules.d/99-sis-external-media.rules";
+const USB_LOG_RULES_PATH: &str = "/etc/udev/rules.d/99-sis-usb.rules";
+
+static LAST: Lazy<Mutex<Option<Vec<RemovableDevice>>>> = Lazy::new(|| Mutex::new(None));
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MountedFs {
+    device: String,
+    mount_point: String,
+    fs_type: String,
+    total: u64,
+    available: u64,
+    used: u64,
+    removable: bool,
+    read_only: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RemovableDevice {
+    id: String, // UDisks2 block object path
+    device: String,
+    label: String,
+    fs_type: String,
+    size: u64,
+    bus: String, // usb | sdio | loop | other
+    model: String,
+    read_only: bool,
+    mount_points: Vec<String>,
+    can_eject: bool,
+    drive_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StoragePolicy {
+    usb_storage: String, // allow | readonly | block（MDM restrictions.usb_storage）
+    mount_options: Option<String>,
+    usb_logging: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BusyHolder { pid: u32, name: String, path: String }
+
+type Props = HashMap<String, OwnedValue>;
+
+fn prop<T: TryFrom<OwnedValue>>(props: &Props, key: &str) -> Option<T> {
+    props.get(key)?.try_clone().ok().and_then(|v| T::try_from(v).ok())
+}
+
+// "ay"（NUL終端のバイト列）を文字列に
+fn bytestring(v: &Value<'_>) -> Option<String> {
+    let bytes: Vec<u8> = match v { Value::Array(a) => a.iter().filter_map(|x| u8::try_from(x).ok()).collect(), _ => return None };
+    let s = String::from_utf8_lossy(&bytes).trim_end_matches('\0').to_string();
+    if s.is_empty() { None } else { Some(s) }
+}
+
+fn bytestring_list(props: &Props, key: &str) -> Vec<String> {
+    match props.get(key).map(|v| &**v) {
+        Some(Value::Array(a)) => a.iter().filter_map(bytestring).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn system_bus() -> Result<Connection, String> {
+    static CONN: Lazy<Option<Connection>> = Lazy::new(|| Connection::system().ok());
+    CONN.clone().ok_or_else(|| "dbus-unavailable".to_string())
+}
+
+fn my_uid() -> u32 { fs::metadata("/proc/self").map(|m| m.uid()).unwrap_or(u32::MAX) }
+
+fn managed_objects(conn: &Connection) -> Result<HashMap<OwnedObjectPath, HashMap<String, Props>>, String> {
+    let om = ObjectManagerProxy::builder(conn)
+        .destination(UDISKS_DEST).and_then(|b| b.path(UDISKS_PATH))
+        .and_then(|b| b.build())
+        .map_err(|e| format!("udisks-unavailable: {}", e))?;
+    let objs = om.get_managed_objects().map_err(|e| format!("udisks-unavailable: {}", e))?;
+    Ok(objs.into_iter().map(|(p, ifs)| (p, ifs.into_iter().map(|(k, v)| (k.to_string(), v)).collect())).collect())
+}
+
+fn list_removable(conn: &Connection) -> Result<Vec<RemovableDevice>, String> {
+    let objs = managed_objects(conn)?;
+    let uid = my_uid();
+    let mut out = Vec::new();
+    for (path, ifs) in &objs {
+        let (block, fs_props) = match (ifs.get(IF_BLOCK), ifs.get(IF_FS)) { (Some(b), Some(f)) => (b, f), _ => continue };
+        if prop::<bool>(block, "HintIgnore").unwrap_or(false) { continue; }
+        let drive_path = prop::<OwnedObjectPath>(block, "Drive").filter(|p| p.as_str() != "/");
+        let drive = drive_path.as_ref().and_then(|p| objs.get(p)).and_then(|d| d.get(IF_DRIVE));
+        // ループデバイスは自分で loop-setup したものだけを外部媒体として扱う（動作確認用）
+        let loop_dev = ifs.get(IF_LOOP);
+        let bus = if loop_dev.is_some() { "loop".to_string() } else { drive.and_then(|d| prop::<String>(d, "ConnectionBus")).unwrap_or_default() };
+        let removable = match (loop_dev, drive) {
+            (Some(l), _) => prop::<u32>(l, "SetupByUID") == Some(uid),
+            (None, Some(d)) => prop::<bool>(d, "Removable").unwrap_or(false) || prop::<bool>(d, "Ejectable").unwrap_or(false) || bus == "usb" || bus == "sdio",
+            _ => false,
+        };
+        if !removable { continue; }
+        let device = block.get("PreferredDevice").or_else(|| block.get("Device")).and_then(|v| bytestring(v)).unwrap_or_default();
+        let label = prop::<String>(block, "IdLabel").filter(|s| !s.is_empty())
+            .unwrap_or_else(|| Path::new(&device).file_name().map(|s| s.to_string_lossy().to_string()).unwrap_or_default());
+        let model = drive.map(|d| format!("{} {}", prop::<String>(d, "Vendor").unwrap_or_default(), prop::<String>(d, "Model").unwrap_or_default()).trim().to_string()).unwrap_or_default();
+        out.push(RemovableDevice {
+            id: path.to_string(),
+            device,
+            label,
+            fs_type: prop::<String>(block, "IdType").unwrap_or_default(),
+            size: prop::<u64>(block, "Size").unwrap_or(0),
+            bus: match bus.as_str() { "usb" | "sdio" | "loop" => bus.clone(), _ => "other".into() },
+            model,
+            read_only: prop::<bool>(block, "ReadOnly").unwrap_or(false),
+            mount_points: bytestring_list(fs_props, "MountPoints"),
+            can_eject: loop_dev.is_some() || drive.map(|d| prop::<bool>(d, "Ejectable").unwrap_or(false) || prop::<bool>(d, "CanPowerOff").unwrap_or(false)).unwrap_or(false),
+            drive_id: drive_path.map(|p| p.to_string()),
+        });
+    }
+    out.sort_by(|a, b| a.device.cmp(&b.device));
+    Ok(out)
+}
+
+pub fn read_policy() -> StoragePolicy {
+    let usb_storage = fs::read_to_string(POLICY_PATH).ok()
+        .and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok())
+        .and_then(|v| v.pointer("/restrictions/usb_storage").and_then(|x| x.as_str()).map(|s| s.to_string()))
+        .filter(|s| matches!(s.as_str(), "allow" | "readonly" | "block"))
+        .unwrap_or_else(|| "allow".into());
+    let mount_options = fs::read_to_string(MEDIA_RULES_PATH).ok().and_then(|s| {
+        let re = regex::Regex::new(r#"UDISKS_MOUNT_OPTIONS_DEFAULTS\}="([^"]*)""#).unwrap();
+        re.captures(&s).and_then(|c| c.get(1)).map(|m| m.as_str().to_string())
+    });
+    StoragePolicy { usb_storage, mount_options, usb_logging: Path::new(USB_LOG_RULES_PATH).exists() }
+}
+
+fn find_device(conn: &Connection, id: &str) -> Result<RemovableDevice, String> {
+    list_removable(conn)?.into_iter().find(|d| d.id == id || d.device == id).ok_or_else(|| "not-found".to_string())
+}
+
+fn options(pairs: Vec<(&'static str, Value<'static>)>) -> HashMap<&'static str, Value<'static>> {
+    pairs.into_iter().collect()
+}
+
+fn dbus_err(prefix: &str, e: zbus::Error) -> String {
+    let s = e.to_string();
+    if s.contains("DeviceBusy") || s.contains("target is busy") { return "device-busy".into(); }
+    if s.contains("NotAuthorized") { return "not-authorized".into(); }
+    format!("{}: {}", prefix, s)
+}
+
+fn mount_device(conn: &Connection, dev: &RemovableDevice) -> Result<String, String> {
+    if let Some(mp) = dev.mount_points.first() { return Ok(mp.clone()); }
+    let policy = read_policy();
+    if policy.usb_storage == "block" { return Err("blocked-by-policy".into()); }
+    let fs = Proxy::new(conn, UDISKS_DEST, dev.id.as_str(), IF_FS).map_err(|e| format!("udisks-unavailable: {}", e))?;
+    let opts = if policy.usb_storage == "readonly" { options(vec![("options", Value::from("ro"))]) } else { options(Vec::new()) };
+    let mp: String = fs.call("Mount", &(opts,)).map_err(|e| dbus_err("mount-failed", e))?;
+    log_append("INFO", &format!("storage: mounted {} at {} (policy={})", dev.device, mp, policy.usb_storage));
+    Ok(mp)
+}
+
+fn unmount_device(conn: &Connection, dev: &RemovableDevice) -> Result<(), String> {
+    if dev.mount_points.is_empty() { return Ok(()); }
+    let fs = Proxy::new(conn, UDISKS_DEST, dev.id.as_str(), IF_FS).map_err(|e| format!("udisks-unavailable: {}", e))?;
+    let _: () = fs.call("Unmount", &(options(Vec::new()),)).map_err(|e| dbus_err("unmount-failed", e))?;
+    log_append("INFO", &format!("storage: unmounted {}", dev.device));
+    Ok(())
+}
+
+// マウントポイント配下を cwd/exe/fd で参照しているプロセスを探す（自ユーザー分のみ可視）
+fn busy_holders(mount_points: &[String]) -> Vec<BusyHolder> {
+    let mut out = Vec::new();
+    let under = |p: &Path| mount_points.iter().any(|m| p.starts_with(m));
+    let rd = match fs::read_dir("/proc") { Ok(r) => r, Err(_) => return out };
+    for e in rd.flatten() {
+        let pid = match e.file_name().to_str().and_then(|s| s.parse::<u32>().ok()) { Some(p) => p, None => continue };
+        let base = e.path();
+        let mut hit: Option<String> = None;
+        for link in ["cwd", "exe", "root"] {
+            if let Ok(t) = fs::read_link(base.join(link)) { if under(&t) { hit = Some(t.to_string_lossy().to_string()); break; } }
+        }
+        if hit.is_none() {
+            if let Ok(fds) = fs::read_dir(base.join("fd")) {
+                for fd in fds.flatten() {
+                    if let Ok(t) = fs::read_link(fd.path()) { if under(&t) { hit = Some(t.to_string_lossy().to_string()); break; } }
+                }
+            }
+        }
+        if let Some(path) = hit {
+            let name = fs::read_to_string(base.join("comm")).map(|s| s.trim().to_string()).unwrap_or_default();
+            out.push(BusyHolder { pid, name, path });
+        }
+    }
+    out
+}
+
+fn emit_diff(app_handle: &tauri::AppHandle, prev: &[RemovableDevice], cur: &[RemovableDevice]) {
+    for d in cur {
+        match prev.iter().find(|p| p.id == d.id) {
+            None => {
+                log_append("INFO", &format!("storage: added {} ({}, {})", d.device, d.label, d.bus));
+                let _ = app_handle.emit("sis:storage-added", d);
+                if !d.mount_points.is_empty() { let _ = app_handle.emit("sis:storage-mounted", d); }
+            }
+            Some(p) if p.mount_points.is_empty() && !d.mount_points.is_empty() => { let _ = app_handle.emit("sis:storage-mounted", d); }
+            Some(p) if !p.mount_points.is_empty() && d.mount_points.is_empty() => { let _ = app_handle.emit("sis:storage-unmounted", d); }
+            _ => {}
+        }
+    }
+    for p in prev {
+        if !cur.iter().any(|d| d.id == p.id) {
+            log_append("INFO", &format!("storage: removed {}", p.device));
+            let _ = app_handle.emit("sis:storage-removed", p);
+        }
+    }
+}
+
+pub fn start(app_handle: tauri::AppHandle) {
+    let conn = match system_bus() { Ok(c) => c, Err(_) => { log_append("WARN", "storage: system bus unavailable; media watcher disabled"); return; } };
+    if let Err(e) = list_removable(&conn) { log_append("WARN", &format!("storage: media watcher disabled ({})", e)); return; }
+    std::thread::spawn(move || loop {
+        if let Ok(cur) = list_removable(&conn) {
+            let mut last = LAST.lock().unwrap();
+            if last.as_ref() != Some(&cur) {
+                // 初回は通知せず基準値として保持
+                if let Some(prev) = last.as_ref() { emit_diff(&app_handle, prev, &cur); }
+                let _ = app_handle.emit("sis:storage-changed", &cur);
+                *last = Some(cur);
+            }
+        }
+        std::thread::sleep(POLL);
+    });
+}
+
+#[tauri::command]
+pub fn storage_list_mounts() -> Result<Vec<MountedFs>, SisError> {
+    let disks = Disks::new_with_refreshed_list();
+    Ok(disks.list().iter().map(|d| MountedFs {
+        device: d.name().to_string_lossy().to_string(),
+        mount_point: d.mount_point().to_string_lossy().to_string(),
+        fs_type: d.file_system().to_string_lossy().to_string(),
+        total: d.total_space(),
+        available: d.available_space(),
+        used: d.total_space().saturating_sub(d.available_space()),
+        removable: d.is_removable(),
+        read_only: d.is_read_only(),
+    }).collect())
+}
+
+#[tauri::command]
+pub fn storage_list_removable() -> Result<Vec<RemovableDevice>, SisError> {
+    Ok(list_removable(&system_bus()?)?)
+}
+
+#[tauri::command]
+pub fn storage_policy() -> Result<StoragePolicy, SisError> { Ok(read_policy()) }
+
+#[tauri::command]
+pub fn storage_mount(id: String) -> Result<String, SisError> {
+    let conn = system_bus()?;
+    let dev = find_device(&conn, &id)?;
+    Ok(mount_device(&conn, &dev)?)
+}
+
+/// 失敗時 "device-busy" の場合は storage_busy_holders で使用中のプロセスを取得できる
+#[tauri::command]
+pub fn storage_unmount(id: String) -> Result<String, SisError> {
+    let conn = system_bus()?;
+    let dev = find_device(&conn, &id)?;
+    unmount_device(&conn, &dev)?;
+    Ok("unmounted".into())
+}
+
+#[tauri::command]
+pub fn storage_busy_holders(id: String) -> Result<Vec<BusyHolder>, SisError> {
+    let conn = system_bus()?;
+    let dev = find_device(&conn, &id)?;
+    Ok(busy_holders(&dev.mount_points))
+}
+
+/// 安全な取り外し: 同じドライブ上の全ファイルシステムをアンマウントしてから取り出し/電源断
+#[tauri::command]
+pub fn storage_eject(id: String) -> Result<String, SisError> {
+    let conn = system_bus()?;
+    let dev = find_device(&conn, &id)?;
+    let siblings: Vec<RemovableDevice> = match &dev.drive_id {
+        Some(drive) => list_removable(&conn)?.into_iter().filter(|d| d.drive_id.as_deref() == Some(drive.as_str())).collect(),
+        None => vec![dev.clone()],
+    };
+    for d in &siblings { unmount_device(&conn, d)?; }
+    if dev.bus == "loop" {
+        let lp = Proxy::new(&conn, UDISKS_DEST, dev.id.as_str(), IF_LOOP).map_err(|e| format!("udisks-unavailable: {}", e))?;
+        let _: () = lp.call("Delete", &(options(Vec::new()),)).map_err(|e| dbus_err("eject-failed", e))?;
+    } else if let Some(drive) = &dev.drive_id {
+        let dp = Proxy::new(&conn, UDISKS_DEST, drive.as_str(), IF_DRIVE).map_err(|e| format!("udisks-unavailable: {}", e))?;
+        if dp.get_property::<bool>("Ejectable").unwrap_or(false) {
+            let _: () = dp.call("Eject", &(options(Vec::new()),)).map_err(|e| dbus_err("eject-failed", e))?;
+        }
+        // USBメモリは電源断まで行うと抜去可能表示になる（失敗は無視）
+        if dp.get_property::<bool>("CanPowerOff").unwrap_or(false) {
+            if let Err(e) = dp.call::<_, _, ()>("PowerOff", &(options(Vec::new()),)) { log_append("WARN", &format!("storage: power-off failed: {}", e)); }
+        }
+    }
+    log_append("INFO", &format!("storage: ejected {}", dev.device));
+    Ok("ejected".into())
+}
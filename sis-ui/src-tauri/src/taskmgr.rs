@@ -0,0 +1,280 @@
+// Task manager: プロセス一覧・アプリ単位のグルーピング・終了(確認トークン付き)・renice・ストリーミング
+use std::collections::HashMap;
+use std::process::Command;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, Signal, UpdateKind, Users};
+use tauri::{Emitter, Manager};
+
+use crate::error::SisError;
+use crate::telemetry::SharedSystem;
+use crate::{log_append, match_desktop_to_window, run_out, which};
+
+// 確認トークンの有効期限
+const TOKEN_TTL: Duration = Duration::from_secs(30);
+const STREAM_MIN_MS: u64 = 1000;
+
+static PENDING: Lazy<Mutex<HashMap<String, PendingKill>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+// 送信スレッドの世代（奇数なら送信中）。開始・停止のたびに進めるので、停止直後に再開しても古いスレッドは次の周回で抜ける
+static STREAM_GEN: AtomicU64 = AtomicU64::new(0);
+static STREAM_MS: AtomicU64 = AtomicU64::new(2000);
+static USERS: Lazy<Mutex<Users>> = Lazy::new(|| Mutex::new(Users::new_with_refreshed_list()));
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProcessInfo {
+    pid: u32,
+    ppid: Option<u32>,
+    name: String,
+    cmdline: String,
+    user: Option<String>,
+    state: String,
+    cpu: f32,    // % (1コア=100)
+    memory: u64, // bytes (RSS)
+    start_time: u64,
+    own: bool,   // 現在のユーザーのプロセスか
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AppGroup {
+    key: String, // desktop ID / wm_class / "background" / "system"
+    name: String,
+    icon_data_url: Option<String>,
+    window_ids: Vec<String>,
+    pids: Vec<u32>,
+    cpu: f32,
+    memory: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProcessSnapshot {
+    processes: Vec<ProcessInfo>,
+    groups: Vec<AppGroup>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KillConfirmation {
+    token: String,
+    signal: String,
+    targets: Vec<ProcessInfo>,
+    expires_in_secs: u64,
+}
+
+struct PendingKill {
+    // pid 再利用対策に開始時刻も照合する
+    targets: Vec<(u32, u64)>,
+    signal: Signal,
+    created: Instant,
+}
+
+fn current_uid(sys: &sysinfo::System) -> Option<String> {
+    let me = sysinfo::get_current_pid().ok()?;
+    sys.process(me)?.user_id().map(|u| u.to_string())
+}
+
+fn refresh(system: &SharedSystem) {
+    let mut sys = system.lock().unwrap();
+    sys.refresh_processes_specifics(
+        ProcessesToUpdate::All,
+        true,
+        ProcessRefreshKind::nothing().with_cpu().with_memory().with_user(UpdateKind::OnlyIfNotSet).with_cmd(UpdateKind::OnlyIfNotSet),
+    );
+}
+
+fn collect(system: &SharedSystem) -> Vec<ProcessInfo> {
+    let sys = system.lock().unwrap();
+    let me = current_uid(&sys);
+    let users = USERS.lock().unwrap();
+    let mut out: Vec<ProcessInfo> = sys.processes().values()
+        // スレッド(タスク)は除外
+        .filter(|p| p.thread_kind() != Some(sysinfo::ThreadKind::Userland))
+        .map(|p| {
+            let uid = p.user_id();
+            ProcessInfo {
+                pid: p.pid().as_u32(),
+                ppid: p.parent().map(|x| x.as_u32()),
+                name: p.name().to_string_lossy().to_string(),
+                cmdline: p.cmd().iter().map(|s| s.to_string_lossy()).collect::<Vec<_>>().join(" "),
+                user: uid.map(|u| users.get_user_by_id(u).map(|x| x.name().to_string()).unwrap_or_else(|| u.to_string())),
+                state: p.status().to_string(),
+                cpu: p.cpu_usage(),
+                memory: p.memory(),
+                start_time: p.start_time(),
+                own: uid.map(|u| u.to_string()) == me,
+            }
+        })
+        .collect();
+    out.sort_by(|a, b| b.cpu.partial_cmp(&a.cpu).unwrap_or(std::cmp::Ordering::Equal).then(b.memory.cmp(&a.memory)));
+    out
+}
+
+// wmctrl -lxp: ID DESKTOP PID WM_CLASS HOST TITLE
+fn list_windows() -> Vec<(String, u32, String, String)> {
+    if !which("wmctrl") { return Vec::new(); }
+    let text = match run_out("sh", &["-lc", "wmctrl -lxp 2>/dev/null"]) { Some(s) => s, None => return Vec::new() };
+    let re = regex::Regex::new(r"^(\S+)\s+\S+\s+(\d+)\s+(\S+)\s+\S+\s+(.*)$").unwrap();
+    text.lines().filter_map(|line| {
+        let c = re.captures(line)?;
+        let pid = c.get(2)?.as_str().parse::<u32>().ok().filter(|p| *p > 0)?;
+        Some((c.get(1)?.as_str().to_string(), pid, c.get(3)?.as_str().to_string(), c.get(4)?.as_str().trim().to_string()))
+    }).collect()
+}
+
+// ウィンドウを持つプロセスを起点に、その子孫をアプリとしてまとめる
+fn group(processes: &[ProcessInfo]) -> Vec<AppGroup> {
+    let parent: HashMap<u32, u32> = processes.iter().filter_map(|p| p.ppid.map(|pp| (p.pid, pp))).collect();
+    let mut roots: HashMap<u32, usize> = HashMap::new();
+    let mut groups: Vec<AppGroup> = Vec::new();
+    for (id, pid, wclass, title) in list_windows() {
+        let app = match_desktop_to_window(&wclass, &title);
+        let key = app.as_ref().and_then(|a| a.desktop_id.clone()).unwrap_or_else(|| wclass.to_lowercase());
+        let idx = match groups.iter().position(|g| g.key == key) {
+            Some(i) => i,
+            None => {
+                let name = app.as_ref().map(|a| a.name.clone()).unwrap_or_else(|| wclass.split('.').last().unwrap_or(&wclass).to_string());
+                groups.push(AppGroup { key, name, icon_data_url: app.and_then(|a| a.icon_data_url), window_ids: Vec::new(), pids: Vec::new(), cpu: 0.0, memory: 0 });
+                groups.len() - 1
+            }
+        };
+        groups[idx].window_ids.push(id);
+        roots.entry(pid).or_insert(idx);
+    }
+    let mut background = AppGroup { key: "background".into(), name: "バックグラウンド".into(), icon_data_url: None, window_ids: Vec::new(), pids: Vec::new(), cpu: 0.0, memory: 0 };
+    let mut system = AppGroup { key: "system".into(), name: "システム".into(), icon_data_url: None, window_ids: Vec::new(), pids: Vec::new(), cpu: 0.0, memory: 0 };
+    for p in processes {
+        // 祖先をたどって最初に見つかったウィンドウ所有プロセスのグループへ
+        let mut cur = p.pid;
+        let mut hit = None;
+        for _ in 0..64 {
+            if let Some(i) = roots.get(&cur) { hit = Some(*i); break; }
+            match parent.get(&cur) { Some(pp) if *pp != cur => cur = *pp, _ => break }
+        }
+        let g = match hit { Some(i) => &mut groups[i], None if p.own => &mut background, None => &mut system };
+        g.pids.push(p.pid);
+        g.cpu += p.cpu;
+        g.memory += p.memory;
+    }
+    groups.sort_by(|a, b| b.cpu.partial_cmp(&a.cpu).unwrap_or(std::cmp::Ordering::Equal));
+    groups.push(background);
+    groups.push(system);
+    groups
+}
+
+fn snapshot(system: &SharedSystem) -> ProcessSnapshot {
+    refresh(system);
+    let processes = collect(system);
+    let groups = group(&processes);
+    ProcessSnapshot { processes, groups }
+}
+
+#[tauri::command]
+pub fn process_list(system: tauri::State<SharedSystem>) -> Result<ProcessSnapshot, SisError> {
+    Ok(snapshot(&system))
+}
+
+fn parse_signal(s: &str) -> Result<Signal, String> {
+    match s {
+        "term" => Ok(Signal::Term),
+        "kill" => Ok(Signal::Kill),
+        "stop" => Ok(Signal::Stop),
+        "cont" => Ok(Signal::Continue),
+        other => Err(format!("unsupported-signal: {}", other)),
+    }
+}
+
+fn new_token(pids: &[u32]) -> String {
+    let nanos = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0);
+    let seed = pids.iter().fold(nanos as u64, |acc, p| acc.rotate_left(7) ^ (*p as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    let mut rnd = [0u8; 8];
+    if let Ok(mut f) = std::fs::File::open("/dev/urandom") { use std::io::Read; let _ = f.read_exact(&mut rnd); }
+    format!("{:016x}{:016x}", seed, u64::from_le_bytes(rnd))
+}
+
+/// 操作してよいプロセスか（init・自分自身・他ユーザー(root含む)のプロセスは対象外）
+fn check_target(all: &[ProcessInfo], pid: u32) -> Result<&ProcessInfo, String> {
+    if pid <= 1 || pid == std::process::id() { return Err(format!("protected-process: {}", pid)); }
+    let p = all.iter().find(|p| p.pid == pid).ok_or_else(|| format!("not-found: {}", pid))?;
+    if !p.own { return Err(format!("permission-denied: {}", pid)); }
+    Ok(p)
+}
+
+/// 終了要求。対象を確認用に返し、process_kill_confirm(token) で実行する
+#[tauri::command]
+pub fn process_kill_request(system: tauri::State<SharedSystem>, pids: Vec<u32>, signal: Option<String>) -> Result<KillConfirmation, SisError> {
+    let sig_name = signal.unwrap_or_else(|| "term".into());
+    let sig = parse_signal(&sig_name)?;
+    if pids.is_empty() { return Err("no-targets".into()); }
+    refresh(&system);
+    let all = collect(&system);
+    let mut targets = Vec::new();
+    for pid in &pids { targets.push(check_target(&all, *pid)?.clone()); }
+    let token = new_token(&pids);
+    let mut pending = PENDING.lock().unwrap();
+    pending.retain(|_, v| v.created.elapsed() < TOKEN_TTL);
+    pending.insert(token.clone(), PendingKill { targets: targets.iter().map(|p| (p.pid, p.start_time)).collect(), signal: sig, created: Instant::now() });
+    Ok(KillConfirmation { token, signal: sig_name, targets, expires_in_secs: TOKEN_TTL.as_secs() })
+}
+
+#[tauri::command]
+pub fn process_kill_confirm(system: tauri::State<SharedSystem>, token: String) -> Result<Vec<u32>, SisError> {
+    let req = PENDING.lock().unwrap().remove(&token).ok_or("invalid-token")?;
+    if req.created.elapsed() >= TOKEN_TTL { return Err("token-expired".into()); }
+    refresh(&system);
+    let sys = system.lock().unwrap();
+    let mut done = Vec::new();
+    for (pid, start) in req.targets {
+        let p = match sys.process(Pid::from_u32(pid)) { Some(p) => p, None => continue };
+        if p.start_time() != start { log_append("WARN", &format!("taskmgr: pid {} was reused; skipped", pid)); continue; }
+        match p.kill_with(req.signal) {
+            Some(true) => done.push(pid),
+            Some(false) => log_append("WARN", &format!("taskmgr: signal {:?} to pid {} failed", req.signal, pid)),
+            None => log_append("WARN", &format!("taskmgr: signal {:?} unsupported", req.signal)),
+        }
+    }
+    log_append("INFO", &format!("taskmgr: sent {:?} to {:?}", req.signal, done));
+    if done.is_empty() { return Err("kill-failed".into()); }
+    Ok(done)
+}
+
+/// nice 値の変更（-20..19）。一般ユーザーは優先度を下げる方向のみ可能
+#[tauri::command]
+pub fn process_renice(system: tauri::State<SharedSystem>, pid: u32, nice: i32) -> Result<String, SisError> {
+    if !(-20..=19).contains(&nice) { return Err("invalid-nice".into()); }
+    refresh(&system);
+    check_target(&collect(&system), pid)?;
+    let out = Command::new("renice").args(["-n", &nice.to_string(), "-p", &pid.to_string()]).output()
+        .map_err(|e| format!("failed-to-run-renice: {}", e))?;
+    if out.status.success() {
+        log_append("INFO", &format!("taskmgr: renice pid={} nice={}", pid, nice));
+        Ok("reniced".into())
+    } else {
+        Err(SisError::failed("renice-failed", String::from_utf8_lossy(&out.stderr).trim()))
+    }
+}
+
+/// sis:processes イベントで一覧を定期送信する（タスクマネージャー表示中のみ）
+#[tauri::command]
+pub fn process_stream_start(app_handle: tauri::AppHandle, interval_ms: Option<u64>) -> Result<String, SisError> {
+    STREAM_MS.store(interval_ms.unwrap_or(2000).max(STREAM_MIN_MS), Ordering::Relaxed);
+    let Ok(prev) = STREAM_GEN.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |g| if g & 1 == 1 { None } else { Some(g + 1) }) else {
+        return Ok("already-streaming".into());
+    };
+    let generation = prev + 1;
+    let system = app_handle.state::<SharedSystem>().inner().clone();
+    std::thread::spawn(move || {
+        while STREAM_GEN.load(Ordering::SeqCst) == generation {
+            let _ = app_handle.emit("sis:processes", &snapshot(&system));
+            std::thread::sleep(Duration::from_millis(STREAM_MS.load(Ordering::Relaxed)));
+        }
+    });
+    Ok("streaming".into())
+}
+
+#[tauri::command]
+pub fn process_stream_stop() -> Result<String, SisError> {
+    let _ = STREAM_GEN.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |g| if g & 1 == 1 { Some(g + 1) } else { None });
+    Ok("stopped".into())
+}
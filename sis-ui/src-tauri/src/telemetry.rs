@@ -0,0 +1,261 @@
+// System telemetry: 管理下の sysinfo::System を使い回すバックグラウンドサンプラー
+use std::collections::{HashSet, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use sysinfo::{Components, Disks, Networks, System};
+use tauri::{Emitter, Manager};
+
+use crate::error::SisError;
+use crate::{log_append, read_settings};
+
+/// tauri state として manage される共有 System（プロセス一覧など他機能も同じものを使う）
+pub type SharedSystem = Arc<Mutex<System>>;
+
+pub const DEFAULT_INTERVAL_MS: u64 = 1000;
+const MIN_INTERVAL_MS: u64 = 250;
+const MAX_INTERVAL_MS: u64 = 10_000;
+// スパークライン用の履歴（件数）
+const HISTORY_LEN: usize = 120;
+// センサー/ディスク一覧の再スキャン間隔（ホットプラグ対策）
+const RESCAN_EVERY: Duration = Duration::from_secs(30);
+
+static INTERVAL_MS: AtomicU64 = AtomicU64::new(DEFAULT_INTERVAL_MS);
+static HISTORY: Lazy<Mutex<VecDeque<TelemetrySample>>> = Lazy::new(|| Mutex::new(VecDeque::with_capacity(HISTORY_LEN)));
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LoadAverage { one: f64, five: f64, fifteen: f64 }
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MemoryInfo {
+    total: u64,
+    used: u64,
+    available: u64,
+    swap_total: u64,
+    swap_used: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Temperature {
+    label: String,
+    celsius: f32,
+    critical: Option<f32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NetInterface {
+    name: String,
+    rx_bps: f64,
+    tx_bps: f64,
+    rx_total: u64,
+    tx_total: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DiskIo {
+    name: String,
+    mount_point: String,
+    read_bps: f64,
+    write_bps: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TelemetrySample {
+    ts: i64, // unix ms
+    interval_ms: u64,
+    cpu_total: f32,
+    cpu_per_core: Vec<f32>,
+    cpu_freq_mhz: Vec<u64>,
+    load: LoadAverage,
+    memory: MemoryInfo,
+    temperatures: Vec<Temperature>,
+    network: Vec<NetInterface>,
+    net_rx_bps: f64,
+    net_tx_bps: f64,
+    disks: Vec<DiskIo>,
+}
+
+pub fn clamp_interval(ms: u64) -> u64 { ms.clamp(MIN_INTERVAL_MS, MAX_INTERVAL_MS) }
+
+pub fn set_interval(ms: u64) { INTERVAL_MS.store(clamp_interval(ms), Ordering::Relaxed); }
+
+// ループバックや仮想ブリッジはレート表示に含めない
+fn skip_interface(name: &str) -> bool {
+    name == "lo" || name.starts_with("veth") || name.starts_with("docker") || name.starts_with("virbr")
+}
+
+struct Sampler {
+    networks: Networks,
+    disks: Disks,
+    components: Components,
+    last: Instant,
+    last_rescan: Instant,
+}
+
+impl Sampler {
+    fn new() -> Self {
+        Sampler {
+            networks: Networks::new_with_refreshed_list(),
+            disks: Disks::new_with_refreshed_list(),
+            components: Components::new_with_refreshed_list(),
+            last: Instant::now(),
+            last_rescan: Instant::now(),
+        }
+    }
+
+    fn sample(&mut self, system: &SharedSystem) -> TelemetrySample {
+        let dt = self.last.elapsed().as_secs_f64().max(0.001);
+        self.last = Instant::now();
+        let rescan = self.last_rescan.elapsed() >= RESCAN_EVERY;
+        if rescan { self.last_rescan = Instant::now(); }
+
+        // System のロックは CPU/メモリ更新の間だけ保持する
+        let (cpu_total, cpu_per_core, cpu_freq_mhz, memory) = {
+            let mut sys = system.lock().unwrap();
+            sys.refresh_cpu_all();
+            sys.refresh_memory();
+            (
+                sys.global_cpu_usage(),
+                sys.cpus().iter().map(|c| c.cpu_usage()).collect::<Vec<_>>(),
+                sys.cpus().iter().map(|c| c.frequency()).collect::<Vec<_>>(),
+                MemoryInfo {
+                    total: sys.total_memory(),
+                    used: sys.used_memory(),
+                    available: sys.available_memory(),
+                    swap_total: sys.total_swap(),
+                    swap_used: sys.used_swap(),
+                },
+            )
+        };
+        let la = System::load_average();
+
+        if rescan { self.components.refresh(true); } else { self.components.refresh(false); }
+        let temperatures = self.components.iter()
+            .filter_map(|c| c.temperature().filter(|t| t.is_finite()).map(|t| Temperature {
+                label: c.label().to_string(),
+                celsius: t,
+                critical: c.critical(),
+            }))
+            .collect::<Vec<_>>();
+
+        self.networks.refresh(rescan);
+        let mut network = Vec::new();
+        let (mut net_rx_bps, mut net_tx_bps) = (0.0, 0.0);
+        for (name, data) in self.networks.iter() {
+            if skip_interface(name) { continue; }
+            let rx_bps = data.received() as f64 / dt;
+            let tx_bps = data.transmitted() as f64 / dt;
+            net_rx_bps += rx_bps;
+            net_tx_bps += tx_bps;
+            network.push(NetInterface { name: name.clone(), rx_bps, tx_bps, rx_total: data.total_received(), tx_total: data.total_transmitted() });
+        }
+        network.sort_by(|a, b| a.name.cmp(&b.name));
+
+        if rescan { self.disks.refresh(true); } else { self.disks.refresh(false); }
+        // 同一デバイスの複数マウント（bind mount等）は最初の1つだけ数える
+        let mut seen: HashSet<String> = HashSet::new();
+        let mut disks = Vec::new();
+        for d in self.disks.list() {
+            let name = d.name().to_string_lossy().to_string();
+            if name.starts_with("/dev/loop") || !seen.insert(name.clone()) { continue; }
+            let u = d.usage();
+            disks.push(DiskIo {
+                name,
+                mount_point: d.mount_point().to_string_lossy().to_string(),
+                read_bps: u.read_bytes as f64 / dt,
+                write_bps: u.written_bytes as f64 / dt,
+            });
+        }
+
+        TelemetrySample {
+            ts: chrono::Utc::now().timestamp_millis(),
+            interval_ms: INTERVAL_MS.load(Ordering::Relaxed),
+            cpu_total,
+            cpu_per_core,
+            cpu_freq_mhz,
+            load: LoadAverage { one: la.one, five: la.five, fifteen: la.fifteen },
+            memory,
+            temperatures,
+            network,
+            net_rx_bps,
+            net_tx_bps,
+            disks,
+        }
+    }
+}
+
+fn push_history(s: TelemetrySample) {
+    let mut h = HISTORY.lock().unwrap();
+    if h.len() >= HISTORY_LEN { h.pop_front(); }
+    h.push_back(s);
+}
+
+fn latest() -> Option<TelemetrySample> { HISTORY.lock().unwrap().back().cloned() }
+
+pub fn start(app_handle: tauri::AppHandle) {
+    let system = app_handle.state::<SharedSystem>().inner().clone();
+    if let Some(ms) = read_settings().telemetry_interval_ms { set_interval(ms); }
+    std::thread::spawn(move || {
+        let mut sampler = Sampler::new();
+        // CPU使用率は2回の更新差分から計算されるため、初回は捨てる
+        { system.lock().unwrap().refresh_cpu_all(); }
+        std::thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+        log_append("INFO", &format!("telemetry: sampler started interval={}ms", INTERVAL_MS.load(Ordering::Relaxed)));
+        loop {
+            let s = sampler.sample(&system);
+            let _ = app_handle.emit("sis:telemetry", &s);
+            push_history(s);
+            std::thread::sleep(Duration::from_millis(INTERVAL_MS.load(Ordering::Relaxed)));
+        }
+    });
+}
+
+/// 旧 get_system_info 互換の概要（cpu%, mem%, MB/s）
+pub fn legacy_summary(system: &SharedSystem) -> serde_json::Value {
+    let s = match latest() {
+        Some(s) => s,
+        None => {
+            // サンプラー未起動時は共有 System から単発で取得
+            let mut sampler = Sampler::new();
+            sampler.sample(system)
+        }
+    };
+    let mem_usage = if s.memory.total > 0 { (s.memory.used as f64 / s.memory.total as f64 * 100.0) as u64 } else { 0 };
+    let mb = 1024.0 * 1024.0;
+    serde_json::json!({
+        "cpuUsage": (s.cpu_total as f64 * 10.0).round() / 10.0,
+        "memUsage": mem_usage,
+        "downloadSpeed": (s.net_rx_bps / mb * 100.0).round() / 100.0,
+        "uploadSpeed": (s.net_tx_bps / mb * 100.0).round() / 100.0,
+    })
+}
+
+#[tauri::command]
+pub fn telemetry_snapshot(system: tauri::State<SharedSystem>) -> Result<TelemetrySample, SisError> {
+    match latest() {
+        Some(s) => Ok(s),
+        None => Ok(Sampler::new().sample(&system)),
+    }
+}
+
+/// 直近の履歴（古い順）。limit 省略時は全件
+#[tauri::command]
+pub fn telemetry_history(limit: Option<usize>) -> Result<Vec<TelemetrySample>, SisError> {
+    let h = HISTORY.lock().unwrap();
+    let n = limit.unwrap_or(HISTORY_LEN).min(h.len());
+    Ok(h.iter().skip(h.len() - n).cloned().collect())
+}
+
+#[tauri::command]
+pub fn telemetry_set_interval(interval_ms: u64) -> Result<u64, SisError> {
+    let ms = clamp_interval(interval_ms);
+    set_interval(ms);
+    let mut cur = read_settings();
+    cur.telemetry_interval_ms = Some(ms);
+    crate::write_settings(&cur);
+    log_append("INFO", &format!("telemetry: interval set to {}ms", ms));
+    Ok(ms)
+}
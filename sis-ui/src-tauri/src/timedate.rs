@@ -0,0 +1,224 @@
+// 日付と時刻: systemd-timedated（org.freedesktop.timedate1）でタイムゾーン・NTP・手動の時刻設定を行う。
+// 校外学習などで持ち出した端末のタイムゾーンの変化は /etc/localtime を見て検知し、sis:time-state で送る。
+// 12/24 時間表示は SIS の表示設定（time.json）
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{Local, NaiveDateTime, Offset, TimeZone};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+use zbus::blocking::Proxy;
+
+use crate::error::SisError;
+use crate::{exam_mode, history_dir, log_append, run_out, which};
+
+const TIMEDATE1: &str = "org.freedesktop.timedate1";
+const TIMEDATE_PATH: &str = "/org/freedesktop/timedate1";
+const ZONE1970_TAB: &str = "/usr/share/zoneinfo/zone1970.tab";
+// timedated はしばらく使わないと終了するので、D-Bus ではなく /etc/localtime を見て変化を検知する
+const POLL: Duration = Duration::from_secs(5);
+
+static APP: Lazy<Mutex<Option<tauri::AppHandle>>> = Lazy::new(|| Mutex::new(None));
+static LAST_TZ: Lazy<Mutex<Option<String>>> = Lazy::new(|| Mutex::new(None));
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct TimeConfig {
+    hour12: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TimeState {
+    timezone: Option<String>,
+    utc_offset_min: i32,
+    now_ms: i64,
+    can_ntp: bool,
+    ntp: bool,
+    ntp_synchronized: bool,
+    local_rtc: bool,
+    hour12: bool,
+    available: bool, // timedated を使えるか
+    exam_mode: bool, // 試験中は NTP の停止と手動設定を禁止する
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TimezoneInfo {
+    name: String,
+    country: Option<String>, // ISO 3166 の国コード（複数国にまたがる場合はカンマ区切り）
+    comment: Option<String>,
+}
+
+fn config_path() -> Option<PathBuf> { history_dir().map(|d| d.join("time.json")) }
+
+fn read_config() -> TimeConfig {
+    config_path().and_then(|p| fs::read_to_string(p).ok()).and_then(|s| serde_json::from_str(&s).ok()).unwrap_or_default()
+}
+
+fn write_config(c: &TimeConfig) -> Result<(), String> {
+    let p = config_path().ok_or("no-data-dir")?;
+    if let Some(dir) = p.parent() { let _ = fs::create_dir_all(dir); }
+    fs::write(&p, serde_json::to_string_pretty(c).map_err(|e| e.to_string())?).map_err(|e| format!("write-failed: {}", e))
+}
+
+fn timedate() -> Result<Proxy<'static>, String> {
+    let conn = crate::session::system_bus().map_err(|_| "timedated-unavailable".to_string())?;
+    Proxy::new(&conn, TIMEDATE1, TIMEDATE_PATH, TIMEDATE1).map_err(|e| format!("timedated-unavailable: {}", e))
+}
+
+fn td_err(prefix: &str, e: zbus::Error) -> String {
+    let s = e.to_string();
+    if s.contains("NotAuthorized") || s.contains("AccessDenied") || s.contains("InteractiveAuthorizationRequired") { return "not-authorized".into(); }
+    if s.contains("ServiceUnknown") { return "timedated-unavailable".into(); }
+    if s.contains("InvalidArgs") && s.contains("timezone") { return "invalid-timezone".into(); }
+    format!("{}: {}", prefix, s)
+}
+
+/// 現在のタイムゾーン名（timedated を起こさずに済むよう /etc/localtime のリンク先を先に見る）
+pub fn timezone_name() -> Option<String> {
+    if let Some(tz) = fs::read_link("/etc/localtime").ok().and_then(|l| l.to_string_lossy().split("zoneinfo/").nth(1).map(|t| t.to_string())) {
+        return Some(tz);
+    }
+    if let Some(tz) = fs::read_to_string("/etc/timezone").ok().map(|s| s.trim().to_string()).filter(|s| !s.is_empty()) {
+        return Some(tz);
+    }
+    run_out("timedatectl", &["show", "-p", "Timezone", "--value"]).map(|s| s.trim().to_string()).filter(|s| !s.is_empty())
+}
+
+fn read_state() -> TimeState {
+    let now = Local::now();
+    let mut st = TimeState {
+        timezone: timezone_name(),
+        utc_offset_min: now.offset().fix().local_minus_utc() / 60,
+        now_ms: now.timestamp_millis(),
+        can_ntp: false,
+        ntp: false,
+        ntp_synchronized: false,
+        local_rtc: false,
+        hour12: read_config().hour12,
+        available: false,
+        exam_mode: exam_mode(),
+    };
+    // プロパティはキャッシュされるので毎回プロキシを作り直す
+    if let Ok(p) = timedate() {
+        if let Ok(tz) = p.get_property::<String>("Timezone") {
+            st.available = true;
+            if !tz.is_empty() { st.timezone = Some(tz); }
+            st.can_ntp = p.get_property::<bool>("CanNTP").unwrap_or(false);
+            st.ntp = p.get_property::<bool>("NTP").unwrap_or(false);
+            st.ntp_synchronized = p.get_property::<bool>("NTPSynchronized").unwrap_or(false);
+            st.local_rtc = p.get_property::<bool>("LocalRTC").unwrap_or(false);
+        }
+    }
+    st
+}
+
+fn emit_state() -> TimeState {
+    let st = read_state();
+    *LAST_TZ.lock().unwrap() = st.timezone.clone();
+    if let Some(app) = APP.lock().unwrap().as_ref() { let _ = app.emit("sis:time-state", &st); }
+    st
+}
+
+/// zone1970.tab の国コードと説明（"Asia/Tokyo" → JP）
+fn zone_comments() -> std::collections::HashMap<String, (String, Option<String>)> {
+    fs::read_to_string(ZONE1970_TAB).unwrap_or_default().lines().filter(|l| !l.starts_with('#')).filter_map(|l| {
+        let cols: Vec<&str> = l.split('\t').collect();
+        let name = cols.get(2)?.to_string();
+        Some((name, (cols.first()?.to_string(), cols.get(3).map(|c| c.to_string()))))
+    }).collect()
+}
+
+fn list_timezones() -> Vec<String> {
+    if let Ok(list) = timedate().and_then(|p| p.call::<_, _, Vec<String>>("ListTimezones", &()).map_err(|e| e.to_string())) {
+        if !list.is_empty() { return list; }
+    }
+    if which("timedatectl") {
+        if let Some(out) = run_out("timedatectl", &["list-timezones", "--no-pager"]) {
+            return out.lines().map(|l| l.trim().to_string()).filter(|l| !l.is_empty()).collect();
+        }
+    }
+    let mut v: Vec<String> = zone_comments().into_keys().collect();
+    v.sort();
+    v
+}
+
+fn watch_loop() {
+    loop {
+        std::thread::sleep(POLL);
+        let tz = timezone_name();
+        let prev = LAST_TZ.lock().unwrap().clone();
+        if tz != prev {
+            log_append("INFO", &format!("time: timezone changed: {} -> {}", prev.as_deref().unwrap_or("-"), tz.as_deref().unwrap_or("-")));
+            emit_state();
+        }
+    }
+}
+
+pub fn start(app_handle: tauri::AppHandle) {
+    *APP.lock().unwrap() = Some(app_handle);
+    *LAST_TZ.lock().unwrap() = timezone_name();
+    std::thread::spawn(watch_loop);
+}
+
+#[tauri::command]
+pub fn time_state() -> Result<TimeState, SisError> { Ok(read_state()) }
+
+/// 検索語（名前・国コード・説明の部分一致、大文字小文字は区別しない）で絞り込んだタイムゾーン一覧
+#[tauri::command]
+pub fn time_list_timezones(query: Option<String>) -> Result<Vec<TimezoneInfo>, SisError> {
+    let comments = zone_comments();
+    let q = query.unwrap_or_default().trim().to_lowercase().replace(' ', "_");
+    Ok(list_timezones().into_iter().map(|name| {
+        let (country, comment) = comments.get(&name).cloned().map(|(c, m)| (Some(c), m)).unwrap_or((None, None));
+        TimezoneInfo { name, country, comment }
+    }).filter(|z| {
+        q.is_empty()
+            || z.name.to_lowercase().contains(&q)
+            || z.country.as_deref().is_some_and(|c| c.to_lowercase().split(',').any(|x| x == q))
+            || z.comment.as_deref().is_some_and(|c| c.to_lowercase().replace(' ', "_").contains(&q))
+    }).collect())
+}
+
+#[tauri::command]
+pub fn time_set_timezone(timezone: String) -> Result<TimeState, SisError> {
+    if timezone.is_empty() || timezone.starts_with('/') || timezone.contains("..") { return Err("invalid-timezone".into()); }
+    timedate()?.call::<_, _, ()>("SetTimezone", &(timezone.as_str(), true)).map_err(|e| td_err("set-timezone-failed", e))?;
+    log_append("INFO", &format!("time: timezone set: {}", timezone));
+    Ok(emit_state())
+}
+
+#[tauri::command]
+pub fn time_set_ntp(enabled: bool) -> Result<TimeState, SisError> {
+    if !enabled && exam_mode() { return Err("exam-mode".into()); }
+    let p = timedate()?;
+    if enabled && !p.get_property::<bool>("CanNTP").unwrap_or(false) { return Err("ntp-unavailable".into()); }
+    p.call::<_, _, ()>("SetNTP", &(enabled, true)).map_err(|e| td_err("set-ntp-failed", e))?;
+    Ok(emit_state())
+}
+
+/// 手動で時刻を合わせる（NTP が有効な間は timedated が拒否するので先に確かめる）。
+/// datetime は "YYYY-MM-DDTHH:MM[:SS]" のローカル時刻
+#[tauri::command]
+pub fn time_set_time(datetime: String) -> Result<TimeState, SisError> {
+    if exam_mode() { return Err("exam-mode".into()); }
+    let p = timedate()?;
+    if p.get_property::<bool>("NTP").unwrap_or(false) { return Err("ntp-enabled".into()); }
+    let naive = NaiveDateTime::parse_from_str(&datetime, "%Y-%m-%dT%H:%M:%S")
+        .or_else(|_| NaiveDateTime::parse_from_str(&datetime, "%Y-%m-%dT%H:%M"))
+        .map_err(|_| "invalid-datetime".to_string())?;
+    // 夏時間の切替で存在しない時刻は拒否し、重複する時刻は早い方を使う
+    let local = Local.from_local_datetime(&naive).earliest().ok_or("invalid-datetime")?;
+    let usec = local.timestamp_micros();
+    p.call::<_, _, ()>("SetTime", &(usec, false, true)).map_err(|e| td_err("set-time-failed", e))?;
+    log_append("INFO", &format!("time: time set manually: {}", local.to_rfc3339()));
+    Ok(emit_state())
+}
+
+#[tauri::command]
+pub fn time_set_hour12(enabled: bool) -> Result<TimeState, SisError> {
+    write_config(&TimeConfig { hour12: enabled })?;
+    Ok(emit_state())
+}
@@ -0,0 +1,400 @@
+// システムトレイ(StatusNotifierItem / AppIndicator): Watcher と Host を兼ね、アイテムのアイコン・ツールチップ・
+// com.canonical.dbusmenu のメニューをフロントへ中継する。Activate / スクロールはアプリへ転送する
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use base64::Engine;
+use image::{DynamicImage, ImageBuffer, ImageFormat, Rgba};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+use zbus::blocking::fdo::{DBusProxy, PropertiesProxy};
+use zbus::blocking::{Connection, Proxy};
+use zbus::names::{BusName, InterfaceName};
+use zbus::zvariant::{OwnedValue, Value};
+
+use crate::error::SisError;
+use crate::{log_append, resolve_icon_path, to_data_url};
+
+const POLL: Duration = Duration::from_secs(2);
+const WATCHER_NAME: &str = "org.kde.StatusNotifierWatcher";
+const WATCHER_PATH: &str = "/StatusNotifierWatcher";
+const DEFAULT_ITEM_PATH: &str = "/StatusNotifierItem";
+const IF_ITEM: &str = "org.kde.StatusNotifierItem";
+const IF_MENU: &str = "com.canonical.dbusmenu";
+// パネル表示に使うアイコンの目標サイズ
+const ICON_TARGET: i32 = 32;
+// アイコン 1 辺の上限（これより大きい pixmap は使わない）
+const ICON_MAX_SIDE: i32 = 512;
+const ICON_CACHE_MAX: usize = 128;
+
+// 登録済みアイテム（bus 名, オブジェクトパス）
+static ITEMS: Lazy<Mutex<Vec<(String, String)>>> = Lazy::new(|| Mutex::new(Vec::new()));
+static OWN_WATCHER: Lazy<Mutex<bool>> = Lazy::new(|| Mutex::new(false));
+static ICON_CACHE: Lazy<Mutex<HashMap<u64, String>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+static LAST: Lazy<Mutex<Vec<TrayItem>>> = Lazy::new(|| Mutex::new(Vec::new()));
+static SERVER: Lazy<Mutex<Option<Connection>>> = Lazy::new(|| Mutex::new(None));
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TrayItem {
+    id: String, // bus 名 + パス（操作時の ID）
+    item_id: String, // アプリが名乗る Id
+    category: String, // ApplicationStatus / Communications / SystemServices / Hardware
+    status: String,   // passive | active | needsattention
+    title: String,
+    icon: Option<String>, // data: URL（NeedsAttention 時は注意アイコン）
+    tooltip_title: String,
+    tooltip_body: String,
+    item_is_menu: bool,
+    menu: Option<String>, // dbusmenu のオブジェクトパス
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TrayMenuItem {
+    id: i32,
+    label: String,
+    kind: String, // standard | separator
+    enabled: bool,
+    visible: bool,
+    icon: Option<String>,
+    toggle_type: String, // "" | checkmark | radio
+    toggle_state: i32,   // 0: off, 1: on, -1: 不定
+    children: Vec<TrayMenuItem>,
+}
+
+fn session_bus() -> Result<Connection, String> {
+    static CONN: Lazy<Option<Connection>> = Lazy::new(|| Connection::session().ok());
+    CONN.clone().ok_or_else(|| "dbus-unavailable".to_string())
+}
+
+type Props = HashMap<String, OwnedValue>;
+
+fn prop<T: TryFrom<OwnedValue>>(props: &Props, key: &str) -> Option<T> {
+    props.get(key)?.try_clone().ok().and_then(|v| T::try_from(v).ok())
+}
+
+fn split_id(id: &str) -> (String, String) {
+    match id.find('/') {
+        Some(i) => (id[..i].to_string(), id[i..].to_string()),
+        None => (id.to_string(), DEFAULT_ITEM_PATH.to_string()),
+    }
+}
+
+fn emit_watcher_signal(name: &str, arg: Option<&str>) {
+    if let Some(conn) = SERVER.lock().unwrap().as_ref() {
+        let _ = match arg {
+            Some(a) => conn.emit_signal(None::<BusName>, WATCHER_PATH, WATCHER_NAME, name, &(a,)),
+            None => conn.emit_signal(None::<BusName>, WATCHER_PATH, WATCHER_NAME, name, &()),
+        };
+    }
+}
+
+struct Watcher;
+
+#[zbus::interface(name = "org.kde.StatusNotifierWatcher")]
+impl Watcher {
+    // service は bus 名またはオブジェクトパス（libappindicator はパスのみ送ってくる）
+    fn register_status_notifier_item(&self, service: String, #[zbus(header)] hdr: zbus::message::Header<'_>) {
+        let sender = hdr.sender().map(|s| s.to_string()).unwrap_or_default();
+        let (bus, path) = if service.starts_with('/') { (sender, service) } else { (service, DEFAULT_ITEM_PATH.to_string()) };
+        if bus.is_empty() { return; }
+        let key = format!("{}{}", bus, path);
+        {
+            let mut items = ITEMS.lock().unwrap();
+            if items.iter().any(|(b, p)| *b == bus && *p == path) { return; }
+            items.push((bus, path));
+        }
+        log_append("INFO", &format!("tray: registered {}", key));
+        emit_watcher_signal("StatusNotifierItemRegistered", Some(&key));
+    }
+
+    fn register_status_notifier_host(&self, _service: String) {
+        emit_watcher_signal("StatusNotifierHostRegistered", None);
+    }
+
+    #[zbus(property)]
+    fn registered_status_notifier_items(&self) -> Vec<String> {
+        ITEMS.lock().unwrap().iter().map(|(b, p)| format!("{}{}", b, p)).collect()
+    }
+
+    #[zbus(property)]
+    fn is_status_notifier_host_registered(&self) -> bool { true }
+
+    #[zbus(property)]
+    fn protocol_version(&self) -> i32 { 0 }
+}
+
+fn cache_key(parts: &[&[u8]]) -> u64 {
+    let mut h = DefaultHasher::new();
+    for p in parts { p.hash(&mut h); }
+    h.finish()
+}
+
+// IconPixmap (a(iiay), ARGB32 ネットワークバイトオーダー) → PNG data: URL
+fn pixmap_url(pixmaps: &[(i32, i32, Vec<u8>)]) -> Option<String> {
+    let best = pixmaps.iter().filter_map(|(w, h, d)| {
+        // 幅・高さはアプリが送ってくる値なので、上限を設けて usize で掛ける
+        if !(1..=ICON_MAX_SIDE).contains(w) || !(1..=ICON_MAX_SIDE).contains(h) { return None; }
+        let pixels = (*w as usize).checked_mul(*h as usize)?;
+        (d.len() >= pixels.checked_mul(4)?).then_some((*w, *h, d, pixels))
+    }).min_by_key(|(w, _, _, _)| if *w >= ICON_TARGET { *w - ICON_TARGET } else { (ICON_TARGET - *w) * 4 })?;
+    let (w, h, data, pixels) = best;
+    let key = cache_key(&[data, &w.to_le_bytes(), &h.to_le_bytes()]);
+    if let Some(hit) = ICON_CACHE.lock().unwrap().get(&key) { return Some(hit.clone()); }
+    let rgba: Vec<u8> = data.chunks_exact(4).take(pixels).flat_map(|p| [p[1], p[2], p[3], p[0]]).collect();
+    let buf: ImageBuffer<Rgba<u8>, Vec<u8>> = ImageBuffer::from_vec(w as u32, h as u32, rgba)?;
+    let mut cursor = std::io::Cursor::new(Vec::<u8>::new());
+    DynamicImage::ImageRgba8(buf).write_to(&mut cursor, ImageFormat::Png).ok()?;
+    let url = format!("data:image/png;base64,{}", base64::engine::general_purpose::STANDARD.encode(cursor.into_inner()));
+    cache_icon(key, &url);
+    Some(url)
+}
+
+fn cache_icon(key: u64, url: &str) {
+    let mut c = ICON_CACHE.lock().unwrap();
+    if c.len() >= ICON_CACHE_MAX { c.clear(); }
+    c.insert(key, url.to_string());
+}
+
+// IconName は IconThemePath（アプリ同梱のテーマ）を優先して探す
+fn named_icon_url(name: &str, theme_path: &str) -> Option<String> {
+    if name.is_empty() { return None; }
+    let key = cache_key(&[name.as_bytes(), theme_path.as_bytes()]);
+    if let Some(hit) = ICON_CACHE.lock().unwrap().get(&key) { return Some(hit.clone()); }
+    let mut found = None;
+    if !theme_path.is_empty() {
+        for ext in ["png", "svg"] {
+            let p = Path::new(theme_path).join(format!("{}.{}", name, ext));
+            if p.exists() { found = Some(p); break; }
+        }
+        if found.is_none() {
+            let sizes = ["32x32", "48x48", "64x64", "24x24", "22x22", "16x16", "scalable"];
+            'outer: for sz in sizes {
+                for sub in ["apps", "status"] {
+                    for ext in ["png", "svg"] {
+                        let p = Path::new(theme_path).join("hicolor").join(sz).join(sub).join(format!("{}.{}", name, ext));
+                        if p.exists() { found = Some(p); break 'outer; }
+                    }
+                }
+            }
+        }
+    }
+    let url = found.or_else(|| resolve_icon_path(name)).and_then(|p| to_data_url(&p))?;
+    cache_icon(key, &url);
+    Some(url)
+}
+
+fn get_all(conn: &Connection, bus: &str, path: &str, iface: &str) -> Result<Props, String> {
+    let p = PropertiesProxy::builder(conn)
+        .destination(bus.to_string()).and_then(|b| b.path(path.to_string()))
+        .and_then(|b| b.build())
+        .map_err(|e| format!("tray-unavailable: {}", e))?;
+    let iface = InterfaceName::try_from(iface).map_err(|e| e.to_string())?;
+    p.get_all(Some(iface).into()).map_err(|e| format!("tray-unavailable: {}", e))
+}
+
+fn read_item(conn: &Connection, bus: &str, path: &str) -> Result<TrayItem, String> {
+    let p = get_all(conn, bus, path, IF_ITEM)?;
+    let status = prop::<String>(&p, "Status").unwrap_or_else(|| "Active".into()).to_ascii_lowercase();
+    let theme = prop::<String>(&p, "IconThemePath").unwrap_or_default();
+    let icon_of = |name_key: &str, pix_key: &str| {
+        prop::<String>(&p, name_key).and_then(|n| named_icon_url(&n, &theme))
+            .or_else(|| prop::<Vec<(i32, i32, Vec<u8>)>>(&p, pix_key).and_then(|px| pixmap_url(&px)))
+    };
+    let normal = icon_of("IconName", "IconPixmap");
+    let icon = if status == "needsattention" { icon_of("AttentionIconName", "AttentionIconPixmap").or(normal) } else { normal };
+    let (tooltip_title, tooltip_body) = prop::<(String, Vec<(i32, i32, Vec<u8>)>, String, String)>(&p, "ToolTip")
+        .map(|(_, _, t, b)| (t, b)).unwrap_or_default();
+    let item_id = prop::<String>(&p, "Id").unwrap_or_default();
+    Ok(TrayItem {
+        id: format!("{}{}", bus, path),
+        title: prop::<String>(&p, "Title").filter(|s| !s.is_empty()).unwrap_or_else(|| item_id.clone()),
+        item_id,
+        category: prop::<String>(&p, "Category").unwrap_or_default(),
+        status,
+        icon,
+        tooltip_title,
+        tooltip_body,
+        item_is_menu: prop::<bool>(&p, "ItemIsMenu").unwrap_or(false),
+        menu: prop::<zbus::zvariant::OwnedObjectPath>(&p, "Menu").map(|o| o.to_string()).filter(|s| s != "/"),
+    })
+}
+
+// 他の Watcher（KDE 等）が動いている場合はそちらの一覧を使う
+fn registered(conn: &Connection) -> Vec<(String, String)> {
+    if *OWN_WATCHER.lock().unwrap() { return ITEMS.lock().unwrap().clone(); }
+    Proxy::new(conn, WATCHER_NAME, WATCHER_PATH, WATCHER_NAME).ok()
+        .and_then(|p| p.get_property::<Vec<String>>("RegisteredStatusNotifierItems").ok())
+        .unwrap_or_default().iter().map(|s| split_id(s)).collect()
+}
+
+fn read_items(conn: &Connection) -> Vec<TrayItem> {
+    registered(conn).iter().filter_map(|(b, p)| read_item(conn, b, p).ok()).collect()
+}
+
+pub fn start(app_handle: tauri::AppHandle) {
+    let conn = match session_bus() { Ok(c) => c, Err(_) => { log_append("WARN", "tray: D-Bus unavailable; tray disabled"); return; } };
+    let built = zbus::blocking::connection::Builder::session()
+        .and_then(|b| b.name(WATCHER_NAME))
+        .and_then(|b| b.serve_at(WATCHER_PATH, Watcher))
+        .and_then(|b| b.build());
+    match built {
+        Ok(c) => {
+            *SERVER.lock().unwrap() = Some(c);
+            *OWN_WATCHER.lock().unwrap() = true;
+            log_append("INFO", "tray: serving org.kde.StatusNotifierWatcher");
+        }
+        Err(e) => {
+            log_append("WARN", &format!("tray: could not own {} ({}); acting as host only", WATCHER_NAME, e));
+            let host = format!("org.kde.StatusNotifierHost-{}", std::process::id());
+            if conn.request_name(host.as_str()).is_ok() {
+                if let Ok(w) = Proxy::new(&conn, WATCHER_NAME, WATCHER_PATH, WATCHER_NAME) {
+                    let _ = w.call::<_, _, ()>("RegisterStatusNotifierHost", &(host.as_str(),));
+                }
+            }
+        }
+    }
+    std::thread::spawn(move || loop {
+        // 終了したアプリのアイテムを取り除く
+        if *OWN_WATCHER.lock().unwrap() {
+            if let Ok(dbus) = DBusProxy::new(&conn) {
+                let items = ITEMS.lock().unwrap().clone();
+                for (bus, path) in items {
+                    let alive = BusName::try_from(bus.as_str()).ok().and_then(|n| dbus.name_has_owner(n).ok()).unwrap_or(false);
+                    if !alive {
+                        ITEMS.lock().unwrap().retain(|(b, p)| !(*b == bus && *p == path));
+                        emit_watcher_signal("StatusNotifierItemUnregistered", Some(&format!("{}{}", bus, path)));
+                    }
+                }
+            }
+        }
+        let cur = read_items(&conn);
+        {
+            let mut last = LAST.lock().unwrap();
+            if *last != cur {
+                let _ = app_handle.emit("sis:tray-changed", &cur);
+                *last = cur;
+            }
+        }
+        std::thread::sleep(POLL);
+    });
+}
+
+fn item_proxy<'a>(conn: &Connection, id: &str) -> Result<Proxy<'a>, String> {
+    let (bus, path) = split_id(id);
+    Proxy::new(conn, bus, path, IF_ITEM).map_err(|e| format!("tray-unavailable: {}", e))
+}
+
+// dbusmenu のラベルはアンダースコアでニーモニックを表す（"__" はリテラルの "_"）
+fn strip_mnemonic(s: &str) -> String {
+    let mut out = String::new();
+    let mut it = s.chars().peekable();
+    while let Some(c) = it.next() {
+        if c == '_' {
+            if it.peek() == Some(&'_') { out.push('_'); it.next(); }
+            continue;
+        }
+        out.push(c);
+    }
+    out
+}
+
+type MenuNode = (i32, HashMap<String, OwnedValue>, Vec<OwnedValue>);
+
+// 子要素（av）は variant に包まれた (ia{sv}av)
+fn parse_child(v: OwnedValue) -> Option<TrayMenuItem> {
+    let v = match &*v { Value::Value(inner) => inner.try_to_owned().ok()?, _ => v };
+    parse_node(MenuNode::try_from(v).ok()?)
+}
+
+fn parse_node((id, props, children): MenuNode) -> Option<TrayMenuItem> {
+    let icon = prop::<String>(&props, "icon-name").and_then(|n| named_icon_url(&n, ""))
+        .or_else(|| prop::<Vec<u8>>(&props, "icon-data").filter(|b| !b.is_empty())
+            .map(|b| format!("data:image/png;base64,{}", base64::engine::general_purpose::STANDARD.encode(b))));
+    Some(TrayMenuItem {
+        id,
+        label: strip_mnemonic(&prop::<String>(&props, "label").unwrap_or_default()),
+        kind: prop::<String>(&props, "type").unwrap_or_else(|| "standard".into()),
+        enabled: prop::<bool>(&props, "enabled").unwrap_or(true),
+        visible: prop::<bool>(&props, "visible").unwrap_or(true),
+        icon,
+        toggle_type: prop::<String>(&props, "toggle-type").unwrap_or_default(),
+        toggle_state: prop::<i32>(&props, "toggle-state").unwrap_or(-1),
+        children: children.into_iter().filter_map(parse_child).filter(|c| c.visible).collect(),
+    })
+}
+
+fn menu_proxy<'a>(conn: &Connection, id: &str) -> Result<Proxy<'a>, String> {
+    let item = LAST.lock().unwrap().iter().find(|i| i.id == id).cloned();
+    let item = match item { Some(i) => i, None => { let (b, p) = split_id(id); read_item(conn, &b, &p)? } };
+    let menu = item.menu.ok_or("no-menu")?;
+    let (bus, _) = split_id(id);
+    Proxy::new(conn, bus, menu, IF_MENU).map_err(|e| format!("tray-unavailable: {}", e))
+}
+
+#[tauri::command]
+pub fn tray_items() -> Result<Vec<TrayItem>, SisError> { Ok(read_items(&session_bus()?)) }
+
+/// 左クリック。メニュー専用アイテム（ItemIsMenu / Activate 非対応）は "menu" を返すのでフロントでメニューを開く
+#[tauri::command]
+pub fn tray_activate(id: String, x: i32, y: i32) -> Result<String, SisError> {
+    let conn = session_bus()?;
+    if LAST.lock().unwrap().iter().any(|i| i.id == id && i.item_is_menu) { return Ok("menu".into()); }
+    match item_proxy(&conn, &id)?.call::<_, _, ()>("Activate", &(x, y)) {
+        Ok(()) => Ok("activated".into()),
+        Err(e) if e.to_string().contains("UnknownMethod") => Ok("menu".into()),
+        Err(e) => Err(SisError::failed("activate-failed", e)),
+    }
+}
+
+/// 中クリック
+#[tauri::command]
+pub fn tray_secondary_activate(id: String, x: i32, y: i32) -> Result<String, SisError> {
+    let conn = session_bus()?;
+    item_proxy(&conn, &id)?.call::<_, _, ()>("SecondaryActivate", &(x, y)).map_err(|e| format!("activate-failed: {}", e))?;
+    Ok("activated".into())
+}
+
+/// dbusmenu を持たないアイテム向けの右クリック（アプリ側がメニューを出す）
+#[tauri::command]
+pub fn tray_context_menu(id: String, x: i32, y: i32) -> Result<String, SisError> {
+    let conn = session_bus()?;
+    item_proxy(&conn, &id)?.call::<_, _, ()>("ContextMenu", &(x, y)).map_err(|e| format!("context-menu-failed: {}", e))?;
+    Ok("shown".into())
+}
+
+#[tauri::command]
+pub fn tray_scroll(id: String, delta: i32, orientation: Option<String>) -> Result<String, SisError> {
+    let conn = session_bus()?;
+    let o = if orientation.as_deref() == Some("horizontal") { "horizontal" } else { "vertical" };
+    item_proxy(&conn, &id)?.call::<_, _, ()>("Scroll", &(delta, o)).map_err(|e| format!("scroll-failed: {}", e))?;
+    Ok("scrolled".into())
+}
+
+/// メニュー全体（ルートの children が最上位の項目）
+#[tauri::command]
+pub fn tray_menu(id: String) -> Result<TrayMenuItem, SisError> {
+    let conn = session_bus()?;
+    let m = menu_proxy(&conn, &id)?;
+    // 開く前に通知すると内容を更新するアプリがある（戻り値は無視）
+    let _ = m.call::<_, _, bool>("AboutToShow", &(0i32,));
+    let empty: Vec<&str> = Vec::new();
+    let (_rev, root): (u32, MenuNode) = m.call("GetLayout", &(0i32, -1i32, empty))
+        .map_err(|e| format!("menu-failed: {}", e))?;
+    Ok(parse_node(root).ok_or("menu-parse-failed")?)
+}
+
+/// メニュー項目のイベント（clicked / opened / closed / hovered）
+#[tauri::command]
+pub fn tray_menu_event(id: String, menu_id: i32, event: Option<String>) -> Result<String, SisError> {
+    let conn = session_bus()?;
+    let m = menu_proxy(&conn, &id)?;
+    let ev = event.unwrap_or_else(|| "clicked".into());
+    let ts = (chrono::Utc::now().timestamp() & 0xFFFF_FFFF) as u32;
+    m.call::<_, _, ()>("Event", &(menu_id, ev.as_str(), Value::from(0i32), ts)).map_err(|e| format!("menu-failed: {}", e))?;
+    Ok("sent".into())
+}
@@ -1,15 +1,16 @@
 import { useEffect, useRef, useState } from 'react';
 import './AppStore.css';
-import { api } from '../services/api';
+import { api, findFavorite, flattenDock, type DockEntry } from '../services/api';
 import { IconApp } from '../assets/icons';
 
-export type AppInfo = { name: string; exec?: string; icon_data_url?: string }
+export type AppInfo = { name: string; exec?: string; icon_data_url?: string; desktop_id?: string }
 
 function AppStore() {
   const [apps, setApps] = useState<AppInfo[]>([])
   const [error, setError] = useState<string | null>(null)
   const [showAll, setShowAll] = useState(false)
-  const [fav, setFav] = useState<AppInfo[]>([])
+  const [dock, setDock] = useState<DockEntry[]>([])
+  const fav = flattenDock(dock)
   const [sort, setSort] = useState<'name'|'recent'|'installed'>('name')
   const [settings, setSettings] = useState<any | null>(null)
   const dragIndex = useRef<number | null>(null)
@@ -36,7 +37,7 @@ function AppStore() {
       })
     })
     .catch(() => {})
-  api.getFavoriteApps().then(setFav).catch(()=>setFav([]))
+  api.getDockEntries().then(setDock).catch(()=>setDock([]))
   api.getSettings().then((s)=>{ setSettings(s); if (s?.app_sort) setSort(s.app_sort) })
     return () => { mounted = false }
   }, [])
@@ -64,12 +65,9 @@ function AppStore() {
   const visible = bySort(showAll ? apps : apps.filter(a => !!a.icon_data_url))
 
   const toggleFav = async (app: AppInfo) => {
-    const exists = fav.some(f => f.name === app.name)
-    if (exists) {
-      const r = await api.removeFavoriteApp(app.name); if (r.ok) setFav(fav.filter(f=>f.name!==app.name))
-    } else {
-      const r = await api.addFavoriteApp(app); if (r.ok) setFav([...fav, app])
-    }
+    const f = findFavorite(fav, app)
+    if (f) { await api.removeFavoriteApp(f.id) } else { await api.addFavoriteApp(app) }
+    setDock(await api.getDockEntries())
   }
 
   const onSortChange = async (v: 'name'|'recent'|'installed') => {
@@ -88,12 +86,13 @@ function AppStore() {
     const from = dragIndex.current
     dragIndex.current = null
     if (from==null || from===overIndex) return
-    const next = [...fav]
-    const [m] = next.splice(from, 1)
-    next.splice(overIndex, 0, m)
-    setFav(next)
-    // persist order by names
-    await api.reorderFavoriteApps(next.map(x=>x.name))
+    // 落とした先のアプリの位置（フォルダ内ならそのフォルダ）へ移す
+    const target = fav[overIndex]
+    const top = dock.findIndex(e => e.id === target.id)
+    const folder = dock.find(e => e.kind === 'folder' && e.apps.some(a => a.id === target.id))
+    if (top >= 0) await api.dockMove(fav[from].id, top)
+    else if (folder?.kind === 'folder') await api.dockMove(fav[from].id, folder.apps.findIndex(a => a.id === target.id), folder.id)
+    setDock(await api.getDockEntries())
   }
 
   return (
@@ -121,7 +120,7 @@ function AppStore() {
           <div style={{ fontSize:12, opacity:.8, margin:'0 2px 6px' }}>お気に入り</div>
           <div className="app-grid" style={{ gridTemplateColumns:'repeat(8, minmax(0,1fr))' }}>
             {fav.map((app, index) => (
-              <div key={index} className="app-card" draggable onDragStart={onFavDragStart(index)} onDragOver={onFavDragOver} onDrop={onFavDrop(index)} onClick={() => launch(app)} title="ドラッグで並び替え">
+              <div key={app.id} className="app-card" draggable onDragStart={onFavDragStart(index)} onDragOver={onFavDragOver} onDrop={onFavDrop(index)} onClick={() => launch(app)} title="ドラッグで並び替え">
                 <img src={app.icon_data_url || IconApp} alt={app.name} />
                 <span>{app.name}</span>
               </div>
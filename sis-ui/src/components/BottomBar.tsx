@@ -1,11 +1,15 @@
-import { useEffect, useMemo, useState } from 'react';
-import { api } from '../services/api';
+import { useEffect, useMemo, useRef, useState } from 'react';
+import { listen } from '@tauri-apps/api/event';
+import { api, type DockEntry, type DockFolder, type FavoriteApp } from '../services/api';
 import './BottomBar.css';
+import TrayArea from './TrayArea';
 
 function BottomBar() {
   const [openWindows, setOpenWindows] = useState<{ id: string; wclass: string; title: string; icon_data_url?: string }[]>([]);
-  const [pinned, setPinned] = useState<any[]>([])
+  const [pinned, setPinned] = useState<DockEntry[]>([])
+  const [openFolder, setOpenFolder] = useState<string | null>(null)
   const [hoveredApp, setHoveredApp] = useState<string | null>(null);
+  const dragId = useRef<string | null>(null)
 
   useEffect(() => {
     let mounted = true;
@@ -24,14 +28,15 @@ function BottomBar() {
           )
         })
         setOpenWindows(w as any)
-        try { const fav = await api.getFavoriteApps(); setPinned(fav||[]) } catch {}
+        try { const entries = await api.getDockEntries(); setPinned(entries||[]) } catch {}
       } catch {}
     }
     load()
     const iv = setInterval(load, 3000)
     const onFav = ()=> load()
     window.addEventListener('sis:favorites-updated', onFav)
-    return ()=>{ mounted = false; clearInterval(iv); window.removeEventListener('sis:favorites-updated', onFav) }
+    const un = listen<DockEntry[]>('sis:favorites-updated', (e)=>{ if (mounted) setPinned(e.payload || []) })
+    return ()=>{ mounted = false; clearInterval(iv); un.then(f=>f()); window.removeEventListener('sis:favorites-updated', onFav) }
   }, []);
 
   const launchApp = async (app: any) => {
@@ -41,6 +46,77 @@ function BottomBar() {
 
   const focusWin = async (id: string) => { await api.focusWindow(id) }
 
+  // ピン留めの並べ替え・フォルダ化: アイコンの中央に落とすとまとめる/フォルダへ入れる、端に落とすとその前後へ移動
+  const onDragStart = (id: string) => (e: React.DragEvent) => { dragId.current = id; e.dataTransfer.effectAllowed = 'move'; setHoveredApp(null) }
+  const onDragOver = (e: React.DragEvent) => { e.preventDefault(); e.dataTransfer.dropEffect = 'move' }
+  const dropZone = (e: React.DragEvent): 'before' | 'into' | 'after' => {
+    const r = (e.currentTarget as HTMLElement).getBoundingClientRect()
+    const x = (e.clientX - r.left) / (r.width || 1)
+    return x < 0.25 ? 'before' : x > 0.75 ? 'after' : 'into'
+  }
+  const onDropEntry = (target: DockEntry, index: number) => async (e: React.DragEvent) => {
+    e.preventDefault(); e.stopPropagation()
+    const id = dragId.current
+    dragId.current = null
+    if (!id || id === target.id) return
+    const zone = dropZone(e)
+    const dragged = pinned.find(x => x.id === id)
+    if (zone === 'into' && target.kind === 'folder' && dragged?.kind !== 'folder') {
+      await api.dockMove(id, target.apps.length, target.id)
+    } else if (zone === 'into' && target.kind === 'app' && dragged?.kind !== 'folder') {
+      const f = await api.dockCreateFolder('フォルダ', [target.id, id])
+      if (f) setOpenFolder(f.id)
+    } else {
+      // 取り外し後の位置に合わせる
+      const from = pinned.findIndex(x => x.id === id)
+      let to = zone === 'after' ? index + 1 : index
+      if (from >= 0 && from < to) to -= 1
+      await api.dockMove(id, to)
+    }
+  }
+  const onDropFolderApp = (folder: DockFolder, index: number) => async (e: React.DragEvent) => {
+    e.preventDefault(); e.stopPropagation()
+    const id = dragId.current
+    dragId.current = null
+    if (!id || pinned.some(x => x.kind === 'folder' && x.id === id)) return
+    const from = folder.apps.findIndex(a => a.id === id)
+    let to = dropZone(e) === 'after' ? index + 1 : index
+    if (from >= 0 && from < to) to -= 1
+    await api.dockMove(id, to, folder.id)
+  }
+  // ドックの空き部分に落とすとトップレベルの末尾へ（フォルダから出す）
+  const onDropEnd = async (e: React.DragEvent) => {
+    e.preventDefault()
+    const id = dragId.current
+    dragId.current = null
+    if (id) await api.dockMove(id, pinned.length)
+  }
+  const unpinApp = async (a: FavoriteApp) => {
+    if (window.confirm(`${a.name} をドックから外しますか？`)) await api.dockRemove(a.id)
+  }
+  // フォルダ名の変更。空にするとフォルダを解除して中身をドックへ戻す
+  const editFolder = async (f: DockFolder) => {
+    const name = window.prompt('フォルダ名（空欄でフォルダを解除）', f.name)
+    if (name === null) return
+    if (name.trim()) await api.dockRenameFolder(f.id, name)
+    else { await api.dockRemove(f.id); setOpenFolder(null) }
+  }
+
+  const pinnedApp = (a: FavoriteApp, props: React.HTMLAttributes<HTMLDivElement>) => (
+    <div key={a.id} className="dock-app" draggable onDragStart={onDragStart(a.id)} onDragOver={onDragOver}
+      onClick={()=> a.exec && api.launchApp(a.exec)} onContextMenu={(e)=>{ e.preventDefault(); unpinApp(a) }}
+      onMouseEnter={()=> setHoveredApp(a.id)} onMouseLeave={()=> setHoveredApp(null)} {...props}>
+      <div className="app-icon-container" style={{ width: 'clamp(32px, 3vw, 56px)', height: 'clamp(32px, 3vw, 56px)' }}>
+        <img src={a.icon_data_url || '/vite.svg'} alt={a.name} className="app-icon" style={{ width: '92%', height: '92%' }} />
+      </div>
+      {hoveredApp === a.id && (
+        <div className="app-tooltip">
+          {a.name}
+        </div>
+      )}
+    </div>
+  )
+
   // Dock表示: 起動中とピン留めのみ（最近使用したアプリは非表示）
   const openIcons = useMemo(() => openWindows.map(w=>({ key:w.id, title:w.title, icon:w.icon_data_url, type:'win' as const })), [openWindows])
 
@@ -84,19 +160,30 @@ function BottomBar() {
       <div className="dock-separator" aria-hidden="true"></div>
 
       <div className="dock-right">
-        {/* ピン留め（固定表示） */}
-  {pinned.map((a:any)=>(
-          <div key={a.name} className="dock-app" onClick={()=> a.exec && api.launchApp(a.exec)} onMouseEnter={()=> setHoveredApp(a.name)} onMouseLeave={()=> setHoveredApp(null)}>
-            <div className="app-icon-container" style={{ width: 'clamp(32px, 3vw, 56px)', height: 'clamp(32px, 3vw, 56px)' }}>
-              <img src={a.icon_data_url || '/vite.svg'} alt={a.name} className="app-icon" style={{ width: '92%', height: '92%' }} />
-            </div>
-            {hoveredApp === a.name && (
-              <div className="app-tooltip">
-                {a.name}
+        {/* ピン留め（固定表示）。フォルダはクリックでその場に中身を展開する */}
+        <div className="dock-pinned" onDragOver={onDragOver} onDrop={onDropEnd}>
+          {pinned.map((entry, index) => entry.kind === 'app'
+            ? pinnedApp(entry, { onDrop: onDropEntry(entry, index) })
+            : (
+              <div key={entry.id} className={`dock-folder${openFolder === entry.id ? ' open' : ''}`}>
+                <div className="dock-app" draggable onDragStart={onDragStart(entry.id)} onDragOver={onDragOver} onDrop={onDropEntry(entry, index)}
+                  onClick={()=> setOpenFolder(openFolder === entry.id ? null : entry.id)}
+                  onContextMenu={(e)=>{ e.preventDefault(); editFolder(entry) }}
+                  onMouseEnter={()=> setHoveredApp(entry.id)} onMouseLeave={()=> setHoveredApp(null)}>
+                  <div className="app-icon-container dock-folder-icon" style={{ width: 'clamp(32px, 3vw, 56px)', height: 'clamp(32px, 3vw, 56px)' }}>
+                    {entry.apps.slice(0, 4).map(a => <img key={a.id} src={a.icon_data_url || '/vite.svg'} alt="" />)}
+                  </div>
+                  {hoveredApp === entry.id && (
+                    <div className="app-tooltip">
+                      {entry.name}
+                    </div>
+                  )}
+                </div>
+                {openFolder === entry.id && entry.apps.map((a, i) => pinnedApp(a, { onDrop: onDropFolderApp(entry, i) }))}
               </div>
-            )}
-          </div>
-        ))}
+            ))}
+        </div>
+        <TrayArea size={24} />
       </div>
     </div>
   );
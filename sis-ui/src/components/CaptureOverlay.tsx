@@ -0,0 +1,62 @@
+import { useEffect, useState } from 'react'
+import { listen } from '@tauri-apps/api/event'
+import { api, type ScreenshotRegionRequest } from '../services/api'
+import './CaptureOverlay.css'
+
+type Rect = { x: number; y: number; w: number; h: number }
+
+const normalize = (a: { x: number; y: number }, b: { x: number; y: number }): Rect => ({
+  x: Math.min(a.x, b.x), y: Math.min(a.y, b.y), w: Math.abs(a.x - b.x), h: Math.abs(a.y - b.y),
+})
+
+// 範囲選択オーバーレイ（"capture" ウィンドウ）。撮影済みの全画面画像の上で矩形をドラッグする
+export default function CaptureOverlay() {
+  const [req, setReq] = useState<ScreenshotRegionRequest | null>(null)
+  const [start, setStart] = useState<{ x: number; y: number } | null>(null)
+  const [rect, setRect] = useState<Rect | null>(null)
+
+  useEffect(() => {
+    const un = listen<ScreenshotRegionRequest>('sis:screenshot-select', (e) => { setReq(e.payload); setStart(null); setRect(null) })
+    const onKey = (e: KeyboardEvent) => { if (e.key === 'Escape') cancel() }
+    window.addEventListener('keydown', onKey)
+    return () => { un.then(f => f()); window.removeEventListener('keydown', onKey) }
+  }, [])
+
+  const cancel = () => { setReq(null); setRect(null); api.screenshotRegionCancel() }
+
+  const commit = async (r: Rect) => {
+    if (!req) return
+    // 画面座標（CSS px）→ 画像ピクセル
+    const sx = req.width / window.innerWidth
+    const sy = req.height / window.innerHeight
+    setReq(null); setRect(null)
+    await api.screenshotRegionCommit(r.x * sx, r.y * sy, r.w * sx, r.h * sy)
+  }
+
+  if (!req) return null
+
+  return (
+    <div
+      className={`capture-overlay ${rect ? 'selecting' : ''}`}
+      style={{ backgroundImage: `url(${req.image})` }}
+      onMouseDown={(e) => { if (e.button !== 0) { cancel(); return } setStart({ x: e.clientX, y: e.clientY }); setRect(null) }}
+      onMouseMove={(e) => { if (start) setRect(normalize(start, { x: e.clientX, y: e.clientY })) }}
+      onMouseUp={(e) => {
+        if (!start) return
+        const r = normalize(start, { x: e.clientX, y: e.clientY })
+        setStart(null)
+        if (r.w < 4 || r.h < 4) { setRect(null); return }
+        commit(r)
+      }}
+      onContextMenu={(e) => { e.preventDefault(); cancel() }}
+    >
+      {rect ? (
+        <div className="capture-selection" style={{ left: rect.x, top: rect.y, width: rect.w, height: rect.h }}>
+          <span className="capture-size">{Math.round(rect.w * req.width / window.innerWidth)} × {Math.round(rect.h * req.height / window.innerHeight)}</span>
+        </div>
+      ) : (
+        <div className="capture-hint">ドラッグで範囲を選択（Esc / 右クリックでキャンセル）</div>
+      )}
+    </div>
+  )
+}
@@ -97,6 +97,11 @@ export default function CommandPalette({ isVisible, onClose }: CommandPalettePro
     const staticItems = [
       { id: 'launcher', label: 'アプリランチャーを開く (Alt+Space)' },
       { id: 'screenshot', label: 'スクリーンショットを撮る' },
+      { id: 'screenshot-region', label: '範囲を指定してスクリーンショット' },
+      { id: 'screenshot-window', label: 'ウィンドウのスクリーンショット' },
+      { id: 'record-start', label: '画面の録画を開始' },
+      { id: 'record-stop', label: '画面の録画を停止' },
+      { id: 'lock', label: '画面をロック' },
       { id: 'music-play', label: '音楽 再生/一時停止' },
       { id: 'logs-backend', label: 'バックログを表示' },
     ]
@@ -129,6 +134,11 @@ export default function CommandPalette({ isVisible, onClose }: CommandPalettePro
 
   const run = async (id: string) => {
   if (id === 'screenshot') await api.takeScreenshot()
+  if (id === 'screenshot-region') await api.screenshotCapture('region')
+  if (id === 'screenshot-window') await api.screenshotCapture('window')
+  if (id === 'record-start') await api.recorderStart('full')
+  if (id === 'record-stop') await api.recorderStop()
+  if (id === 'lock') await api.lockScreen()
   if (id === 'music-play') await api.playPauseMusic()
   if (id === 'logs-backend') { const text = await api.getBackendLog(200); alert(text || '(空)'); return }
   if (id === 'lm-settings') { setQ('@settings'); await preloadLm(); return }
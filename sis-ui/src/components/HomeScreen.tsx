@@ -1,13 +1,15 @@
 import { useEffect, useState } from 'react';
-import { api, type AppInfo } from '../services/api';
+import { listen } from '@tauri-apps/api/event';
+import { api, findFavorite, type AppInfo, type FavoriteApp, type RemovableDevice } from '../services/api';
 import './HomeScreen.css';
 import './Settings.css';
 
 function HomeScreen() {
   const [apps, setApps] = useState<AppInfo[]>([]);
-  const [favorites, setFavorites] = useState<AppInfo[]>([]);
+  const [favorites, setFavorites] = useState<FavoriteApp[]>([]);
   const [desktopFiles, setDesktopFiles] = useState<{ name: string; path: string; is_dir?: boolean }[]>([]);
   const [thumbs, setThumbs] = useState<Record<string, string>>({})
+  const [media, setMedia] = useState<RemovableDevice[]>([])
   const [currentTime, setCurrentTime] = useState(new Date());
   const [theme, setTheme] = useState<'system'|'light'|'dark'>('system')
   const [wallpaper, setWallpaper] = useState<string>('')
@@ -49,7 +51,7 @@ function HomeScreen() {
         const deduped = Array.from(byKey.values())
         if (mounted) setApps(deduped);
         // お気に入り
-        const fav = await api.getFavoriteApps();
+        const fav = await api.getFavorites();
         if (mounted) setFavorites(fav);
 
         // デスクトップ表示（バックエンドに委譲）
@@ -130,14 +132,33 @@ function HomeScreen() {
     return ()=>{ cancelled = true }
   }, [desktopFiles])
 
+  // USBメモリ/SDカードをデスクトップに表示
+  useEffect(()=>{
+    let mounted = true
+    api.storageListRemovable().then(list=>{ if(mounted) setMedia(list) })
+    const un = listen<RemovableDevice[]>('sis:storage-changed', (e)=>{ if(mounted) setMedia(e.payload || []) })
+    return ()=>{ mounted=false; un.then(f=>f()) }
+  }, [])
+
+  const openMedia = async (d: RemovableDevice) => {
+    let mp = d.mount_points[0]
+    if (!mp) {
+      const r = await api.storageMount(d.id)
+      if (!r.ok) { alert(r.code==='blocked-by-policy' ? '学校のポリシーによりUSBメモリは使用できません' : `マウントに失敗: ${r.message||''}`); return }
+      mp = r.mountPoint || ''
+    }
+    if (mp) api.openPath(mp)
+  }
+
   const openFile = (path: string) => { api.openPath(path) };
 
   const launchApp = (exec: string) => { api.launchApp(exec) };
 
-  const isFav = (a: AppInfo) => favorites.some(f=>f.name===a.name)
+  const isFav = (a: AppInfo) => !!findFavorite(favorites, a)
   const togglePin = async (a: AppInfo)=>{
-    if (isFav(a)) { await api.removeFavoriteApp(a.name!) } else { await api.addFavoriteApp(a) }
-  const fav = await api.getFavoriteApps(); setFavorites(fav)
+    const f = findFavorite(favorites, a)
+    if (f) { await api.removeFavoriteApp(f.id) } else { await api.addFavoriteApp(a) }
+  const fav = await api.getFavorites(); setFavorites(fav)
   // サイドバーへ更新通知
   window.dispatchEvent(new Event('sis:favorites-updated'))
   }
@@ -247,10 +268,9 @@ function HomeScreen() {
           </div>
           <div className="apps-grid">
             {(() => {
-              const favSet = new Set(favorites.map(f=>f.name))
               const list = (apps.filter(a=>a.icon_data_url).length ? apps.filter(a=>a.icon_data_url) : apps)
               const sorted = [...list].sort((a,b)=>{
-                const af = favSet.has(a.name!); const bf = favSet.has(b.name!)
+                const af = isFav(a); const bf = isFav(b)
                 if (af!==bf) return af? -1 : 1
                 return (a.name||'').localeCompare(b.name||'')
               })
@@ -292,6 +312,12 @@ function HomeScreen() {
             <div className="card-badge">{desktopFiles.length}</div>
           </div>
           <div className="files-grid">
+            {media.map((d) => (
+              <div key={d.id} className="file-card" onClick={() => openMedia(d)} title={d.device}>
+                <div className="file-thumb"><div className="file-glyph">💾</div></div>
+                <div className="file-label" title={d.label}>{d.label}</div>
+              </div>
+            ))}
             {desktopFiles.map((file, index) => (
               <div
                 key={file.path}
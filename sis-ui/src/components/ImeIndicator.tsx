@@ -0,0 +1,36 @@
+import { useEffect, useState } from 'react'
+import { listen } from '@tauri-apps/api/event'
+import { api, type ImeState } from '../services/api'
+import './ImeIndicator.css'
+
+// IME の TopBar インジケータ。クリックで日本語/英字を切り替え、右クリックで次のエンジンへ
+export default function ImeIndicator() {
+  const [state, setState] = useState<ImeState | null>(null)
+
+  useEffect(() => {
+    api.imeState().then(setState)
+    const un = listen<ImeState>('sis:ime-state', (e) => { setState(e.payload) })
+    return () => { un.then(f => f()) }
+  }, [])
+
+  if (!state?.framework || state.engines.length === 0) return null
+  const current = state.engines.find(e => e.current)
+
+  const next = async () => {
+    const i = state.engines.findIndex(e => e.current)
+    const target = state.engines[(i + 1) % state.engines.length]
+    const s = await api.imeSetEngine(target.name)
+    if (s) setState(s)
+  }
+
+  return (
+    <button
+      className={`ime-indicator ${state.active ? 'active' : ''}`}
+      title={`${current?.label ?? state.engine ?? ''}（クリックで日本語/英字、右クリックで切替）`}
+      onClick={async () => { const s = await api.imeToggle(); if (s) setState(s) }}
+      onContextMenu={(e) => { e.preventDefault(); next() }}
+    >
+      {state.indicator}
+    </button>
+  )
+}
@@ -0,0 +1,96 @@
+import { useEffect, useRef, useState } from 'react'
+import { listen } from '@tauri-apps/api/event'
+import { api, type LockReason, type LockState } from '../services/api'
+import './LockScreen.css'
+
+const REASON_LABEL: Record<LockReason, string> = {
+  user: 'ロック中',
+  idle: '一定時間操作がなかったためロックしました',
+  suspend: 'スリープから復帰しました',
+  logind: '管理者によりロックされました',
+  screentime: 'スクリーンタイム',
+  exam: '試験モード',
+}
+
+const ERROR_LABEL: Record<string, string> = {
+  cooldown: 'しばらく待ってからもう一度お試しください',
+  busy: '確認中です…',
+  'empty-password': 'パスワードを入力してください',
+}
+
+// ロック画面（"lock" ウィンドウ）。解除は PAM（バックエンド）で確認し、結果は sis:lock-auth で届く
+export default function LockScreen() {
+  const [state, setState] = useState<LockState | null>(null)
+  const [now, setNow] = useState(() => new Date())
+  const [password, setPassword] = useState('')
+  const [error, setError] = useState<string | null>(null)
+  const [checking, setChecking] = useState(false)
+  const inputRef = useRef<HTMLInputElement>(null)
+
+  useEffect(() => {
+    api.lockState().then(s => s && setState(s))
+    const unState = listen<LockState>('sis:lock-state', (e) => {
+      setState(e.payload)
+      if (!e.payload.locked) { setPassword(''); setError(null); setChecking(false) }
+    })
+    const unAuth = listen<{ ok: boolean; message: string }>('sis:lock-auth', (e) => {
+      setChecking(false)
+      setPassword('')
+      setError(e.payload.ok ? null : e.payload.message)
+    })
+    const t = setInterval(() => setNow(new Date()), 1000)
+    return () => { unState.then(f => f()); unAuth.then(f => f()); clearInterval(t) }
+  }, [])
+
+  // 表示されたら常に入力欄へフォーカスを戻す
+  useEffect(() => {
+    if (state?.locked && !checking) inputRef.current?.focus()
+  }, [state?.locked, checking, error])
+
+  const submit = async () => {
+    if (checking || !password) return
+    setChecking(true)
+    setError(null)
+    const r = await api.lockAuthenticate(password)
+    if (!r.ok) {
+      setChecking(false)
+      setPassword('')
+      setError(ERROR_LABEL[r.message ?? ''] ?? '認証できませんでした')
+    }
+  }
+
+  if (!state?.locked) return <div className="lock-screen" />
+
+  const cooling = !!state.retry_after_ms && state.retry_after_ms > now.getTime()
+  const policy = state.reason === 'screentime' || state.reason === 'exam'
+  const reason = state.reason ? REASON_LABEL[state.reason] : REASON_LABEL.user
+
+  return (
+    <div className="lock-screen" onMouseDown={() => inputRef.current?.focus()}>
+      <div className="lock-clock">
+        <div className="lock-time">{now.toLocaleTimeString('ja-JP', { hour: '2-digit', minute: '2-digit' })}</div>
+        <div className="lock-date">{now.toLocaleDateString('ja-JP', { month: 'long', day: 'numeric', weekday: 'long' })}</div>
+      </div>
+      <div className={`lock-panel ${policy ? 'policy' : ''}`}>
+        <div className="lock-avatar">{(state.display_name || state.user || '?').slice(0, 1).toUpperCase()}</div>
+        <div className="lock-user">{state.display_name || state.user}</div>
+        <div className="lock-reason">{reason}</div>
+        {state.message && <div className="lock-message">{state.message}</div>}
+        <form onSubmit={(e) => { e.preventDefault(); submit() }}>
+          <input
+            ref={inputRef}
+            className="game-input lock-input"
+            type="password"
+            autoFocus
+            placeholder={cooling ? `${Math.ceil((state.retry_after_ms! - now.getTime()) / 1000)}秒後に再試行できます` : 'パスワード'}
+            value={password}
+            disabled={checking || cooling}
+            onChange={(e) => setPassword(e.target.value)}
+          />
+        </form>
+        <div className="lock-error">{checking ? '確認中…' : error ?? ''}</div>
+        {!state.grabbed && <div className="lock-note">キーボードを占有できていません</div>}
+      </div>
+    </div>
+  )
+}
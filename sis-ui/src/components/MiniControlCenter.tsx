@@ -1,6 +1,7 @@
 import { useEffect, useState } from 'react'
+import { listen } from '@tauri-apps/api/event'
 import './MiniControlCenter.css'
-import { api } from '../services/api'
+import { api, type BacklightDevice, type NightLightState } from '../services/api'
 
 type Props = { open: boolean; onClose: () => void }
 
@@ -9,6 +10,7 @@ export default function MiniControlCenter({ open, onClose }: Props) {
   const [brightness, setBrightness] = useState(80)
   const [network, setNetwork] = useState(true)
   const [bluetooth, setBluetooth] = useState(true)
+  const [nightLight, setNightLight] = useState<NightLightState | null>(null)
 
   useEffect(() => {
     if (!open) return
@@ -17,10 +19,14 @@ export default function MiniControlCenter({ open, onClose }: Props) {
     ;(async ()=>{
       try {
         const s = await api.controlCenterState()
-        setVolume(s.volume); setBrightness(s.brightness); setNetwork(s.network); setBluetooth(s.bluetooth)
+        setVolume(s.volume); if (typeof s.brightness === 'number') setBrightness(s.brightness); setNetwork(s.network); setBluetooth(s.bluetooth); if (s.night_light) setNightLight(s.night_light)
       } catch {}
     })()
-    return () => window.removeEventListener('keydown', onKey)
+    // ハードウェアキーでの変更に追従
+    const unVol = listen<{ volume: number }>('sis:volume', (e)=>{ setVolume(e.payload.volume) })
+    const unBri = listen<BacklightDevice>('sis:brightness', (e)=>{ if (e.payload.kind === 'panel') setBrightness(e.payload.percent) })
+    const unNight = listen<NightLightState>('sis:nightlight-state', (e)=>{ setNightLight(e.payload) })
+    return () => { window.removeEventListener('keydown', onKey); unVol.then(f=>f()); unBri.then(f=>f()); unNight.then(f=>f()) }
   }, [open, onClose])
 
   if (!open) return null
@@ -38,6 +44,7 @@ export default function MiniControlCenter({ open, onClose }: Props) {
     briTimer = setTimeout(async () => { const r = await api.setBrightness(v); if (!r.ok) console.warn('輝度変更に失敗') }, 120)
   }
   const toggleNetwork = async () => { const n = !network; setNetwork(n); const r = await api.networkSet(n); if (!r.ok) alert('ネットワーク切替失敗') }
+  const toggleNightLight = async () => { if (!nightLight) return; const s = await api.nightLightSet({ enabled: !nightLight.enabled }); if (s) setNightLight(s); else alert('夜間モード切替失敗') }
   const toggleBluetooth = async () => { const b = !bluetooth; setBluetooth(b); const r = await api.bluetoothSet(b); if (!r.ok) alert('Bluetooth切替失敗') }
 
   return (
@@ -60,6 +67,12 @@ export default function MiniControlCenter({ open, onClose }: Props) {
             <div className="cc-tile-title">Bluetooth</div>
             <div className="cc-tile-body">{bluetooth?'ON':'OFF'}</div>
           </div>
+          {nightLight && nightLight.error !== 'no-backend' && (
+            <div className={`cc-tile ${nightLight.enabled?'active':''}`} onClick={toggleNightLight}>
+              <div className="cc-tile-title">夜間モード</div>
+              <div className="cc-tile-body">{!nightLight.enabled ? 'OFF' : nightLight.active ? `${nightLight.current_temperature}K` : '待機中'}</div>
+            </div>
+          )}
           <div className="cc-tile" onClick={()=>api.takeScreenshot()}>
             <div className="cc-tile-title">スクショ</div>
             <div className="cc-tile-body">撮影</div>
@@ -0,0 +1,88 @@
+import { useEffect, useRef, useState } from 'react'
+import { listen } from '@tauri-apps/api/event'
+import { getCurrentWindow } from '@tauri-apps/api/window'
+import { LogicalSize } from '@tauri-apps/api/dpi'
+import { api, type SisNotification, type Screenshot } from '../services/api'
+import './NotificationToasts.css'
+
+const MAX_VISIBLE = 4
+const WIDTH = 380
+const SHOT_TIMEOUT_MS = 8000
+
+// 通知ウィンドウ（右上）。表示中の通知がある間だけウィンドウを出す
+export default function NotificationToasts() {
+  const [items, setItems] = useState<SisNotification[]>([])
+  // 撮影直後の編集/共有ポップアップ
+  const [shot, setShot] = useState<Screenshot | null>(null)
+  const boxRef = useRef<HTMLDivElement | null>(null)
+
+  useEffect(() => {
+    const un1 = listen<SisNotification>('sis:notification', (e) => {
+      const n = e.payload
+      if (n.suppressed) return
+      setItems(prev => [n, ...prev.filter(x => x.id !== n.id)].slice(0, MAX_VISIBLE))
+    })
+    const un2 = listen<{ id: number }>('sis:notification-closed', (e) => {
+      setItems(prev => prev.filter(x => x.id !== e.payload.id))
+    })
+    const un3 = listen<Screenshot>('sis:screenshot-taken', (e) => { setShot(e.payload) })
+    return () => { un1.then(f => f()); un2.then(f => f()); un3.then(f => f()) }
+  }, [])
+
+  useEffect(() => {
+    if (!shot) return
+    const t = setTimeout(() => setShot(null), SHOT_TIMEOUT_MS)
+    return () => clearTimeout(t)
+  }, [shot])
+
+  useEffect(() => {
+    const w = getCurrentWindow()
+    ;(async () => {
+      try {
+        if (items.length === 0 && !shot) { await w.hide(); return }
+        const h = Math.ceil(boxRef.current?.getBoundingClientRect().height || 120) + 4
+        await w.setSize(new LogicalSize(WIDTH, h))
+        await w.show()
+      } catch {}
+    })()
+  }, [items, shot])
+
+  return (
+    <div className="toast-stack" ref={boxRef}>
+      {shot && (
+        <div className="toast shot-toast">
+          {shot.thumbnail && <img className="shot-thumb" src={shot.thumbnail} alt="" onClick={() => shot.path && api.openPath(shot.path)} />}
+          <div className="toast-body">
+            <div className="toast-app">スクリーンショット</div>
+            <div className="toast-summary">{shot.width} × {shot.height}{shot.copied ? '（コピー済み）' : ''}</div>
+            <div className="toast-actions">
+              {shot.path && <button className="qa-btn" onClick={() => { api.openPath(shot.path!); setShot(null) }}>編集</button>}
+              {shot.path && <button className="qa-btn" onClick={() => { api.openPath(shot.path!.replace(/\/[^/]*$/, '')); setShot(null) }}>フォルダ</button>}
+              {shot.path && !shot.copied && <button className="qa-btn" onClick={async () => { if ((await api.screenshotCopy(shot.path!)).ok) setShot({ ...shot, copied: true }) }}>コピー</button>}
+              {shot.path && <button className="qa-btn" onClick={() => { api.screenshotDelete(shot.path!); setShot(null) }}>削除</button>}
+            </div>
+          </div>
+          <button className="toast-close" title="閉じる" onClick={() => setShot(null)}>×</button>
+        </div>
+      )}
+      {items.map(n => (
+        <div key={n.id} className={`toast urgency-${n.urgency}`} onClick={() => api.notifyInvokeAction(n.id, 'default')}>
+          {n.icon && <img className="toast-icon" src={n.icon} alt="" />}
+          <div className="toast-body">
+            <div className="toast-app">{n.app_name}</div>
+            <div className="toast-summary">{n.summary}</div>
+            {n.body && <div className="toast-text">{n.body}</div>}
+            {n.actions.filter(a => a.key !== 'default').length > 0 && (
+              <div className="toast-actions">
+                {n.actions.filter(a => a.key !== 'default').map(a => (
+                  <button key={a.key} className="qa-btn" onClick={(e) => { e.stopPropagation(); api.notifyInvokeAction(n.id, a.key) }}>{a.label}</button>
+                ))}
+              </div>
+            )}
+          </div>
+          <button className="toast-close" title="閉じる" onClick={(e) => { e.stopPropagation(); api.notifyClose(n.id) }}>×</button>
+        </div>
+      ))}
+    </div>
+  )
+}
@@ -0,0 +1,49 @@
+import { useEffect, useState } from 'react'
+import { listen } from '@tauri-apps/api/event'
+import { api, type RecorderStatus } from '../services/api'
+import './RecordingIndicator.css'
+
+const pad = (n: number) => String(n).padStart(2, '0')
+
+export const formatElapsed = (ms: number) => {
+  const s = Math.max(0, Math.floor(ms / 1000))
+  const h = Math.floor(s / 3600)
+  return h > 0 ? `${h}:${pad(Math.floor(s / 60) % 60)}:${pad(s % 60)}` : `${pad(Math.floor(s / 60))}:${pad(s % 60)}`
+}
+
+// 録画中の TopBar インジケータ（クリックで停止）
+export default function RecordingIndicator() {
+  const [status, setStatus] = useState<RecorderStatus | null>(null)
+  const [now, setNow] = useState(Date.now())
+
+  useEffect(() => {
+    api.recorderStatus().then(setStatus)
+    const un = listen<RecorderStatus>('sis:recording-state', (e) => { setStatus(e.payload) })
+    return () => { un.then(f => f()) }
+  }, [])
+
+  const active = status?.state === 'recording' || status?.state === 'stopping'
+  useEffect(() => {
+    if (!active) return
+    const t = setInterval(() => setNow(Date.now()), 1000)
+    return () => clearInterval(t)
+  }, [active])
+
+  if (!status || status.state === 'idle' || status.state === 'selecting') return null
+  const elapsed = status.started_at_ms ? now - status.started_at_ms : 0
+
+  return (
+    <button
+      className={`recording-indicator ${status.state}`}
+      title={status.state === 'recording' ? 'クリックで録画を停止' : undefined}
+      disabled={status.state !== 'recording'}
+      onClick={() => api.recorderStop()}
+    >
+      <span className="rec-dot" />
+      <span className="rec-label">
+        {status.state === 'starting' ? '準備中' : status.state === 'stopping' ? '保存中' : formatElapsed(elapsed)}
+      </span>
+      {status.mic && <span className="rec-mic" title="マイク録音中">🎤</span>}
+    </button>
+  )
+}
@@ -0,0 +1,32 @@
+import { useEffect, useState } from 'react'
+import { listen } from '@tauri-apps/api/event'
+import { api, type ScreenTimeState } from '../services/api'
+import './ScreenTimeIndicator.css'
+
+const formatRemaining = (secs: number) => {
+  if (secs <= 0) return '0分'
+  if (secs < 60) return `${secs}秒`
+  const m = Math.ceil(secs / 60)
+  return m >= 60 ? `${Math.floor(m / 60)}時間${m % 60 ? `${m % 60}分` : ''}` : `${m}分`
+}
+
+// スクリーンタイムの TopBar インジケータ。前面のアプリに上限があるときだけ残り時間を出す
+export default function ScreenTimeIndicator() {
+  const [state, setState] = useState<ScreenTimeState | null>(null)
+
+  useEffect(() => {
+    api.screentimeState().then(setState)
+    const un = listen<ScreenTimeState>('sis:screentime', (e) => { setState(e.payload) })
+    return () => { un.then(f => f()) }
+  }, [])
+
+  const rem = state?.active_remaining_secs
+  if (typeof rem !== 'number') return null
+  const level = rem <= 60 ? 'critical' : rem <= 600 ? 'warning' : ''
+
+  return (
+    <div className={`screentime-indicator ${level}`} title={`${state?.active_name ?? ''} の今日の残り時間`}>
+      ⏳ {formatRemaining(rem)}
+    </div>
+  )
+}
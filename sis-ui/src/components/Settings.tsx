@@ -1,5 +1,5 @@
 import { useEffect, useState } from 'react'
-import { api } from '../services/api'
+import { api, type SessionAction } from '../services/api'
 import './Settings.css'
 
 const DEFAULT_SETTINGS = { 
@@ -96,8 +96,8 @@ function Settings() {
   const setBrt = async (v:number)=>{ setBrightness(v); const r = await api.setBrightness(v); if(!r.ok) alert('輝度の変更に失敗') }
   const toggleNet = async ()=>{ const n=!network; setNetwork(n); const r=await api.networkSet(n); if(!r.ok) alert('ネットワーク切替に失敗') }
   const toggleBt = async ()=>{ const n=!bluetooth; setBluetooth(n); const r=await api.bluetoothSet(n); if(!r.ok) alert('Bluetooth切替に失敗') }
-  const power = async (a:'shutdown'|'reboot'|'logout')=>{ 
-    const label = a==='shutdown'?'シャットダウン':a==='reboot'?'再起動':'ログアウト'
+  const power = async (a:SessionAction)=>{ 
+    const label = a==='shutdown'?'シャットダウン':a==='reboot'?'再起動':a==='suspend'?'スリープ':a==='hibernate'?'休止':'ログアウト'
     if(!confirm(`${label}を実行します。よろしいですか？`)) return
     const r=await api.powerAction(a); if(!r.ok) alert('電源操作に失敗') 
   }
@@ -1,5 +1,5 @@
 import { useEffect, useState } from 'react';
-import { api } from '../services/api';
+import { api, type SessionAction } from '../services/api';
 import './Settings.css';
 
 // デフォルト設定のテンプレート
@@ -139,7 +139,7 @@ function Settings() {
     if (!r.ok) alert('Bluetooth切替に失敗しました')
   }
 
-  const power = async (action: 'shutdown' | 'reboot' | 'logout') => {
+  const power = async (action: SessionAction) => {
     const r = await api.powerAction(action)
     if (!r.ok) alert('電源操作に失敗しました')
   }
@@ -1,5 +1,6 @@
-import { useEffect, useState } from 'react';
-import { api, type AppInfo } from '../services/api';
+import { useEffect, useRef, useState } from 'react';
+import { listen } from '@tauri-apps/api/event';
+import { api, flattenDock, type DockEntry, type FavoriteApp, type RemovableDevice, type StoragePolicy, type AccessPoint, type BluetoothState, type BtDevice, type BtPasskeyRequest, type AudioState, type BacklightDevice, type MediaState, type SisNotification, type DndStatus, type RecorderStatus, type ScreenshotMode, type ClipboardList, type PowerCapabilities, type SessionState, type SessionAction, type Capability, type LockConfig, type NightLightConfig, type NightLightState, type NightLightSchedule, type InputConfig, type InputState, type ImeState, type TimeState, type TimezoneInfo, type SisError, type OrganizerConfig, type OrganizeRule, type OrganizePlan, type OrganizeCollision, type DownloadWatchConfig } from '../services/api';
 import './Sidebar.css';
 import './Settings.css';
 
@@ -11,29 +12,53 @@ type SidebarProps = {
 function Sidebar({ isCollapsed, onToggle }: SidebarProps) {
   console.log(`[Sidebar] render collapsed=${isCollapsed}`);
   const [activeSection, setActiveSection] = useState('actions');
-  const [notifications] = useState<any[]>([]); // 実際の通知がない場合は空配列
-  const [fav, setFav] = useState<AppInfo[]>([])
+  const [notifications, setNotifications] = useState<SisNotification[]>([])
+  const [fav, setFav] = useState<FavoriteApp[]>([])
+  const [media, setMedia] = useState<RemovableDevice[]>([])
 
   useEffect(()=>{
     let mounted = true
-  const load = async ()=>{ try { const a = await api.getFavoriteApps(); if(mounted) setFav(a) } catch { if(mounted) setFav([]) } }
+  const load = async ()=>{ try { const a = await api.getFavorites(); if(mounted) setFav(a) } catch { if(mounted) setFav([]) } }
     load()
   const onFav = ()=> load()
   window.addEventListener('sis:favorites-updated', onFav)
   // Desktop側からのアプリ一覧更新でも反映
   const onAppsChange = ()=> load()
   window.addEventListener('sis:apps-refreshed', onAppsChange)
-  return ()=>{ mounted=false; window.removeEventListener('sis:favorites-updated', onFav); window.removeEventListener('sis:apps-refreshed', onAppsChange) }
+  // ドック側（別ウィンドウ）での変更はバックエンドのイベントで届く
+  const un = listen<DockEntry[]>('sis:favorites-updated', (e)=>{ if(mounted) setFav(flattenDock(e.payload || [])) })
+  return ()=>{ mounted=false; un.then(f=>f()); window.removeEventListener('sis:favorites-updated', onFav); window.removeEventListener('sis:apps-refreshed', onAppsChange) }
   },[])
 
-  const unpin = async (name: string)=>{ const r=await api.removeFavoriteApp(name); if(r.ok){ const list=await api.getFavoriteApps(); setFav(list); window.dispatchEvent(new Event('sis:favorites-updated')) } }
+  // 外部メディア（USB/SD）の抜き差しを反映
+  useEffect(()=>{
+    let mounted = true
+    api.storageListRemovable().then(list=>{ if(mounted) setMedia(list) })
+    const un = listen<RemovableDevice[]>('sis:storage-changed', (e)=>{ if(mounted) setMedia(e.payload || []) })
+    const unAdd = listen<RemovableDevice>('sis:storage-added', ()=>{ if(mounted) setActiveSection('media') })
+    return ()=>{ mounted=false; un.then(f=>f()); unAdd.then(f=>f()) }
+  },[])
+
+  // 通知履歴
+  useEffect(()=>{
+    let mounted = true
+    const load = async ()=>{ const h = await api.notifyHistory(); if(mounted) setNotifications(h) }
+    load()
+    const un = listen('sis:notification', ()=>{ load() })
+    return ()=>{ mounted=false; un.then(f=>f()) }
+  },[])
+
+  const unpin = async (a: FavoriteApp)=>{ const r=await api.removeFavoriteApp(a.id); if(r.ok){ const list=await api.getFavorites(); setFav(list); window.dispatchEvent(new Event('sis:favorites-updated')) } }
 
   // このコンポーネントは見た目のみを担い、開閉は親に委ねる
 
   const sections = [
   { id: 'pinned', icon: 'PIN', label: 'ピン留め', count: fav.length },
   { id: 'actions', icon: 'SYS', label: 'システム', count: 0 },
-    { id: 'notifications', icon: '!', label: 'お知らせ', count: notifications.length },
+  { id: 'media', icon: 'USB', label: '外部メディア', count: media.length },
+    { id: 'notifications', icon: '!', label: 'お知らせ', count: notifications.filter(n=>!n.read).length },
+    { id: 'clipboard', icon: 'CB', label: 'クリップボード', count: 0 },
+    { id: 'organize', icon: 'ORG', label: 'ファイル整理', count: 0 },
     { id: 'tasks', icon: 'T', label: 'タスク', count: 0 },
   ];
 
@@ -113,42 +138,32 @@ function Sidebar({ isCollapsed, onToggle }: SidebarProps) {
               <div className="empty-state"><p>ピン留めはありません。アプリ一覧で右クリック→「ピン留め」で登録。</p></div>
             )}
             {fav.filter(a=>a.icon_data_url).map((a)=> (
-              <div key={a.name} className="notification-item" style={{display:'flex',alignItems:'center',gap:8,cursor:'pointer'}} onClick={()=> a.exec && api.launchApp(a.exec!)} title="クリックで起動、解除でピン留めを外す">
+              <div key={a.id} className="notification-item" style={{display:'flex',alignItems:'center',gap:8,cursor:'pointer'}} onClick={()=> a.exec && api.launchApp(a.exec!)} title="クリックで起動、解除でピン留めを外す">
                 <img src={a.icon_data_url} alt="" style={{width:20,height:20,borderRadius:4}} />
                 <div className="notification-content">
                   <div className="notification-title">{a.name}</div>
                 </div>
-                <button className="qa-btn" onClick={(e)=>{ e.stopPropagation(); unpin(a.name!) }} style={{marginLeft:'auto'}}>解除</button>
+                <button className="qa-btn" onClick={(e)=>{ e.stopPropagation(); unpin(a) }} style={{marginLeft:'auto'}}>解除</button>
               </div>
             ))}
           </div>
         </div>
       )}
 
+      {!isCollapsed && activeSection === 'media' && (
+        <MediaList media={media} />
+      )}
+
       {!isCollapsed && activeSection === 'notifications' && (
-        <div className="sidebar-content">
-          <div className="content-header">
-            <h3>最新の通知</h3>
-          </div>
-          <div className="notifications-list">
-            {notifications.length === 0 ? (
-              <div className="empty-state">
-                <p>新しい通知はありません</p>
-              </div>
-            ) : (
-              notifications.map((notification) => (
-                <div key={notification.id} className="notification-item">
-                  <div className="notification-content">
-                    <div className="notification-title">{notification.title}</div>
-                    <div className="notification-message">{notification.message}</div>
-                    <div className="notification-time">{notification.time}</div>
-                  </div>
-                  <div className="notification-indicator"></div>
-                </div>
-              ))
-            )}
-          </div>
-        </div>
+        <NotificationCenter items={notifications} onChange={async()=>setNotifications(await api.notifyHistory())} />
+      )}
+
+      {!isCollapsed && activeSection === 'clipboard' && (
+        <ClipboardHistory />
+      )}
+
+      {!isCollapsed && activeSection === 'organize' && (
+        <FileOrganizer />
       )}
 
       {!isCollapsed && activeSection === 'tasks' && (
@@ -169,11 +184,686 @@ function Sidebar({ isCollapsed, onToggle }: SidebarProps) {
 
 export default Sidebar;
 
+const formatSize = (bytes: number) => {
+  if (bytes >= 1e9) return `${(bytes/1e9).toFixed(1)} GB`
+  if (bytes >= 1e6) return `${(bytes/1e6).toFixed(0)} MB`
+  return `${Math.max(0, Math.round(bytes/1e3))} KB`
+}
+
+const DND_LABEL: Record<DndStatus['config']['mode'], string> = { off: 'オフ', on: 'オン', schedule: '授業時間' }
+const hhmmLabel = (s: string) => `${s.slice(0, 2)}:${s.slice(2)}`
+
+function ClipboardHistory() {
+  const [data, setData] = useState<ClipboardList | null>(null)
+  const [query, setQuery] = useState('')
+
+  useEffect(()=>{
+    let mounted = true
+    const load = async ()=>{ const d = await api.clipboardList(query || undefined); if(mounted) setData(d) }
+    load()
+    const un = listen('sis:clipboard-changed', ()=>{ load() })
+    return ()=>{ mounted=false; un.then(f=>f()) }
+  },[query])
+
+  const entries = data?.entries || []
+
+  return (
+    <div className="sidebar-content">
+      <div className="content-header" style={{ display:'flex', alignItems:'center', gap:8 }}>
+        <h3>クリップボード履歴</h3>
+        {entries.length > 0 && <button className="qa-btn" onClick={()=>api.clipboardClear()} style={{marginLeft:'auto'}} title="ピン留め以外を消去">消去</button>}
+      </div>
+      {data?.exam_mode ? (
+        <div className="empty-state"><p>試験モード中は履歴を記録しません</p></div>
+      ) : data && !data.enabled ? (
+        <div className="empty-state"><p>xclip / wl-clipboard が見つからないため履歴は無効です</p></div>
+      ) : (
+        <>
+          <input className="game-input" placeholder="履歴を検索" value={query} onChange={(e)=>setQuery(e.target.value)} style={{ width:'100%', marginBottom: 8 }} />
+          <div className="notifications-list">
+            {entries.length === 0 ? (
+              <div className="empty-state"><p>{query ? '一致する項目はありません' : 'コピーした内容がここに表示されます'}</p></div>
+            ) : entries.map(e => (
+              <div key={e.id} className="notification-item" style={{ cursor:'pointer' }} title="クリックでコピー" onClick={()=>api.clipboardCopy(e.id)}>
+                <div className="notification-content" style={{ minWidth: 0 }}>
+                  {e.kind === 'image' && e.thumbnail
+                    ? <img src={e.thumbnail} alt="" style={{ maxWidth:'100%', maxHeight:96, borderRadius:6 }} />
+                    : <div className="notification-message" style={{ whiteSpace:'pre-wrap', maxHeight:60, overflow:'hidden' }}>{e.text}</div>}
+                  <div className="notification-time">
+                    {e.kind === 'image' ? `画像 ${e.width}×${e.height}` : e.kind === 'html' ? 'リッチテキスト' : e.selection === 'primary' ? '選択範囲' : 'テキスト'} ・ {new Date(e.created_ms).toLocaleString('ja-JP')}
+                  </div>
+                  <div style={{ display:'flex', gap:6, marginTop:4 }}>
+                    <button className={`game-btn toggle ${e.pinned?'active':''}`} onClick={(ev)=>{ ev.stopPropagation(); api.clipboardPin(e.id, !e.pinned) }}>{e.pinned?'ピン留め中':'ピン留め'}</button>
+                    {e.kind === 'html' && <button className="qa-btn" onClick={(ev)=>{ ev.stopPropagation(); api.clipboardCopy(e.id, true) }}>テキストでコピー</button>}
+                    <button className="qa-btn" onClick={(ev)=>{ ev.stopPropagation(); api.clipboardDelete(e.id) }}>削除</button>
+                  </div>
+                </div>
+              </div>
+            ))}
+          </div>
+        </>
+      )}
+    </div>
+  )
+}
+
+const COLLISION_LABEL: Record<OrganizeCollision, string> = { rename: '連番を付ける', skip: 'スキップ', replace: '置き換える' }
+const PLAN_REASON_LABEL: Record<string, string> = { 'no-rule': '一致するルールなし', 'already-in-place': '移動先と同じ場所', 'exists': '同名のファイルあり', 'target-is-directory': '同名のフォルダあり' }
+const EMPTY_RULE: OrganizeRule = { name: '', enabled: true, mime: [], extensions: [], name_regex: null, source_url_regex: null, min_size: null, max_size: null, min_age_days: null, max_age_days: null, target: '', collision: null }
+
+const splitList = (v: string) => v.split(',').map(x => x.trim()).filter(Boolean)
+const optNum = (v: string, scale = 1) => v.trim() === '' || isNaN(Number(v)) ? null : Number(v) * scale
+const baseName = (p: string) => p.split('/').pop() || p
+
+function RuleEditor({ rule, onSave, onCancel }: { rule: OrganizeRule; onSave: (r: OrganizeRule) => void; onCancel: () => void }) {
+  const [r, setR] = useState(rule)
+  const MB = 1024 * 1024
+  const field = (label: string, value: string, set: (v: string) => void, placeholder = '') => (
+    <div className="control-item">
+      <label className="setting-label">{label}</label>
+      <input className="game-input" value={value} placeholder={placeholder} onChange={e=>set(e.target.value)} />
+    </div>
+  )
+  return (
+    <div className="control-grid">
+      {field('名前', r.name, v=>setR({ ...r, name: v }))}
+      {field('MIME（カンマ区切り）', r.mime.join(', '), v=>setR({ ...r, mime: splitList(v) }), 'image/*, application/pdf')}
+      {field('拡張子（カンマ区切り）', r.extensions.join(', '), v=>setR({ ...r, extensions: splitList(v) }), 'pdf, docx')}
+      {field('ファイル名（正規表現）', r.name_regex || '', v=>setR({ ...r, name_regex: v || null }), '^課題_')}
+      {field('入手元 URL（正規表現）', r.source_url_regex || '', v=>setR({ ...r, source_url_regex: v || null }), 'classroom\\.google\\.com')}
+      {field('最小サイズ（MB）', r.min_size != null ? String(r.min_size / MB) : '', v=>setR({ ...r, min_size: optNum(v, MB) }))}
+      {field('最大サイズ（MB）', r.max_size != null ? String(r.max_size / MB) : '', v=>setR({ ...r, max_size: optNum(v, MB) }))}
+      {field('何日以上前のファイル', r.min_age_days != null ? String(r.min_age_days) : '', v=>setR({ ...r, min_age_days: optNum(v) }))}
+      {field('移動先', r.target, v=>setR({ ...r, target: v }), '{documents}/{host}/{year}-{month}')}
+      <div className="control-item">
+        <label className="setting-label">同名のファイルがあるとき</label>
+        <select className="game-input" value={r.collision || ''} onChange={e=>setR({ ...r, collision: (e.target.value || null) as OrganizeCollision | null })}>
+          <option value="">全体の設定に従う</option>
+          {(Object.keys(COLLISION_LABEL) as OrganizeCollision[]).map(c => <option key={c} value={c}>{COLLISION_LABEL[c]}</option>)}
+        </select>
+      </div>
+      <div style={{ display:'flex', gap:6 }}>
+        <button className="qa-btn" disabled={!r.name.trim() || !r.target.trim()} onClick={()=>onSave(r)}>保存</button>
+        <button className="qa-btn" onClick={onCancel}>キャンセル</button>
+      </div>
+    </div>
+  )
+}
+
+function FileOrganizer() {
+  const [cfg, setCfg] = useState<OrganizerConfig | null>(null)
+  const [plans, setPlans] = useState<OrganizePlan[] | null>(null)
+  const [editing, setEditing] = useState<number | null>(null) // -1 は新規
+  const [busy, setBusy] = useState(false)
+  const [watch, setWatch] = useState<DownloadWatchConfig | null>(null)
+
+  useEffect(()=>{ api.organizerGetConfig().then(setCfg); api.downloadsWatchGetConfig().then(setWatch) },[])
+  // 自動整理で動いたら予行の表示を古いままにしない
+  useEffect(()=>{
+    const uns: Array<() => void> = []
+    for (const ev of ['sis:download-organized', 'sis:download-restored']) {
+      listen(ev, ()=> setPlans(null)).then(u => uns.push(u))
+    }
+    return ()=> uns.forEach(u => u())
+  },[])
+
+  const saveWatch = async (c: DownloadWatchConfig)=>{
+    const r = await api.downloadsWatchSetConfig(c)
+    if (r.ok) setWatch(r.config || c)
+    else alert(`自動整理を設定できませんでした: ${r.message || ''}`)
+  }
+
+  const save = async (c: OrganizerConfig)=>{
+    const r = await api.organizerSetConfig(c)
+    if (!r.ok) { alert(`保存できませんでした: ${r.message || ''}`); return false }
+    setCfg(r.config || c); setPlans(null)
+    return true
+  }
+
+  const preview = async ()=>{
+    setBusy(true)
+    const r = await api.organizePreview()
+    setBusy(false)
+    if (r.ok) setPlans(r.plans || [])
+    else alert(`予行できませんでした: ${r.message || ''}`)
+  }
+
+  const run = async ()=>{
+    if (!plans) return
+    setBusy(true)
+    let moved = 0
+    const failed: string[] = []
+    for (const p of plans.filter(p => p.action === 'move')) {
+      const r = await api.organizeFile(p.source)
+      if (r.ok && r.plan?.done) moved++
+      else if (!r.ok) failed.push(`${baseName(p.source)}: ${r.message || ''}`)
+    }
+    setBusy(false)
+    if (failed.length) alert(`${moved} 件を整理しました。失敗:\n${failed.join('\n')}`)
+    preview()
+  }
+
+  if (!cfg) return null
+  const moves = plans?.filter(p => p.action === 'move').length || 0
+
+  return (
+    <div className="sidebar-content">
+      <div className="content-header" style={{ display:'flex', alignItems:'center', gap:8 }}>
+        <h3>ファイル整理</h3>
+        <button className="qa-btn" disabled={busy} onClick={preview} style={{ marginLeft:'auto' }}>ダウンロードを予行</button>
+      </div>
+
+      {plans && (
+        <div className="notifications-list">
+          {plans.length === 0 && <div className="empty-state"><p>ダウンロードフォルダにファイルはありません</p></div>}
+          {plans.map(p => (
+            <div key={p.source} className="notification-item">
+              <div className="notification-content" style={{ minWidth: 0 }}>
+                <div className="notification-title" style={{ overflow:'hidden', textOverflow:'ellipsis' }}>{baseName(p.source)}</div>
+                <div className="notification-message" style={{ wordBreak:'break-all' }}>
+                  {p.action === 'move'
+                    ? `→ ${p.destination}${p.collision === 'renamed' ? '（連番を付けます）' : p.collision === 'replaced' ? '（既存を置き換えます）' : ''}${p.cross_device ? '（別ドライブへコピー）' : ''}`
+                    : `移動しない: ${PLAN_REASON_LABEL[p.reason || ''] || p.reason || ''}`}
+                </div>
+                <div className="notification-time">{p.rule ? `ルール: ${p.rule}` : p.action === 'move' ? 'その他' : ''}</div>
+              </div>
+            </div>
+          ))}
+          {moves > 0 && <button className="qa-btn" disabled={busy} onClick={run}>{moves} 件を整理する</button>}
+        </div>
+      )}
+
+      {watch && (
+        <div className="control-grid" style={{ marginTop: 12 }}>
+          <div className="control-item">
+            <label className="setting-label">ダウンロードを自動で整理</label>
+            <button className={`game-btn toggle ${watch.enabled?'active':''}`} onClick={()=>saveWatch({ ...watch, enabled: !watch.enabled })}>{watch.enabled?'オン':'オフ'}</button>
+          </div>
+          <div className="control-item">
+            <label className="setting-label">書き込みが止まってから待つ秒数</label>
+            <input className="game-input" type="number" min={2} max={600} defaultValue={watch.quiet_secs} onBlur={e=>{ const v = Math.round(Number(e.target.value)); if (v && v !== watch.quiet_secs) saveWatch({ ...watch, quiet_secs: v }) }} />
+          </div>
+          <div className="control-item">
+            <label className="setting-label">整理したら通知する（元に戻す・フォルダを開く）</label>
+            <button className={`game-btn toggle ${watch.notify?'active':''}`} onClick={()=>saveWatch({ ...watch, notify: !watch.notify })}>{watch.notify?'オン':'オフ'}</button>
+          </div>
+        </div>
+      )}
+
+      <div className="content-header" style={{ marginTop: 12 }}><h3>ルール（上から順に判定）</h3></div>
+      <div className="notifications-list">
+        {cfg.rules.map((r, i) => editing === i ? (
+          <RuleEditor key={i} rule={r} onCancel={()=>setEditing(null)} onSave={async (nr)=>{ if (await save({ ...cfg, rules: cfg.rules.map((x, k) => k === i ? nr : x) })) setEditing(null) }} />
+        ) : (
+          <div key={i} className="notification-item">
+            <div className="notification-content" style={{ minWidth: 0 }}>
+              <div className="notification-title">{r.name}</div>
+              <div className="notification-message" style={{ wordBreak:'break-all' }}>
+                {[...r.mime, ...r.extensions.map(e => `.${e}`)].join(', ') || '条件なし'}{r.name_regex ? ` ・名前 /${r.name_regex}/` : ''}{r.source_url_regex ? ` ・入手元 /${r.source_url_regex}/` : ''} → {r.target}
+              </div>
+              <div style={{ display:'flex', gap:6, marginTop:4 }}>
+                <button className={`game-btn toggle ${r.enabled?'active':''}`} onClick={()=>save({ ...cfg, rules: cfg.rules.map((x, k) => k === i ? { ...x, enabled: !x.enabled } : x) })}>{r.enabled?'有効':'無効'}</button>
+                <button className="qa-btn" disabled={i === 0} onClick={()=>{ const rules = [...cfg.rules]; [rules[i-1], rules[i]] = [rules[i], rules[i-1]]; save({ ...cfg, rules }) }}>↑</button>
+                <button className="qa-btn" onClick={()=>setEditing(i)}>編集</button>
+                <button className="qa-btn" onClick={()=>save({ ...cfg, rules: cfg.rules.filter((_, k) => k !== i) })}>削除</button>
+              </div>
+            </div>
+          </div>
+        ))}
+        {editing === -1
+          ? <RuleEditor rule={EMPTY_RULE} onCancel={()=>setEditing(null)} onSave={async (nr)=>{ if (await save({ ...cfg, rules: [...cfg.rules, nr] })) setEditing(null) }} />
+          : <button className="qa-btn" onClick={()=>setEditing(-1)}>ルールを追加</button>}
+      </div>
+
+      <div className="control-grid" style={{ marginTop: 12 }}>
+        <div className="control-item">
+          <label className="setting-label">同名のファイルがあるとき</label>
+          <select className="game-input" value={cfg.collision} onChange={e=>save({ ...cfg, collision: e.target.value as OrganizeCollision })}>
+            {(Object.keys(COLLISION_LABEL) as OrganizeCollision[]).map(c => <option key={c} value={c}>{COLLISION_LABEL[c]}</option>)}
+          </select>
+        </div>
+        <div className="control-item">
+          <label className="setting-label">どのルールにも合わないファイルの移動先（空欄なら移動しない）</label>
+          <input className="game-input" defaultValue={cfg.fallback_target || ''} onBlur={e=>{ if ((e.target.value || null) !== (cfg.fallback_target || null)) save({ ...cfg, fallback_target: e.target.value.trim() || null }) }} />
+        </div>
+        <div className="notification-time">移動先で使える変数: {'{downloads} {documents} {pictures} {music} {videos} {desktop} {home} {year} {month} {day} {ext} {mime} {host}'}</div>
+      </div>
+    </div>
+  )
+}
+
+function NotificationCenter({ items, onChange }: { items: SisNotification[]; onChange: () => void }) {
+  const [dnd, setDnd] = useState<DndStatus | null>(null)
+
+  useEffect(()=>{
+    api.notifyGetDnd().then(setDnd)
+    const un = listen('sis:dnd-changed', ()=>{ api.notifyGetDnd().then(setDnd) })
+    // 開いたら既読にする
+    api.notifyMarkRead().then(()=>onChange())
+    return ()=>{ un.then(f=>f()) }
+  },[])
+
+  const cycleDnd = async ()=>{
+    if (!dnd) return
+    const next = dnd.config.mode === 'off' ? 'on' : dnd.config.mode === 'on' ? 'schedule' : 'off'
+    const r = await api.notifySetDnd({ ...dnd.config, mode: next })
+    if (r.ok && r.status) setDnd(r.status); else alert('おやすみモードの変更に失敗')
+  }
+
+  return (
+    <div className="sidebar-content">
+      <div className="content-header" style={{ display:'flex', alignItems:'center', gap:8 }}>
+        <h3>最新の通知</h3>
+        {items.length > 0 && <button className="qa-btn" onClick={async()=>{ await api.notifyClearHistory(); onChange() }} style={{marginLeft:'auto'}}>すべて消去</button>}
+      </div>
+      {dnd && (
+        <div className="control-item" style={{ marginBottom: 12 }}>
+          <label className="setting-label">おやすみモード{dnd.active ? '（有効）' : ''}</label>
+          <button className={`game-btn toggle ${dnd.config.mode!=='off'?'active':''}`} onClick={cycleDnd}>{DND_LABEL[dnd.config.mode]}</button>
+          {dnd.config.mode === 'schedule' && (
+            <div className="notification-time">{hhmmLabel(dnd.config.start_hhmm)}〜{hhmmLabel(dnd.config.end_hhmm)}{dnd.config.weekdays_only ? '（平日）' : ''}</div>
+          )}
+        </div>
+      )}
+      <div className="notifications-list">
+        {items.length === 0 ? (
+          <div className="empty-state">
+            <p>新しい通知はありません</p>
+          </div>
+        ) : (
+          items.map((n) => (
+            <div key={n.id} className="notification-item" style={{ cursor: 'pointer' }} onClick={()=>api.notifyInvokeAction(n.id, 'default')}>
+              <div className="notification-content">
+                <div className="notification-title">{n.summary}</div>
+                {n.body && <div className="notification-message">{n.body}</div>}
+                {n.actions.filter(a=>a.key!=='default').length > 0 && (
+                  <div style={{ display:'flex', gap:6, marginBottom:6 }}>
+                    {n.actions.filter(a=>a.key!=='default').map(a=> <button key={a.key} className="qa-btn" onClick={(e)=>{ e.stopPropagation(); api.notifyInvokeAction(n.id, a.key) }}>{a.label}</button>)}
+                  </div>
+                )}
+                <div className="notification-time">{n.app_name} ・ {new Date(n.timestamp).toLocaleString('ja-JP')}{n.suppressed ? ' ・ おやすみ中に受信' : ''}</div>
+              </div>
+              {!n.read && <div className="notification-indicator"></div>}
+            </div>
+          ))
+        )}
+      </div>
+    </div>
+  )
+}
+
+function MediaList({ media }: { media: RemovableDevice[] }) {
+  const [policy, setPolicy] = useState<StoragePolicy | null>(null)
+  const [busy, setBusy] = useState<string | null>(null)
+
+  useEffect(()=>{ api.storagePolicy().then(setPolicy) },[])
+
+  const open = async (d: RemovableDevice) => {
+    const mp = d.mount_points[0] || (await (async()=>{
+      const r = await api.storageMount(d.id)
+      if (!r.ok) { alert(r.code==='blocked-by-policy' ? '学校のポリシーによりUSBメモリは使用できません' : `マウントに失敗: ${r.message||''}`); return '' }
+      return r.mountPoint || ''
+    })())
+    if (mp) api.openPath(mp)
+  }
+
+  const eject = async (d: RemovableDevice) => {
+    setBusy(d.id)
+    const r = await api.storageEject(d.id)
+    setBusy(null)
+    if (r.ok) { alert(`${d.label} を安全に取り外せます`); return }
+    if (r.code === 'device-busy') {
+      const holders = await api.storageBusyHolders(d.id)
+      const names = holders.map(h=>`${h.name} (PID ${h.pid})`).join('\n')
+      alert(`使用中のため取り外せません。次のアプリを終了してください:\n${names || '(不明なプロセス)'}`)
+    } else {
+      alert(`取り外しに失敗: ${r.message||''}`)
+    }
+  }
+
+  return (
+    <div className="sidebar-content">
+      <div className="content-header"><h3>外部メディア</h3></div>
+      {policy && policy.usb_storage !== 'allow' && (
+        <div className="empty-state"><p>{policy.usb_storage==='block' ? 'USBメモリの使用は学校のポリシーで禁止されています' : 'USBメモリは読み取り専用で接続されます（学校のポリシー）'}</p></div>
+      )}
+      <div className="notifications-list">
+        {media.length===0 && (
+          <div className="empty-state"><p>USBメモリやSDカードは接続されていません</p></div>
+        )}
+        {media.map(d=> (
+          <div key={d.id} className="notification-item" style={{display:'flex',alignItems:'center',gap:8,cursor:'pointer'}} onClick={()=>open(d)} title={d.device}>
+            <div className="notification-content">
+              <div className="notification-title">{d.label}</div>
+              <div className="notification-message">{formatSize(d.size)} {d.fs_type}{d.mount_points.length ? '' : '（未マウント）'}</div>
+            </div>
+            {d.can_eject && (
+              <button className="qa-btn" disabled={busy===d.id} onClick={(e)=>{ e.stopPropagation(); eject(d) }} style={{marginLeft:'auto'}}>取り外し</button>
+            )}
+          </div>
+        ))}
+      </div>
+      {policy?.mount_options && (
+        <div style={{ fontSize: 12, opacity: .8, marginTop: 8 }}>マウントオプション: {policy.mount_options}{policy.usb_logging ? '・接続は記録されます' : ''}</div>
+      )}
+    </div>
+  )
+}
+
+const SECURITY_LABEL: Record<AccessPoint['security'], string> = { open: '保護なし', wep: 'WEP', 'wpa-psk': 'WPA2', sae: 'WPA3', enterprise: 'WPA2-Enterprise' }
+
+function WifiPicker() {
+  const [aps, setAps] = useState<AccessPoint[]>([])
+  const [scanning, setScanning] = useState(false)
+
+  const load = async (rescan: boolean) => {
+    setScanning(rescan)
+    setAps(await api.wifiListAccessPoints(rescan))
+    // スキャン結果は数秒後に揃うため再取得
+    if (rescan) setTimeout(async()=>{ setAps(await api.wifiListAccessPoints(false)); setScanning(false) }, 3000)
+  }
+
+  useEffect(()=>{
+    load(true)
+    const un = listen('sis:network-changed', ()=>{ load(false) })
+    return ()=>{ un.then(f=>f()) }
+  },[])
+
+  const connect = async (ap: AccessPoint) => {
+    if (ap.active) return
+    let r
+    if (ap.saved || ap.security === 'open') {
+      r = await api.wifiConnect(ap.ssid)
+    } else if (ap.security === 'enterprise') {
+      const identity = prompt(`${ap.ssid} のユーザーID`) || ''
+      if (!identity) return
+      const password = prompt('パスワード') || ''
+      if (!password) return
+      r = await api.wifiConnect(ap.ssid, { eap: { identity, password } })
+    } else {
+      const password = prompt(`${ap.ssid} のパスワード`) || ''
+      if (!password) return
+      r = await api.wifiConnect(ap.ssid, { password })
+    }
+    if (!r.ok) alert(`接続に失敗: ${r.message||''}`)
+  }
+
+  const forget = async (ap: AccessPoint) => {
+    if (!confirm(`${ap.ssid} の保存済み設定を削除しますか？`)) return
+    const r = await api.networkForget(ap.ssid)
+    if (r.ok) load(false); else alert('削除に失敗')
+  }
+
+  return (
+    <div style={{ marginTop: 12 }}>
+      <div style={{ display:'flex', alignItems:'center', gap:8 }}>
+        <label className="setting-label">Wi-Fi</label>
+        <button className="qa-btn" disabled={scanning} onClick={()=>load(true)} style={{marginLeft:'auto'}}>{scanning?'スキャン中…':'再スキャン'}</button>
+      </div>
+      <div className="notifications-list">
+        {aps.length===0 && <div className="empty-state"><p>ネットワークが見つかりません</p></div>}
+        {aps.map(ap=> (
+          <div key={ap.ssid} className="notification-item" style={{display:'flex',alignItems:'center',gap:8,cursor:'pointer'}} onClick={()=>connect(ap)} title={ap.bssid}>
+            <div className="notification-content">
+              <div className="notification-title">{ap.ssid}{ap.active ? '（接続中）' : ''}</div>
+              <div className="notification-message">{ap.strength}% ・ {SECURITY_LABEL[ap.security]}</div>
+            </div>
+            {ap.saved && <button className="qa-btn" onClick={(e)=>{ e.stopPropagation(); forget(ap) }} style={{marginLeft:'auto'}}>削除</button>}
+          </div>
+        ))}
+      </div>
+    </div>
+  )
+}
+
+function btIcon(icon: string) {
+  if (icon.startsWith('audio')) return '🎧'
+  if (icon === 'input-keyboard') return '⌨️'
+  if (icon === 'input-mouse' || icon === 'input-tablet') return '🖱️'
+  if (icon === 'input-gaming') return '🎮'
+  if (icon === 'phone') return '📱'
+  return '🔵'
+}
+
+function BluetoothDevices() {
+  const [st, setSt] = useState<BluetoothState | null>(null)
+  const [busy, setBusy] = useState<string | null>(null)
+  const [prompt, setPrompt] = useState<BtPasskeyRequest | null>(null)
+  const [code, setCode] = useState('')
+
+  useEffect(()=>{
+    api.bluetoothState().then(setSt)
+    const un1 = listen<BluetoothState>('sis:bluetooth-changed', (e)=>{ setSt(e.payload) })
+    const un2 = listen<{ address: string; action: string; ok: boolean; error?: SisError | null }>('sis:bluetooth-action', (e)=>{
+      setBusy(null)
+      setPrompt(null)
+      if (!e.payload.ok) alert(`${e.payload.action === 'pair' ? 'ペアリング' : '接続'}に失敗: ${e.payload.error?.message||''}`)
+    })
+    const un3 = listen<BtPasskeyRequest>('sis:bluetooth-passkey', (e)=>{
+      setCode('')
+      setPrompt(e.payload.kind === 'cancel' ? null : e.payload)
+    })
+    return ()=>{ un1.then(f=>f()); un2.then(f=>f()); un3.then(f=>f()) }
+  },[])
+
+  const reply = async (accept: boolean) => {
+    if (!prompt?.request) { setPrompt(null); return }
+    const r = await api.bluetoothAgentReply(prompt.request, accept, code.trim() || undefined)
+    setPrompt(null)
+    if (!r.ok) alert(`応答できませんでした: ${r.message||''}`)
+  }
+
+  if (!st?.adapter) return null
+  const discovering = st.adapter.discovering
+
+  const activate = async (d: BtDevice) => {
+    if (busy) return
+    let r
+    if (d.connected) r = await api.bluetoothDisconnect(d.address)
+    else { setBusy(d.address); r = d.paired ? await api.bluetoothConnect(d.address) : await api.bluetoothPair(d.address) }
+    if (!r.ok) { setBusy(null); alert(`操作に失敗: ${r.message||''}`) }
+  }
+
+  const remove = async (d: BtDevice) => {
+    if (!confirm(`${d.name} のペアリングを解除しますか？`)) return
+    const r = await api.bluetoothRemove(d.address)
+    if (!r.ok) alert('解除に失敗')
+  }
+
+  // 未ペアの機器は検出中のみ表示
+  const devices = st.devices.filter(d=>d.paired || discovering)
+
+  return (
+    <div style={{ marginTop: 12 }}>
+      <div style={{ display:'flex', alignItems:'center', gap:8 }}>
+        <label className="setting-label">Bluetooth デバイス</label>
+        <button className="qa-btn" onClick={()=>api.bluetoothDiscover(!discovering)} style={{marginLeft:'auto'}}>{discovering?'検出を停止':'デバイスを検出'}</button>
+      </div>
+      {prompt && (
+        <div className="notification-item" style={{ flexDirection:'column', alignItems:'stretch', gap:6 }}>
+          <div className="notification-title">{btIcon(prompt.icon || '')} {prompt.name || prompt.address || 'Bluetooth デバイス'}</div>
+          {prompt.kind === 'confirm' && <div className="notification-message">機器に表示されている番号が <b style={{ fontSize:18, letterSpacing:2 }}>{prompt.passkey}</b> と同じなら「一致」を押してください</div>}
+          {prompt.kind === 'display-passkey' && <div className="notification-message">機器で <b style={{ fontSize:18, letterSpacing:2 }}>{prompt.passkey}</b> を入力して Enter を押してください{typeof prompt.entered === 'number' && prompt.entered > 0 ? `（${prompt.entered} 桁入力済み）` : ''}</div>}
+          {prompt.kind === 'display-pin' && <div className="notification-message">機器で PIN <b style={{ fontSize:18, letterSpacing:2 }}>{prompt.passkey}</b> を入力してください</div>}
+          {prompt.kind === 'authorize' && <div className="notification-message">この機器からのペアリングを許可しますか？</div>}
+          {(prompt.kind === 'request-passkey' || prompt.kind === 'request-pin') && (
+            <>
+              <div className="notification-message">機器に表示されている{prompt.kind === 'request-pin' ? ' PIN' : '番号'}を入力してください</div>
+              <input className="game-input" autoFocus inputMode={prompt.kind === 'request-passkey' ? 'numeric' : 'text'} maxLength={prompt.kind === 'request-passkey' ? 6 : 16} value={code} onChange={e=>setCode(e.target.value)} onKeyDown={e=>{ if (e.key === 'Enter' && code.trim()) reply(true) }} />
+            </>
+          )}
+          <div style={{ display:'flex', gap:6 }}>
+            {prompt.request !== undefined
+              ? <>
+                  <button className="qa-btn" disabled={(prompt.kind === 'request-passkey' || prompt.kind === 'request-pin') && !code.trim()} onClick={()=>reply(true)}>{prompt.kind === 'confirm' ? '一致' : prompt.kind === 'authorize' ? '許可' : 'OK'}</button>
+                  <button className="qa-btn" onClick={()=>reply(false)}>{prompt.kind === 'confirm' ? '一致しない' : 'キャンセル'}</button>
+                </>
+              : <button className="qa-btn" onClick={()=>setPrompt(null)}>閉じる</button>}
+          </div>
+        </div>
+      )}
+      <div className="notifications-list">
+        {devices.length===0 && <div className="empty-state"><p>デバイスがありません</p></div>}
+        {devices.map(d=> (
+          <div key={d.path} className="notification-item" style={{display:'flex',alignItems:'center',gap:8,cursor:'pointer'}} onClick={()=>activate(d)} title={d.address}>
+            <div className="notification-icon">{btIcon(d.icon)}</div>
+            <div className="notification-content">
+              <div className="notification-title">{d.name}</div>
+              <div className="notification-message">
+                {busy===d.address ? '接続中…' : d.connected ? '接続済み' : d.paired ? 'ペアリング済み' : 'タップしてペアリング'}
+                {typeof d.battery === 'number' ? ` ・ 🔋${d.battery}%` : ''}
+              </div>
+            </div>
+            {d.paired && <button className="qa-btn" onClick={(e)=>{ e.stopPropagation(); remove(d) }} style={{marginLeft:'auto'}}>解除</button>}
+          </div>
+        ))}
+      </div>
+    </div>
+  )
+}
+
+function AudioMixer() {
+  const [st, setSt] = useState<AudioState | null>(null)
+
+  useEffect(()=>{
+    api.audioState().then(setSt)
+    const un = listen<AudioState>('sis:audio-changed', (e)=>{ setSt(e.payload) })
+    return ()=>{ un.then(f=>f()) }
+  },[])
+
+  if (!st) return null
+  const sink = st.sinks.find(d=>d.is_default)
+  const source = st.sources.find(d=>d.is_default)
+  const label = (d: { description: string; port?: string | null }) => d.port ? `${d.description}（${d.port}）` : d.description
+
+  return (
+    <div style={{ marginTop: 12 }}>
+      {st.sinks.length > 1 && (
+        <div className="control-item">
+          <label className="setting-label">出力</label>
+          <select className="game-input" value={sink?.name ?? ''} onChange={async e=>{ const r = await api.audioSetDefault('sink', e.target.value); if(!r.ok) alert('出力の切替に失敗') }}>
+            {st.sinks.map(d=> <option key={d.name} value={d.name}>{label(d)}</option>)}
+          </select>
+        </div>
+      )}
+      {st.sources.length > 0 && (
+        <div className="control-item">
+          <label className="setting-label">マイク</label>
+          <div style={{ display:'flex', alignItems:'center', gap:8 }}>
+            {st.sources.length > 1 && (
+              <select className="game-input" value={source?.name ?? ''} onChange={async e=>{ const r = await api.audioSetDefault('source', e.target.value); if(!r.ok) alert('マイクの切替に失敗') }}>
+                {st.sources.map(d=> <option key={d.name} value={d.name}>{label(d)}</option>)}
+              </select>
+            )}
+            {source && <button className={`game-btn toggle ${source.muted?'':'active'}`} onClick={()=>api.audioSetMute('source', source.name)}>{source.muted?'ミュート中':'オン'}</button>}
+          </div>
+          {source && (
+            <div className="slider-container">
+              <input type="range" min={0} max={100} value={Math.min(source.volume,100)} onChange={e=>api.audioSetVolume('source', source.name, parseInt(e.target.value))} className="game-slider" />
+              <span className="slider-value">{source.volume}%</span>
+            </div>
+          )}
+        </div>
+      )}
+      {st.streams.filter(s=>s.kind==='playback').length > 0 && <label className="setting-label">アプリごとの音量</label>}
+      {st.streams.filter(s=>s.kind==='playback').map(s=> (
+        <div key={s.index} className="control-item">
+          <div style={{ display:'flex', alignItems:'center', gap:8 }}>
+            <span title={s.media_name}>{s.app_name}</span>
+            <button className="qa-btn" onClick={()=>api.audioSetStreamMute(s.kind, s.index)} style={{marginLeft:'auto'}}>{s.muted?'🔇':'🔊'}</button>
+          </div>
+          <div className="slider-container">
+            <input type="range" min={0} max={100} value={Math.min(s.volume,100)} onChange={e=>api.audioSetStreamVolume(s.kind, s.index, parseInt(e.target.value))} className="game-slider" />
+            <span className="slider-value">{s.volume}%</span>
+          </div>
+        </div>
+      ))}
+    </div>
+  )
+}
+
+function formatTime(ms: number) {
+  const t = Math.max(0, Math.floor(ms / 1000))
+  return `${Math.floor(t / 60)}:${String(t % 60).padStart(2, '0')}`
+}
+
+const LOOP_NEXT: Record<string, 'none' | 'track' | 'playlist'> = { none: 'playlist', playlist: 'track', track: 'none' }
+
+function NowPlaying() {
+  const [st, setSt] = useState<MediaState | null>(null)
+  const [base, setBase] = useState({ pos: 0, at: Date.now() })
+  const [, setTick] = useState(0)
+
+  useEffect(()=>{
+    const apply = (s: MediaState | null) => {
+      setSt(s)
+      const p = s?.players.find(x=>x.id===s.active)
+      setBase({ pos: p?.position_ms ?? 0, at: Date.now() })
+    }
+    api.mediaState().then(apply)
+    const un = listen<MediaState>('sis:media-changed', (e)=>{ apply(e.payload) })
+    // 再生中は位置をローカルで進める
+    const t = setInterval(()=>setTick(n=>n+1), 1000)
+    return ()=>{ un.then(f=>f()); clearInterval(t) }
+  },[])
+
+  const p = st?.players.find(x=>x.id===st.active)
+  if (!st || !p) return null
+  const dur = p.duration_ms ?? 0
+  const pos = Math.min(p.status === 'playing' ? base.pos + (Date.now() - base.at) : base.pos, dur || Infinity)
+
+  return (
+    <div className="control-item" style={{ marginBottom: 12 }}>
+      <div style={{ display:'flex', alignItems:'center', gap:8 }}>
+        <label className="setting-label">再生中</label>
+        {st.players.length > 1 && (
+          <select className="game-input" value={p.id} onChange={e=>{ api.mediaSetActive(e.target.value); api.mediaState().then(setSt) }} style={{marginLeft:'auto'}}>
+            {st.players.map(x=> <option key={x.id} value={x.id}>{x.identity}</option>)}
+          </select>
+        )}
+      </div>
+      <div style={{ display:'flex', alignItems:'center', gap:10 }}>
+        {p.art_url ? <img src={p.art_url} alt="" style={{width:56,height:56,borderRadius:6,objectFit:'cover'}} /> : <div style={{width:56,height:56,borderRadius:6,display:'flex',alignItems:'center',justifyContent:'center'}}>♪</div>}
+        <div className="notification-content" style={{ minWidth: 0 }}>
+          <div className="notification-title" title={p.title}>{p.title || p.identity}</div>
+          <div className="notification-message">{[p.artist.join(', '), p.album].filter(Boolean).join(' ・ ')}</div>
+        </div>
+      </div>
+      {dur > 0 && (
+        <div className="slider-container">
+          <span className="slider-value">{formatTime(pos)}</span>
+          <input type="range" min={0} max={dur} value={pos} disabled={!p.can_seek} className="game-slider"
+            onChange={e=>{ const v = parseInt(e.target.value); setBase({ pos: v, at: Date.now() }); api.mediaSeek(v, p.id) }} />
+          <span className="slider-value">{formatTime(dur)}</span>
+        </div>
+      )}
+      <div style={{ display:'flex', alignItems:'center', gap:6, justifyContent:'center' }}>
+        {typeof p.shuffle === 'boolean' && <button className={`qa-btn ${p.shuffle?'active':''}`} onClick={()=>api.mediaSetShuffle(!p.shuffle, p.id)} title="シャッフル">🔀</button>}
+        <button className="qa-btn" disabled={!p.can_go_previous} onClick={()=>api.mediaControl('previous', p.id)}>⏮</button>
+        <button className="qa-btn" disabled={!p.can_control} onClick={()=>api.mediaControl('play-pause', p.id)}>{p.status === 'playing' ? '⏸' : '▶'}</button>
+        <button className="qa-btn" disabled={!p.can_go_next} onClick={()=>api.mediaControl('next', p.id)}>⏭</button>
+        {p.loop_status && <button className={`qa-btn ${p.loop_status!=='none'?'active':''}`} onClick={()=>api.mediaSetLoop(LOOP_NEXT[p.loop_status!], p.id)} title="リピート">{p.loop_status==='track'?'🔂':'🔁'}</button>}
+      </div>
+      {typeof p.volume === 'number' && (
+        <div className="slider-container">
+          <span className="slider-value">🎵</span>
+          <input type="range" min={0} max={100} value={p.volume} onChange={e=>api.mediaSetVolume(parseInt(e.target.value), p.id)} className="game-slider" />
+          <span className="slider-value">{p.volume}%</span>
+        </div>
+      )}
+    </div>
+  )
+}
+
 function SystemControls() {
   const [volume, setVolume] = useState(50)
+  const [muted, setMuted] = useState(false)
   const [brightness, setBrightness] = useState(80)
+  const [hasBacklight, setHasBacklight] = useState(true)
+  const [kbdBacklight, setKbdBacklight] = useState<number | null>(null)
   const [network, setNetwork] = useState(true)
   const [bluetooth, setBluetooth] = useState(true)
+  const [airplane, setAirplane] = useState(false)
   const [loggingEnabled, setLoggingEnabled] = useState(false)
 
   useEffect(() => {
@@ -188,37 +878,58 @@ function SystemControls() {
         const st = await api.controlCenterState()
         if (!mounted || !st) return
         if (typeof st.volume === 'number') setVolume(st.volume)
-        if (typeof st.brightness === 'number') setBrightness(st.brightness)
+        if (typeof st.muted === 'boolean') setMuted(st.muted)
+        if (typeof st.brightness === 'number') setBrightness(st.brightness); else setHasBacklight(false)
         if (typeof st.network === 'boolean') setNetwork(st.network)
         if (typeof st.bluetooth === 'boolean') setBluetooth(st.bluetooth)
       } catch {}
+      const kbd = (await api.backlightList()).find(d=>d.kind==='keyboard')
+      if (mounted && kbd) setKbdBacklight(kbd.percent)
+      const ns = await api.networkStatus()
+      if (mounted && ns) setAirplane(ns.airplane_mode)
     })()
-    return () => { mounted = false }
+    // ハードウェアキーや他アプリでの音量変更に追従
+    const un = listen<{ volume: number; muted: boolean }>('sis:volume', (e)=>{ setVolume(e.payload.volume); setMuted(e.payload.muted) })
+    const unBri = listen<BacklightDevice>('sis:brightness', (e)=>{ if (e.payload.kind === 'panel') setBrightness(e.payload.percent); else setKbdBacklight(e.payload.percent) })
+    return () => { mounted = false; un.then(f=>f()); unBri.then(f=>f()) }
   }, [])
 
   const setVol = async (v:number)=>{ setVolume(v); const r = await api.setVolume(v); if(!r.ok) alert('音量の変更に失敗') }
+  const toggleMute = async ()=>{ setMuted(!muted); const r = await api.audioSetMute('sink', '@DEFAULT_SINK@'); if(!r.ok) alert('ミュートの切替に失敗') }
   const setBrt = async (v:number)=>{ setBrightness(v); const r = await api.setBrightness(v); if(!r.ok) alert('輝度の変更に失敗') }
+  const cycleKbd = async ()=>{ const r = await api.keyboardBacklightSet(); if(r.ok && typeof r.percent === 'number') setKbdBacklight(r.percent) }
   const toggleNet = async ()=>{ const n=!network; setNetwork(n); const r=await api.networkSet(n); if(!r.ok) alert('ネットワーク切替に失敗') }
   const toggleBt = async ()=>{ const n=!bluetooth; setBluetooth(n); const r=await api.bluetoothSet(n); if(!r.ok) alert('Bluetooth切替に失敗') }
+  const toggleAirplane = async ()=>{ const n=!airplane; setAirplane(n); const r=await api.networkSetAirplaneMode(n); if(!r.ok) alert('機内モードの切替に失敗'); else setBluetooth(!n) }
 
   return (
     <div className="sidebar-content">
       <div className="content-header"><h3>システムコントロール</h3></div>
+      <NowPlaying />
       <div className="control-grid">
         <div className="control-item">
           <label className="setting-label">音量</label>
           <div className="slider-container">
-            <input type="range" min={0} max={100} value={volume} onChange={e=>setVol(parseInt(e.target.value))} className="game-slider" />
+            <button className="qa-btn" onClick={toggleMute} title="ミュート">{muted?'🔇':'🔊'}</button>
+            <input type="range" min={0} max={100} value={Math.min(volume,100)} onChange={e=>setVol(parseInt(e.target.value))} className="game-slider" />
             <span className="slider-value">{volume}%</span>
           </div>
         </div>
-        <div className="control-item">
-          <label className="setting-label">輝度</label>
-          <div className="slider-container">
-            <input type="range" min={0} max={100} value={brightness} onChange={e=>setBrt(parseInt(e.target.value))} className="game-slider" />
-            <span className="slider-value">{brightness}%</span>
+        {hasBacklight && (
+          <div className="control-item">
+            <label className="setting-label">輝度</label>
+            <div className="slider-container">
+              <input type="range" min={0} max={100} value={brightness} onChange={e=>setBrt(parseInt(e.target.value))} className="game-slider" />
+              <span className="slider-value">{brightness}%</span>
+            </div>
           </div>
-        </div>
+        )}
+        {kbdBacklight !== null && (
+          <div className="control-item">
+            <label className="setting-label">キーボードライト</label>
+            <button className={`game-btn toggle ${kbdBacklight>0?'active':''}`} onClick={cycleKbd}>{kbdBacklight>0?`${kbdBacklight}%`:'オフ'}</button>
+          </div>
+        )}
         <div className="control-item">
           <label className="setting-label">ネットワーク</label>
           <button className={`game-btn toggle ${network?'active':''}`} onClick={toggleNet}>{network?'オン':'オフ'}</button>
@@ -227,12 +938,18 @@ function SystemControls() {
           <label className="setting-label">Bluetooth</label>
           <button className={`game-btn toggle ${bluetooth?'active':''}`} onClick={toggleBt}>{bluetooth?'オン':'オフ'}</button>
         </div>
+        <div className="control-item">
+          <label className="setting-label">機内モード</label>
+          <button className={`game-btn toggle ${airplane?'active':''}`} onClick={toggleAirplane}>{airplane?'オン':'オフ'}</button>
+        </div>
       </div>
-      <div className="power-controls">
-        <button className="game-btn danger small" onClick={async()=>{ if(!confirm('ログアウトします。よろしいですか？')) return; await api.powerAction('logout') }}>ログアウト</button>
-        <button className="game-btn danger small" onClick={async()=>{ if(!confirm('再起動します。よろしいですか？')) return; await api.powerAction('reboot') }}>再起動</button>
-        <button className="game-btn danger small" onClick={async()=>{ if(!confirm('電源を切ります。よろしいですか？')) return; await api.powerAction('shutdown') }}>シャットダウン</button>
-      </div>
+      <NightLightControls />
+      <InputSettings />
+      <DateTimeSettings />
+      <AudioMixer />
+      {network && !airplane && <WifiPicker />}
+      {bluetooth && !airplane && <BluetoothDevices />}
+      <PowerControls />
       <div style={{ marginTop: 12 }}>
         <label className="setting-label">バックエンドログ</label>
         <div style={{ display:'flex', alignItems:'center', gap:8 }}>
@@ -253,7 +970,438 @@ function SystemControls() {
       <div className="quick-actions" style={{ marginTop: 12 }}>
         <button className="qa-btn" onClick={() => api.openPath('~')}>ホームを開く</button>
         <button className="qa-btn" onClick={()=>api.launchApp('gnome-terminal')}>ターミナル</button>
-        <button className="qa-btn" onClick={async()=>{ const btn = document.activeElement as HTMLButtonElement | null; if(btn){ btn.disabled=true; const t=btn.innerText; btn.innerText='10秒後に撮影…'; setTimeout(async()=>{ await api.takeScreenshot(); if(btn){ btn.disabled=false; btn.innerText=t } }, 10000) } }}>スクリーンショット(10秒後)</button>
+        <button className="qa-btn" onClick={()=>api.screenshotCapture('region')}>範囲を撮影</button>
+        <button className="qa-btn" onClick={()=>api.screenshotCapture('window')}>ウィンドウを撮影</button>
+        <button className="qa-btn" onClick={()=>api.screenshotCapture('full', { delaySecs: 10 })}>スクリーンショット(10秒後)</button>
+      </div>
+      <ScreenRecorder />
+    </div>
+  )
+}
+
+// 画面録画（~/Videos に保存）。経過時間は TopBar のインジケータに出る
+const NIGHT_SCHEDULE_LABEL: Record<NightLightSchedule, string> = { always: '常にオン', fixed: '時刻を指定', sunset: '日の入りから日の出まで' }
+
+function NightLightControls() {
+  const [cfg, setCfg] = useState<NightLightConfig | null>(null)
+  const [state, setState] = useState<NightLightState | null>(null)
+  const timer = useRef<any>(null)
+
+  useEffect(()=>{
+    api.nightLightGetConfig().then(setCfg)
+    api.nightLightState().then(setState)
+    const un = listen<NightLightState>('sis:nightlight-state', (e)=>{ setState(e.payload) })
+    return ()=>{ un.then(f=>f()) }
+  },[])
+
+  // スライダーは操作が落ち着いてから保存する
+  const save = (c: NightLightConfig, debounce = false)=>{
+    setCfg(c)
+    clearTimeout(timer.current)
+    const run = async ()=>{ const r = await api.nightLightSetConfig(c); if (r.ok && r.state) setState(r.state); else if (!r.ok) alert(`夜間モードを設定できませんでした: ${r.message || ''}`) }
+    if (debounce) timer.current = setTimeout(run, 200); else run()
+  }
+
+  if (!cfg) return null
+  const noBackend = state?.error === 'no-backend'
+
+  return (
+    <div className="control-grid" style={{ marginTop: 12 }}>
+      <div className="control-item">
+        <label className="setting-label">夜間モード{state?.active ? `（${state.current_temperature}K）` : ''}</label>
+        <button className={`game-btn toggle ${cfg.enabled?'active':''}`} disabled={noBackend} onClick={()=>save({ ...cfg, enabled: !cfg.enabled })}>{cfg.enabled?'オン':'オフ'}</button>
+      </div>
+      {noBackend && <div className="notification-message">この環境では画面の色温度を変更できません</div>}
+      {cfg.enabled && (
+        <>
+          <div className="control-item">
+            <label className="setting-label">色温度</label>
+            <div className="slider-container">
+              <input type="range" min={1900} max={6500} step={100} value={cfg.temperature} onChange={e=>save({ ...cfg, temperature: parseInt(e.target.value) }, true)} className="game-slider" />
+              <span className="slider-value">{cfg.temperature}K</span>
+            </div>
+          </div>
+          <div className="control-item">
+            <label className="setting-label">強さ</label>
+            <div className="slider-container">
+              <input type="range" min={0} max={100} value={cfg.intensity} onChange={e=>save({ ...cfg, intensity: parseInt(e.target.value) }, true)} className="game-slider" />
+              <span className="slider-value">{cfg.intensity}%</span>
+            </div>
+          </div>
+          <div className="control-item">
+            <label className="setting-label">スケジュール</label>
+            <select className="game-input" value={cfg.schedule} onChange={e=>save({ ...cfg, schedule: e.target.value as NightLightSchedule })}>
+              {(Object.keys(NIGHT_SCHEDULE_LABEL) as NightLightSchedule[]).map(k => <option key={k} value={k}>{NIGHT_SCHEDULE_LABEL[k]}</option>)}
+            </select>
+          </div>
+          {cfg.schedule === 'fixed' && (
+            <div className="control-item">
+              <label className="setting-label">時間帯</label>
+              <div style={{ display:'flex', alignItems:'center', gap:6 }}>
+                <input className="game-input" type="time" value={cfg.from} onChange={e=>e.target.value && save({ ...cfg, from: e.target.value })} />
+                〜
+                <input className="game-input" type="time" value={cfg.to} onChange={e=>e.target.value && save({ ...cfg, to: e.target.value })} />
+              </div>
+            </div>
+          )}
+          {cfg.schedule === 'sunset' && (
+            <div className="control-item">
+              <label className="setting-label">位置（緯度・経度）</label>
+              <div style={{ display:'flex', alignItems:'center', gap:6 }}>
+                <input className="game-input" type="number" step="0.01" placeholder="自動" value={cfg.latitude ?? ''} onChange={e=>save({ ...cfg, latitude: e.target.value === '' ? null : Number(e.target.value) }, true)} />
+                <input className="game-input" type="number" step="0.01" placeholder="自動" value={cfg.longitude ?? ''} onChange={e=>save({ ...cfg, longitude: e.target.value === '' ? null : Number(e.target.value) }, true)} />
+              </div>
+              <div className="notification-message">
+                {state?.sunset && state?.sunrise
+                  ? `今日は ${state.sunset} 〜 翌 ${state.sunrise}${state.location_source === 'timezone' ? '（タイムゾーンから推定）' : ''}`
+                  : '位置がわかりません。緯度・経度を入力してください'}
+              </div>
+            </div>
+          )}
+        </>
+      )}
+    </div>
+  )
+}
+
+// よく使う XKB オプション（名前は xkeyboard-config のもの）
+const XKB_OPTION_LABEL: Record<string, string> = {
+  'ctrl:nocaps': 'Caps Lock を Ctrl にする',
+  'ctrl:swapcaps': 'Caps Lock と左 Ctrl を入れ替える',
+  'japan:hztg_escape': '半角/全角を Esc にする',
+  'compose:ralt': '右 Alt を Compose キーにする',
+}
+
+function InputSettings() {
+  const [state, setState] = useState<InputState | null>(null)
+  const [ime, setIme] = useState<ImeState | null>(null)
+  const [layouts, setLayouts] = useState<[string, string][]>([])
+  const timer = useRef<any>(null)
+
+  useEffect(()=>{
+    api.inputState().then(setState)
+    api.imeState().then(setIme)
+    api.inputListLayouts().then(setLayouts)
+    const un1 = listen<InputState>('sis:input-state', (e)=>{ setState(e.payload) })
+    const un2 = listen<ImeState>('sis:ime-state', (e)=>{ setIme(e.payload) })
+    return ()=>{ un1.then(f=>f()); un2.then(f=>f()) }
+  },[])
+
+  const save = (c: InputConfig, debounce = false)=>{
+    if (!state) return
+    setState({ ...state, config: c })
+    clearTimeout(timer.current)
+    const run = async ()=>{ const r = await api.inputSetConfig(c); if (r.ok && r.state) setState(r.state); else if (!r.ok) alert(`入力設定を変更できませんでした: ${r.message || ''}`) }
+    if (debounce) timer.current = setTimeout(run, 250); else run()
+  }
+
+  if (!state) return null
+  const cfg = state.config
+  const hasTouchpad = state.backend !== 'x11' || state.devices.some(d => d.kind === 'touchpad')
+  const setLayout = (i: number, layout: string)=>save({ ...cfg, layouts: cfg.layouts.map((l, k) => k === i ? { layout, variant: null } : l) })
+
+  return (
+    <div className="control-grid" style={{ marginTop: 12 }}>
+      {ime?.framework && (
+        <div className="control-item">
+          <label className="setting-label">入力方式（{ime.framework}）</label>
+          <select className="game-input" value={ime.engine ?? ''} onChange={async e=>{ const s = await api.imeSetEngine(e.target.value); if (s) setIme(s) }}>
+            {ime.engines.map(en => <option key={en.name} value={en.name}>{en.label}{en.japanese ? '（日本語）' : ''}</option>)}
+          </select>
+        </div>
+      )}
+      <div className="control-item">
+        <label className="setting-label">キーボード配列</label>
+        <div style={{ display:'flex', flexDirection:'column', gap:4 }}>
+          {cfg.layouts.map((l, i) => (
+            <div key={i} style={{ display:'flex', gap:4 }}>
+              <select className="game-input" value={l.layout} onChange={e=>setLayout(i, e.target.value)}>
+                {!layouts.some(([n]) => n === l.layout) && <option value={l.layout}>{l.layout}</option>}
+                {layouts.map(([n, d]) => <option key={n} value={n}>{d}（{n}）</option>)}
+              </select>
+              {cfg.layouts.length > 1 && <button className="qa-btn" onClick={()=>save({ ...cfg, layouts: cfg.layouts.filter((_, k) => k !== i) })}>削除</button>}
+            </div>
+          ))}
+          <button className="qa-btn" onClick={()=>save({ ...cfg, layouts: [...cfg.layouts, { layout: 'us', variant: null }] })}>配列を追加</button>
+        </div>
+      </div>
+      {Object.entries(XKB_OPTION_LABEL).map(([opt, label]) => (
+        <div className="control-item" key={opt}>
+          <label className="setting-label">{label}</label>
+          <button className={`game-btn toggle ${cfg.options.includes(opt)?'active':''}`} onClick={async()=>{
+            const r = await api.inputSetXkbOption(opt, !cfg.options.includes(opt))
+            if (!r.ok) alert(`設定できませんでした: ${r.message || ''}`)
+            else api.inputState().then(setState)
+          }}>{cfg.options.includes(opt)?'オン':'オフ'}</button>
+        </div>
+      ))}
+      <div className="control-item">
+        <label className="setting-label">キーリピート</label>
+        <button className={`game-btn toggle ${cfg.repeat_enabled?'active':''}`} onClick={()=>save({ ...cfg, repeat_enabled: !cfg.repeat_enabled })}>{cfg.repeat_enabled?'オン':'オフ'}</button>
+      </div>
+      {cfg.repeat_enabled && (
+        <>
+          <div className="control-item">
+            <label className="setting-label">リピート開始までの時間</label>
+            <div className="slider-container">
+              <input type="range" min={150} max={1500} step={50} value={cfg.repeat_delay_ms} onChange={e=>save({ ...cfg, repeat_delay_ms: parseInt(e.target.value) }, true)} className="game-slider" />
+              <span className="slider-value">{cfg.repeat_delay_ms}ms</span>
+            </div>
+          </div>
+          <div className="control-item">
+            <label className="setting-label">リピートの速さ</label>
+            <div className="slider-container">
+              <input type="range" min={5} max={60} value={cfg.repeat_rate} onChange={e=>save({ ...cfg, repeat_rate: parseInt(e.target.value) }, true)} className="game-slider" />
+              <span className="slider-value">{cfg.repeat_rate}回/秒</span>
+            </div>
+          </div>
+        </>
+      )}
+      <div className="control-item">
+        <label className="setting-label">マウスの速さ</label>
+        <div className="slider-container">
+          <input type="range" min={-100} max={100} value={Math.round(cfg.mouse_speed * 100)} onChange={e=>save({ ...cfg, mouse_speed: parseInt(e.target.value) / 100 }, true)} className="game-slider" />
+          <span className="slider-value">{Math.round(cfg.mouse_speed * 100)}</span>
+        </div>
+      </div>
+      <div className="control-item">
+        <label className="setting-label">マウスのナチュラルスクロール</label>
+        <button className={`game-btn toggle ${cfg.mouse_natural_scroll?'active':''}`} onClick={()=>save({ ...cfg, mouse_natural_scroll: !cfg.mouse_natural_scroll })}>{cfg.mouse_natural_scroll?'オン':'オフ'}</button>
+      </div>
+      {hasTouchpad && (
+        <>
+          <div className="control-item">
+            <label className="setting-label">タッチパッドの速さ</label>
+            <div className="slider-container">
+              <input type="range" min={-100} max={100} value={Math.round(cfg.touchpad_speed * 100)} onChange={e=>save({ ...cfg, touchpad_speed: parseInt(e.target.value) / 100 }, true)} className="game-slider" />
+              <span className="slider-value">{Math.round(cfg.touchpad_speed * 100)}</span>
+            </div>
+          </div>
+          <div className="control-item">
+            <label className="setting-label">タッチパッドのナチュラルスクロール</label>
+            <button className={`game-btn toggle ${cfg.touchpad_natural_scroll?'active':''}`} onClick={()=>save({ ...cfg, touchpad_natural_scroll: !cfg.touchpad_natural_scroll })}>{cfg.touchpad_natural_scroll?'オン':'オフ'}</button>
+          </div>
+          <div className="control-item">
+            <label className="setting-label">タップでクリック</label>
+            <button className={`game-btn toggle ${cfg.tap_to_click?'active':''}`} onClick={()=>save({ ...cfg, tap_to_click: !cfg.tap_to_click })}>{cfg.tap_to_click?'オン':'オフ'}</button>
+          </div>
+        </>
+      )}
+      {state.exam_mode && <div className="notification-message">試験モード中はウィンドウ切替（Alt+Tab）と画面切替キーが無効です</div>}
+    </div>
+  )
+}
+
+function formatOffset(min: number) {
+  const sign = min < 0 ? '-' : '+'
+  const a = Math.abs(min)
+  return `UTC${sign}${String(Math.floor(a / 60)).padStart(2, '0')}:${String(a % 60).padStart(2, '0')}`
+}
+
+function DateTimeSettings() {
+  const [state, setState] = useState<TimeState | null>(null)
+  const [query, setQuery] = useState('')
+  const [zones, setZones] = useState<TimezoneInfo[]>([])
+  const [manual, setManual] = useState('')
+  const [busy, setBusy] = useState(false)
+
+  useEffect(()=>{
+    api.timeState().then(setState)
+    const un = listen<TimeState>('sis:time-state', (e)=>{ setState(e.payload) })
+    return ()=>{ un.then(f=>f()) }
+  },[])
+
+  useEffect(()=>{
+    if (!query.trim()) { setZones([]); return }
+    const t = setTimeout(()=>{ api.timeListTimezones(query).then(z => setZones(z.slice(0, 30))) }, 200)
+    return ()=>clearTimeout(t)
+  },[query])
+
+  const run = async (p: Promise<{ ok: boolean; state?: TimeState; message?: string }>)=>{
+    setBusy(true)
+    const r = await p
+    setBusy(false)
+    if (r.ok && r.state) setState(r.state)
+    else if (!r.ok) alert(`変更できませんでした: ${r.message || ''}`)
+    return r.ok
+  }
+
+  if (!state) return null
+  return (
+    <div className="control-grid" style={{ marginTop: 12 }}>
+      <div className="control-item">
+        <label className="setting-label">タイムゾーン</label>
+        <div style={{ display:'flex', flexDirection:'column', gap:4 }}>
+          <div className="notification-message">{state.timezone || '不明'}（{formatOffset(state.utc_offset_min)}）</div>
+          <input className="game-input" placeholder="都市名・国コードで検索（例: Tokyo, JP）" value={query} onChange={e=>setQuery(e.target.value)} disabled={!state.available} />
+          {zones.map(z => (
+            <button key={z.name} className="qa-btn" disabled={busy || z.name === state.timezone} onClick={async()=>{ if (await run(api.timeSetTimezone(z.name))) setQuery('') }}>
+              {z.name}{z.country ? `（${z.country}${z.comment ? ` ${z.comment}` : ''}）` : ''}
+            </button>
+          ))}
+        </div>
+      </div>
+      <div className="control-item">
+        <label className="setting-label">時刻を自動で合わせる（NTP）{state.ntp ? (state.ntp_synchronized ? ' ・同期済み' : ' ・同期待ち') : ''}</label>
+        <button className={`game-btn toggle ${state.ntp?'active':''}`} disabled={busy || !state.available || (!state.can_ntp && !state.ntp) || (state.ntp && state.exam_mode)} onClick={()=>run(api.timeSetNtp(!state.ntp))}>{state.ntp?'オン':'オフ'}</button>
+      </div>
+      {!state.ntp && state.available && !state.exam_mode && (
+        <div className="control-item">
+          <label className="setting-label">日時を手動で設定</label>
+          <div style={{ display:'flex', gap:4 }}>
+            <input type="datetime-local" className="game-input" value={manual} onChange={e=>setManual(e.target.value)} />
+            <button className="qa-btn" disabled={busy || !manual} onClick={async()=>{ if (await run(api.timeSetTime(manual))) setManual('') }}>設定</button>
+          </div>
+        </div>
+      )}
+      <div className="control-item">
+        <label className="setting-label">24 時間表示</label>
+        <button className={`game-btn toggle ${!state.hour12?'active':''}`} onClick={async()=>{ const s = await api.timeSetHour12(!state.hour12); if (s) setState(s) }}>{state.hour12?'オフ':'オン'}</button>
+      </div>
+      {!state.available && <div className="notification-message">時刻設定サービス（timedated）を使えないため、表示のみです</div>}
+    </div>
+  )
+}
+
+function ScreenRecorder() {
+  const [status, setStatus] = useState<RecorderStatus | null>(null)
+  const [mic, setMic] = useState(false)
+  const [error, setError] = useState<string | null>(null)
+
+  useEffect(() => {
+    api.recorderStatus().then(setStatus)
+    const un = listen<RecorderStatus>('sis:recording-state', (e) => { setStatus(e.payload); setError(e.payload.error || null) })
+    return () => { un.then(f => f()) }
+  }, [])
+
+  const start = async (mode: ScreenshotMode) => {
+    const r = await api.recorderStart(mode, { mic })
+    setError(r.ok ? null : (r.message || 'failed'))
+  }
+  const idle = !status || status.state === 'idle'
+
+  return (
+    <div className="quick-actions" style={{ marginTop: 8 }}>
+      {idle ? (
+        <>
+          <button className="qa-btn" onClick={() => start('full')}>画面を録画</button>
+          <button className="qa-btn" onClick={() => start('window')}>ウィンドウを録画</button>
+          <button className="qa-btn" onClick={() => start('region')}>範囲を録画</button>
+          <button className={`game-btn toggle ${mic ? 'active' : ''}`} onClick={() => setMic(!mic)}>マイク {mic ? 'ON' : 'OFF'}</button>
+        </>
+      ) : (
+        <button className="qa-btn" disabled={status?.state !== 'recording'} onClick={() => api.recorderStop()}>
+          {status?.state === 'recording' ? '録画を停止' : status?.state === 'selecting' ? '範囲を選択中…' : status?.state === 'stopping' ? '保存中…' : '準備中…'}
+        </button>
+      )}
+      {error && <span style={{ fontSize: 12, color: '#ff8080' }}>録画できませんでした: {error}</span>}
+    </div>
+  )
+}
+
+const allowed = (c?: Capability) => c === 'yes' || c === 'challenge'
+const SESSION_LABEL: Record<string, string> = { logout: 'ログアウト', reboot: '再起動', poweroff: 'シャットダウン', shutdown: 'シャットダウン', suspend: 'スリープ', hibernate: '休止' }
+
+// 電源操作（logind）。ログアウト等はアプリに閉じる要求を出し、閉じないアプリがあれば確認する
+function PowerControls() {
+  const [caps, setCaps] = useState<PowerCapabilities | null>(null)
+  const [state, setState] = useState<SessionState | null>(null)
+  const [scheduleAt, setScheduleAt] = useState('')
+  const [lockCfg, setLockCfg] = useState<LockConfig | null>(null)
+
+  useEffect(()=>{
+    api.lockGetConfig().then(setLockCfg)
+    api.sessionCapabilities().then(setCaps)
+    api.sessionState().then(setState)
+    const un1 = listen<SessionState>('sis:session-state', (e)=>{ setState(e.payload) })
+    const un2 = listen('sis:shutdown-scheduled', ()=>{ api.sessionCapabilities().then(setCaps) })
+    return ()=>{ un1.then(f=>f()); un2.then(f=>f()) }
+  },[])
+
+  const run = async (a: SessionAction)=>{
+    if (a !== 'suspend' && !confirm(`${SESSION_LABEL[a]}します。よろしいですか？`)) return
+    const r = await api.sessionAction(a)
+    if (!r.ok) alert(`${SESSION_LABEL[a]}できませんでした: ${r.message || ''}`)
+  }
+
+  const schedule = async ()=>{
+    if (!scheduleAt) return
+    const [h, m] = scheduleAt.split(':').map(Number)
+    const at = new Date(); at.setHours(h, m, 0, 0)
+    if (at.getTime() <= Date.now()) at.setDate(at.getDate() + 1)
+    const r = await api.sessionScheduleShutdown('poweroff', at.getTime())
+    if (!r.ok) alert(`予約できませんでした: ${r.message || ''}`)
+    else api.sessionCapabilities().then(setCaps)
+  }
+
+  const saveLockCfg = async (c: LockConfig)=>{
+    setLockCfg(c)
+    const r = await api.lockSetConfig(c)
+    if (!r.ok) alert(`保存できませんでした: ${r.message || ''}`)
+  }
+
+  const busy = state && state.phase !== 'idle'
+
+  return (
+    <div style={{ marginTop: 12 }}>
+      {busy && state && (
+        <div className="notification-item" style={{ marginBottom: 8 }}>
+          <div className="notification-content">
+            {state.phase === 'blocked' ? (
+              <>
+                <div className="notification-title">{SESSION_LABEL[state.action || ''] || state.action}を妨げているアプリがあります</div>
+                {state.apps.map(a => <div key={a.window_id} className="notification-message">・{a.title || a.window_id}</div>)}
+                {state.inhibitors.map((i, k) => <div key={k} className="notification-message">・{i.who}: {i.why}</div>)}
+                {state.apps.length === 0 && state.inhibitors.length === 0 && <div className="notification-message">開いているアプリを確認できませんでした。続行すると保存していない作業が失われることがあります</div>}
+                <div style={{ display:'flex', gap:6, marginTop:6 }}>
+                  <button className="game-btn danger small" onClick={()=>api.sessionAction((state.action === 'poweroff' ? 'shutdown' : state.action) as SessionAction, true)}>強制的に続行</button>
+                  <button className="qa-btn" onClick={()=>api.sessionCancel()}>キャンセル</button>
+                </div>
+              </>
+            ) : (
+              <>
+                <div className="notification-title">{state.phase === 'closing' ? 'アプリを閉じています…' : state.phase === 'terminating' ? '応答しないアプリを終了しています…' : `${SESSION_LABEL[state.action || ''] || ''}しています…`}</div>
+                {state.phase === 'closing' && <button className="qa-btn" onClick={()=>api.sessionCancel()}>キャンセル</button>}
+              </>
+            )}
+          </div>
+        </div>
+      )}
+      <div className="power-controls">
+        <button className="game-btn secondary small" onClick={()=>api.lockScreen()}>ロック</button>
+        {allowed(caps?.suspend) && <button className="game-btn secondary small" disabled={!!busy} onClick={()=>run('suspend')}>スリープ</button>}
+        {allowed(caps?.hibernate) && <button className="game-btn secondary small" disabled={!!busy} onClick={()=>run('hibernate')}>休止</button>}
+        <button className="game-btn danger small" disabled={!!busy} onClick={()=>run('logout')}>ログアウト</button>
+        {(!caps || allowed(caps.reboot)) && <button className="game-btn danger small" disabled={!!busy} onClick={()=>run('reboot')}>再起動</button>}
+        {(!caps || allowed(caps.power_off)) && <button className="game-btn danger small" disabled={!!busy} onClick={()=>run('shutdown')}>シャットダウン</button>}
+      </div>
+      {lockCfg && (
+        <div className="control-item" style={{ marginTop: 8 }}>
+          <label className="setting-label">自動ロック</label>
+          <div style={{ display:'flex', alignItems:'center', gap:8 }}>
+            <select className="game-input" value={lockCfg.idle_minutes} onChange={(e)=>saveLockCfg({ ...lockCfg, idle_minutes: Number(e.target.value) })}>
+              {[0, 1, 3, 5, 10, 15, 30].map(m => <option key={m} value={m}>{m === 0 ? 'しない' : `${m}分操作がないとき`}</option>)}
+            </select>
+            <label style={{ display:'flex', alignItems:'center', gap:4 }}>
+              <input type="checkbox" checked={lockCfg.lock_on_suspend} onChange={(e)=>saveLockCfg({ ...lockCfg, lock_on_suspend: e.target.checked })} />
+              スリープ時
+            </label>
+          </div>
+        </div>
+      )}
+      <div className="control-item" style={{ marginTop: 8 }}>
+        <label className="setting-label">シャットダウン予約</label>
+        {caps?.scheduled ? (
+          <div style={{ display:'flex', alignItems:'center', gap:8 }}>
+            <span>{new Date(caps.scheduled.at_ms).toLocaleString('ja-JP')} に{caps.scheduled.kind === 'reboot' ? '再起動' : 'シャットダウン'}</span>
+            <button className="qa-btn" onClick={async()=>{ await api.sessionCancelScheduledShutdown(); api.sessionCapabilities().then(setCaps) }}>取り消す</button>
+          </div>
+        ) : (
+          <div style={{ display:'flex', alignItems:'center', gap:8 }}>
+            <input className="game-input" type="time" value={scheduleAt} onChange={(e)=>setScheduleAt(e.target.value)} />
+            <button className="qa-btn" disabled={!scheduleAt} onClick={schedule}>予約</button>
+          </div>
+        )}
       </div>
     </div>
   )
@@ -1,14 +1,27 @@
 import { useState, useEffect } from 'react';
 import './TopBar.css';
-import { api } from '../services/api';
+import { listen } from '@tauri-apps/api/event';
+import { api, type TimeState } from '../services/api';
+import TrayArea from './TrayArea';
+import RecordingIndicator from './RecordingIndicator';
+import ImeIndicator from './ImeIndicator';
+import ScreenTimeIndicator from './ScreenTimeIndicator';
 
 function TopBar() {
   const [systemInfo, setSystemInfo] = useState({ cpuUsage: 0, memUsage: 0, downloadSpeed: 0, uploadSpeed: 0 });
   const [currentTime, setCurrentTime] = useState(new Date());
+  const [timeState, setTimeState] = useState<TimeState | null>(null);
   const [batteryLevel, setBatteryLevel] = useState<number | null>(null);
   const [isOnline, setIsOnline] = useState<boolean | null>(null);
   const [mdm, setMdm] = useState<{ monitoring: { screen: boolean; web_history: boolean; images: boolean; files: boolean } } | null>(null);
 
+  // WebView はタイムゾーンの変更を拾わないことがあるので、表示はシステムのタイムゾーン名で行う
+  useEffect(() => {
+    api.timeState().then(setTimeState);
+    const un = listen<TimeState>('sis:time-state', (e) => { setTimeState(e.payload) });
+    return () => { un.then(f => f()) };
+  }, []);
+
   useEffect(() => {
   const timer = setInterval(() => { 
       setCurrentTime(new Date());
@@ -45,16 +58,23 @@ function TopBar() {
     return () => { clearInterval(timer) };
   }, []);
 
+  const timeZone = (() => {
+    const tz = timeState?.timezone;
+    if (!tz) return undefined;
+    try { new Intl.DateTimeFormat('ja-JP', { timeZone: tz }); return tz } catch { return undefined }
+  })();
   const formattedTime = currentTime.toLocaleTimeString('ja-JP', {
     hour: '2-digit',
     minute: '2-digit',
-    hour12: false,
+    hour12: timeState?.hour12 ?? false,
+    timeZone,
   });
 
   const formattedDate = currentTime.toLocaleDateString('ja-JP', {
     month: 'short',
     day: 'numeric',
     weekday: 'short',
+    timeZone,
   });
 
   const getGaugeColor = (value: number, type: 'cpu' | 'memory' | 'battery') => {
@@ -129,6 +149,10 @@ function TopBar() {
 
       {/* 日時・通知エリア */}
       <div className="datetime-panel">
+        <RecordingIndicator />
+        <ScreenTimeIndicator />
+        <ImeIndicator />
+        <TrayArea />
         <div className="time-display">{formattedTime}</div>
         <div className="date-display">{formattedDate}</div>
         <div className="notification-badge" title="通知">
@@ -0,0 +1,50 @@
+import { useEffect, useState } from 'react'
+import { listen } from '@tauri-apps/api/event'
+import { api, type TrayItem } from '../services/api'
+import './TrayArea.css'
+
+export type TrayMenuRequest = { id: string; x: number; y: number }
+
+// メニューは全画面の Desktop ウィンドウ側（TrayMenuHost）で描画する
+const openMenu = (item: TrayItem, x: number, y: number) => {
+  if (item.menu) api.emitGlobalEvent('sis:tray-open-menu', { id: item.id, x, y } as TrayMenuRequest)
+  else api.trayContextMenu(item.id, x, y)
+}
+
+export default function TrayArea({ size = 22 }: { size?: number }) {
+  const [items, setItems] = useState<TrayItem[]>([])
+
+  useEffect(() => {
+    api.trayItems().then(setItems)
+    const un = listen<TrayItem[]>('sis:tray-changed', (e) => { setItems(e.payload || []) })
+    return () => { un.then(f => f()) }
+  }, [])
+
+  // Passive（待機中）のアイテムは表示しない
+  const visible = items.filter(i => i.status !== 'passive')
+  if (visible.length === 0) return null
+
+  return (
+    <div className="tray-area">
+      {visible.map(item => (
+        <button
+          key={item.id}
+          className={`tray-item ${item.status === 'needsattention' ? 'attention' : ''}`}
+          title={[item.tooltip_title || item.title, item.tooltip_body].filter(Boolean).join('\n')}
+          onClick={async (e) => {
+            const r = await api.trayActivate(item.id, e.screenX, e.screenY)
+            if (r.ok && r.message === 'menu') openMenu(item, e.screenX, e.screenY)
+          }}
+          onAuxClick={(e) => { if (e.button === 1) api.traySecondaryActivate(item.id, e.screenX, e.screenY) }}
+          onContextMenu={(e) => { e.preventDefault(); openMenu(item, e.screenX, e.screenY) }}
+          onWheel={(e) => {
+            const horizontal = Math.abs(e.deltaX) > Math.abs(e.deltaY)
+            api.trayScroll(item.id, horizontal ? e.deltaX : -e.deltaY, horizontal ? 'horizontal' : 'vertical')
+          }}
+        >
+          {item.icon ? <img src={item.icon} alt={item.title} style={{ width: size, height: size }} /> : <span className="tray-fallback">{(item.title || '?').slice(0, 1)}</span>}
+        </button>
+      ))}
+    </div>
+  )
+}
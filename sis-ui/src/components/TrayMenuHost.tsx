@@ -0,0 +1,67 @@
+import { useEffect, useState } from 'react'
+import { listen } from '@tauri-apps/api/event'
+import { api, type TrayMenuItem } from '../services/api'
+import type { TrayMenuRequest } from './TrayArea'
+import './TrayArea.css'
+
+const MENU_WIDTH = 260
+
+function MenuList({ itemId, entries, onDone }: { itemId: string; entries: TrayMenuItem[]; onDone: () => void }) {
+  const [open, setOpen] = useState<number | null>(null)
+  return (
+    <div className="tray-menu">
+      {entries.map(m => m.kind === 'separator' ? <div key={m.id} className="tray-menu-sep" /> : (
+        <div
+          key={m.id}
+          className={`tray-menu-item ${m.enabled ? '' : 'disabled'}`}
+          onMouseEnter={() => setOpen(m.children.length > 0 ? m.id : null)}
+          onClick={(e) => {
+            e.stopPropagation()
+            if (!m.enabled || m.children.length > 0) return
+            api.trayMenuEvent(itemId, m.id, 'clicked')
+            onDone()
+          }}
+        >
+          <span className="tray-menu-check">{m.toggle_type && m.toggle_state === 1 ? (m.toggle_type === 'radio' ? '●' : '✓') : ''}</span>
+          {m.icon && <img src={m.icon} alt="" className="tray-menu-icon" />}
+          <span className="tray-menu-label">{m.label}</span>
+          {m.children.length > 0 && <span className="tray-menu-arrow">›</span>}
+          {open === m.id && m.children.length > 0 && (
+            <div className="tray-submenu"><MenuList itemId={itemId} entries={m.children} onDone={onDone} /></div>
+          )}
+        </div>
+      ))}
+    </div>
+  )
+}
+
+// Desktop ウィンドウ上にトレイのメニューを表示する
+export default function TrayMenuHost() {
+  const [req, setReq] = useState<TrayMenuRequest | null>(null)
+  const [menu, setMenu] = useState<TrayMenuItem | null>(null)
+
+  useEffect(() => {
+    const un = listen<TrayMenuRequest>('sis:tray-open-menu', async (e) => {
+      const m = await api.trayMenu(e.payload.id)
+      if (!m) return
+      api.trayMenuEvent(e.payload.id, 0, 'opened')
+      setReq(e.payload); setMenu(m)
+    })
+    return () => { un.then(f => f()) }
+  }, [])
+
+  if (!req || !menu) return null
+  const close = () => { api.trayMenuEvent(req.id, 0, 'closed'); setReq(null); setMenu(null) }
+  // 画面端からはみ出さないよう配置（Dock から開いた場合は上方向へ）
+  const left = Math.min(req.x, window.innerWidth - MENU_WIDTH - 8)
+  const fromBottom = req.y > window.innerHeight / 2
+  const pos = fromBottom ? { left, bottom: window.innerHeight - req.y + 8 } : { left, top: req.y + 8 }
+
+  return (
+    <div style={{ position: 'fixed', inset: 0, zIndex: 9999 }} onClick={close} onContextMenu={(e) => { e.preventDefault(); close() }}>
+      <div style={{ position: 'absolute', width: MENU_WIDTH, ...pos }} onClick={(e) => e.stopPropagation()}>
+        <MenuList itemId={req.id} entries={menu.children} onDone={close} />
+      </div>
+    </div>
+  )
+}
@@ -23,6 +23,10 @@ import { api } from './services/api'
 import { applyAllToDom, ensureSystemThemeWatcher } from './services/domApply'
 import HaloHud from './components/HaloHud'
 import CircularMenu from './components/CircularMenu'
+import NotificationToasts from './components/NotificationToasts'
+import TrayMenuHost from './components/TrayMenuHost'
+import CaptureOverlay from './components/CaptureOverlay'
+import LockScreen from './components/LockScreen'
 
 function useWindowLabel() {
   const [label, setLabel] = useState<string>('');
@@ -215,6 +219,7 @@ function DesktopRoot() {
       <HomeScreen />
       <MiniControlCenter open={ccOpen} onClose={()=>setCcOpen(false)} />
       <CommandPalette isVisible={isMenuVisible} onClose={() => setIsMenuVisible(false)} />
+      <TrayMenuHost />
       <SimpleTerminal open={termOpen} initialCmd={termCmd} initialAutoRun={termAutoRun} onClose={()=>{ setTermOpen(false); setTermAutoRun(false) }} />
       {/* Halo HUD + 円形メニュー: 体験の顔 */}
       <HaloHud visible={haloVisible} onClose={()=>setHaloVisible(false)} onAction={(id)=>{
@@ -525,6 +530,44 @@ function SidebarRoot() {
   );
 }
 
+function NotificationsRoot() {
+  useEffect(() => {
+    ensureSystemThemeWatcher()
+    try {
+      document.title = 'SIS Notifications'
+      document.documentElement.style.background = 'transparent'
+      document.body.style.background = 'transparent'
+    } catch {}
+  }, []);
+  return <NotificationToasts />;
+}
+function CaptureRoot() {
+  useEffect(() => {
+    try {
+      document.title = 'SIS Capture'
+      document.documentElement.style.background = 'transparent'
+      document.body.style.background = 'transparent'
+    } catch {}
+  }, []);
+  return <CaptureOverlay />;
+}
+
+function LockRoot() {
+  useEffect(() => {
+    try { document.title = 'SIS Lock' } catch {}
+    // ブラウザ既定のショートカット（再読み込み・開発者ツール等）で抜けられないようにする
+    const onKey = (e: KeyboardEvent) => {
+      if (['F4', 'F5', 'F11', 'F12'].includes(e.key)) e.preventDefault()
+      if (e.ctrlKey && ['r', 'w', 'q', 'p', 'f'].includes(e.key.toLowerCase())) e.preventDefault()
+    }
+    const onMenu = (e: MouseEvent) => e.preventDefault()
+    window.addEventListener('keydown', onKey, true)
+    window.addEventListener('contextmenu', onMenu, true)
+    return () => { window.removeEventListener('keydown', onKey, true); window.removeEventListener('contextmenu', onMenu, true) }
+  }, []);
+  return <LockScreen />;
+}
+
 function Root() {
   const label = useWindowLabel();
   if (!label) return null; // 初期化待ち
@@ -555,6 +598,9 @@ function Root() {
   if (label === 'topbar') return <TopBarRoot />;
   if (label === 'dock') return <DockRoot />;
   if (label === 'sidebar') return <SidebarRoot />;
+  if (label === 'notifications') return <NotificationsRoot />;
+  if (label === 'capture') return <CaptureRoot />;
+  if (label === 'lock') return <LockRoot />;
   // 'settings' window was removed
   // 既存の単一ウィンドウモード互換
   return <App />;
@@ -52,8 +52,244 @@ export type SystemInfo = {
   uploadSpeed: number
 }
 
-export type AppInfo = { name: string; exec?: string; icon_data_url?: string }
+export type AppInfo = { name: string; exec?: string; icon_data_url?: string; desktop_id?: string }
 
+export const normalizeExec = (exec?: string): string =>
+  (exec || '').replace(/%[UuFfick]/g, '').split(/\s+/).filter(Boolean).join(' ')
+
+export type FavoriteApp = { id: string; name: string; exec: string; icon_data_url?: string; desktop_id?: string }
+export type DockFolder = { id: string; name: string; apps: FavoriteApp[] }
+export type DockEntry = ({ kind: 'app' } & FavoriteApp) | ({ kind: 'folder' } & DockFolder)
+
+// 一覧のアプリに対応するお気に入りを探す（IDはバックエンドが決めたものを使う）
+export const findFavorite = (favs: FavoriteApp[], a: AppInfo): FavoriteApp | undefined => {
+  const exec = normalizeExec(a.exec)
+  return favs.find(f => (!!a.desktop_id && f.desktop_id === a.desktop_id) || (!!exec && normalizeExec(f.exec) === exec))
+}
+
+export const flattenDock = (entries: DockEntry[]): FavoriteApp[] =>
+  entries.flatMap(e => e.kind === 'folder' ? e.apps : [e])
+
+export type ScreenTimeEntry = {
+  key: string
+  name: string
+  kind: 'app' | 'category'
+  used_secs: number
+  limit_secs?: number | null
+  remaining_secs?: number | null
+  blocked: boolean
+}
+export type ScreenTimeState = { date: string; active?: string | null; active_name?: string | null; active_remaining_secs?: number | null; entries: ScreenTimeEntry[] }
+
+export type MimeHandler = { desktop_id: string; name: string; exec: string; icon_data_url?: string | null; is_default: boolean }
+export type MimeHandlers = { mime: string; default?: MimeHandler | null; handlers: MimeHandler[] }
+
+export type TelemetrySample = {
+  ts: number
+  interval_ms: number
+  cpu_total: number
+  cpu_per_core: number[]
+  cpu_freq_mhz: number[]
+  load: { one: number; five: number; fifteen: number }
+  memory: { total: number; used: number; available: number; swap_total: number; swap_used: number }
+  temperatures: { label: string; celsius: number; critical?: number | null }[]
+  network: { name: string; rx_bps: number; tx_bps: number; rx_total: number; tx_total: number }[]
+  net_rx_bps: number
+  net_tx_bps: number
+  disks: { name: string; mount_point: string; read_bps: number; write_bps: number }[]
+}
+
+export type BatteryStatus = {
+  present: boolean
+  percentage: number
+  state: 'charging' | 'discharging' | 'full' | 'not-charging' | 'unknown'
+  ac_online: boolean
+  time_to_empty_secs?: number | null
+  time_to_full_secs?: number | null
+  health_percent?: number | null
+  energy_full_wh?: number | null
+  energy_full_design_wh?: number | null
+  source: 'upower' | 'sysfs'
+}
+
+export type ProcessInfo = {
+  pid: number
+  ppid?: number | null
+  name: string
+  cmdline: string
+  user?: string | null
+  state: string
+  cpu: number
+  memory: number
+  start_time: number
+  own: boolean
+}
+export type AppGroup = { key: string; name: string; icon_data_url?: string | null; window_ids: string[]; pids: number[]; cpu: number; memory: number }
+export type ProcessSnapshot = { processes: ProcessInfo[]; groups: AppGroup[] }
+export type KillSignal = 'term' | 'kill' | 'stop' | 'cont'
+export type KillConfirmation = { token: string; signal: KillSignal; targets: ProcessInfo[]; expires_in_secs: number }
+
+export type MountedFs = { device: string; mount_point: string; fs_type: string; total: number; available: number; used: number; removable: boolean; read_only: boolean }
+export type RemovableDevice = {
+  id: string
+  device: string
+  label: string
+  fs_type: string
+  size: number
+  bus: 'usb' | 'sdio' | 'loop' | 'other'
+  model: string
+  read_only: boolean
+  mount_points: string[]
+  can_eject: boolean
+  drive_id?: string | null
+}
+export type StoragePolicy = { usb_storage: 'allow' | 'readonly' | 'block'; mount_options?: string | null; usb_logging: boolean }
+export type BusyHolder = { pid: number; name: string; path: string }
+
+export type NetDevice = { path: string; interface: string; kind: 'ethernet' | 'wifi' | 'bluetooth' | 'modem' | 'wireguard' | 'other'; state: string; connection?: string | null }
+export type ActiveConn = { id: string; kind: 'wifi' | 'ethernet' | 'vpn' | 'wireguard' | 'hotspot' | 'other'; state: string }
+export type NetworkStatus = {
+  networking_enabled: boolean
+  wireless_enabled: boolean
+  airplane_mode: boolean
+  connectivity: 'unknown' | 'none' | 'portal' | 'limited' | 'full'
+  primary?: string | null
+  devices: NetDevice[]
+  active: ActiveConn[]
+  vpn_active: boolean
+  hotspot_active: boolean
+}
+export type AccessPoint = { path: string; ssid: string; strength: number; security: 'open' | 'wep' | 'wpa-psk' | 'sae' | 'enterprise'; frequency: number; bssid: string; active: boolean; saved: boolean }
+export type SavedConnection = { path: string; id: string; uuid: string; kind: string; ssid?: string | null; autoconnect: boolean; last_used: number }
+export type EapCredentials = { identity: string; password: string; method?: 'peap' | 'ttls'; phase2?: 'mschapv2' | 'pap'; anonymous_identity?: string; ca_cert?: string; domain_suffix_match?: string }
+
+export type BtAdapter = { path: string; name: string; address: string; powered: boolean; discovering: boolean }
+export type BtDevice = { path: string; address: string; name: string; icon: string; paired: boolean; trusted: boolean; connected: boolean; rssi?: number | null; battery?: number | null }
+export type BluetoothState = { adapter?: BtAdapter | null; devices: BtDevice[] }
+/** ペアリング用エージェントからの要求（sis:bluetooth-passkey）。request がある種類は bluetoothAgentReply で応答する */
+export type BtPasskeyRequest = { kind: 'confirm' | 'request-passkey' | 'request-pin' | 'authorize' | 'display-passkey' | 'display-pin' | 'cancel'; request?: number; device?: string; address?: string; name?: string; icon?: string; passkey?: string | null; entered?: number | null }
+
+export type AudioDevice = { index: number; name: string; description: string; port?: string | null; form_factor?: string | null; bus?: string | null; volume: number; muted: boolean; is_default: boolean }
+export type AudioStream = { index: number; kind: 'playback' | 'record'; app_name: string; media_name: string; icon?: string | null; pid?: number | null; device: number; volume: number; muted: boolean; corked: boolean }
+export type AudioState = { sinks: AudioDevice[]; sources: AudioDevice[]; streams: AudioStream[]; default_sink?: string | null; default_source?: string | null }
+
+export type BacklightDevice = { subsystem: 'backlight' | 'leds'; name: string; kind: 'panel' | 'keyboard'; backlight_type?: 'firmware' | 'platform' | 'raw' | null; brightness: number; max_brightness: number; percent: number }
+
+export type MediaPlayer = {
+  id: string
+  identity: string
+  desktop_entry?: string | null
+  status: 'playing' | 'paused' | 'stopped'
+  title: string
+  artist: string[]
+  album: string
+  art_url?: string | null
+  track_id?: string | null
+  position_ms: number
+  duration_ms?: number | null
+  volume?: number | null
+  shuffle?: boolean | null
+  loop_status?: 'none' | 'track' | 'playlist' | null
+  can_seek: boolean
+  can_go_next: boolean
+  can_go_previous: boolean
+  can_control: boolean
+}
+export type MediaState = { active?: string | null; players: MediaPlayer[] }
+
+export type NotifAction = { key: string; label: string }
+export type SisNotification = {
+  id: number
+  app_name: string
+  icon?: string | null
+  summary: string
+  body: string
+  actions: NotifAction[]
+  urgency: 'low' | 'normal' | 'critical'
+  category?: string | null
+  desktop_entry?: string | null
+  timestamp: number
+  expire_ms?: number | null
+  resident: boolean
+  transient: boolean
+  suppressed: boolean
+  read: boolean
+  source: 'dbus' | 'sis'
+}
+export type DndConfig = { mode: 'off' | 'on' | 'schedule'; start_hhmm: string; end_hhmm: string; weekdays_only: boolean; allow_critical: boolean }
+export type DndStatus = { config: DndConfig; active: boolean }
+
+export type TrayItem = { id: string; item_id: string; category: string; status: 'passive' | 'active' | 'needsattention'; title: string; icon?: string | null; tooltip_title: string; tooltip_body: string; item_is_menu: boolean; menu?: string | null }
+export type TrayMenuItem = { id: number; label: string; kind: 'standard' | 'separator'; enabled: boolean; visible: boolean; icon?: string | null; toggle_type: '' | 'checkmark' | 'radio'; toggle_state: number; children: TrayMenuItem[] }
+
+export type ScreenshotMode = 'full' | 'window' | 'region'
+export type Screenshot = { mode: ScreenshotMode; path?: string | null; width: number; height: number; copied: boolean; thumbnail?: string | null; backend: 'x11' | 'portal' }
+export type ScreenshotRegionRequest = { image: string; width: number; height: number }
+
+export type RecorderState = 'idle' | 'selecting' | 'starting' | 'recording' | 'stopping'
+export type RecorderStatus = { state: RecorderState; mode?: ScreenshotMode | null; path?: string | null; started_at_ms?: number | null; mic: boolean; backend?: 'x11' | 'portal' | null; error?: string | null }
+
+export type ClipEntry = { id: number; kind: 'text' | 'html' | 'image'; selection: 'clipboard' | 'primary'; text: string; html?: string | null; image?: string | null; width?: number | null; height?: number | null; thumbnail?: string | null; bytes: number; hash: string; pinned: boolean; created_ms: number }
+export type ClipboardList = { enabled: boolean; exam_mode: boolean; backend?: 'x11' | 'wayland' | null; entries: ClipEntry[] }
+
+export type Capability = 'yes' | 'no' | 'challenge' | 'na'
+export type ScheduledShutdown = { kind: 'poweroff' | 'reboot' | 'halt'; at_ms: number }
+export type PowerCapabilities = { suspend: Capability; hibernate: Capability; hybrid_sleep: Capability; suspend_then_hibernate: Capability; reboot: Capability; power_off: Capability; scheduled?: ScheduledShutdown | null }
+export type Inhibitor = { id?: number | null; what: string; who: string; why: string; mode: 'block' | 'delay'; uid: number; pid: number }
+export type BlockingApp = { window_id: string; pid?: number | null; title: string }
+export type SessionAction = 'shutdown' | 'reboot' | 'logout' | 'suspend' | 'hibernate'
+export type SessionState = { phase: 'idle' | 'closing' | 'blocked' | 'terminating' | 'executing'; action?: string | null; apps: BlockingApp[]; inhibitors: Inhibitor[] }
+
+export type LockReason = 'user' | 'idle' | 'suspend' | 'logind' | 'screentime' | 'exam'
+export type LockState = { locked: boolean; reason?: LockReason | null; message?: string | null; since_ms?: number | null; user: string; display_name: string; failed_attempts: number; retry_after_ms?: number | null; grabbed: boolean }
+export type LockConfig = { idle_minutes: number; lock_on_suspend: boolean }
+
+export type NightLightSchedule = 'always' | 'fixed' | 'sunset'
+export type NightLightConfig = { enabled: boolean; temperature: number; intensity: number; schedule: NightLightSchedule; from: string; to: string; latitude?: number | null; longitude?: number | null }
+export type NightLightState = { enabled: boolean; active: boolean; schedule: NightLightSchedule; temperature: number; intensity: number; current_temperature: number; backend?: 'randr' | 'gnome' | 'gammastep' | null; sunset?: string | null; sunrise?: string | null; location_source?: 'config' | 'timezone' | null; error?: string | null }
+
+export type XkbLayout = { layout: string; variant?: string | null }
+export type InputConfig = { layouts: XkbLayout[]; model?: string | null; options: string[]; repeat_enabled: boolean; repeat_delay_ms: number; repeat_rate: number; mouse_speed: number; mouse_natural_scroll: boolean; touchpad_speed: number; touchpad_natural_scroll: boolean; tap_to_click: boolean }
+export type InputDevice = { id: number; name: string; kind: 'keyboard' | 'mouse' | 'touchpad'; accel_speed?: number | null; natural_scroll?: boolean | null; tapping?: boolean | null }
+export type InputState = { backend?: 'x11' | 'gnome' | null; config: InputConfig; active_options: string[]; devices: InputDevice[]; exam_mode: boolean }
+export type ImeEngine = { name: string; label: string; language?: string | null; japanese: boolean; current: boolean }
+export type ImeState = { framework?: 'fcitx5' | 'ibus' | null; active: boolean; engine?: string | null; indicator: string; engines: ImeEngine[] }
+export type TimeState = { timezone?: string | null; utc_offset_min: number; now_ms: number; can_ntp: boolean; ntp: boolean; ntp_synchronized: boolean; local_rtc: boolean; hour12: boolean; available: boolean; exam_mode: boolean }
+export type TimezoneInfo = { name: string; country?: string | null; comment?: string | null }
+export type OrganizeCollision = 'rename' | 'skip' | 'replace'
+export type OrganizeRule = { name: string; enabled: boolean; mime: string[]; extensions: string[]; name_regex?: string | null; source_url_regex?: string | null; min_size?: number | null; max_size?: number | null; min_age_days?: number | null; max_age_days?: number | null; target: string; collision?: OrganizeCollision | null }
+export type OrganizerConfig = { rules: OrganizeRule[]; fallback_target?: string | null; collision: OrganizeCollision }
+export type DownloadWatchConfig = { enabled: boolean; quiet_secs: number; notify: boolean }
+export type OrganizePlan = { source: string; rule?: string | null; destination?: string | null; action: 'move' | 'skip' | 'none'; collision?: 'renamed' | 'replaced' | 'skipped' | null; reason?: string | null; cross_device: boolean; done: boolean }
+
+export type PowerAction = 'none' | 'warn' | 'dim' | 'suspend'
+export type PowerPolicy = { low_percent: number; critical_percent: number; low_action: PowerAction; critical_action: PowerAction }
+
+
+/** バックエンドのエラー（Rust の SisError）。message は OS のロケールに合わせて日本語/英語で届く */
+export type SisError = { code: string; message: string; details?: string | null }
+
+function isSisError(e: unknown): e is SisError {
+  return typeof e === 'object' && e !== null && typeof (e as SisError).code === 'string' && typeof (e as SisError).message === 'string'
+}
+
+/** エラーのコード（"not-authorized" など）。分岐に使う */
+export function errorCode(e: unknown): string | undefined {
+  if (isSisError(e)) return e.code
+  if (typeof e === 'string') return e
+  return (e as Error)?.message
+}
+
+/** 利用者に見せる文言 */
+export function errorMessage(e: unknown): string {
+  if (isSisError(e)) return e.message
+  if (typeof e === 'string') return e
+  return (e as Error)?.message || String(e)
+}
+
+function fail(e: unknown) {
+  return { ok: false as const, code: errorCode(e), message: errorMessage(e), details: isSisError(e) ? e.details ?? undefined : undefined }
+}
 
 async function safeInvoke<T = unknown>(cmd: string, payload?: Record<string, unknown>): Promise<T> {
   let inv = await resolveInvoke()
@@ -251,10 +487,246 @@ export const api = {
     }
   },
 
-  async controlCenterState(): Promise<{ volume: number; brightness: number; network: boolean; bluetooth: boolean }>{
+  async controlCenterState(): Promise<{ volume: number; brightness: number | null; network: boolean; bluetooth: boolean; muted?: boolean; battery?: BatteryStatus | null; night_light?: NightLightState }>{
     return await safeInvoke('control_center_state')
   },
 
+  /** バッテリー状態（非搭載機は null）。変化は sis:power、残量警告は sis:power-warning で届く */
+  async batteryStatus(): Promise<BatteryStatus | null> {
+  try { return await safeInvoke<BatteryStatus | null>('battery_status') }
+  catch { return null }
+  },
+
+  async powerGetPolicy(): Promise<PowerPolicy | null> {
+  try { return await safeInvoke<PowerPolicy>('power_get_policy') }
+  catch { return null }
+  },
+
+  async powerSetPolicy(policy: PowerPolicy): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('power_set_policy', { policy }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
+  /** NetworkManager の状態。変化は sis:network-changed、疎通状態の変化は sis:connectivity-changed で届く */
+  async networkStatus(): Promise<NetworkStatus | null> {
+  try { return await safeInvoke<NetworkStatus>('network_status') }
+  catch { return null }
+  },
+
+  async wifiListAccessPoints(rescan = false): Promise<AccessPoint[]> {
+  try { return await safeInvoke<AccessPoint[]>('wifi_list_access_points', { rescan }) }
+  catch { return [] }
+  },
+
+  async wifiConnect(ssid: string, opts?: { password?: string; eap?: EapCredentials; hidden?: boolean }): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('wifi_connect', { ssid, password: opts?.password ?? null, eap: opts?.eap ?? null, hidden: opts?.hidden ?? null }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
+  async networkSavedConnections(): Promise<SavedConnection[]> {
+  try { return await safeInvoke<SavedConnection[]>('network_saved_connections') }
+  catch { return [] }
+  },
+
+  async networkForget(id: string): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('network_forget', { id }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
+  async networkSetAirplaneMode(enable: boolean): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('network_set_airplane_mode', { enable }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
+  /** BlueZ の状態。変化は sis:bluetooth-changed、接続/ペア状態の変化は sis:bluetooth-device で届く */
+  async bluetoothState(): Promise<BluetoothState | null> {
+  try { return await safeInvoke<BluetoothState>('bluetooth_state') }
+  catch { return null }
+  },
+
+  async bluetoothDiscover(enable: boolean, seconds?: number): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('bluetooth_discover', { enable, seconds: seconds ?? null }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
+  /** ペアリング〜接続。結果は sis:bluetooth-action で届く */
+  async bluetoothPair(id: string): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('bluetooth_pair', { id }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
+  async bluetoothAgentReply(request: number, accept: boolean, value?: string): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('bluetooth_agent_reply', { request, accept, value: value ?? null }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
+  async bluetoothConnect(id: string): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('bluetooth_connect', { id }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
+  async bluetoothDisconnect(id: string): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('bluetooth_disconnect', { id }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
+  async bluetoothSetTrusted(id: string, trusted: boolean): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('bluetooth_set_trusted', { id, trusted }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
+  async bluetoothRemove(id: string): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('bluetooth_remove', { id }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
+  /** 出力/入力デバイスとアプリ別ストリーム。変化は sis:audio-changed、既定出力の音量変化は sis:volume で届く */
+  async audioState(): Promise<AudioState | null> {
+  try { return await safeInvoke<AudioState>('audio_state') }
+  catch { return null }
+  },
+
+  async audioSetDefault(kind: 'sink' | 'source', name: string): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('audio_set_default', { kind, name }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
+  async audioSetVolume(kind: 'sink' | 'source', name: string, volume: number): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('audio_set_volume', { kind, name, volume }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
+  /** mute を省略するとトグル */
+  async audioSetMute(kind: 'sink' | 'source', name: string, mute?: boolean): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('audio_set_mute', { kind, name, mute: mute ?? null }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
+  async audioSetStreamVolume(kind: AudioStream['kind'], index: number, volume: number): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('audio_set_stream_volume', { kind, index, volume }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
+  async audioSetStreamMute(kind: AudioStream['kind'], index: number, mute?: boolean): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('audio_set_stream_mute', { kind, index, mute: mute ?? null }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
+  async audioMoveStream(kind: AudioStream['kind'], index: number, device: string): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('audio_move_stream', { kind, index, device }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
+  /** パネル/キーボードのバックライト。ハードウェアキー等による変化は sis:brightness で届く */
+  async backlightList(): Promise<BacklightDevice[]> {
+  try { return await safeInvoke<BacklightDevice[]>('backlight_list') }
+  catch { return [] }
+  },
+
+  /** device 省略時は全パネル。戻り値は実際に設定された % */
+  async backlightSet(percent: number, device?: string): Promise<{ ok: boolean; percent?: number; code?: string; message?: string; details?: string }> {
+    try { const p = await safeInvoke<number>('backlight_set', { device: device ?? null, percent: Math.max(0, Math.min(100, Math.floor(percent))) }); return { ok: true, percent: p } }
+    catch (e) { return fail(e) }
+  },
+
+  /** percent 省略時は次の段階へ循環 */
+  async keyboardBacklightSet(percent?: number): Promise<{ ok: boolean; percent?: number; code?: string; message?: string; details?: string }> {
+    try { const p = await safeInvoke<number>('keyboard_backlight_set', { percent: percent ?? null }); return { ok: true, percent: p } }
+    catch (e) { return fail(e) }
+  },
+
+  async storageListMounts(): Promise<MountedFs[]> {
+  try { return await safeInvoke<MountedFs[]>('storage_list_mounts') }
+  catch { return [] }
+  },
+
+  /** USB/SD などの外部媒体。抜き差しは sis:storage-added / sis:storage-removed / sis:storage-changed で届く */
+  async storageListRemovable(): Promise<RemovableDevice[]> {
+  try { return await safeInvoke<RemovableDevice[]>('storage_list_removable') }
+  catch { return [] }
+  },
+
+  async storagePolicy(): Promise<StoragePolicy | null> {
+  try { return await safeInvoke<StoragePolicy>('storage_policy') }
+  catch { return null }
+  },
+
+  async storageMount(id: string): Promise<{ ok: boolean; mountPoint?: string; code?: string; message?: string; details?: string }> {
+    try { const mountPoint = await safeInvoke<string>('storage_mount', { id }); return { ok: true, mountPoint } }
+    catch (e) { return fail(e) }
+  },
+
+  async storageUnmount(id: string): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('storage_unmount', { id }); return { ok: true, message: msg } }
+    // 'device-busy' を判定できるようバックエンドのエラーコードをそのまま返す
+    catch (e) { return fail(e) }
+  },
+
+  async storageBusyHolders(id: string): Promise<BusyHolder[]> {
+  try { return await safeInvoke<BusyHolder[]>('storage_busy_holders', { id }) }
+  catch { return [] }
+  },
+
+  async storageEject(id: string): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('storage_eject', { id }); return { ok: true, message: msg } }
+    // 'device-busy' を判定できるようバックエンドのエラーコードをそのまま返す
+    catch (e) { return fail(e) }
+  },
+
+  async processList(): Promise<ProcessSnapshot | null> {
+  try { return await safeInvoke<ProcessSnapshot>('process_list') }
+  catch { return null }
+  },
+
+  /** 終了の確認トークンを取得。ユーザー確認後に processKillConfirm(token) で実行 */
+  async processKillRequest(pids: number[], signal: KillSignal = 'term'): Promise<KillConfirmation | null> {
+  try { return await safeInvoke<KillConfirmation>('process_kill_request', { pids, signal }) }
+  catch { return null }
+  },
+
+  async processKillConfirm(token: string): Promise<{ ok: boolean; pids?: number[]; code?: string; message?: string; details?: string }> {
+    try { const pids = await safeInvoke<number[]>('process_kill_confirm', { token }); return { ok: true, pids } }
+    catch (e) { return fail(e) }
+  },
+
+  async processRenice(pid: number, nice: number): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('process_renice', { pid, nice }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
+  /** sis:processes イベントでの定期送信を開始/停止 */
+  async processStreamStart(intervalMs?: number): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('process_stream_start', { intervalMs: intervalMs ?? null }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
+  async processStreamStop(): Promise<{ ok: boolean }> {
+  try { await safeInvoke('process_stream_stop'); return { ok: true } }
+  catch { return { ok: false } }
+  },
+
+  /** 最新のテレメトリ。継続的な値は sis:telemetry イベントで届く */
+  async telemetrySnapshot(): Promise<TelemetrySample | null> {
+  try { return await safeInvoke<TelemetrySample>('telemetry_snapshot') }
+  catch { return null }
+  },
+
+  /** スパークライン用の直近履歴（古い順） */
+  async telemetryHistory(limit?: number): Promise<TelemetrySample[]> {
+  try { return await safeInvoke<TelemetrySample[]>('telemetry_history', { limit: limit ?? null }) }
+  catch { return [] }
+  },
+
+  async telemetrySetInterval(intervalMs: number): Promise<number | null> {
+  try { return await safeInvoke<number>('telemetry_set_interval', { intervalMs }) }
+  catch { return null }
+  },
+
+  /** スクリーンタイム（アプリ/カテゴリ別の使用時間と残り時間）。更新は sis:screentime イベントでも届く */
+  async screentimeState(): Promise<ScreenTimeState | null> {
+    try { return await safeInvoke<ScreenTimeState>('screentime_state') } catch { return null }
+  },
+
   async setVolume(volume: number): Promise<{ ok: boolean }> {
   try { await safeInvoke('set_volume', { volume }); return { ok: true } }
   catch { return { ok: false } }
@@ -302,28 +774,28 @@ export const api = {
     try { return await safeInvoke('list_documents_items') } catch { return [] }
   },
 
-  async setWallpaper(path: string): Promise<{ ok: boolean; message?: string }>{
+  async setWallpaper(path: string): Promise<{ ok: boolean; code?: string; message?: string; details?: string }>{
     try { const msg = await safeInvoke<string>('set_wallpaper', { path }); return { ok: true, message: msg } }
-    catch (e) { return { ok: false, message: (e as Error)?.message } }
+    catch (e) { return fail(e) }
   },
 
   async getSettings(): Promise<any> { try { return await safeInvoke('get_settings') } catch { return {} } },
-  async setSettings(s: any): Promise<{ ok: boolean; message?: string }>{
+  async setSettings(s: any): Promise<{ ok: boolean; code?: string; message?: string; details?: string }>{
     try { const msg = await safeInvoke<string>('set_settings', { new_s: s }); return { ok: true, message: msg } }
-    catch (e) { return { ok: false, message: (e as Error)?.message } }
+    catch (e) { return fail(e) }
   },
 
-  async openSettingsWindow(): Promise<{ ok: boolean; message?: string }>{
+  async openSettingsWindow(): Promise<{ ok: boolean; code?: string; message?: string; details?: string }>{
     try { const msg = await safeInvoke<string>('open_settings_window'); return { ok: true, message: msg } }
-    catch (e) { return { ok: false, message: (e as Error)?.message } }
+    catch (e) { return fail(e) }
   },
-  async tryStartLmStudio(): Promise<{ ok: boolean; message?: string }>{
+  async tryStartLmStudio(): Promise<{ ok: boolean; code?: string; message?: string; details?: string }>{
     try { const msg = await safeInvoke<string>('try_start_lmstudio'); return { ok: true, message: msg } }
-    catch (e) { return { ok: false, message: (e as Error)?.message } }
+    catch (e) { return fail(e) }
   },
-  async reorderFavoriteApps(names: string[]): Promise<{ ok: boolean; message?: string }>{
-    try { const msg = await safeInvoke<string>('reorder_favorite_apps', { names }); return { ok: true, message: msg } }
-    catch (e) { return { ok: false, message: (e as Error)?.message } }
+  async reorderFavoriteApps(ids: string[]): Promise<{ ok: boolean; code?: string; message?: string; details?: string }>{
+    try { const msg = await safeInvoke<string>('reorder_favorite_apps', { ids }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
   },
 
   async getFavoriteApps(): Promise<AppInfo[]> {
@@ -336,16 +808,98 @@ export const api = {
   catch { return { ok: false } }
   },
 
-  async removeFavoriteApp(appName: string): Promise<{ ok: boolean }> {
-  try { await safeInvoke('remove_favorite_app', { appName }); return { ok: true } }
+  async getFavorites(): Promise<FavoriteApp[]> {
+  return flattenDock(await this.getDockEntries())
+  },
+
+  async removeFavoriteApp(appId: string): Promise<{ ok: boolean }> {
+  try { await safeInvoke('remove_favorite_app', { appId }); return { ok: true } }
   catch { return { ok: false } }
   },
 
+  async getDockEntries(): Promise<DockEntry[]> {
+  try { return await safeInvoke<DockEntry[]>('get_dock_entries') }
+  catch { return [] }
+  },
+
+  async dockAddApp(app: AppInfo, folderId?: string, index?: number): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('dock_add_app', { app, folderId: folderId ?? null, index: index ?? null }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
+  async dockRemove(id: string): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('dock_remove', { id }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
+  async dockMove(id: string, index: number, folderId?: string): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('dock_move', { id, folderId: folderId ?? null, index }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
+  async dockReorder(ids: string[], folderId?: string): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('dock_reorder', { ids, folderId: folderId ?? null }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
+  async dockCreateFolder(name: string, appIds: string[]): Promise<DockFolder | null> {
+  try { return await safeInvoke<DockFolder>('dock_create_folder', { name, appIds }) }
+  catch { return null }
+  },
+
+  async dockRenameFolder(id: string, name: string): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('dock_rename_folder', { id, name }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
   async takeScreenshot(): Promise<{ ok: boolean }> {
   try { await safeInvoke('take_screenshot'); return { ok: true } }
   catch { return { ok: false } }
   },
 
+  /** 撮影結果は sis:screenshot-taken / sis:screenshot-failed で届く */
+  async screenshotCapture(mode: ScreenshotMode, opts: { windowId?: string; delaySecs?: number; copy?: boolean; save?: boolean } = {}): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('screenshot_capture', { mode, windowId: opts.windowId, delaySecs: opts.delaySecs, copy: opts.copy, save: opts.save }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
+  /** 座標は撮影画像のピクセル単位 */
+  async screenshotRegionCommit(x: number, y: number, width: number, height: number): Promise<Screenshot | null> {
+  try { return await safeInvoke<Screenshot>('screenshot_region_commit', { x: Math.round(x), y: Math.round(y), width: Math.round(width), height: Math.round(height) }) }
+  catch { return null }
+  },
+
+  async screenshotRegionCancel(): Promise<{ ok: boolean }> {
+  try { await safeInvoke('screenshot_region_cancel'); return { ok: true } }
+  catch { return { ok: false } }
+  },
+
+  async screenshotCopy(path: string): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('screenshot_copy', { path }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
+  /** 録画。状態の変化は sis:recording-state で届く */
+  async recorderStatus(): Promise<RecorderStatus | null> {
+  try { return await safeInvoke<RecorderStatus>('recorder_status') }
+  catch { return null }
+  },
+
+  async recorderStart(mode: ScreenshotMode, opts: { windowId?: string; mic?: boolean; framerate?: number } = {}): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('recorder_start', { mode, windowId: opts.windowId, mic: opts.mic, framerate: opts.framerate }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
+  async recorderStop(): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('recorder_stop'); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
+  async screenshotDelete(path: string): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('screenshot_delete', { path }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
   async playPauseMusic(): Promise<{ ok: boolean }> {
   try { await safeInvoke('play_pause_music'); return { ok: true } }
   catch { return { ok: false } }
@@ -361,35 +915,220 @@ export const api = {
   catch { return { ok: false } }
   },
 
-  async organizeFile(filePath: string): Promise<{ ok: boolean; path: string }> {
-  try { await safeInvoke('organize_file', { filePath }); return { ok: true, path: filePath } }
-  catch { return { ok: false, path: filePath } }
+  /** MPRIS プレイヤーの状態。変化（曲・再生状態・シーク）は sis:media-changed で届く */
+  async mediaState(): Promise<MediaState | null> {
+  try { return await safeInvoke<MediaState>('media_state') }
+  catch { return null }
   },
 
-  async organizeLatestDownload(): Promise<{ ok: boolean; message?: string }> {
-    try {
-      const msg = await safeInvoke<string>('organize_latest_download')
-      return { ok: true, message: msg }
-    } catch (e) {
-      return { ok: false, message: (e as Error)?.message }
-    }
+  async mediaControl(action: 'play' | 'pause' | 'play-pause' | 'next' | 'previous' | 'stop', player?: string): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('media_control', { action, player: player ?? null }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
+  /** 操作対象のプレイヤーを固定（省略で自動選択） */
+  async mediaSetActive(player?: string): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('media_set_active', { player: player ?? null }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
+  async mediaSeek(positionMs: number, player?: string): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('media_seek', { positionMs: Math.max(0, Math.floor(positionMs)), player: player ?? null }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
+  async mediaSetVolume(volume: number, player?: string): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('media_set_volume', { volume, player: player ?? null }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
+  async mediaSetShuffle(shuffle: boolean, player?: string): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('media_set_shuffle', { shuffle, player: player ?? null }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
+  async mediaSetLoop(status: 'none' | 'track' | 'playlist', player?: string): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('media_set_loop', { status, player: player ?? null }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
+  /** 通知を出す。新着は sis:notification、閉じたときは sis:notification-closed で届く */
+  async notifyPost(summary: string, opts?: { body?: string; appName?: string; icon?: string; urgency?: SisNotification['urgency']; actions?: NotifAction[]; timeoutMs?: number }): Promise<number | null> {
+  try { return await safeInvoke<number>('notify_post', { summary, body: opts?.body ?? null, appName: opts?.appName ?? null, icon: opts?.icon ?? null, urgency: opts?.urgency ?? null, actions: opts?.actions ?? null, timeoutMs: opts?.timeoutMs ?? null }) }
+  catch { return null }
+  },
+
+  async notifyClose(id: number): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('notify_close', { id }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
+  /** "default" は本文クリック */
+  async notifyInvokeAction(id: number, actionKey: string): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('notify_invoke_action', { id, actionKey }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
+  async notifyHistory(limit?: number): Promise<SisNotification[]> {
+  try { return await safeInvoke<SisNotification[]>('notify_history', { limit: limit ?? null }) }
+  catch { return [] }
+  },
+
+  async notifyMarkRead(id?: number): Promise<{ ok: boolean }> {
+  try { await safeInvoke('notify_mark_read', { id: id ?? null }); return { ok: true } }
+  catch { return { ok: false } }
+  },
+
+  async notifyClearHistory(id?: number): Promise<{ ok: boolean }> {
+  try { await safeInvoke('notify_clear_history', { id: id ?? null }); return { ok: true } }
+  catch { return { ok: false } }
+  },
+
+  /** おやすみモード。切り替わりは sis:dnd-changed で届く */
+  async notifyGetDnd(): Promise<DndStatus | null> {
+  try { return await safeInvoke<DndStatus>('notify_get_dnd') }
+  catch { return null }
+  },
+
+  async notifySetDnd(config: DndConfig): Promise<{ ok: boolean; status?: DndStatus; code?: string; message?: string; details?: string }> {
+    try { const st = await safeInvoke<DndStatus>('notify_set_dnd', { config }); return { ok: true, status: st } }
+    catch (e) { return fail(e) }
+  },
+
+  /** トレイ（StatusNotifierItem）。変化は sis:tray-changed で届く */
+  async trayItems(): Promise<TrayItem[]> {
+  try { return await safeInvoke<TrayItem[]>('tray_items') }
+  catch { return [] }
+  },
+
+  /** 戻り値 message が 'menu' のときはメニューを開く */
+  async trayActivate(id: string, x: number, y: number): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('tray_activate', { id, x: Math.round(x), y: Math.round(y) }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
+  async traySecondaryActivate(id: string, x: number, y: number): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('tray_secondary_activate', { id, x: Math.round(x), y: Math.round(y) }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
+  async trayContextMenu(id: string, x: number, y: number): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('tray_context_menu', { id, x: Math.round(x), y: Math.round(y) }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
   },
 
-  async setBrightness(percent: number): Promise<{ ok: boolean; message?: string }> {
+  async trayScroll(id: string, delta: number, orientation: 'vertical' | 'horizontal' = 'vertical'): Promise<{ ok: boolean }> {
+  try { await safeInvoke('tray_scroll', { id, delta: Math.round(delta), orientation }); return { ok: true } }
+  catch { return { ok: false } }
+  },
+
+  async trayMenu(id: string): Promise<TrayMenuItem | null> {
+  try { return await safeInvoke<TrayMenuItem>('tray_menu', { id }) }
+  catch { return null }
+  },
+
+  async trayMenuEvent(id: string, menuId: number, event: 'clicked' | 'opened' | 'closed' = 'clicked'): Promise<{ ok: boolean }> {
+  try { await safeInvoke('tray_menu_event', { id, menuId, event }); return { ok: true } }
+  catch { return { ok: false } }
+  },
+
+  /** クリップボード履歴。変化は sis:clipboard-changed で届く（試験モード中は空） */
+  async clipboardList(query?: string, kind?: ClipEntry['kind'], limit?: number): Promise<ClipboardList | null> {
+  try { return await safeInvoke<ClipboardList>('clipboard_list', { query, kind, limit }) }
+  catch { return null }
+  },
+
+  async clipboardCopy(id: number, plain = false): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('clipboard_copy', { id, plain }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
+  async clipboardPin(id: number, pinned: boolean): Promise<{ ok: boolean }> {
+  try { await safeInvoke('clipboard_pin', { id, pinned }); return { ok: true } }
+  catch { return { ok: false } }
+  },
+
+  async clipboardDelete(id: number): Promise<{ ok: boolean }> {
+  try { await safeInvoke('clipboard_delete', { id }); return { ok: true } }
+  catch { return { ok: false } }
+  },
+
+  async clipboardClear(includePinned = false): Promise<{ ok: boolean }> {
+  try { await safeInvoke('clipboard_clear', { includePinned }); return { ok: true } }
+  catch { return { ok: false } }
+  },
+
+  /** 「このアプリで開く」候補（MIME指定またはファイルパスから判定） */
+  async mimeListHandlers(args: { mime?: string; path?: string }): Promise<MimeHandlers | null> {
+    try { return await safeInvoke<MimeHandlers>('mime_list_handlers', args) } catch { return null }
+  },
+
+  async mimeSetDefault(mime: string, desktopId: string): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('mime_set_default', { mime, desktopId }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
+  async mimeAddAssociation(mime: string, desktopId: string): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('mime_add_association', { mime, desktopId }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
+  async openWith(path: string, desktopId: string): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('open_with', { path, desktopId }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
+  async organizeFile(filePath: string, dryRun = false): Promise<{ ok: boolean; plan?: OrganizePlan; code?: string; message?: string; details?: string }> {
+    try { const plan = await safeInvoke<OrganizePlan>('organize_file', { filePath, dryRun }); return { ok: true, plan } }
+    catch (e) { return fail(e) }
+  },
+
+  async organizeLatestDownload(dryRun = false): Promise<{ ok: boolean; plan?: OrganizePlan; code?: string; message?: string; details?: string }> {
+    try { const plan = await safeInvoke<OrganizePlan>('organize_latest_download', { dryRun }); return { ok: true, plan } }
+    catch (e) { return fail(e) }
+  },
+
+  /** 整理の予行（ファイルは動かさない）。paths 省略時はダウンロードフォルダ全体 */
+  async organizePreview(paths?: string[]): Promise<{ ok: boolean; plans?: OrganizePlan[]; code?: string; message?: string; details?: string }> {
+    try { const plans = await safeInvoke<OrganizePlan[]>('organize_preview', { paths: paths ?? null }); return { ok: true, plans } }
+    catch (e) { return fail(e) }
+  },
+
+  async organizerGetConfig(): Promise<OrganizerConfig | null> {
+    try { return await safeInvoke<OrganizerConfig>('organizer_get_config') }
+    catch { return null }
+  },
+
+  async organizerSetConfig(config: OrganizerConfig): Promise<{ ok: boolean; config?: OrganizerConfig; code?: string; message?: string; details?: string }> {
+    try { const saved = await safeInvoke<OrganizerConfig>('organizer_set_config', { config }); return { ok: true, config: saved } }
+    catch (e) { return fail(e) }
+  },
+
+  async downloadsWatchGetConfig(): Promise<DownloadWatchConfig | null> {
+    try { return await safeInvoke<DownloadWatchConfig>('downloads_watch_get_config') }
+    catch { return null }
+  },
+
+  async downloadsWatchSetConfig(config: DownloadWatchConfig): Promise<{ ok: boolean; config?: DownloadWatchConfig; code?: string; message?: string; details?: string }> {
+    try { const saved = await safeInvoke<DownloadWatchConfig>('downloads_watch_set_config', { config }); return { ok: true, config: saved } }
+    catch (e) { return fail(e) }
+  },
+
+  async setBrightness(percent: number): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
     try {
       const msg = await safeInvoke<string>('set_brightness', { percent: Math.max(0, Math.min(100, Math.floor(percent))) })
       return { ok: true, message: msg }
     } catch (e) {
-      return { ok: false, message: (e as Error)?.message }
+      return fail(e)
     }
   },
 
-  async launchApp(exec: string): Promise<{ ok: boolean; message?: string }> {
+  async launchApp(exec: string): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
     try {
       const msg = await safeInvoke<string>('launch_app', { exec })
       return { ok: true, message: msg }
     } catch (e) {
-      return { ok: false, message: (e as Error)?.message }
+      return fail(e)
     }
   },
 
@@ -401,75 +1140,252 @@ export const api = {
     }
   },
 
-  async overlayStart(): Promise<{ ok: boolean; message?: string }> {
+  async overlayStart(): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
     try {
       const msg = await safeInvoke<string>('overlay_start')
       return { ok: true, message: msg }
     } catch (e) {
-      return { ok: false, message: (e as Error)?.message }
+      return fail(e)
     }
   },
 
-  async overlayStop(): Promise<{ ok: boolean; message?: string }> {
+  async overlayStop(): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
     try {
       const msg = await safeInvoke<string>('overlay_stop')
       return { ok: true, message: msg }
     } catch (e) {
-      return { ok: false, message: (e as Error)?.message }
+      return fail(e)
     }
   },
 
-  async networkSet(enable: boolean): Promise<{ ok: boolean; message?: string }> {
+  async networkSet(enable: boolean): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
     try {
       const msg = await safeInvoke<string>('network_set', { enable })
       return { ok: true, message: msg }
     } catch (e) {
-      return { ok: false, message: (e as Error)?.message }
+      return fail(e)
     }
   },
 
-  async bluetoothSet(enable: boolean): Promise<{ ok: boolean; message?: string }> {
+  async bluetoothSet(enable: boolean): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
     try {
       const msg = await safeInvoke<string>('bluetooth_set', { enable })
       return { ok: true, message: msg }
     } catch (e) {
-      return { ok: false, message: (e as Error)?.message }
+      return fail(e)
     }
   },
 
-  async powerAction(action: 'shutdown' | 'reboot' | 'logout'): Promise<{ ok: boolean; message?: string }> {
+  async powerAction(action: SessionAction): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
     try {
       const msg = await safeInvoke<string>('power_action', { action })
       return { ok: true, message: msg }
     } catch (e) {
-      return { ok: false, message: (e as Error)?.message }
+      return fail(e)
     }
   },
 
-  async llmQuery(prompt: string): Promise<{ ok: boolean; text?: string; message?: string }> {
+  /** logind の各操作が許可されているか（yes/challenge なら実行可能） */
+  async sessionCapabilities(): Promise<PowerCapabilities | null> {
+  try { return await safeInvoke<PowerCapabilities>('session_capabilities') }
+  catch { return null }
+  },
+
+  /** 進捗は sis:session-state で届く */
+  async sessionState(): Promise<SessionState | null> {
+  try { return await safeInvoke<SessionState>('session_state') }
+  catch { return null }
+  },
+
+  /** force=true で閉じなかったアプリを終了させて続行 */
+  async sessionAction(action: SessionAction, force = false): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('session_action', { action, force }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
+  async sessionCancel(): Promise<{ ok: boolean }> {
+  try { await safeInvoke('session_cancel'); return { ok: true } }
+  catch { return { ok: false } }
+  },
+
+  async sessionListInhibitors(): Promise<Inhibitor[]> {
+  try { return await safeInvoke<Inhibitor[]>('session_list_inhibitors') }
+  catch { return [] }
+  },
+
+  async sessionInhibit(what: string, why: string, mode: 'block' | 'delay' = 'block'): Promise<number | null> {
+  try { return await safeInvoke<number>('session_inhibit', { what, why, mode }) }
+  catch { return null }
+  },
+
+  async sessionUninhibit(id: number): Promise<{ ok: boolean }> {
+  try { await safeInvoke('session_uninhibit', { id }); return { ok: true } }
+  catch { return { ok: false } }
+  },
+
+  async sessionScheduleShutdown(kind: 'poweroff' | 'reboot', atMs: number): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('session_schedule_shutdown', { kind, atMs: Math.round(atMs) }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
+  async sessionCancelScheduledShutdown(): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('session_cancel_scheduled_shutdown'); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
+  /** 入力デバイス設定。変化は sis:input-state で届く */
+  async inputState(): Promise<InputState | null> {
+    try { return await safeInvoke<InputState>('input_state') }
+    catch { return null }
+  },
+
+  async inputSetConfig(config: InputConfig): Promise<{ ok: boolean; state?: InputState; code?: string; message?: string; details?: string }> {
+    try { const state = await safeInvoke<InputState>('input_set_config', { config }); return { ok: true, state } }
+    catch (e) { return fail(e) }
+  },
+
+  async inputSetXkbOption(option: string, enabled: boolean): Promise<{ ok: boolean; options?: string[]; code?: string; message?: string; details?: string }> {
+    try { const options = await safeInvoke<string[]>('input_set_xkb_option', { option, enabled }); return { ok: true, options } }
+    catch (e) { return fail(e) }
+  },
+
+  /** [名前, 説明] の一覧（xkeyboard-config） */
+  async inputListLayouts(): Promise<[string, string][]> {
+    try { return await safeInvoke<[string, string][]>('input_list_layouts') }
+    catch { return [] }
+  },
+
+  /** IME の状態。変化は sis:ime-state で届く */
+  async imeState(): Promise<ImeState | null> {
+    try { return await safeInvoke<ImeState>('ime_state') }
+    catch { return null }
+  },
+
+  async imeSetEngine(name: string): Promise<ImeState | null> {
+    try { return await safeInvoke<ImeState>('ime_set_engine', { name }) }
+    catch { return null }
+  },
+
+  async imeSetJapanese(enabled: boolean): Promise<ImeState | null> {
+    try { return await safeInvoke<ImeState>('ime_set_japanese', { enabled }) }
+    catch { return null }
+  },
+
+  async imeToggle(): Promise<ImeState | null> {
+    try { return await safeInvoke<ImeState>('ime_toggle') }
+    catch { return null }
+  },
+
+  /** 日付と時刻の状態。タイムゾーンの変化などは sis:time-state で届く */
+  async timeState(): Promise<TimeState | null> {
+    try { return await safeInvoke<TimeState>('time_state') }
+    catch { return null }
+  },
+
+  async timeListTimezones(query?: string): Promise<TimezoneInfo[]> {
+    try { return await safeInvoke<TimezoneInfo[]>('time_list_timezones', { query: query || null }) }
+    catch { return [] }
+  },
+
+  async timeSetTimezone(timezone: string): Promise<{ ok: boolean; state?: TimeState; code?: string; message?: string; details?: string }> {
+    try { const state = await safeInvoke<TimeState>('time_set_timezone', { timezone }); return { ok: true, state } }
+    catch (e) { return fail(e) }
+  },
+
+  async timeSetNtp(enabled: boolean): Promise<{ ok: boolean; state?: TimeState; code?: string; message?: string; details?: string }> {
+    try { const state = await safeInvoke<TimeState>('time_set_ntp', { enabled }); return { ok: true, state } }
+    catch (e) { return fail(e) }
+  },
+
+  /** datetime は "YYYY-MM-DDTHH:MM" のローカル時刻（input type="datetime-local" の値） */
+  async timeSetTime(datetime: string): Promise<{ ok: boolean; state?: TimeState; code?: string; message?: string; details?: string }> {
+    try { const state = await safeInvoke<TimeState>('time_set_time', { datetime }); return { ok: true, state } }
+    catch (e) { return fail(e) }
+  },
+
+  async timeSetHour12(enabled: boolean): Promise<TimeState | null> {
+    try { return await safeInvoke<TimeState>('time_set_hour12', { enabled }) }
+    catch { return null }
+  },
+
+  /** 夜間モードの状態。変化は sis:nightlight-state で届く */
+  async nightLightState(): Promise<NightLightState | null> {
+    try { return await safeInvoke<NightLightState>('nightlight_state') }
+    catch { return null }
+  },
+
+  async nightLightGetConfig(): Promise<NightLightConfig | null> {
+    try { return await safeInvoke<NightLightConfig>('nightlight_get_config') }
+    catch { return null }
+  },
+
+  async nightLightSetConfig(config: NightLightConfig): Promise<{ ok: boolean; state?: NightLightState; code?: string; message?: string; details?: string }> {
+    try { const state = await safeInvoke<NightLightState>('nightlight_set_config', { config }); return { ok: true, state } }
+    catch (e) { return fail(e) }
+  },
+
+  /** 指定した項目だけ変更する（コントロールセンター用） */
+  async nightLightSet(opts: { enabled?: boolean; temperature?: number; intensity?: number }): Promise<NightLightState | null> {
+    try { return await safeInvoke<NightLightState>('nightlight_set', opts) }
+    catch { return null }
+  },
+
+  /** [日の入り, 日の出]（HH:MM）。位置を省略すると設定値/タイムゾーンから求める */
+  async nightLightSunTimes(latitude?: number | null, longitude?: number | null): Promise<[string, string] | null> {
+    try { return await safeInvoke<[string, string]>('nightlight_sun_times', { latitude, longitude }) }
+    catch { return null }
+  },
+
+  async lockScreen(reason?: LockReason, message?: string): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('lock_screen', { reason, message }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
+  async lockState(): Promise<LockState | null> {
+    try { return await safeInvoke<LockState>('lock_state') }
+    catch { return null }
+  },
+
+  /** 結果は sis:lock-auth イベント（{ ok, message }）で届く */
+  async lockAuthenticate(password: string): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('lock_authenticate', { password }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
+  async lockGetConfig(): Promise<LockConfig | null> {
+    try { return await safeInvoke<LockConfig>('lock_get_config') }
+    catch { return null }
+  },
+
+  async lockSetConfig(config: LockConfig): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('lock_set_config', { config }); return { ok: true, message: msg } }
+    catch (e) { return fail(e) }
+  },
+
+  async llmQuery(prompt: string): Promise<{ ok: boolean; text?: string; code?: string; message?: string; details?: string }> {
     try {
       const text = await safeInvoke<string>('llm_query', { prompt })
       return { ok: true, text }
     } catch (e) {
-      return { ok: false, message: (e as Error)?.message }
+      return fail(e)
     }
   },
 
-  async llmQueryRemote(baseUrl: string, prompt: string, apiKey?: string, model?: string): Promise<{ ok: boolean; text?: string; message?: string }>{
+  async llmQueryRemote(baseUrl: string, prompt: string, apiKey?: string, model?: string): Promise<{ ok: boolean; text?: string; code?: string; message?: string; details?: string }>{
     try {
       const text = await safeInvoke<string>('llm_query_remote', { baseUrl, apiKey, model, prompt })
       return { ok: true, text }
     } catch (e) {
-      return { ok: false, message: (e as Error)?.message }
+      return fail(e)
     }
   },
 
-  async llmDownloadHf(modelId: string): Promise<{ ok: boolean; path?: string; message?: string }>{
+  async llmDownloadHf(modelId: string): Promise<{ ok: boolean; path?: string; code?: string; message?: string; details?: string }>{
     try {
       const path = await safeInvoke<string>('llm_download_hf', { modelId })
       return { ok: true, path }
     } catch (e) {
-      return { ok: false, message: (e as Error)?.message }
+      return fail(e)
     }
   },
 
@@ -478,16 +1394,16 @@ export const api = {
     catch { return [] }
   },
 
-  async runSafeCommand(cmdline: string): Promise<{ ok: boolean; text?: string; message?: string }> {
+  async runSafeCommand(cmdline: string): Promise<{ ok: boolean; text?: string; code?: string; message?: string; details?: string }> {
     try {
       const text = await safeInvoke<string>('run_safe_command', { cmdline })
       return { ok: true, text }
     } catch (e) {
-      return { ok: false, message: (e as Error)?.message }
+      return fail(e)
     }
   },
 
-  async openPath(path: string): Promise<{ ok: boolean; message?: string }>{
+  async openPath(path: string): Promise<{ ok: boolean; code?: string; message?: string; details?: string }>{
     try {
       let pth = (path || '').trim()
       if (pth.startsWith('~')) {
@@ -510,7 +1426,7 @@ export const api = {
       const text = await safeInvoke<string>('run_safe_command', { cmdline: cmd })
       return { ok: (text||'').includes('OK') }
     } catch (e) {
-      return { ok: false, message: (e as Error)?.message }
+      return fail(e)
     }
   },
 
@@ -580,83 +1496,83 @@ export const api = {
     })
   },
 
-  async clamavScan(path: string): Promise<{ ok: boolean; text?: string; message?: string }> {
+  async clamavScan(path: string): Promise<{ ok: boolean; text?: string; code?: string; message?: string; details?: string }> {
     try {
       const text = await safeInvoke<string>('clamav_scan', { path })
       return { ok: true, text }
     } catch (e) {
-      return { ok: false, message: (e as Error)?.message }
+      return fail(e)
     }
   },
 
-  async kdeconnectList(): Promise<{ ok: boolean; text?: string; message?: string }> {
+  async kdeconnectList(): Promise<{ ok: boolean; text?: string; code?: string; message?: string; details?: string }> {
     try {
       const text = await safeInvoke<string>('kdeconnect_list')
       return { ok: true, text }
     } catch (e) {
-      return { ok: false, message: (e as Error)?.message }
+      return fail(e)
     }
   },
 
   async getBackendLog(limit?: number): Promise<string> {
     try { return await safeInvoke<string>('get_backend_log', { limit }) } catch { return '' }
   },
-  async clearBackendLog(): Promise<{ ok: boolean; message?: string }> {
-    try { const msg = await safeInvoke<string>('clear_backend_log'); return { ok: true, message: msg } } catch (e) { return { ok: false, message: (e as Error)?.message } }
+  async clearBackendLog(): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
+    try { const msg = await safeInvoke<string>('clear_backend_log'); return { ok: true, message: msg } } catch (e) { return fail(e) }
   },
 
-  async runWithSudo(cmdline: string, password: string): Promise<{ ok: boolean; text?: string; message?: string }> {
+  async runWithSudo(cmdline: string, password: string): Promise<{ ok: boolean; text?: string; code?: string; message?: string; details?: string }> {
     try {
       const text = await safeInvoke<string>('run_with_sudo', { cmdline, password })
       return { ok: true, text }
     } catch (e) {
-      return { ok: false, message: (e as Error)?.message }
+      return fail(e)
     }
   },
 
   // Ubuntu固有のシステム機能
-  async ubuntuUpdateSystem(): Promise<{ ok: boolean; message?: string }> {
+  async ubuntuUpdateSystem(): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
     try {
       const msg = await safeInvoke<string>('ubuntu_update_system')
       return { ok: true, message: msg }
     } catch (e) {
-      return { ok: false, message: (e as Error)?.message }
+      return fail(e)
     }
   },
 
-  async ubuntuInstallPackage(packageName: string): Promise<{ ok: boolean; message?: string }> {
+  async ubuntuInstallPackage(packageName: string): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
     try {
       const msg = await safeInvoke<string>('ubuntu_install_package', { packageName })
       return { ok: true, message: msg }
     } catch (e) {
-      return { ok: false, message: (e as Error)?.message }
+      return fail(e)
     }
   },
 
-  async ubuntuSystemSettings(): Promise<{ ok: boolean; message?: string }> {
+  async ubuntuSystemSettings(): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
     try {
       const msg = await safeInvoke<string>('ubuntu_system_settings')
       return { ok: true, message: msg }
     } catch (e) {
-      return { ok: false, message: (e as Error)?.message }
+      return fail(e)
     }
   },
 
-  async ubuntuSoftwareCenter(): Promise<{ ok: boolean; message?: string }> {
+  async ubuntuSoftwareCenter(): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
     try {
       const msg = await safeInvoke<string>('ubuntu_software_center')
       return { ok: true, message: msg }
     } catch (e) {
-      return { ok: false, message: (e as Error)?.message }
+      return fail(e)
     }
   },
 
-  async ubuntuSetTheme(theme: 'light' | 'dark'): Promise<{ ok: boolean; message?: string }> {
+  async ubuntuSetTheme(theme: 'light' | 'dark'): Promise<{ ok: boolean; code?: string; message?: string; details?: string }> {
     try {
       const msg = await safeInvoke<string>('ubuntu_set_theme', { theme })
       return { ok: true, message: msg }
     } catch (e) {
-      return { ok: false, message: (e as Error)?.message }
+      return fail(e)
     }
   },
 
@@ -157,7 +157,7 @@ Priority: optional
 Architecture: $ARCH
 Maintainer: $MAINTAINER
 Description: $DESCRIPTION
-Depends: libc6 (>= 2.31), libgtk-3-0, libwebkit2gtk-4.1-0 | libwebkit2gtk-4.0-37, xdg-utils
+Depends: libc6 (>= 2.31), libgtk-3-0, libpam0g, libwebkit2gtk-4.1-0 | libwebkit2gtk-4.0-37, xdg-utils
 EOF
 
 # postinst: ensure autostart entry for SISUI desktop session